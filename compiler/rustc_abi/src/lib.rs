@@ -1883,6 +1883,51 @@ pub fn eq_abi(&self, other: &Self) -> bool {
             && self.max_repr_align == other.max_repr_align
             && self.unadjusted_abi_align == other.unadjusted_abi_align
     }
+
+    /// If this is the layout of an enum with a directly encoded tag and one variant
+    /// that is more than three times as large as all others, returns that variant
+    /// and the size the enum would have if the fields of that variant were moved
+    /// behind a pointer, such as a `Box`.
+    ///
+    /// Returns `None` if doing so would not make the enum smaller.
+    pub fn oversized_variant<C: HasDataLayout>(
+        &self,
+        cx: &C,
+    ) -> Option<OversizedVariant<VariantIdx>> {
+        let Variants::Multiple { tag, tag_encoding: TagEncoding::Direct, ref variants, .. } =
+            self.variants
+        else {
+            return None;
+        };
+        let dl = cx.data_layout();
+        let tag_size = tag.size(dl);
+        let payload = |variant: &Self| variant.size.bytes().saturating_sub(tag_size.bytes());
+
+        let (variant, largest) = variants.iter_enumerated().max_by_key(|(_, v)| payload(v))?;
+        let others = || variants.iter_enumerated().filter(|&(i, _)| i != variant).map(|(_, v)| v);
+        let second_largest = others().map(payload).max().unwrap_or(0);
+        if payload(largest) <= second_largest * 3 {
+            return None;
+        }
+
+        // The tag stays where it is, and the pointer to the boxed fields follows it.
+        let align = others()
+            .map(|v| v.align.abi)
+            .fold(dl.pointer_align.abi.max(tag.align(dl).abi), Ord::max);
+        let boxed_variant = tag_size.align_to(dl.pointer_align.abi) + dl.pointer_size;
+        let boxed_size = others().map(|v| v.size).fold(boxed_variant, Ord::max).align_to(align);
+        (boxed_size < self.size).then_some(OversizedVariant { variant, boxed_size })
+    }
+}
+
+/// An enum variant that makes the enum much larger than its other variants do,
+/// as found by [`LayoutData::oversized_variant`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OversizedVariant<VariantIdx> {
+    /// The oversized variant.
+    pub variant: VariantIdx,
+    /// The size of the enum if the fields of the variant were boxed.
+    pub boxed_size: Size,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -247,6 +247,19 @@ builtin_macros_multiple_defaults = multiple declared defaults
     .note = only one variant can be default
     .suggestion = make `{$ident}` default
 
+builtin_macros_multiversion_duplicate_target = the same target features are listed twice in `#[multiversion]`
+    .label = first listed here
+
+builtin_macros_multiversion_malformed = malformed `multiversion` attribute input
+    .help = list the sets of target features to compile versions for, e.g. `#[multiversion(targets = ["avx2", "sse4.2,fma"])]`
+
+builtin_macros_multiversion_no_std = `#[multiversion]` cannot be used in `#![no_std]` crates
+    .note = the target features are detected at runtime with the macros of `std::arch`
+
+builtin_macros_multiversion_not_fn = `#[multiversion]` can only be applied to free functions
+
+builtin_macros_multiversion_unsupported = `#[multiversion]` cannot be applied to {$kind}
+
 builtin_macros_naked_functions_testing_attribute =
     cannot use `#[naked]` with testing attributes
     .label = function marked with testing attribute here
@@ -1030,3 +1030,43 @@ pub(crate) struct NonGenericPointee {
     #[primary_span]
     pub span: Span,
 }
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_multiversion_not_fn)]
+pub(crate) struct MultiversionNotFn {
+    #[primary_span]
+    pub span: Span,
+}
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_multiversion_unsupported)]
+pub(crate) struct MultiversionUnsupported {
+    #[primary_span]
+    pub span: Span,
+    pub kind: &'static str,
+}
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_multiversion_malformed)]
+#[help]
+pub(crate) struct MultiversionMalformed {
+    #[primary_span]
+    pub span: Span,
+}
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_multiversion_no_std)]
+#[note]
+pub(crate) struct MultiversionNoStd {
+    #[primary_span]
+    pub span: Span,
+}
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_multiversion_duplicate_target)]
+pub(crate) struct MultiversionDuplicateTarget {
+    #[primary_span]
+    pub span: Span,
+    #[label]
+    pub first: Span,
+}
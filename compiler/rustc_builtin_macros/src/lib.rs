@@ -51,6 +51,7 @@
 mod format_foreign;
 mod global_allocator;
 mod log_syntax;
+mod multiversion;
 mod pattern_type;
 mod source_util;
 mod test;
@@ -121,6 +122,7 @@ pub fn register_builtin_macros(resolver: &mut dyn ResolverExpand) {
         derive: derive::Expander { is_const: false },
         derive_const: derive::Expander { is_const: true },
         global_allocator: global_allocator::expand,
+        multiversion_expand: multiversion::expand,
         test: test::expand_test,
         test_case: test::expand_test_case,
     }
@@ -146,4 +148,6 @@ pub fn register_builtin_macros(resolver: &mut dyn ResolverExpand) {
     register(sym::contracts_requires, requires);
     let ensures = SyntaxExtensionKind::Attr(Arc::new(contracts::ExpandEnsures));
     register(sym::contracts_ensures, ensures);
+    let multiversion = SyntaxExtensionKind::Attr(Arc::new(multiversion::ExpandMultiversion));
+    register(sym::multiversion, multiversion);
 }
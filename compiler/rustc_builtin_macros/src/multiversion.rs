@@ -0,0 +1,712 @@
+//! Implements `#[multiversion(targets = ["avx2", "sse4.2,fma"])]`, which compiles a
+//! function once for each of the given sets of target features, plus once without
+//! them, and dispatches to the best version the running CPU supports.
+//!
+//! The attributed function
+//!
+//! ```ignore (illustrative)
+//! #[multiversion(targets = ["avx2", "sse4.2,fma"])]
+//! fn sum(values: &[f32]) -> f32 { /* ... */ }
+//! ```
+//!
+//! expands, on x86 ELF targets whose C library resolves ifuncs, to roughly
+//!
+//! ```ignore (illustrative)
+//! fn sum(arg0: &[f32]) -> f32 {
+//!     #[target_feature(enable = "avx2")]
+//!     fn sum_avx2(values: &[f32]) -> f32 { /* ... */ }
+//!     #[target_feature(enable = "sse4.2,fma")]
+//!     fn sum_sse4_2_fma(values: &[f32]) -> f32 { /* ... */ }
+//!     fn sum_default(values: &[f32]) -> f32 { /* ... */ }
+//!
+//!     extern "C" fn resolve() -> *const () {
+//!         if is_x86_feature_detected!("avx2") {
+//!             sum_avx2 as unsafe fn(_) -> _ as *const ()
+//!         } else if is_x86_feature_detected!("sse4.2") && is_x86_feature_detected!("fma") {
+//!             sum_sse4_2_fma as unsafe fn(_) -> _ as *const ()
+//!         } else {
+//!             sum_default as unsafe fn(_) -> _ as *const ()
+//!         }
+//!     }
+//!     global_asm!(
+//!         ".globl __rust_multiversion_<hash>",
+//!         ".type __rust_multiversion_<hash>, STT_GNU_IFUNC",
+//!         ".set __rust_multiversion_<hash>, {}",
+//!         sym resolve,
+//!     );
+//!     unsafe extern "Rust" {
+//!         fn __rust_multiversion_<hash>(arg0: &[f32]) -> f32;
+//!     }
+//!     unsafe { __rust_multiversion_<hash>(arg0) }
+//! }
+//! ```
+//!
+//! so that the dynamic loader calls the resolver once, when it binds the symbol, and
+//! later calls go straight to the selected version. Elsewhere, and for generic
+//! functions, which have no single symbol to bind, the choice is made on the first call
+//! and cached in a static instead:
+//!
+//! ```ignore (illustrative)
+//! fn sum(arg0: &[f32]) -> f32 {
+//!     // The versions, as above.
+//!
+//!     static SELECTED: AtomicU8 = AtomicU8::new(0);
+//!     let mut choice = SELECTED.load(Relaxed);
+//!     if choice == 0 {
+//!         choice = if is_x86_feature_detected!("avx2") {
+//!             1
+//!         } else if is_x86_feature_detected!("sse4.2") && is_x86_feature_detected!("fma") {
+//!             2
+//!         } else {
+//!             3
+//!         };
+//!         SELECTED.store(choice, Relaxed);
+//!     }
+//!     unsafe {
+//!         match choice {
+//!             1 => sum_avx2(arg0),
+//!             2 => sum_sse4_2_fma(arg0),
+//!             _ => sum_default(arg0),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The first target that the CPU supports wins, so targets should be listed from the
+//! most to the least preferred. On targets without runtime feature detection in
+//! `std::arch`, the function is left as it is. Since the detection macros are only in
+//! `std`, the attribute is an error in `#![no_std]` crates.
+//!
+//! Arguments of AST-based attribute macros have to be meta items, which can't hold
+//! arrays. So `#[multiversion]` itself only rewrites its `targets = [...]` into
+//! `#[multiversion_expand(targets(...))]`, which does the expansion above.
+
+use std::iter;
+use std::ops::ControlFlow;
+
+use rustc_ast::ptr::P;
+use rustc_ast::token::{self, Delimiter, Token};
+use rustc_ast::tokenstream::{DelimSpacing, DelimSpan, Spacing, TokenStream, TokenTree};
+use rustc_ast::util::literal::escape_string_symbol;
+use rustc_ast::visit::{self, Visitor};
+use rustc_ast::{
+    self as ast, AttrArgs, AttrStyle, AttrVec, BinOpKind, BlockCheckMode, DelimArgs, Expr, Extern,
+    GenericArg, GenericParamKind, ItemKind, LitKind, MetaItemInner, MetaItemLit, Mutability,
+    Safety, StmtKind, TyKind, UnsafeSource, attr,
+};
+use rustc_errors::ErrorGuaranteed;
+use rustc_expand::base::{Annotatable, AttrProcMacro, ExtCtxt};
+use rustc_span::{Ident, Span, Symbol, kw, sym};
+use rustc_target::spec::BinaryFormat;
+use thin_vec::{ThinVec, thin_vec};
+
+use crate::errors;
+use crate::util::warn_on_duplicate_attribute;
+
+pub(crate) struct ExpandMultiversion;
+
+impl AttrProcMacro for ExpandMultiversion {
+    fn expand<'cx>(
+        &self,
+        ecx: &'cx mut ExtCtxt<'_>,
+        span: Span,
+        annotation: TokenStream,
+        annotated: TokenStream,
+    ) -> Result<TokenStream, ErrorGuaranteed> {
+        let Some(targets) = targets_list(&annotation) else {
+            ecx.dcx().emit_err(errors::MultiversionMalformed { span });
+            return Ok(annotated);
+        };
+
+        // `#[$crate::prelude::v1::multiversion_expand(targets(...))]`
+        let span = ecx.with_def_site_ctxt(span);
+        let delimited = |delim, tokens| {
+            TokenTree::Delimited(
+                DelimSpan::from_single(span),
+                DelimSpacing::new(Spacing::JointHidden, Spacing::JointHidden),
+                delim,
+                tokens,
+            )
+        };
+        let mut attr = Vec::new();
+        for (i, name) in [kw::DollarCrate, sym::prelude, sym::v1, sym::multiversion_expand]
+            .into_iter()
+            .enumerate()
+        {
+            if i > 0 {
+                attr.push(TokenTree::token_joint(token::PathSep, span));
+            }
+            attr.push(TokenTree::Token(
+                Token::from_ast_ident(Ident::new(name, span)),
+                Spacing::Alone,
+            ));
+        }
+        attr.push(delimited(
+            Delimiter::Parenthesis,
+            TokenStream::new(vec![
+                TokenTree::Token(
+                    Token::from_ast_ident(Ident::new(sym::targets, span)),
+                    Spacing::Alone,
+                ),
+                delimited(Delimiter::Parenthesis, targets),
+            ]),
+        ));
+
+        let mut expanded = TokenStream::new(vec![
+            TokenTree::token_alone(token::Pound, span),
+            delimited(Delimiter::Bracket, TokenStream::new(attr)),
+        ]);
+        expanded.push_stream(annotated);
+        Ok(expanded)
+    }
+}
+
+/// The tokens between the brackets of `targets = [...]`, if that is all the attribute
+/// holds and the list isn't empty.
+fn targets_list(annotation: &TokenStream) -> Option<TokenStream> {
+    let mut iter = annotation.iter();
+    match (iter.next(), iter.next(), iter.next(), iter.next()) {
+        (
+            Some(TokenTree::Token(name, _)),
+            Some(TokenTree::Token(Token { kind: token::Eq, .. }, _)),
+            Some(TokenTree::Delimited(_, _, Delimiter::Bracket, list)),
+            None,
+        ) if name.is_ident_named(sym::targets) && !list.is_empty() => Some(list.clone()),
+        _ => None,
+    }
+}
+
+pub(crate) fn expand(
+    ecx: &mut ExtCtxt<'_>,
+    _span: Span,
+    meta_item: &ast::MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    warn_on_duplicate_attribute(ecx, &item, sym::multiversion);
+
+    let Some(targets) = parse_targets(ecx, meta_item) else {
+        return vec![item];
+    };
+
+    // Allow using `#[multiversion]` on an item statement
+    let (fn_item, f, is_stmt) = if let Annotatable::Item(fn_item) = &item
+        && let ItemKind::Fn(f) = &fn_item.kind
+    {
+        (fn_item, f, false)
+    } else if let Annotatable::Stmt(stmt) = &item
+        && let StmtKind::Item(fn_item) = &stmt.kind
+        && let ItemKind::Fn(f) = &fn_item.kind
+    {
+        (fn_item, f, true)
+    } else {
+        ecx.dcx().emit_err(errors::MultiversionNotFn { span: item.span() });
+        return vec![item];
+    };
+
+    let unsupported = if f.sig.header.coroutine_kind.is_some() {
+        Some("`async` functions")
+    } else if let ast::Const::Yes(_) = f.sig.header.constness {
+        Some("`const` functions")
+    } else if f.sig.decl.c_variadic() {
+        Some("C-variadic functions")
+    } else if f.body.is_none() {
+        Some("functions without a body")
+    } else if let ast::FnRetTy::Ty(ty) = &f.sig.decl.output
+        && contains_impl_trait(ty)
+    {
+        // Each version would return a different opaque type.
+        Some("functions that return `impl Trait`")
+    } else {
+        None
+    };
+    if let Some(kind) = unsupported {
+        ecx.dcx().emit_err(errors::MultiversionUnsupported { span: fn_item.span, kind });
+        return vec![item];
+    }
+
+    // The feature detection macros are only in `std`. The attribute was generated by
+    // `#[multiversion]`, so the error points at that instead.
+    if ecx.no_std {
+        let span = meta_item.span.source_callsite();
+        ecx.dcx().emit_err(errors::MultiversionNoStd { span });
+        return vec![item];
+    }
+
+    let Some(detect) = feature_detection_macro(&ecx.sess.target.arch) else {
+        // Without runtime detection, only the default version could ever be called.
+        return vec![item];
+    };
+
+    let span = ecx.with_def_site_ctxt(fn_item.span);
+    let name = fn_item.ident;
+    let mut stmts = ThinVec::new();
+
+    // The versions of the function, the default one last.
+    let mut versions = Vec::with_capacity(targets.len() + 1);
+    for target in &targets {
+        let ident = Ident::from_str_and_span(&format!("{name}_{}", slug(target.1)), span);
+        let attr = target_feature_attr(ecx, &target.0);
+        stmts.push(ecx.stmt_item(span, version(ecx, span, f, ident, thin_vec![attr])));
+        versions.push(ident);
+    }
+    let default = Ident::from_str_and_span(&format!("{name}_default"), span);
+    stmts.push(ecx.stmt_item(span, version(ecx, span, f, default, AttrVec::new())));
+    versions.push(default);
+
+    // The parameters of the dispatcher, which may be patterns in the original function.
+    let mut params = ThinVec::with_capacity(f.sig.decl.inputs.len());
+    let mut args = ThinVec::with_capacity(f.sig.decl.inputs.len());
+    for (i, param) in f.sig.decl.inputs.iter().enumerate() {
+        let arg = Ident::from_str_and_span(&format!("arg{i}"), span);
+        params.push(ast::Param { pat: ecx.pat_ident(span, arg), ..param.clone() });
+        args.push(ecx.expr_ident(span, arg));
+    }
+
+    // Whether the resolved version depends on generic arguments.
+    let has_impl_trait_param = f.sig.decl.inputs.iter().any(|param| contains_impl_trait(&param.ty));
+    let is_generic = has_impl_trait_param
+        || f.generics.params.iter().any(|param| !matches!(param.kind, GenericParamKind::Lifetime));
+
+    let dispatch = if !is_generic && supports_ifunc(ecx) {
+        dispatch_through_ifunc(ecx, span, f, &targets, &versions, detect, params.clone(), args)
+    } else {
+        // Generic arguments can't be given explicitly when there is `impl Trait` in
+        // argument position, in which case they have to be inferred.
+        let generic_args: Vec<GenericArg> = if has_impl_trait_param {
+            Vec::new()
+        } else {
+            f.generics
+                .params
+                .iter()
+                .filter_map(|param| match param.kind {
+                    GenericParamKind::Lifetime => None,
+                    GenericParamKind::Type { .. } | GenericParamKind::Const { .. } => {
+                        Some(GenericArg::Type(ecx.ty_ident(span, param.ident)))
+                    }
+                })
+                .collect()
+        };
+        dispatch_through_static(ecx, span, &targets, &versions, detect, args, generic_args)
+    };
+    stmts.extend(dispatch);
+
+    let mut dispatcher = fn_item.clone();
+    dispatcher.kind = ItemKind::Fn(Box::new(ast::Fn {
+        sig: ast::FnSig { decl: ecx.fn_decl(params, f.sig.decl.output.clone()), ..f.sig.clone() },
+        define_opaque: None,
+        body: Some(ecx.block(span, stmts)),
+        ..(**f).clone()
+    }));
+
+    if is_stmt {
+        vec![Annotatable::Stmt(P(ecx.stmt_item(span, dispatcher)))]
+    } else {
+        vec![Annotatable::Item(dispatcher)]
+    }
+}
+
+/// Whether the target binds ifuncs when a program is loaded. The resolver may run before
+/// the program is fully relocated or initialized, which the feature detection of
+/// `std::arch` only copes with on x86, where it just executes `cpuid`.
+fn supports_ifunc(ecx: &ExtCtxt<'_>) -> bool {
+    let target = &ecx.sess.target;
+    target.binary_format == BinaryFormat::Elf
+        && matches!(&*target.arch, "x86" | "x86_64")
+        && ((target.os == "linux" && target.env == "gnu") || target.os == "freebsd")
+        // Miri can't run `global_asm!`.
+        && !ecx.sess.psess.config.contains(&(sym::miri, None))
+}
+
+/// The statements of a dispatcher that calls a symbol defined as an ifunc, whose resolver
+/// returns the first version that the CPU supports.
+fn dispatch_through_ifunc(
+    ecx: &ExtCtxt<'_>,
+    span: Span,
+    f: &ast::Fn,
+    targets: &[(MetaItemLit, Symbol)],
+    versions: &[Ident],
+    detect: &str,
+    params: ThinVec<ast::Param>,
+    args: ThinVec<P<Expr>>,
+) -> ThinVec<ast::Stmt> {
+    let mut stmts = ThinVec::new();
+
+    // `extern "C" fn resolve() -> *const () { if <supported> { f_avx2 as *const () } ... }`,
+    // where each version is cast to `unsafe fn(_) -> _` first.
+    let ptr = ecx.ty_ptr(span, ecx.ty(span, TyKind::Tup(ThinVec::new())), Mutability::Not);
+    let fn_ptr = ecx.ty(
+        span,
+        TyKind::BareFn(P(ast::BareFnTy {
+            safety: Safety::Unsafe(span),
+            ext: f.sig.header.ext.clone(),
+            generic_params: ThinVec::new(),
+            decl: ecx.fn_decl(
+                f.sig
+                    .decl
+                    .inputs
+                    .iter()
+                    .map(|_| ecx.param(span, Ident::new(kw::Empty, span), ecx.ty_infer(span)))
+                    .collect(),
+                match f.sig.decl.output {
+                    ast::FnRetTy::Default(_) => ast::FnRetTy::Default(span),
+                    ast::FnRetTy::Ty(_) => ast::FnRetTy::Ty(ecx.ty_infer(span)),
+                },
+            ),
+            decl_span: span,
+        })),
+    );
+    let address = |version| {
+        let version =
+            ecx.expr(span, ast::ExprKind::Cast(ecx.expr_ident(span, version), fn_ptr.clone()));
+        ecx.expr(span, ast::ExprKind::Cast(version, ptr.clone()))
+    };
+    let mut resolved = address(*versions.last().unwrap());
+    for (target, &version) in iter::zip(targets, versions).rev() {
+        resolved = ecx.expr_if(
+            span,
+            detected(ecx, span, detect, target.1),
+            address(version),
+            Some(resolved),
+        );
+    }
+    let resolve = Ident::from_str_and_span("resolve", span);
+    let resolver = ast::Fn {
+        defaultness: ast::Defaultness::Final,
+        generics: ast::Generics::default(),
+        sig: ast::FnSig {
+            header: ast::FnHeader {
+                ext: Extern::Explicit(str_lit(span, sym::C), span),
+                ..Default::default()
+            },
+            decl: ecx.fn_decl(ThinVec::new(), ast::FnRetTy::Ty(ptr)),
+            span,
+        },
+        contract: None,
+        define_opaque: None,
+        body: Some(ecx.block_expr(resolved)),
+    };
+    let resolver = ecx.item(span, resolve, AttrVec::new(), ItemKind::Fn(Box::new(resolver)));
+    stmts.push(ecx.stmt_item(span, resolver));
+
+    // The expansion hash includes the stable crate id, which keeps the symbol unique in
+    // the whole program.
+    let hash = ecx.current_expansion.id.to_expn_id().expn_hash();
+    let symbol = format!(
+        "__rust_multiversion_{:016x}{:016x}",
+        hash.stable_crate_id().as_u64(),
+        hash.local_hash().as_u64()
+    );
+
+    // `global_asm!(".globl SYMBOL", ".type SYMBOL, STT_GNU_IFUNC", ".set SYMBOL, {}", sym resolve)`
+    let template = |line: String| {
+        let lit = token::Lit::new(token::Str, Symbol::intern(&line), None);
+        [
+            TokenTree::token_alone(token::Literal(lit), span),
+            TokenTree::token_alone(token::Comma, span),
+        ]
+    };
+    let mut tokens = Vec::new();
+    tokens.extend(template(format!(".globl {symbol}")));
+    tokens.extend(template(format!(".type {symbol}, STT_GNU_IFUNC")));
+    tokens.extend(template(format!(".set {symbol}, {{}}")));
+    tokens.push(TokenTree::token_alone(token::Ident(sym::sym, token::IdentIsRaw::No), span));
+    tokens.push(TokenTree::Token(Token::from_ast_ident(resolve), Spacing::Alone));
+    let global_asm = ecx.path(span, ecx.std_path(&[sym::arch, sym::global_asm]));
+    let global_asm =
+        ecx.macro_call(span, global_asm, Delimiter::Parenthesis, TokenStream::new(tokens));
+    let global_asm = ecx.item(span, Ident::empty(), AttrVec::new(), ItemKind::MacCall(global_asm));
+    stmts.push(ecx.stmt_item(span, global_asm));
+
+    // `unsafe extern "Rust" { fn SYMBOL(arg0: T0, ...) -> R; }`, with the ABI of `f`.
+    let ifunc = Ident::from_str_and_span(&symbol, span);
+    let decl = ast::Fn {
+        defaultness: ast::Defaultness::Final,
+        generics: f.generics.clone(),
+        sig: ast::FnSig {
+            header: ast::FnHeader::default(),
+            decl: ecx.fn_decl(params, f.sig.decl.output.clone()),
+            span,
+        },
+        contract: None,
+        define_opaque: None,
+        body: None,
+    };
+    let decl = P(ast::ForeignItem {
+        attrs: AttrVec::new(),
+        id: ast::DUMMY_NODE_ID,
+        span,
+        vis: ast::Visibility {
+            span: span.shrink_to_lo(),
+            kind: ast::VisibilityKind::Inherited,
+            tokens: None,
+        },
+        ident: ifunc,
+        kind: ast::ForeignItemKind::Fn(Box::new(decl)),
+        tokens: None,
+    });
+    let abi = match &f.sig.header.ext {
+        Extern::None => Some(str_lit(span, sym::Rust)),
+        Extern::Implicit(_) => None,
+        Extern::Explicit(abi, _) => Some(abi.clone()),
+    };
+    let foreign_mod = ItemKind::ForeignMod(ast::ForeignMod {
+        extern_span: span,
+        safety: Safety::Unsafe(span),
+        abi,
+        items: thin_vec![decl],
+    });
+    stmts.push(ecx.stmt_item(span, ecx.item(span, Ident::empty(), AttrVec::new(), foreign_mod)));
+
+    // `unsafe { SYMBOL(arg0, ...) }`
+    let mut call = ecx.block_expr(ecx.expr_call_ident(span, ifunc, args));
+    call.rules = BlockCheckMode::Unsafe(UnsafeSource::CompilerGenerated);
+    stmts.push(ecx.stmt_expr(ecx.expr_block(call)));
+    stmts
+}
+
+/// The statements of a dispatcher that selects a version on its first call, and caches
+/// the choice in a static.
+fn dispatch_through_static(
+    ecx: &ExtCtxt<'_>,
+    span: Span,
+    targets: &[(MetaItemLit, Symbol)],
+    versions: &[Ident],
+    detect: &str,
+    args: ThinVec<P<Expr>>,
+    generic_args: Vec<GenericArg>,
+) -> ThinVec<ast::Stmt> {
+    let mut stmts = ThinVec::new();
+
+    // `static SELECTED: AtomicU8 = AtomicU8::new(0);`, where 0 means that no version has
+    // been selected yet, and `i + 1` that `versions[i]` has.
+    let selected = Ident::from_str_and_span("SELECTED", span);
+    let atomic_u8 = ecx.path(span, ecx.std_path(&[sym::sync, sym::atomic, sym::AtomicU8]));
+    let atomic_u8_new =
+        ecx.path(span, ecx.std_path(&[sym::sync, sym::atomic, sym::AtomicU8, sym::new]));
+    let init = ecx.expr_call(span, ecx.expr_path(atomic_u8_new), thin_vec![lit_u8(ecx, span, 0)]);
+    let selected_static =
+        ecx.item_static(span, selected, ecx.ty_path(atomic_u8), Mutability::Not, init);
+    stmts.push(ecx.stmt_item(span, selected_static));
+
+    let relaxed = || {
+        let path = ecx.std_path(&[sym::sync, sym::atomic, sym::Ordering, sym::Relaxed]);
+        ecx.expr_path(ecx.path(span, path))
+    };
+
+    // `let mut choice = SELECTED.load(Relaxed);`
+    let choice = Ident::from_str_and_span("choice", span);
+    let load = ecx.expr_method_call(
+        span,
+        ecx.expr_ident(span, selected),
+        Ident::new(sym::load, span),
+        thin_vec![relaxed()],
+    );
+    stmts.push(ecx.stmt_let(span, true, choice, load));
+
+    // `if choice == 0 { choice = <first supported target>; SELECTED.store(choice, Relaxed); }`
+    let mut resolved = lit_u8(ecx, span, versions.len());
+    for (i, target) in targets.iter().enumerate().rev() {
+        let supported = detected(ecx, span, detect, target.1);
+        resolved = ecx.expr_if(span, supported, lit_u8(ecx, span, i + 1), Some(resolved));
+    }
+    let assign =
+        ecx.expr(span, ast::ExprKind::Assign(ecx.expr_ident(span, choice), resolved, span));
+    let store = ecx.expr_method_call(
+        span,
+        ecx.expr_ident(span, selected),
+        Ident::new(sym::store, span),
+        thin_vec![ecx.expr_ident(span, choice), relaxed()],
+    );
+    let resolve = ecx.block(span, thin_vec![ecx.stmt_semi(assign), ecx.stmt_semi(store)]);
+    let unresolved =
+        ecx.expr_binary(span, BinOpKind::Eq, ecx.expr_ident(span, choice), lit_u8(ecx, span, 0));
+    let resolve = ecx.expr(span, ast::ExprKind::If(unresolved, resolve, None));
+    stmts.push(ecx.stmt_expr(resolve));
+
+    // `unsafe { match choice { 1 => f_avx2(arg0), ..., _ => f_default(arg0) } }`
+    let arms = versions
+        .iter()
+        .enumerate()
+        .map(|(i, &version)| {
+            let path = ecx.path_all(span, false, vec![version], generic_args.clone());
+            let call = ecx.expr_call(span, ecx.expr_path(path), args.clone());
+            let pat = if i == targets.len() {
+                ecx.pat_wild(span)
+            } else {
+                ecx.pat_lit(span, lit_u8(ecx, span, i + 1))
+            };
+            ecx.arm(span, pat, call)
+        })
+        .collect();
+    let dispatch = ecx.expr_match(span, ecx.expr_ident(span, choice), arms);
+    let mut dispatch = ecx.block_expr(dispatch);
+    dispatch.rules = BlockCheckMode::Unsafe(UnsafeSource::CompilerGenerated);
+    stmts.push(ecx.stmt_expr(ecx.expr_block(dispatch)));
+    stmts
+}
+
+/// Parses the `targets("avx2", "sse4.2,fma")` argument that `#[multiversion]` passes on
+/// into the literals of the feature sets and their contents.
+fn parse_targets(
+    ecx: &ExtCtxt<'_>,
+    meta_item: &ast::MetaItem,
+) -> Option<Vec<(MetaItemLit, Symbol)>> {
+    let malformed = |span| {
+        ecx.dcx().emit_err(errors::MultiversionMalformed { span });
+        None
+    };
+    let attr_span = meta_item.span.source_callsite();
+    let Some([MetaItemInner::MetaItem(targets)]) = meta_item.meta_item_list() else {
+        return malformed(attr_span);
+    };
+    if !targets.has_name(sym::targets) {
+        return malformed(attr_span);
+    }
+    let Some(list) = targets.meta_item_list() else {
+        return malformed(attr_span);
+    };
+    if list.is_empty() {
+        return malformed(attr_span);
+    }
+
+    // The entries were written by the user, so errors about them point there.
+    let mut parsed: Vec<(MetaItemLit, Symbol)> = Vec::with_capacity(list.len());
+    for entry in list {
+        let MetaItemInner::Lit(lit) = entry else { return malformed(entry.span()) };
+        let LitKind::Str(features, _) = lit.kind else { return malformed(lit.span) };
+        if features.as_str().split(',').any(|feature| feature.trim().is_empty()) {
+            return malformed(lit.span);
+        }
+        if let Some((first, _)) = parsed.iter().find(|(_, other)| slug(*other) == slug(features)) {
+            ecx.dcx().emit_err(errors::MultiversionDuplicateTarget {
+                span: lit.span,
+                first: first.span,
+            });
+            return None;
+        }
+        parsed.push((lit.clone(), features));
+    }
+    Some(parsed)
+}
+
+/// The `std::arch` macro that detects target features at runtime on `arch`.
+fn feature_detection_macro(arch: &str) -> Option<&'static str> {
+    Some(match arch {
+        "x86" | "x86_64" => "is_x86_feature_detected",
+        "aarch64" | "arm64ec" => "is_aarch64_feature_detected",
+        "arm" => "is_arm_feature_detected",
+        "riscv32" | "riscv64" => "is_riscv_feature_detected",
+        "loongarch64" => "is_loongarch_feature_detected",
+        "powerpc" => "is_powerpc_feature_detected",
+        "powerpc64" => "is_powerpc64_feature_detected",
+        "mips" => "is_mips_feature_detected",
+        "mips64" => "is_mips64_feature_detected",
+        "s390x" => "is_s390x_feature_detected",
+        _ => return None,
+    })
+}
+
+/// Whether the CPU supports all of `features`, e.g. for `"sse4.2,fma"`
+/// `::std::arch::is_x86_feature_detected!("sse4.2") && ...!("fma")`.
+fn detected(ecx: &ExtCtxt<'_>, span: Span, detect: &str, features: Symbol) -> P<Expr> {
+    features
+        .as_str()
+        .split(',')
+        .map(|feature| {
+            let feature = Symbol::intern(feature.trim());
+            let lit = token::Lit::new(token::Str, escape_string_symbol(feature), None);
+            let tokens = TokenStream::token_alone(token::Literal(lit), span);
+            let path = ecx.path_global(
+                span,
+                vec![
+                    Ident::new(sym::std, span),
+                    Ident::new(sym::arch, span),
+                    Ident::from_str_and_span(detect, span),
+                ],
+            );
+            ecx.expr_macro_call(span, ecx.macro_call(span, path, Delimiter::Parenthesis, tokens))
+        })
+        .reduce(|lhs, rhs| ecx.expr_binary(span, BinOpKind::And, lhs, rhs))
+        .unwrap()
+}
+
+/// A copy of `f` named `ident`, with the contract checked only by the dispatcher.
+fn version(
+    ecx: &ExtCtxt<'_>,
+    span: Span,
+    f: &ast::Fn,
+    ident: Ident,
+    attrs: AttrVec,
+) -> P<ast::Item> {
+    let kind = ItemKind::Fn(Box::new(ast::Fn { contract: None, ..f.clone() }));
+    ecx.item(span, ident, attrs, kind)
+}
+
+/// `#[target_feature(enable = "...")]`, spanned at the target it is generated for, so
+/// that unknown features are reported there.
+fn target_feature_attr(ecx: &ExtCtxt<'_>, target: &MetaItemLit) -> ast::Attribute {
+    let span = ecx.with_def_site_ctxt(target.span);
+    let tokens = TokenStream::new(vec![
+        TokenTree::token_alone(token::Ident(sym::enable, token::IdentIsRaw::No), span),
+        TokenTree::token_alone(token::Eq, span),
+        TokenTree::token_alone(token::Literal(target.as_token_lit()), span),
+    ]);
+    let args = AttrArgs::Delimited(DelimArgs {
+        dspan: DelimSpan::from_single(span),
+        delim: Delimiter::Parenthesis,
+        tokens,
+    });
+    let path = ast::Path::from_ident(Ident::new(sym::target_feature, span));
+    attr::mk_attr(
+        &ecx.sess.psess.attr_id_generator,
+        AttrStyle::Outer,
+        Safety::Default,
+        path,
+        args,
+        span,
+    )
+}
+
+/// The string literal `"{symbol}"`, e.g. for an ABI.
+fn str_lit(span: Span, symbol: Symbol) -> ast::StrLit {
+    ast::StrLit {
+        symbol,
+        suffix: None,
+        symbol_unescaped: symbol,
+        style: ast::StrStyle::Cooked,
+        span,
+    }
+}
+
+/// An unsuffixed integer literal, inferred to be a `u8` where it is used.
+fn lit_u8(ecx: &ExtCtxt<'_>, span: Span, n: usize) -> P<Expr> {
+    let lit = token::Lit::new(token::Integer, sym::integer(n), None);
+    ecx.expr(span, ast::ExprKind::Lit(lit))
+}
+
+/// The part of the name of a version that comes from its features, e.g. `sse4_2_fma`.
+fn slug(features: Symbol) -> String {
+    features
+        .as_str()
+        .split(',')
+        .map(|feature| feature.trim().replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn contains_impl_trait(ty: &ast::Ty) -> bool {
+    struct FindImplTrait;
+
+    impl<'a> Visitor<'a> for FindImplTrait {
+        type Result = ControlFlow<()>;
+
+        fn visit_ty(&mut self, ty: &'a ast::Ty) -> ControlFlow<()> {
+            if let TyKind::ImplTrait(..) = ty.kind {
+                return ControlFlow::Break(());
+            }
+            visit::walk_ty(self, ty)
+        }
+    }
+
+    FindImplTrait.visit_ty(ty).is_break()
+}
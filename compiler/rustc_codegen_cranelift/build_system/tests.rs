@@ -74,6 +74,39 @@ const fn jit_bin(config: &'static str, source: &'static str, args: &'static str)
         &[],
     ),
     TestCase::jit_bin("jit.std_example", "example/std_example.rs", "arg"),
+    TestCase::custom("jit.test_harness", &|runner| {
+        // Without `-Cprefer-dynamic` to load the standard library from its rlib.
+        for mode in ["-Cllvm-args=mode=jit", "-Cllvm-args=mode=jit-lazy"] {
+            let jit_test = |cfg: Option<&str>| {
+                let mut cmd = runner.rustc_command([
+                    "-Zunstable-options",
+                    mode,
+                    "--test",
+                    "-Zpanic-abort-tests",
+                    "--check-cfg=cfg(failing, should_panic)",
+                    "example/jit-test.rs",
+                ]);
+                if let Some(cfg) = cfg {
+                    cmd.arg("--cfg").arg(cfg);
+                }
+                cmd
+            };
+            spawn_and_wait(jit_test(None));
+
+            // A failing test has to fail the test run.
+            let output = jit_test(Some("failing")).output().unwrap();
+            assert!(!output.status.success(), "failing test passed with {mode}");
+
+            // `#[should_panic]` tests are rejected before running any test.
+            let output = jit_test(Some("should_panic")).output().unwrap();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(!output.status.success(), "should_panic test accepted with {mode}");
+            assert!(
+                stderr.contains("`#[should_panic]` tests can't be run in JIT mode"),
+                "unexpected output with {mode}:\n{stderr}"
+            );
+        }
+    }),
     TestCase::build_bin_and_run("aot.std_example", "example/std_example.rs", &["arg"]),
     TestCase::build_bin_and_run("aot.dst_field_align", "example/dst-field-align.rs", &[]),
     TestCase::build_bin_and_run(
@@ -90,6 +123,7 @@ const fn jit_bin(config: &'static str, source: &'static str, args: &'static str)
     TestCase::build_bin_and_run("aot.issue-72793", "example/issue-72793.rs", &[]),
     TestCase::build_bin("aot.issue-59326", "example/issue-59326.rs"),
     TestCase::build_bin_and_run("aot.neon", "example/neon.rs", &[]),
+    TestCase::build_bin_and_run("aot.inline-asm", "example/inline-asm.rs", &[]),
     TestCase::custom("aot.gen_block_iterate", &|runner| {
         runner.run_rustc([
             "example/gen_block_iterate.rs",
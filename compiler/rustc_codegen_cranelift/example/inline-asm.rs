@@ -0,0 +1,114 @@
+// Tests the operands of `asm!` and `global_asm!` that go beyond plain registers.
+
+#![feature(asm_goto_with_outputs)]
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::{asm, global_asm};
+
+// FIXME statics that aren't exported from their codegen unit can't be referenced from asm yet
+#[cfg(target_arch = "x86_64")]
+#[no_mangle]
+static mut COUNTER: u64 = 0;
+
+#[cfg(target_arch = "x86_64")]
+extern "C" fn increment() {
+    unsafe { COUNTER += 1 };
+}
+
+#[cfg(target_arch = "x86_64")]
+global_asm! {
+    ".globl call_increment",
+    "call_increment:",
+    "    push rax",
+    "    call {}",
+    "    pop rax",
+    "    ret",
+    sym increment,
+}
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    fn call_increment();
+}
+
+#[cfg(target_arch = "x86_64")]
+fn sym_fn() {
+    unsafe {
+        asm!("call {}", sym increment, clobber_abi("C"));
+        call_increment();
+        assert_eq!(std::ptr::addr_of!(COUNTER).read(), 2);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn sym_static() {
+    let value: u64;
+    unsafe {
+        asm!("mov {}, qword ptr [rip + {}]", out(reg) value, sym COUNTER);
+    }
+    assert_eq!(value, 2);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn goto_fallthrough() {
+    unsafe {
+        asm!("/* {} */", label {
+            unreachable!();
+        });
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn goto_jump() -> bool {
+    let mut jumped = false;
+    unsafe {
+        asm!("jmp {}", label {
+            jumped = true;
+        });
+    }
+    jumped
+}
+
+#[cfg(target_arch = "x86_64")]
+fn goto_select(cond: bool) -> u32 {
+    unsafe {
+        asm!(
+            "test {0}, {0}",
+            "jz {1}",
+            "jmp {2}",
+            in(reg) cond as u64,
+            label { return 1; },
+            label { return 2; },
+        );
+    }
+    unreachable!()
+}
+
+#[cfg(target_arch = "x86_64")]
+fn goto_with_output() -> u64 {
+    let out: u64;
+    unsafe {
+        asm!(
+            "lea {0}, [{1} + 1]",
+            "jmp {2}",
+            out(reg) out,
+            in(reg) 41u64,
+            label { return out; },
+        );
+    }
+    unreachable!()
+}
+
+#[cfg(target_arch = "x86_64")]
+fn main() {
+    sym_fn();
+    sym_static();
+    goto_fallthrough();
+    assert!(goto_jump());
+    assert_eq!(goto_select(false), 1);
+    assert_eq!(goto_select(true), 2);
+    assert_eq!(goto_with_output(), 42);
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn main() {}
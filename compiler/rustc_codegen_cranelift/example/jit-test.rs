@@ -0,0 +1,47 @@
+// Run as a test harness in JIT mode, with the standard library loaded from its rlib.
+
+use std::collections::HashMap;
+
+fn fib(n: u32) -> u64 {
+    if n < 2 { n as u64 } else { fib(n - 1) + fib(n - 2) }
+}
+
+#[test]
+fn fib_works() {
+    assert_eq!(fib(20), 6765);
+}
+
+#[test]
+fn uses_std() {
+    let mut map = HashMap::new();
+    for word in "a b a c b a".split(' ') {
+        *map.entry(word).or_insert(0) += 1;
+    }
+    assert_eq!(map["a"], 3);
+    assert_eq!(format!("{:?}", map.get("d")), "None");
+}
+
+#[test]
+fn spawns_thread() {
+    let handle = std::thread::spawn(|| fib(10));
+    assert_eq!(handle.join().unwrap(), 55);
+}
+
+#[test]
+#[ignore]
+fn ignored() {
+    panic!("ignored tests are not run");
+}
+
+#[cfg(failing)]
+#[test]
+fn fails() {
+    assert_eq!(fib(10), 0);
+}
+
+#[cfg(should_panic)]
+#[test]
+#[should_panic = "empty input"]
+fn panics() {
+    "".parse::<u32>().expect("empty input");
+}
@@ -5,6 +5,20 @@
 
 include!("../build_system/shared_utils.rs");
 
+const JIT_TEST_HELP: &str = "\
+Builds the tests of a package and runs them in JIT mode, without linking a test executable
+
+Usage: cargo-clif jit-test [CARGO RUSTC OPTIONS]
+
+The options are passed to `cargo rustc --profile test`, for example `--lib` or `--test <NAME>`.
+Arguments for the test harness, like a test name filter, can be passed in `CG_CLIF_JIT_ARGS`.
+
+Limitations:
+  cg_clif doesn't support unwinding in JIT mode, so the tests are run one after another in the
+  rustc process, and the first test that panics aborts the whole test run. Only one failure is
+  reported; the last test that libtest names before the abort is the one that failed. Crates with
+  `#[should_panic]` tests are rejected, and have to be tested without JIT mode.";
+
 fn main() {
     let current_exe = env::current_exe().unwrap();
     let mut sysroot = current_exe.parent().unwrap();
@@ -66,6 +80,29 @@ fn main() {
                 ])
                 .collect()
         }
+        Some("jit-test") => {
+            args.remove(0);
+            if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+                println!("{JIT_TEST_HELP}");
+                return;
+            }
+            eprintln!(
+                "note: the tests run in one process, and the first test that panics aborts the \
+                 test run"
+            );
+            IntoIterator::into_iter([
+                "rustc".to_string(),
+                "--profile".to_string(),
+                "test".to_string(),
+            ])
+            .chain(args)
+            .chain([
+                "--".to_string(),
+                "-Zunstable-options".to_string(),
+                "-Cllvm-args=mode=jit".to_string(),
+            ])
+            .collect()
+        }
         _ => args,
     };
 
@@ -12,6 +12,7 @@
 use rustc_codegen_ssa::assert_module_sources::CguReuse;
 use rustc_codegen_ssa::back::link::ensure_removed;
 use rustc_codegen_ssa::back::metadata::create_compressed_metadata_file;
+use rustc_codegen_ssa::back::write::print_object_file_reports;
 use rustc_codegen_ssa::base::determine_cgu_reuse;
 use rustc_codegen_ssa::{
     CodegenResults, CompiledModule, CrateInfo, ModuleKind, errors as ssa_errors,
@@ -136,6 +137,7 @@ pub(crate) fn join(
             crate_info: self.crate_info,
         };
 
+        print_object_file_reports(sess, &codegen_results.crate_info, &codegen_results.modules);
         produce_final_output_artifacts(sess, &codegen_results, outputs);
 
         (codegen_results, work_products)
@@ -330,7 +332,7 @@ fn produce_final_output_artifacts(
     // These are used in linking steps and will be cleaned up afterward.
 }
 
-fn make_module(sess: &Session, name: String) -> UnwindModule<ObjectModule> {
+pub(crate) fn make_module(sess: &Session, name: String) -> UnwindModule<ObjectModule> {
     let isa = crate::build_isa(sess);
 
     let mut builder =
@@ -548,7 +550,7 @@ fn codegen_cgu_content(
                 }
             }
             MonoItem::GlobalAsm(item_id) => {
-                crate::global_asm::codegen_global_asm_item(tcx, &mut cx.global_asm, item_id);
+                crate::global_asm::codegen_global_asm_item(tcx, &mut cx, module, item_id);
             }
         }
     }
@@ -3,15 +3,25 @@
 
 use std::cell::RefCell;
 use std::ffi::CString;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::{Mutex, OnceLock, mpsc};
 
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
 use cranelift_jit::{JITBuilder, JITModule};
+use object::read::archive::ArchiveFile;
 use rustc_codegen_ssa::CrateInfo;
+use rustc_codegen_ssa::back::link::{linker_and_flavor, relevant_lib};
+use rustc_hir::def_id::CrateNum;
+use rustc_metadata::METADATA_FILENAME;
 use rustc_middle::mir::mono::MonoItem;
-use rustc_session::Session;
+use rustc_session::search_paths::PathKind;
+use rustc_session::utils::NativeLibKind;
 use rustc_span::sym;
+use rustc_target::spec::{BinaryFormat, Cc, LinkerFlavor, PanicStrategy};
 
 use crate::debuginfo::TypeDebugContext;
 use crate::prelude::*;
@@ -68,7 +78,7 @@ fn create_jit_module(tcx: TyCtxt<'_>, hotswap: bool) -> (UnwindModule<JITModule>
     let mut jit_builder = JITBuilder::with_isa(isa, cranelift_module::default_libcall_names());
     jit_builder.hotswap(hotswap);
     crate::compiler_builtins::register_functions_for_jit(&mut jit_builder);
-    jit_builder.symbol_lookup_fn(dep_symbol_lookup_fn(tcx.sess, crate_info));
+    jit_builder.symbol_lookup_fn(dep_symbol_lookup_fn(tcx, crate_info));
     jit_builder.symbol("__clif_jit_fn", clif_jit_fn as *const u8);
     let mut jit_module = UnwindModule::new(JITModule::new(jit_builder), false);
 
@@ -79,7 +89,7 @@ fn create_jit_module(tcx: TyCtxt<'_>, hotswap: bool) -> (UnwindModule<JITModule>
     (jit_module, cx)
 }
 
-pub(crate) fn run_jit(tcx: TyCtxt<'_>, codegen_mode: CodegenMode, jit_args: Vec<String>) -> ! {
+pub(crate) fn run_jit(tcx: TyCtxt<'_>, codegen_mode: CodegenMode, mut jit_args: Vec<String>) -> ! {
     if !tcx.sess.opts.output_types.should_codegen() {
         tcx.dcx().fatal("JIT mode doesn't work with `cargo check`");
     }
@@ -88,6 +98,41 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, codegen_mode: CodegenMode, jit_args: Vec<
         tcx.dcx().fatal("can't jit non-executable crate");
     }
 
+    // With `-Zpanic-abort-tests` libtest runs every test in a new process of the current
+    // executable, which is rustc itself in JIT mode. Run the tests in this process instead. This
+    // means that the first test that panics aborts the whole test run, so the tests are run one at
+    // a time, for which libtest prints the name of each test before running it. That way the last
+    // test named before the abort is the one that failed.
+    if tcx.sess.is_test_crate()
+        && tcx.sess.panic_strategy() == PanicStrategy::Abort
+        && tcx.sess.opts.unstable_opts.panic_abort_tests
+        && !tcx.hir_krate_attrs().iter().any(|attr| attr.has_name(sym::test_runner))
+    {
+        // A test that has to panic to pass would abort the test run, so reject those before
+        // spending any time on codegen.
+        for def_id in tcx.hir_crate_items(()).definitions() {
+            if tcx.has_attr(def_id, sym::should_panic) {
+                tcx.dcx()
+                    .struct_span_err(
+                        tcx.def_span(def_id),
+                        "`#[should_panic]` tests can't be run in JIT mode",
+                    )
+                    .with_note(
+                        "JIT mode runs the tests in the rustc process without unwinding support, \
+                         where any panic aborts the whole test run",
+                    )
+                    .with_help("run the tests without `-Cllvm-args=mode=jit`")
+                    .emit();
+            }
+        }
+        tcx.dcx().abort_if_errors();
+
+        jit_args.push("-Zunstable-options".to_owned());
+        jit_args.push("--force-run-in-process".to_owned());
+        // libtest uses the first `--test-threads`, so this overrides any given in `CG_CLIF_JIT_ARGS`.
+        jit_args.insert(0, "--test-threads=1".to_owned());
+    }
+
     let (mut jit_module, mut cx) =
         create_jit_module(tcx, matches!(codegen_mode, CodegenMode::JitLazy));
     let mut cached_context = Context::new();
@@ -280,12 +325,13 @@ fn jit_fn(instance_ptr: *const Instance<'static>, trampoline_ptr: *const u8) ->
 }
 
 fn dep_symbol_lookup_fn(
-    sess: &Session,
+    tcx: TyCtxt<'_>,
     crate_info: CrateInfo,
 ) -> Box<dyn Fn(&str) -> Option<*const u8> + Send> {
     use rustc_middle::middle::dependency_format::Linkage;
 
     let mut dylib_paths = Vec::new();
+    let mut static_crates = Vec::new();
 
     let data = &crate_info.dependency_formats[&rustc_session::config::CrateType::Executable];
     // `used_crates` is in reverse postorder in terms of dependencies. Reverse the order here to
@@ -296,18 +342,20 @@ fn dep_symbol_lookup_fn(
         let src = &crate_info.used_crate_source[&cnum];
         match data[cnum] {
             Linkage::NotLinked | Linkage::IncludedFromDylib => {}
-            Linkage::Static => {
-                let name = crate_info.crate_name[&cnum];
-                let mut diag = sess.dcx().struct_err(format!("Can't load static lib {}", name));
-                diag.note("rustc_codegen_cranelift can only load dylibs in JIT mode.");
-                diag.emit();
-            }
+            Linkage::Static => static_crates.push(cnum),
             Linkage::Dynamic => {
                 dylib_paths.push(src.dylib.as_ref().unwrap().0.clone());
             }
         }
     }
 
+    // The statically linked crates may depend on any of the dylibs, so they are loaded last.
+    if !static_crates.is_empty() {
+        if let Some(path) = link_static_deps(tcx, &crate_info, &static_crates, &dylib_paths) {
+            dylib_paths.push(path);
+        }
+    }
+
     let imported_dylibs = Box::leak(
         dylib_paths
             .into_iter()
@@ -315,7 +363,7 @@ fn dep_symbol_lookup_fn(
             .collect::<Box<[_]>>(),
     );
 
-    sess.dcx().abort_if_errors();
+    tcx.dcx().abort_if_errors();
 
     Box::new(move |sym_name| {
         for dylib in &*imported_dylibs {
@@ -327,6 +375,195 @@ fn dep_symbol_lookup_fn(
     })
 }
 
+/// Links the rlibs of the statically linked dependencies into a shared library, which can then be
+/// loaded like the dylib dependencies.
+///
+/// The shared library is kept next to the output and is only linked again when one of its inputs
+/// changes, so running the tests again after changing only the local crate doesn't need a linker.
+fn link_static_deps(
+    tcx: TyCtxt<'_>,
+    crate_info: &CrateInfo,
+    static_crates: &[CrateNum],
+    dylib_paths: &[PathBuf],
+) -> Option<PathBuf> {
+    let sess = tcx.sess;
+
+    let (linker, flavor) = linker_and_flavor(sess);
+    if sess.target.binary_format != BinaryFormat::Elf
+        || !matches!(flavor, LinkerFlavor::Gnu(Cc::Yes, _))
+    {
+        for cnum in static_crates {
+            let name = crate_info.crate_name[cnum];
+            let mut diag = sess.dcx().struct_err(format!("Can't load static lib {}", name));
+            diag.note(
+                "rustc_codegen_cranelift can only load rlibs in JIT mode on ELF targets linked \
+                 using a C compiler.",
+            );
+            diag.emit();
+        }
+        return None;
+    }
+
+    // The allocator shim is part of the shared library, as the dependencies call it. A global
+    // allocator of the local crate would have to be called from the shared library too.
+    if tcx.has_global_allocator(LOCAL_CRATE) {
+        sess.dcx().fatal(
+            "JIT mode doesn't support a `#[global_allocator]` in a crate with rlib dependencies",
+        );
+    }
+    let mut allocator_module = super::aot::make_module(sess, "allocator_shim".to_owned());
+    let allocator_shim = crate::allocator::codegen(tcx, &mut allocator_module)
+        .then(|| allocator_module.finish().object.write().unwrap());
+
+    let mut native_args = vec![];
+    for search_path in sess.target_filesearch().cli_search_paths(PathKind::Native) {
+        native_args.push(format!("-L{}", search_path.dir.display()));
+    }
+    for cnum in static_crates {
+        for lib in crate_info.native_libraries[cnum].iter().filter(|lib| relevant_lib(sess, lib)) {
+            let colon = if lib.verbatim { ":" } else { "" };
+            match lib.kind {
+                // Bundled static libraries are part of the rlib.
+                NativeLibKind::Static { bundle: None | Some(true), .. } => {}
+                NativeLibKind::Static { bundle: Some(false), .. }
+                | NativeLibKind::Dylib { .. }
+                | NativeLibKind::Unspecified => {
+                    native_args.push(format!("-l{colon}{}", lib.name));
+                }
+                NativeLibKind::LinkArg => native_args.push(lib.name.to_string()),
+                NativeLibKind::RawDylib
+                | NativeLibKind::Framework { .. }
+                | NativeLibKind::WasmImportModule => {}
+            }
+        }
+    }
+
+    let rlibs: Vec<&Path> = static_crates
+        .iter()
+        .map(|cnum| &*crate_info.used_crate_source[cnum].rlib.as_ref().unwrap().0)
+        .collect();
+
+    let mut hasher = DefaultHasher::new();
+    linker.hash(&mut hasher);
+    for rlib in &rlibs {
+        rlib.hash(&mut hasher);
+        if let Ok(metadata) = fs::metadata(rlib) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    dylib_paths.hash(&mut hasher);
+    native_args.hash(&mut hasher);
+    allocator_shim.hash(&mut hasher);
+
+    let out_dir = &tcx.output_filenames(()).out_directory;
+    let prefix = format!("{}.jit-deps-", tcx.crate_name(LOCAL_CRATE));
+    let out_path = out_dir.join(format!("{prefix}{:016x}.so", hasher.finish()));
+    if out_path.exists() {
+        return Some(out_path);
+    }
+
+    // Remove the shared libraries for earlier versions of the dependencies.
+    if let Ok(entries) = fs::read_dir(out_dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_str().is_some_and(|name| name.starts_with(&prefix)) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    let tmp_dir = out_path.with_extension("tmp");
+    let res = tcx.sess.time("link static dependencies for jit", || {
+        link_shared_library(
+            &linker,
+            &tmp_dir,
+            &out_path,
+            &rlibs,
+            allocator_shim.as_deref(),
+            &native_args,
+            dylib_paths,
+        )
+    });
+    let _ = fs::remove_dir_all(&tmp_dir);
+    if let Err(err) = res {
+        sess.dcx().fatal(format!("failed to link the rlib dependencies for JIT mode: {err}"));
+    }
+
+    Some(out_path)
+}
+
+fn link_shared_library(
+    linker: &Path,
+    tmp_dir: &Path,
+    out_path: &Path,
+    rlibs: &[&Path],
+    allocator_shim: Option<&[u8]>,
+    native_args: &[String],
+    dylib_paths: &[PathBuf],
+) -> Result<(), String> {
+    fs::create_dir_all(tmp_dir).map_err(|err| err.to_string())?;
+
+    let mut objects = vec![];
+    let mut archives = vec![];
+    for (i, rlib) in rlibs.iter().enumerate() {
+        let data = fs::read(rlib).map_err(|err| format!("{}: {err}", rlib.display()))?;
+        let archive =
+            ArchiveFile::parse(&*data).map_err(|err| format!("{}: {err}", rlib.display()))?;
+        for (j, member) in archive.members().enumerate() {
+            let member = member.map_err(|err| format!("{}: {err}", rlib.display()))?;
+            if member.name() == METADATA_FILENAME.as_bytes() {
+                continue;
+            }
+            let member_data =
+                member.data(&*data).map_err(|err| format!("{}: {err}", rlib.display()))?;
+            // Bundled static libraries are archives in the rlib.
+            let path = if member_data.starts_with(b"!<arch>\n") {
+                let path = tmp_dir.join(format!("{i}-{j}.a"));
+                archives.push(path.clone());
+                path
+            } else if object::File::parse(member_data).is_ok() {
+                let path = tmp_dir.join(format!("{i}-{j}.o"));
+                objects.push(path.clone());
+                path
+            } else {
+                continue;
+            };
+            fs::write(&path, member_data).map_err(|err| err.to_string())?;
+        }
+    }
+    if let Some(allocator_shim) = allocator_shim {
+        let path = tmp_dir.join("allocator_shim.o");
+        fs::write(&path, allocator_shim).map_err(|err| err.to_string())?;
+        objects.push(path);
+    }
+
+    let tmp_out_path = tmp_dir.join("out.so");
+    let mut cmd = Command::new(linker);
+    // `-Bsymbolic` makes the dependencies use their own definitions, rather than those of any
+    // other copy of the standard library that rustc itself may have loaded.
+    cmd.arg("-shared").arg("-Wl,-Bsymbolic").arg("-o").arg(&tmp_out_path);
+    cmd.args(&objects).args(&archives);
+    cmd.args(native_args);
+    for dylib in dylib_paths {
+        cmd.arg(dylib);
+        if let Some(dir) = dylib.parent() {
+            cmd.arg(format!("-Wl,-rpath,{}", dir.display()));
+        }
+    }
+    let output =
+        cmd.output().map_err(|err| format!("failed to run `{}`: {err}", linker.display()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`{}` failed with {}\n{}",
+            linker.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr),
+        ));
+    }
+
+    fs::rename(&tmp_out_path, out_path).map_err(|err| err.to_string())
+}
+
 fn codegen_shim<'tcx>(
     tcx: TyCtxt<'tcx>,
     cached_context: &mut Context,
@@ -14,7 +14,12 @@
 
 use crate::prelude::*;
 
-pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String, item_id: ItemId) {
+pub(crate) fn codegen_global_asm_item(
+    tcx: TyCtxt<'_>,
+    cx: &mut crate::CodegenCx,
+    module: &mut dyn Module,
+    item_id: ItemId,
+) {
     let item = tcx.hir_item(item_id);
     if let rustc_hir::ItemKind::GlobalAsm { asm, .. } = item.kind {
         let is_x86 =
@@ -22,14 +27,14 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
 
         if is_x86 {
             if !asm.options.contains(InlineAsmOptions::ATT_SYNTAX) {
-                global_asm.push_str("\n.intel_syntax noprefix\n");
+                cx.global_asm.push_str("\n.intel_syntax noprefix\n");
             } else {
-                global_asm.push_str("\n.att_syntax\n");
+                cx.global_asm.push_str("\n.att_syntax\n");
             }
         }
         for piece in asm.template {
             match *piece {
-                InlineAsmTemplatePiece::String(ref s) => global_asm.push_str(s),
+                InlineAsmTemplatePiece::String(ref s) => cx.global_asm.push_str(s),
                 InlineAsmTemplatePiece::Placeholder { operand_idx, modifier: _, span: op_sp } => {
                     match asm.operands[operand_idx].0 {
                         InlineAsmOperand::Const { ref anon_const } => {
@@ -44,7 +49,7 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
                                         const_value,
                                         FullyMonomorphizedLayoutCx(tcx).layout_of(ty),
                                     );
-                                    global_asm.push_str(&string);
+                                    cx.global_asm.push_str(&string);
                                 }
                                 Err(ErrorHandled::Reported { .. }) => {
                                     // An error has already been reported and compilation is
@@ -56,34 +61,18 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
                             }
                         }
                         InlineAsmOperand::SymFn { expr } => {
-                            if cfg!(not(feature = "inline_asm_sym")) {
-                                tcx.dcx().span_err(
-                                    item.span,
-                                    "asm! and global_asm! sym operands are not yet supported",
-                                );
-                            }
-
                             let ty = tcx.typeck(item_id.owner_id).expr_ty(expr);
                             let instance = match ty.kind() {
                                 &ty::FnDef(def_id, args) => Instance::new(def_id, args),
                                 _ => span_bug!(op_sp, "asm sym is not a function"),
                             };
-                            let symbol = tcx.symbol_name(instance);
-                            // FIXME handle the case where the function was made private to the
-                            // current codegen unit
-                            global_asm.push_str(symbol.name);
+                            let symbol =
+                                crate::inline_asm::sym_fn_wrapper(tcx, cx, module, instance);
+                            cx.global_asm.push_str(&symbol);
                         }
                         InlineAsmOperand::SymStatic { path: _, def_id } => {
-                            if cfg!(not(feature = "inline_asm_sym")) {
-                                tcx.dcx().span_err(
-                                    item.span,
-                                    "asm! and global_asm! sym operands are not yet supported",
-                                );
-                            }
-
-                            let instance = Instance::mono(tcx, def_id);
-                            let symbol = tcx.symbol_name(instance);
-                            global_asm.push_str(symbol.name);
+                            let symbol = crate::inline_asm::sym_static_symbol(tcx, op_sp, def_id);
+                            cx.global_asm.push_str(&symbol);
                         }
                         InlineAsmOperand::In { .. }
                         | InlineAsmOperand::Out { .. }
@@ -97,9 +86,9 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
             }
         }
 
-        global_asm.push('\n');
+        cx.global_asm.push('\n');
         if is_x86 {
-            global_asm.push_str(".att_syntax\n\n");
+            cx.global_asm.push_str(".att_syntax\n\n");
         }
     } else {
         bug!("Expected GlobalAsm found {:?}", item);
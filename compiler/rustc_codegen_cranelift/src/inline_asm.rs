@@ -33,6 +33,10 @@ pub(crate) enum CInlineAsmOperand<'tcx> {
     Symbol {
         symbol: String,
     },
+    Label {
+        /// The index of the target in the `targets` of the terminator.
+        target_index: usize,
+    },
 }
 
 pub(crate) fn codegen_inline_asm_terminator<'tcx>(
@@ -41,7 +45,7 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
     template: &[InlineAsmTemplatePiece],
     operands: &[InlineAsmOperand<'tcx>],
     options: InlineAsmOptions,
-    destination: Option<mir::BasicBlock>,
+    targets: &[mir::BasicBlock],
 ) {
     // Used by panic_abort on Windows, but uses a syntax which only happens to work with
     // asm!() by accident and breaks with the GNU assembler as well as global_asm!() for
@@ -82,12 +86,6 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
                 CInlineAsmOperand::Const { value }
             }
             InlineAsmOperand::SymFn { ref value } => {
-                if cfg!(not(feature = "inline_asm_sym")) {
-                    fx.tcx
-                        .dcx()
-                        .span_err(span, "asm! and global_asm! sym operands are not yet supported");
-                }
-
                 let const_ = fx.monomorphize(value.const_);
                 if let ty::FnDef(def_id, args) = *const_.ty().kind() {
                     let instance = ty::Instance::resolve_for_fn_ptr(
@@ -97,56 +95,111 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
                         args,
                     )
                     .unwrap();
-                    let symbol = fx.tcx.symbol_name(instance);
-
-                    // Pass a wrapper rather than the function itself as the function itself may not
-                    // be exported from the main codegen unit and may thus be unreachable from the
-                    // object file created by an external assembler.
-                    let wrapper_name = format!(
-                        "__inline_asm_{}_wrapper_n{}",
-                        fx.cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
-                        fx.cx.inline_asm_index
-                    );
-                    fx.cx.inline_asm_index += 1;
-                    let sig =
-                        get_function_sig(fx.tcx, fx.target_config.default_call_conv, instance);
-                    create_wrapper_function(fx.module, sig, &wrapper_name, symbol.name);
-
-                    CInlineAsmOperand::Symbol { symbol: wrapper_name }
+                    let symbol = sym_fn_wrapper(fx.tcx, fx.cx, fx.module, instance);
+                    CInlineAsmOperand::Symbol { symbol }
                 } else {
                     span_bug!(span, "invalid type for asm sym (fn)");
                 }
             }
             InlineAsmOperand::SymStatic { def_id } => {
-                assert!(fx.tcx.is_static(def_id));
-                let instance = Instance::mono(fx.tcx, def_id);
-                CInlineAsmOperand::Symbol { symbol: fx.tcx.symbol_name(instance).name.to_owned() }
-            }
-            InlineAsmOperand::Label { .. } => {
-                span_bug!(span, "asm! label operands are not yet supported");
+                CInlineAsmOperand::Symbol { symbol: sym_static_symbol(fx.tcx, span, def_id) }
             }
+            InlineAsmOperand::Label { target_index } => CInlineAsmOperand::Label { target_index },
         })
         .collect::<Vec<_>>();
 
-    codegen_inline_asm_inner(fx, template, &operands, options);
+    let label_index = codegen_inline_asm_inner(fx, template, &operands, options);
 
-    match destination {
-        Some(destination) => {
-            let destination_block = fx.get_block(destination);
-            fx.bcx.ins().jump(destination_block, &[]);
-        }
-        None => {
+    // The first target is the fallthrough destination, unless the asm doesn't return.
+    let fallthrough_block = if options.contains(InlineAsmOptions::NORETURN) {
+        None
+    } else {
+        Some(fx.get_block(targets[0]))
+    };
+
+    match label_index {
+        Some(label_index) => {
+            let mut switch = ::cranelift_frontend::Switch::new();
+            let mut has_entry = vec![false; targets.len()];
+            for operand in &operands {
+                if let CInlineAsmOperand::Label { target_index } = *operand {
+                    // Several labels may jump to the same target.
+                    if !std::mem::replace(&mut has_entry[target_index], true) {
+                        let block = fx.get_block(targets[target_index]);
+                        switch.set_entry(target_index as u128 + 1, block);
+                    }
+                }
+            }
+            if let Some(fallthrough_block) = fallthrough_block {
+                switch.set_entry(0, fallthrough_block);
+            }
+            let unreachable_block = fx.bcx.create_block();
+            fx.bcx.set_cold_block(unreachable_block);
+            switch.emit(&mut fx.bcx, label_index, unreachable_block);
+            fx.bcx.switch_to_block(unreachable_block);
             fx.bcx.ins().trap(TrapCode::user(1 /* unreachable */).unwrap());
         }
+        None => match fallthrough_block {
+            Some(fallthrough_block) => {
+                fx.bcx.ins().jump(fallthrough_block, &[]);
+            }
+            None => {
+                fx.bcx.ins().trap(TrapCode::user(1 /* unreachable */).unwrap());
+            }
+        },
     }
 }
 
+/// Returns the name of a wrapper around the function of a `sym` operand.
+///
+/// The asm is assembled into a separate object file by an external assembler, which can't refer
+/// to the function itself if it isn't exported from its codegen unit, so it refers to an exported
+/// wrapper instead.
+pub(crate) fn sym_fn_wrapper<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    cx: &mut crate::CodegenCx,
+    module: &mut dyn Module,
+    instance: Instance<'tcx>,
+) -> String {
+    let symbol = tcx.symbol_name(instance);
+    let wrapper_name = format!(
+        "__inline_asm_{}_wrapper_n{}",
+        cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
+        cx.inline_asm_index
+    );
+    cx.inline_asm_index += 1;
+    let sig = get_function_sig(tcx, module.target_config().default_call_conv, instance);
+    create_wrapper_function(module, sig, &wrapper_name, symbol.name);
+    wrapper_name
+}
+
+/// Returns the symbol of the static of a `sym` operand.
+///
+/// Unlike for functions there is no wrapper that could stand in for the static, so the asm can
+/// only refer to it if it isn't local to its object file. This is the case for every static
+/// except those with `#[linkage = "internal"]`.
+pub(crate) fn sym_static_symbol(tcx: TyCtxt<'_>, span: Span, def_id: DefId) -> String {
+    assert!(tcx.is_static(def_id));
+    if def_id.is_local()
+        && matches!(crate::linkage::get_static_linkage(tcx, def_id), Linkage::Local)
+    {
+        tcx.dcx().span_err(
+            span,
+            "asm! and global_asm! sym operands can't refer to statics with internal linkage",
+        );
+    }
+    let instance = Instance::mono(tcx, def_id);
+    tcx.symbol_name(instance).name.to_owned()
+}
+
+/// Returns which label the asm jumped to if it has label operands: 0 if it fell through, or else
+/// the index of the target of the label plus one.
 pub(crate) fn codegen_inline_asm_inner<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     template: &[InlineAsmTemplatePiece],
     operands: &[CInlineAsmOperand<'tcx>],
     options: InlineAsmOptions,
-) {
+) -> Option<Value> {
     // FIXME add .eh_frame unwind info directives
 
     let mut asm_gen = InlineAssemblyGenerator {
@@ -160,6 +213,7 @@ pub(crate) fn codegen_inline_asm_inner<'tcx>(
         stack_slots_clobber: Vec::new(),
         stack_slots_input: Vec::new(),
         stack_slots_output: Vec::new(),
+        stack_slot_label: None,
         stack_slot_size: Size::from_bytes(0),
         is_naked: false,
     };
@@ -194,11 +248,20 @@ pub(crate) fn codegen_inline_asm_inner<'tcx>(
                     outputs.push((asm_gen.stack_slots_output[i].unwrap(), *out_place));
                 }
             }
-            CInlineAsmOperand::Const { value: _ } | CInlineAsmOperand::Symbol { symbol: _ } => {}
+            CInlineAsmOperand::Const { value: _ }
+            | CInlineAsmOperand::Symbol { symbol: _ }
+            | CInlineAsmOperand::Label { target_index: _ } => {}
         }
     }
 
-    call_inline_asm(fx, &asm_name, asm_gen.stack_slot_size, inputs, outputs);
+    call_inline_asm(
+        fx,
+        &asm_name,
+        asm_gen.stack_slot_size,
+        inputs,
+        outputs,
+        asm_gen.stack_slot_label,
+    )
 }
 
 pub(crate) fn codegen_naked_asm<'tcx>(
@@ -241,11 +304,6 @@ pub(crate) fn codegen_naked_asm<'tcx>(
                 CInlineAsmOperand::Const { value }
             }
             InlineAsmOperand::SymFn { ref value } => {
-                if cfg!(not(feature = "inline_asm_sym")) {
-                    tcx.dcx()
-                        .span_err(span, "asm! and global_asm! sym operands are not yet supported");
-                }
-
                 let const_ = instance.instantiate_mir_and_normalize_erasing_regions(
                     tcx,
                     ty::TypingEnv::fully_monomorphized(),
@@ -259,33 +317,17 @@ pub(crate) fn codegen_naked_asm<'tcx>(
                         args,
                     )
                     .unwrap();
-                    let symbol = tcx.symbol_name(instance);
-
-                    // Pass a wrapper rather than the function itself as the function itself may not
-                    // be exported from the main codegen unit and may thus be unreachable from the
-                    // object file created by an external assembler.
-                    let wrapper_name = format!(
-                        "__inline_asm_{}_wrapper_n{}",
-                        cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
-                        cx.inline_asm_index
-                    );
-                    cx.inline_asm_index += 1;
-                    let sig =
-                        get_function_sig(tcx, module.target_config().default_call_conv, instance);
-                    create_wrapper_function(module, sig, &wrapper_name, symbol.name);
-
-                    CInlineAsmOperand::Symbol { symbol: wrapper_name }
+                    let symbol = sym_fn_wrapper(tcx, cx, module, instance);
+                    CInlineAsmOperand::Symbol { symbol }
                 } else {
                     span_bug!(span, "invalid type for asm sym (fn)");
                 }
             }
             InlineAsmOperand::SymStatic { def_id } => {
-                assert!(tcx.is_static(def_id));
-                let instance = Instance::mono(tcx, def_id);
-                CInlineAsmOperand::Symbol { symbol: tcx.symbol_name(instance).name.to_owned() }
+                CInlineAsmOperand::Symbol { symbol: sym_static_symbol(tcx, span, def_id) }
             }
             InlineAsmOperand::Label { .. } => {
-                span_bug!(span, "asm! label operands are not yet supported");
+                span_bug!(span, "invalid operand type for naked asm")
             }
         })
         .collect::<Vec<_>>();
@@ -301,6 +343,7 @@ pub(crate) fn codegen_naked_asm<'tcx>(
         stack_slots_clobber: Vec::new(),
         stack_slots_input: Vec::new(),
         stack_slots_output: Vec::new(),
+        stack_slot_label: None,
         stack_slot_size: Size::from_bytes(0),
         is_naked: true,
     };
@@ -320,6 +363,8 @@ struct InlineAssemblyGenerator<'a, 'tcx> {
     stack_slots_clobber: Vec<Option<Size>>,
     stack_slots_input: Vec<Option<Size>>,
     stack_slots_output: Vec<Option<Size>>,
+    /// Where the asm stores which label it jumped to, if it has label operands.
+    stack_slot_label: Option<Size>,
     stack_slot_size: Size,
     is_naked: bool,
 }
@@ -546,6 +591,13 @@ fn allocate_stack_slots(&mut self) {
 
         slot_size = slot_size.max(slot_size_after_input);
 
+        // Allocate a stack slot for the taken label
+        if self.operands.iter().any(|operand| matches!(operand, CInlineAsmOperand::Label { .. })) {
+            let offset = slot_size.align_to(rustc_abi::Align::EIGHT);
+            self.stack_slot_label = Some(offset);
+            slot_size = offset + Size::from_bytes(8);
+        }
+
         self.stack_slots_clobber = slots_clobber;
         self.stack_slots_input = slots_input;
         self.stack_slots_output = slots_output;
@@ -585,8 +637,9 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
         if !self.is_naked {
             Self::prologue(&mut generated_asm, self.arch);
 
-            // Save clobbered registers
-            if !self.options.contains(InlineAsmOptions::NORETURN) {
+            // Save clobbered registers, which have to be restored unless the asm never returns
+            if !self.options.contains(InlineAsmOptions::NORETURN) || self.stack_slot_label.is_some()
+            {
                 for (reg, slot) in self
                     .registers
                     .iter()
@@ -659,6 +712,9 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
                             generated_asm.push_str(value);
                         }
                         CInlineAsmOperand::Symbol { ref symbol } => generated_asm.push_str(symbol),
+                        CInlineAsmOperand::Label { target_index: _ } => {
+                            generated_asm.push_str(&self.label_name(asm_name, *operand_idx));
+                        }
                     }
                 }
             }
@@ -670,7 +726,25 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
         }
 
         if !self.is_naked {
+            // The asm can return by falling through, or by jumping to one of its labels. The
+            // wrapper records which one in the label stack slot for the caller to branch on.
+            let mut exits = vec![];
             if !self.options.contains(InlineAsmOptions::NORETURN) {
+                exits.push((None, 0));
+            } else {
+                Self::epilogue_noreturn(&mut generated_asm, self.arch);
+            }
+            for (i, operand) in self.operands.iter().enumerate() {
+                if let CInlineAsmOperand::Label { target_index } = *operand {
+                    exits.push((Some(i), target_index as u64 + 1));
+                }
+            }
+
+            for (label_operand_idx, label_index) in exits {
+                if let Some(operand_idx) = label_operand_idx {
+                    writeln!(generated_asm, "{}:", self.label_name(asm_name, operand_idx)).unwrap();
+                }
+
                 // Read output registers
                 for (reg, slot) in self
                     .registers
@@ -681,6 +755,10 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
                     Self::save_register(&mut generated_asm, self.arch, reg, slot);
                 }
 
+                if let Some(slot) = self.stack_slot_label {
+                    Self::store_label_index(&mut generated_asm, self.arch, label_index, slot);
+                }
+
                 // Restore clobbered registers
                 for (reg, slot) in self
                     .registers
@@ -692,8 +770,6 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
                 }
 
                 Self::epilogue(&mut generated_asm, self.arch);
-            } else {
-                Self::epilogue_noreturn(&mut generated_asm, self.arch);
             }
         }
 
@@ -718,6 +794,15 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
         generated_asm
     }
 
+    /// The name of the local label of the wrapper that a label operand jumps to.
+    fn label_name(&self, asm_name: &str, operand_idx: usize) -> String {
+        let prefix = match crate::target_triple(self.tcx.sess).binary_format {
+            BinaryFormat::Macho => "L",
+            _ => ".L",
+        };
+        format!("{prefix}{asm_name}_label{operand_idx}")
+    }
+
     fn prologue(generated_asm: &mut String, arch: InlineAsmArch) {
         match arch {
             InlineAsmArch::X86_64 => {
@@ -821,6 +906,31 @@ fn save_register(
         }
     }
 
+    /// Stores which label the asm jumped to. This happens after the output registers have been
+    /// saved, so it may use a register that the call to the wrapper clobbers anyway.
+    fn store_label_index(
+        generated_asm: &mut String,
+        arch: InlineAsmArch,
+        index: u64,
+        offset: Size,
+    ) {
+        match arch {
+            InlineAsmArch::X86_64 => {
+                writeln!(generated_asm, "    mov qword ptr [rbx+0x{:x}], {index}", offset.bytes())
+                    .unwrap();
+            }
+            InlineAsmArch::AArch64 => {
+                writeln!(generated_asm, "    mov x16, #{index}").unwrap();
+                writeln!(generated_asm, "    str x16, [x19, 0x{:x}]", offset.bytes()).unwrap();
+            }
+            InlineAsmArch::RiscV64 => {
+                writeln!(generated_asm, "    li t0, {index}").unwrap();
+                writeln!(generated_asm, "    sd t0, 0x{:x}(s1)", offset.bytes()).unwrap();
+            }
+            _ => unimplemented!("store_label_index for {:?}", arch),
+        }
+    }
+
     fn restore_register(
         generated_asm: &mut String,
         arch: InlineAsmArch,
@@ -870,7 +980,8 @@ fn call_inline_asm<'tcx>(
     slot_size: Size,
     inputs: Vec<(Size, Value)>,
     outputs: Vec<(Size, CPlace<'tcx>)>,
-) {
+    label_slot: Option<Size>,
+) -> Option<Value> {
     let stack_slot =
         fx.create_stack_slot(u32::try_from(slot_size.bytes().next_multiple_of(16)).unwrap(), 16);
 
@@ -916,6 +1027,14 @@ fn call_inline_asm<'tcx>(
         );
         place.write_cvalue(fx, CValue::by_val(value, place.layout()));
     }
+
+    label_slot.map(|offset| {
+        stack_slot.offset(fx, i32::try_from(offset.bytes()).unwrap().into()).load(
+            fx,
+            types::I64,
+            MemFlags::trusted(),
+        )
+    })
 }
 
 fn asm_clif_type<'tcx>(fx: &FunctionCx<'_, '_, 'tcx>, ty: Ty<'tcx>) -> Option<types::Type> {
@@ -58,7 +58,7 @@ fn patchable_function_entry_attrs<'ll>(
 ) -> SmallVec<[&'ll Attribute; 2]> {
     let mut attrs = SmallVec::new();
     let patchable_spec = attr.unwrap_or_else(|| {
-        PatchableFunctionEntry::from_config(cx.tcx.sess.opts.unstable_opts.patchable_function_entry)
+        PatchableFunctionEntry::from_config(cx.tcx.sess.patchable_function_entry())
     });
     let entry = patchable_spec.entry();
     let prefix = patchable_spec.prefix();
@@ -24,7 +24,8 @@
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
 use rustc_session::config::{
-    self, Lto, OutputType, Passes, RemapPathScopeComponents, SplitDwarfKind, SwitchWithOptPath,
+    self, Lto, OutputType, Passes, PrintKind, RemapPathScopeComponents, SplitDwarfKind,
+    SwitchWithOptPath,
 };
 use rustc_span::{BytePos, InnerSpan, Pos, SpanData, SyntaxContext, sym};
 use rustc_target::spec::{CodeModel, FloatAbi, RelocModel, SanitizerSet, SplitDebuginfo, TlsModel};
@@ -231,7 +232,9 @@ pub(crate) fn target_machine_factory(
     let abi = SmallCStr::new(&sess.target.llvm_abiname);
     let trap_unreachable =
         sess.opts.unstable_opts.trap_unreachable.unwrap_or(sess.target.trap_unreachable);
-    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes;
+    // `--print=stack-sizes` reads the frame sizes back from the section.
+    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes
+        || sess.opts.prints.iter().any(|print| print.kind == PrintKind::StackSizes);
 
     let verbose_asm = sess.opts.unstable_opts.verbose_asm;
     let relax_elf_relocations =
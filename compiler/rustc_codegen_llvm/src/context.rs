@@ -254,9 +254,11 @@ pub(crate) unsafe fn create_module<'ll>(
             llvm::LLVMRustSetModulePICLevel(llmod);
         }
         // PIE is potentially more effective than PIC, but can only be used in executables.
-        // If all our outputs are executables, then we can relax PIC to PIE.
+        // If all our outputs are executables, then we can relax PIC to PIE, unless the
+        // objects are also linked into the shared objects of `-Zhot-patch`.
         if reloc_model == RelocModel::Pie
-            || tcx.crate_types().iter().all(|ty| *ty == CrateType::Executable)
+            || (tcx.crate_types().iter().all(|ty| *ty == CrateType::Executable)
+                && !sess.opts.unstable_opts.hot_patch)
         {
             unsafe {
                 llvm::LLVMRustSetModulePIELevel(llmod);
@@ -317,8 +319,7 @@ pub(crate) unsafe fn create_module<'ll>(
 
         // Add "kcfi-offset" module flag with -Z patchable-function-entry (See
         // https://reviews.llvm.org/D141172).
-        let pfe =
-            PatchableFunctionEntry::from_config(sess.opts.unstable_opts.patchable_function_entry);
+        let pfe = PatchableFunctionEntry::from_config(sess.patchable_function_entry());
         if pfe.prefix() > 0 {
             llvm::add_module_flag_u32(
                 llmod,
@@ -11,8 +11,8 @@
     BaseTypeCodegenMethods as _, ConstCodegenMethods, StaticCodegenMethods,
 };
 use rustc_middle::mir::coverage::{
-    BasicCoverageBlock, CovTerm, CoverageIdsInfo, Expression, FunctionCoverageInfo, Mapping,
-    MappingKind, Op,
+    BasicCoverageBlock, CovTerm, CoverageIdsInfo, DecisionInfo, Expression, FunctionCoverageInfo,
+    Mapping, MappingKind, Op,
 };
 use rustc_middle::ty::{Instance, TyCtxt};
 use rustc_span::Span;
@@ -137,9 +137,40 @@ fn fill_region_tables<'tcx>(
         mcdc_decision_regions,
     } = &mut covfun.regions;
 
+    let mut coords =
+        fn_cov_info.mappings.iter().map(|mapping| make_coords(mapping.span)).collect::<Vec<_>>();
+
+    // `-Zcoverage-options=discard-last-condition-spans-in-codegen` discards the
+    // span of the last condition of each MC/DC decision, so that tests can
+    // check that the decision is degraded.
+    if tcx.sess.coverage_discard_last_condition_spans_in_codegen() {
+        for (i, mapping) in fn_cov_info.mappings.iter().enumerate() {
+            if let MappingKind::MCDCDecision(DecisionInfo { num_conditions, .. }) = mapping.kind
+                && let Some(last_condition) = coords.get_mut(i + usize::from(num_conditions))
+            {
+                *last_condition = None;
+            }
+        }
+    }
+
+    // The conditions of an MC/DC decision directly follow the decision. LLVM
+    // rejects a decision that is missing any of its conditions, so if any of
+    // their spans are discarded, degrade the conditions to plain branches.
+    let mut degraded = vec![false; fn_cov_info.mappings.len()];
+    for (i, mapping) in fn_cov_info.mappings.iter().enumerate() {
+        if let MappingKind::MCDCDecision(DecisionInfo { num_conditions, .. }) = mapping.kind {
+            let end = (i + 1 + usize::from(num_conditions)).min(degraded.len());
+            if coords[i..end].iter().any(Option::is_none) {
+                degraded[i..end].fill(true);
+            }
+        }
+    }
+
     // For each counter/region pair in this function+file, convert it to a
     // form suitable for FFI.
-    for &Mapping { ref kind, span } in &fn_cov_info.mappings {
+    for ((Mapping { kind, span: _ }, coords), degraded) in
+        fn_cov_info.mappings.iter().zip(coords).zip(degraded)
+    {
         // If this function is unused, replace all counters with zero.
         let counter_for_bcb = |bcb: BasicCoverageBlock| -> ffi::Counter {
             let term = if covfun.is_used {
@@ -150,10 +181,19 @@ fn fill_region_tables<'tcx>(
             ffi::Counter::from_term(term)
         };
 
-        let Some(coords) = make_coords(span) else { continue };
+        // Degraded conditions become plain branches, without their decision.
+        let kind = match *kind {
+            MappingKind::MCDCBranch { true_bcb, false_bcb, .. } if degraded => {
+                MappingKind::Branch { true_bcb, false_bcb }
+            }
+            MappingKind::MCDCDecision(_) if degraded => continue,
+            ref kind => kind.clone(),
+        };
+
+        let Some(coords) = coords else { continue };
         let cov_span = coords.make_coverage_span(local_file_id);
 
-        match *kind {
+        match kind {
             MappingKind::Code { bcb } => {
                 code_regions.push(ffi::CodeRegion { cov_span, counter: counter_for_bcb(bcb) });
             }
@@ -203,6 +203,10 @@ pub(crate) struct UnknownCompression {
     pub algorithm: &'static str,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_llvm_mcdc_requires_llvm_19)]
+pub(crate) struct McdcRequiresLlvm19;
+
 #[derive(Diagnostic)]
 #[diag(codegen_llvm_mismatch_data_layout)]
 pub(crate) struct MismatchedDataLayout<'a> {
@@ -257,6 +257,11 @@ fn locale_resource(&self) -> &'static str {
 
     fn init(&self, sess: &Session) {
         llvm_util::init(sess); // Make sure llvm is inited
+
+        // Older versions of LLVM silently drop the MC/DC mapping regions.
+        if sess.instrument_coverage_mcdc() && llvm_util::get_version() < (19, 0, 0) {
+            sess.dcx().emit_fatal(errors::McdcRequiresLlvm19);
+        }
     }
 
     fn provide(&self, providers: &mut Providers) {
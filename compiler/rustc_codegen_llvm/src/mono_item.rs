@@ -106,6 +106,12 @@ fn should_assume_dso_local(&self, llval: &llvm::Value, is_declaration: bool) ->
             return true;
         }
 
+        // With `-Zhot-patch`, the definitions of the executable are replaced by
+        // those of the patches loaded into it, and patches use its statics.
+        if self.tcx.sess.opts.unstable_opts.hot_patch {
+            return false;
+        }
+
         // Symbols from executables can't really be imported any further.
         let all_exe = self.tcx.crate_types().iter().all(|ty| *ty == CrateType::Executable);
         let is_declaration_for_linker =
@@ -77,6 +77,9 @@ codegen_ssa_field_associated_value_expected = associated value expected for `{$n
 codegen_ssa_forbidden_target_feature_attr =
     target feature `{$feature}` cannot be enabled with `#[target_feature]`: {$reason}
 
+codegen_ssa_hot_patch_link_failed = linking the hot patch `{$path}` failed: {$status}
+    .note = {$output}
+
 codegen_ssa_ignoring_emit_path = ignoring emit path because multiple .{$extension} files were produced
 
 codegen_ssa_ignoring_output = ignoring -o because multiple .{$extension} files were produced
@@ -243,6 +246,9 @@ codegen_ssa_no_natvis_directory = error enumerating natvis directory: {$error}
 
 codegen_ssa_no_saved_object_file = cached cgu {$cgu_name} should have an object file, but doesn't
 
+codegen_ssa_not_reproducible = `{$path}` differs when the crate is built again in a different environment
+    .note = {$differences}
+
 codegen_ssa_null_on_export = `export_name` may not contain null characters
 
 codegen_ssa_out_of_range_integer = integer value out of range
@@ -251,6 +257,9 @@ codegen_ssa_out_of_range_integer = integer value out of range
 codegen_ssa_processing_dymutil_failed = processing debug info with `dsymutil` failed: {$status}
     .note = {$output}
 
+codegen_ssa_print_requires_objects = `--print={$kind}` requires object files, but none were emitted
+    .help = add `obj` or `link` to `--emit`
+
 codegen_ssa_read_file = failed to read file: {$message}
 
 codegen_ssa_repair_vs_build_tools = the Visual Studio build tools may need to be repaired using the Visual Studio installer
@@ -275,10 +284,17 @@ codegen_ssa_shuffle_indices_evaluation = could not evaluate shuffle_indices at c
 
 codegen_ssa_specify_libraries_to_link = use the `-l` flag to specify native libraries to link
 
+codegen_ssa_stack_sizes_unsupported = `--print=stack-sizes` can only read the frame sizes of ELF targets
+    .note = the frame sizes and stack bounds will be `null`
+
 codegen_ssa_static_library_native_artifacts = Link against the following native artifacts when linking against this static library. The order and any duplication can be significant on some platforms.
 
 codegen_ssa_static_library_native_artifacts_to_file = Native artifacts to link against have been written to {$path}. The order and any duplication can be significant on some platforms.
 
+codegen_ssa_staticlib_localize_symbols = failed to localize the symbols of the staticlib: {$error}
+
+codegen_ssa_staticlib_localize_symbols_unsupported = `-Z staticlib-localize-symbols` is only supported on targets that use ELF
+
 codegen_ssa_stripping_debug_info_failed = stripping debug info with `{$util}` failed: {$status}
     .note = {$output}
 
@@ -395,6 +411,11 @@ codegen_ssa_unsupported_link_self_contained = option `-C link-self-contained` is
 
 codegen_ssa_use_cargo_directive = use the `cargo:rustc-link-lib` directive to specify the native libraries to link with Cargo (see https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-lib)
 
+codegen_ssa_verify_reproducible_build_failed = the second build for `-Z verify-reproducible` failed: {$status}
+    .note = {$output}
+
+codegen_ssa_verify_reproducible_failed = couldn't verify that the crate is reproducible: {$error}
+
 codegen_ssa_version_script_write_failure = failed to write version script: {$error}
 
 codegen_ssa_visual_studio_not_installed = you may need to install Visual Studio build tools with the "C++ build tools" workload
@@ -0,0 +1,274 @@
+//! `-Zhot-patch`: builds the codegen units that changed since the previous
+//! incremental session as shared objects, which a running instance of the
+//! executable can load to replace its functions with their new versions.
+//!
+//! The executable is built so that this is possible: every function has a
+//! patchable entry (see [`Session::patchable_function_entry`]), no symbol is
+//! internalized and all of them are exported, and every call between codegen
+//! units goes through the symbol rather than a local copy.
+//!
+//! Next to the executable `foo`, the directory `foo.hotpatch` holds a
+//! `manifest.json` naming the base executable and listing the patches built
+//! against it, in order. Each patch lives in a directory of its own, and
+//! contains one shared object per changed codegen unit. Every shared object
+//! defines a `__rustc_hotpatch_table` listing the functions it defines: a
+//! `usize` version, a `usize` count, and then for each function a pointer to
+//! its NUL-terminated symbol name followed by a pointer to the function.
+//! Loading a patch, which `std::hot_patch::load` does, means looking
+//! up each of those symbols in the executable and overwriting its patchable
+//! entry with a jump to the new function.
+//!
+//! When the previous session left no codegen unit to reuse, e.g. because
+//! it's the first one or because a command-line option changed, the executable
+//! is recorded as a new base instead of a patch. Otherwise, every codegen unit
+//! that was recompiled goes into the patch, even if that's all of them, as
+//! with crates that have a single codegen unit.
+
+use std::fs;
+use std::path::Path;
+
+use object::write::{Relocation, StandardSection, Symbol, SymbolSection};
+use object::{
+    RelocationEncoding, RelocationFlags, RelocationKind, SymbolFlags, SymbolKind, SymbolScope,
+};
+use rustc_macros::{Decodable, Encodable};
+use rustc_middle::mir::mono::{Linkage, MonoItem};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_session::config::CrateType;
+use rustc_span::Symbol as SpanSymbol;
+use rustc_target::spec::{Endian, LinkOutputKind};
+use serde_json::{Value, json};
+
+use super::link::{escape_string, exec_linker, linker_and_flavor};
+use super::linker::{Linker, get_linker};
+use super::metadata::create_object_file;
+use crate::{CodegenResults, ModuleKind, errors};
+
+/// The name of the table of the functions that a patch replaces.
+const TABLE_SYMBOL: &str = "__rustc_hotpatch_table";
+
+/// The version of the layout of the table.
+const TABLE_VERSION: u64 = 1;
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct HotPatchCgu {
+    name: SpanSymbol,
+    /// The symbols of the functions that the codegen unit defines, and which
+    /// a patch built from it replaces.
+    symbols: Vec<String>,
+    /// Whether the previous session left a work product for the codegen unit.
+    had_work_product: bool,
+}
+
+/// Records the functions that each codegen unit defines.
+pub(crate) fn collect(tcx: TyCtxt<'_>) -> Vec<HotPatchCgu> {
+    tcx.collect_and_partition_mono_items(())
+        .codegen_units
+        .iter()
+        .map(|cgu| {
+            let mut symbols: Vec<String> = cgu
+                .items()
+                .iter()
+                .filter(|(item, data)| {
+                    matches!(item, MonoItem::Fn(_)) && data.linkage == Linkage::External
+                })
+                .map(|(item, _)| item.symbol_name(tcx).name.to_owned())
+                .collect();
+            symbols.sort_unstable();
+            let had_work_product =
+                tcx.dep_graph.previous_work_product(&cgu.work_product_id()).is_some();
+            HotPatchCgu { name: cgu.name(), symbols, had_work_product }
+        })
+        .collect()
+}
+
+/// Builds a patch for the executable `out_filename` from the codegen units
+/// that were recompiled in this session.
+pub(crate) fn link_patches(
+    sess: &Session,
+    codegen_results: &CodegenResults,
+    out_filename: &Path,
+    tmpdir: &Path,
+) {
+    let _timer = sess.timer("link_hot_patches");
+
+    let changed: Vec<_> = codegen_results
+        .modules
+        .iter()
+        .filter(|m| m.kind == ModuleKind::Regular && m.links_from_incr_cache.is_empty())
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    let dir = out_filename.with_extension("hotpatch");
+    let manifest_path = dir.join("manifest.json");
+    let base = out_filename.file_name().unwrap().to_string_lossy().into_owned();
+    let manifest = fs::read(&manifest_path)
+        .ok()
+        .and_then(|manifest| serde_json::from_slice::<Value>(&manifest).ok())
+        .filter(|manifest| manifest["base"] == base && manifest["patches"].is_array());
+
+    // Without a previous session to compare with, there is no telling what
+    // changed, and patches built against the previous executable can't be
+    // applied to the new one.
+    let previous_session =
+        codegen_results.crate_info.hot_patch_cgus.iter().any(|cgu| cgu.had_work_product);
+    let mut manifest = match manifest {
+        Some(manifest) if previous_session => manifest,
+        _ => {
+            let _ = fs::remove_dir_all(&dir);
+            if let Err(error) = fs::create_dir_all(&dir) {
+                sess.dcx().emit_fatal(errors::FailedToWrite { path: dir, error });
+            }
+            write_manifest(sess, &manifest_path, &json!({ "base": base, "patches": [] }));
+            return;
+        }
+    };
+
+    let patches = manifest["patches"].as_array_mut().unwrap();
+    let patch = patches.len().to_string();
+    let patch_dir = dir.join(&patch);
+    if let Err(error) = fs::create_dir_all(&patch_dir) {
+        sess.dcx().emit_fatal(errors::FailedToWrite { path: patch_dir, error });
+    }
+
+    let mut objects = vec![];
+    for module in changed {
+        let Some(cgu) = codegen_results
+            .crate_info
+            .hot_patch_cgus
+            .iter()
+            .find(|cgu| cgu.name.as_str() == module.name)
+        else {
+            continue;
+        };
+        let Some(object) = &module.object else {
+            continue;
+        };
+        let file_name =
+            format!("{}{}{}", sess.target.dll_prefix, module.name, sess.target.dll_suffix);
+        let out = patch_dir.join(&file_name);
+        let table = tmpdir.join(format!("{}.hotpatch.o", module.name));
+        let Some(table_data) = create_table_object(sess, &cgu.symbols) else {
+            // The target can't be patched, which was already checked.
+            return;
+        };
+        if let Err(error) = fs::write(&table, table_data) {
+            sess.dcx().emit_fatal(errors::FailedToWrite { path: table, error });
+        }
+        link_patch(sess, codegen_results, &[object.as_path(), table.as_path()], &out, tmpdir);
+        objects.push(Value::from(file_name));
+    }
+
+    patches.push(json!({ "dir": patch, "objects": objects }));
+    write_manifest(sess, &manifest_path, &manifest);
+}
+
+fn write_manifest(sess: &Session, path: &Path, manifest: &Value) {
+    if let Err(error) = fs::write(path, manifest.to_string()) {
+        sess.dcx().emit_fatal(errors::FailedToWrite { path: path.to_owned(), error });
+    }
+}
+
+/// Links the objects of a changed codegen unit into a shared object that
+/// leaves everything it doesn't define to the executable.
+fn link_patch(
+    sess: &Session,
+    codegen_results: &CodegenResults,
+    objects: &[&Path],
+    out: &Path,
+    tmpdir: &Path,
+) {
+    let (linker_path, flavor) = linker_and_flavor(sess);
+    let mut linker =
+        get_linker(sess, &linker_path, flavor, false, &codegen_results.crate_info.target_cpu);
+    linker.set_output_kind(LinkOutputKind::DynamicDylib, CrateType::Cdylib, out);
+    for object in objects {
+        linker.add_object(object);
+    }
+    linker.link_against_host();
+    linker.output_filename(out);
+    let cmd = linker.take_cmd();
+
+    match exec_linker(sess, &cmd, out, flavor, tmpdir) {
+        Ok(prog) if prog.status.success() => {}
+        Ok(prog) => {
+            let mut output = prog.stderr.clone();
+            output.extend_from_slice(&prog.stdout);
+            sess.dcx().emit_err(errors::HotPatchLinkFailed {
+                path: out.to_owned(),
+                status: prog.status,
+                output: escape_string(&output),
+            });
+        }
+        Err(error) => sess.dcx().emit_fatal(errors::UnableToExeLinker {
+            linker_path,
+            error,
+            command_formatted: format!("{cmd:?}"),
+        }),
+    }
+}
+
+/// Creates an object file defining the `__rustc_hotpatch_table` of the
+/// functions with the given symbols.
+fn create_table_object(sess: &Session, symbols: &[String]) -> Option<Vec<u8>> {
+    let mut file = create_object_file(sess)?;
+    let pointer_size = sess.target.pointer_width as usize / 8;
+    let word = |value: u64| match sess.target.options.endian {
+        Endian::Little => value.to_le_bytes()[..pointer_size].to_vec(),
+        Endian::Big => value.to_be_bytes()[8 - pointer_size..].to_vec(),
+    };
+    let flags = RelocationFlags::Generic {
+        kind: RelocationKind::Absolute,
+        encoding: RelocationEncoding::Generic,
+        size: sess.target.pointer_width as u8,
+    };
+
+    let names = file.section_id(StandardSection::ReadOnlyData);
+    let table = file.section_id(StandardSection::Data);
+    let table_offset = file.append_section_data(table, &word(TABLE_VERSION), pointer_size as u64);
+    file.append_section_data(table, &word(symbols.len() as u64), 1);
+
+    for symbol in symbols {
+        let mut name = symbol.as_bytes().to_vec();
+        name.push(0);
+        let name_offset = file.append_section_data(names, &name, 1);
+        let name_symbol = file.section_symbol(names);
+        let offset = file.append_section_data(table, &word(0), 1);
+        file.add_relocation(
+            table,
+            Relocation { offset, symbol: name_symbol, addend: name_offset as i64, flags },
+        )
+        .ok()?;
+
+        // Within the patch, this resolves to its own definition.
+        let function = file.add_symbol(Symbol {
+            name: symbol.as_bytes().to_vec(),
+            value: 0,
+            size: 0,
+            kind: SymbolKind::Text,
+            scope: SymbolScope::Dynamic,
+            weak: false,
+            section: SymbolSection::Undefined,
+            flags: SymbolFlags::None,
+        });
+        let offset = file.append_section_data(table, &word(0), 1);
+        file.add_relocation(table, Relocation { offset, symbol: function, addend: 0, flags })
+            .ok()?;
+    }
+
+    file.add_symbol(Symbol {
+        name: TABLE_SYMBOL.as_bytes().to_vec(),
+        value: table_offset,
+        size: (pointer_size * (2 + 2 * symbols.len())) as u64,
+        kind: SymbolKind::Data,
+        scope: SymbolScope::Dynamic,
+        weak: false,
+        section: SymbolSection::Section(table),
+        flags: SymbolFlags::None,
+    });
+
+    file.write().ok()
+}
@@ -56,7 +56,7 @@
 use super::linker::{self, Linker};
 use super::metadata::{MetadataPosition, create_wrapper_file};
 use super::rpath::{self, RPathConfig};
-use super::{apple, versioned_llvm_target};
+use super::{apple, hot_patch, partial_link, versioned_llvm_target};
 use crate::{
     CodegenResults, CompiledModule, CrateInfo, NativeLib, errors, looks_like_rust_object_file,
 };
@@ -79,6 +79,7 @@ pub fn link_binary(
 ) {
     let _timer = sess.timer("link_binary");
     let output_metadata = sess.opts.output_types.contains_key(&OutputType::Metadata);
+
     let mut tempfiles_for_stdout_output: Vec<PathBuf> = Vec::new();
     for &crate_type in &codegen_results.crate_info.crate_types {
         // Ignore executable crates if we have -Z no-codegen, as they will error.
@@ -145,6 +146,14 @@ pub fn link_binary(
                         &codegen_results,
                         path.as_ref(),
                     );
+                    if crate_type == CrateType::Executable && sess.opts.unstable_opts.hot_patch {
+                        hot_patch::link_patches(
+                            sess,
+                            &codegen_results,
+                            &out_filename,
+                            path.as_ref(),
+                        );
+                    }
                 }
             }
             if sess.opts.json_artifact_notifications {
@@ -513,6 +522,16 @@ fn link_staticlib(
 
     ab.build(out_filename);
 
+    if sess.opts.unstable_opts.staticlib_localize_symbols {
+        partial_link::localize_staticlib_symbols(
+            sess,
+            archive_builder_builder,
+            codegen_results,
+            out_filename,
+            tempdir.as_ref(),
+        );
+    }
+
     let crates = codegen_results.crate_info.used_crates.iter();
 
     let fmts = codegen_results
@@ -1130,7 +1149,7 @@ fn strip_with_external_utility(sess: &Session, util: &str, out_filename: &Path,
     }
 }
 
-fn escape_string(s: &[u8]) -> String {
+pub(super) fn escape_string(s: &[u8]) -> String {
     match str::from_utf8(s) {
         Ok(s) => s.to_owned(),
         Err(_) => format!("Non-UTF-8 output: {}", s.escape_ascii()),
@@ -1592,7 +1611,7 @@ fn get_object_file_path(sess: &Session, name: &str, self_contained: bool) -> Pat
     PathBuf::from(name)
 }
 
-fn exec_linker(
+pub(super) fn exec_linker(
     sess: &Session,
     cmd: &Command,
     out_filename: &Path,
@@ -2497,6 +2516,11 @@ fn add_order_independent_options(
     // Make the binary compatible with data execution prevention schemes.
     cmd.add_no_exec();
 
+    // The patches built with `-Zhot-patch` are linked against the executable.
+    if crate_type == CrateType::Executable && sess.opts.unstable_opts.hot_patch {
+        cmd.export_dynamic();
+    }
+
     if self_contained_components.is_crt_objects_enabled() {
         cmd.no_crt_objects();
     }
@@ -2558,8 +2582,10 @@ fn add_order_independent_options(
         // sections to ensure we have all the data for PGO.
         let keep_metadata =
             crate_type == CrateType::Dylib || sess.opts.cg.profile_generate.enabled();
-        if crate_type != CrateType::Executable || !sess.opts.unstable_opts.export_executable_symbols
-        {
+        // Patches built with `-Zhot-patch` may use functions that the executable doesn't.
+        let keep_all_sections =
+            sess.opts.unstable_opts.export_executable_symbols || sess.opts.unstable_opts.hot_patch;
+        if crate_type != CrateType::Executable || !keep_all_sections {
             cmd.gc_sections(keep_metadata);
         } else {
             cmd.no_gc_sections();
@@ -3024,7 +3050,8 @@ fn add_dynamic_crate(cmd: &mut dyn Linker, sess: &Session, cratepath: &Path) {
     cmd.link_dylib_by_path(&rehome_lib_path(sess, cratepath), true);
 }
 
-fn relevant_lib(sess: &Session, lib: &NativeLib) -> bool {
+/// Whether the `cfg` of the `#[link]` attribute of `lib`, if any, holds.
+pub fn relevant_lib(sess: &Session, lib: &NativeLib) -> bool {
     match lib.cfg {
         Some(ref cfg) => rustc_attr_parsing::cfg_matches(cfg, sess, CRATE_NODE_ID, None),
         None => true,
@@ -344,6 +344,12 @@ fn add_eh_frame_header(&mut self) {}
     fn add_no_exec(&mut self) {}
     fn add_as_needed(&mut self) {}
     fn reset_per_library_state(&mut self) {}
+    /// Makes the exported symbols of an executable available to the shared
+    /// objects it loads.
+    fn export_dynamic(&mut self) {}
+    /// Lets a shared object leave symbols to be resolved against the
+    /// executable that loads it, and binds calls to its own functions locally.
+    fn link_against_host(&mut self) {}
 }
 
 impl dyn Linker + '_ {
@@ -899,6 +905,17 @@ fn add_as_needed(&mut self) {
             self.link_args(&["-z", "ignore"]);
         }
     }
+
+    fn export_dynamic(&mut self) {
+        self.link_arg("--export-dynamic");
+    }
+
+    fn link_against_host(&mut self) {
+        // Undefined symbols are allowed in ELF shared objects by default, and
+        // the statics that the patch defines are interposed by the ones of the
+        // executable, which exports them.
+        self.link_arg("-Bsymbolic-functions");
+    }
 }
 
 struct MsvcLinker<'a> {
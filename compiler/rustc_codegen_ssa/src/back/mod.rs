@@ -5,11 +5,16 @@
 pub mod apple;
 pub mod archive;
 pub(crate) mod command;
+pub mod hot_patch;
 pub mod link;
 pub(crate) mod linker;
 pub mod lto;
 pub mod metadata;
+pub mod mono_item_sizes;
+pub(crate) mod partial_link;
+pub mod reproducible;
 pub(crate) mod rpath;
+pub mod stack_sizes;
 pub mod symbol_export;
 pub mod write;
 
@@ -0,0 +1,204 @@
+//! `--print=mono-item-sizes`: attributes the code in the object files to the
+//! mono items that produced it.
+//!
+//! The mono items and their partitioning into codegen units are recorded in
+//! the [`CrateInfo`] while the `TyCtxt` is still alive. Once the backend has
+//! written the object files, the size of each item's symbol is read back from
+//! them, and the report is printed as JSON.
+
+use std::fs;
+
+use object::{BinaryFormat, Object, ObjectSection, ObjectSymbol, SectionIndex, SymbolKind};
+use rustc_attr_parsing::InlineAttr;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_macros::{Decodable, Encodable};
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrs;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_session::Session;
+use rustc_session::config::OutFileName;
+use rustc_span::Symbol;
+use serde_json::json;
+
+use crate::{CompiledModule, CrateInfo, errors};
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct MonoItemSizeInfo {
+    /// The instance, e.g. `Vec::<u8>::push`.
+    name: String,
+    symbol: String,
+    /// The item it was instantiated from, e.g. `Vec::<T>::push`.
+    generic: String,
+    /// `fn` or `static`.
+    kind: String,
+    /// The codegen units the item was codegened in. Items that are
+    /// instantiated as local copies can be in several of them.
+    cgus: Vec<Symbol>,
+    /// Whether the item is a local copy, instantiated in every codegen unit
+    /// that uses it, rather than shared between them.
+    local_copy: bool,
+    /// The `#[inline]` attribute of a function: `none`, `hint`, `always`,
+    /// `never` or `force`.
+    inline_attr: String,
+    /// The size estimate of the MIR used to partition the codegen units.
+    size_estimate: usize,
+}
+
+/// Records the mono items of the crate, if `--print=mono-item-sizes` was
+/// requested.
+pub(crate) fn collect(tcx: TyCtxt<'_>) -> Vec<MonoItemSizeInfo> {
+    let mut items: FxIndexMap<MonoItem<'_>, MonoItemSizeInfo> = Default::default();
+    for cgu in tcx.collect_and_partition_mono_items(()).codegen_units {
+        for (&mono_item, data) in cgu.items() {
+            let info = items.entry(mono_item).or_insert_with(|| {
+                let (name, kind, inline_attr) = match mono_item {
+                    MonoItem::Fn(instance) => (
+                        with_no_trimmed_paths!(instance.to_string()),
+                        "fn",
+                        inline_attr_str(tcx.codegen_fn_attrs(instance.def_id())),
+                    ),
+                    MonoItem::Static(def_id) => {
+                        (with_no_trimmed_paths!(tcx.def_path_str(def_id)), "static", "none")
+                    }
+                    MonoItem::GlobalAsm(_) => (String::new(), "global_asm", "none"),
+                };
+                MonoItemSizeInfo {
+                    name,
+                    symbol: mono_item.symbol_name(tcx).name.to_owned(),
+                    generic: with_no_trimmed_paths!(tcx.def_path_str(mono_item.def_id())),
+                    kind: kind.to_owned(),
+                    cgus: vec![],
+                    local_copy: data.inlined,
+                    inline_attr: inline_attr.to_owned(),
+                    size_estimate: data.size_estimate,
+                }
+            });
+            info.cgus.push(cgu.name());
+        }
+    }
+    // Global asm has no symbol of its own to measure.
+    items.into_values().filter(|info| info.kind != "global_asm").collect()
+}
+
+fn inline_attr_str(attrs: &CodegenFnAttrs) -> &'static str {
+    match attrs.inline {
+        InlineAttr::None => "none",
+        InlineAttr::Hint => "hint",
+        InlineAttr::Always => "always",
+        InlineAttr::Never => "never",
+        InlineAttr::Force { .. } => "force",
+    }
+}
+
+/// Reads the symbol sizes from the object files of `modules`, and prints the
+/// report.
+///
+/// This runs right after codegen, before the objects that are not needed for
+/// linking are removed, so that it also works with `--emit=obj` and `-Zno-link`.
+pub(crate) fn print(
+    sess: &Session,
+    crate_info: &CrateInfo,
+    modules: &[CompiledModule],
+    out: &OutFileName,
+) {
+    if !modules.iter().any(|module| module.object.is_some()) {
+        sess.dcx().emit_err(errors::PrintRequiresObjects { kind: "mono-item-sizes" });
+        return;
+    }
+    let symbol_sizes = symbol_sizes(sess, modules);
+    let CrateInfo { mono_item_sizes, local_crate_name, .. } = crate_info;
+
+    let mut instantiations: FxHashMap<&str, usize> = Default::default();
+    for info in mono_item_sizes {
+        *instantiations.entry(&info.generic).or_default() += 1;
+    }
+
+    let mut items: Vec<_> = mono_item_sizes
+        .iter()
+        .map(|info| (info, symbol_sizes.get(info.symbol.as_str()).copied()))
+        .collect();
+    // Largest first, so that the culprits are at the top.
+    items.sort_by(|(a, a_size), (b, b_size)| b_size.cmp(a_size).then_with(|| a.name.cmp(&b.name)));
+
+    let total: u64 = items.iter().filter_map(|(_, size)| *size).sum();
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|(info, size)| {
+            json!({
+                "name": info.name,
+                "symbol": info.symbol,
+                "generic": info.generic,
+                "instantiations_of_generic": instantiations[info.generic.as_str()],
+                "kind": info.kind,
+                "cgus": info.cgus.iter().map(|cgu| cgu.as_str()).collect::<Vec<_>>(),
+                "local_copy": info.local_copy,
+                "inline_attr": info.inline_attr,
+                "size_estimate": info.size_estimate,
+                "bytes": size,
+            })
+        })
+        .collect();
+
+    let report = json!({
+        "version": 1,
+        "crate": local_crate_name.as_str(),
+        "total_bytes": total,
+        "items": items,
+    });
+    let mut report = serde_json::to_string_pretty(&report).unwrap();
+    report.push('\n');
+    out.overwrite(&report, sess);
+}
+
+/// Returns the total size of each defined code or data symbol, summed over
+/// all object files, since local copies are emitted once per codegen unit.
+fn symbol_sizes(sess: &Session, modules: &[CompiledModule]) -> FxHashMap<String, u64> {
+    // Mach-O and 32-bit Windows prefix every symbol with an underscore.
+    let prefix =
+        if sess.target.is_like_osx || (sess.target.is_like_windows && sess.target.arch == "x86") {
+            "_"
+        } else {
+            ""
+        };
+
+    let mut sizes = FxHashMap::default();
+    for path in modules.iter().filter_map(|m| m.object.as_ref()) {
+        // Objects that can't be read (e.g. because they only hold bitcode for
+        // linker-plugin LTO) simply contribute nothing.
+        let Ok(data) = fs::read(path) else { continue };
+        let Ok(file) = object::File::parse(&*data) else { continue };
+
+        let mut symbols: Vec<(SectionIndex, u64, u64, &str)> = file
+            .symbols()
+            .filter(|sym| {
+                sym.is_definition() && matches!(sym.kind(), SymbolKind::Text | SymbolKind::Data)
+            })
+            .filter_map(|sym| {
+                let section = sym.section_index()?;
+                let name = sym.name().ok()?;
+                Some((section, sym.address(), sym.size(), name))
+            })
+            .collect();
+
+        // Mach-O doesn't record symbol sizes, so those extend to the next
+        // symbol in the same section, or to the end of the section.
+        symbols.sort_by_key(|&(section, address, ..)| (section.0, address));
+        for i in 0..symbols.len() {
+            let (section, address, mut size, name) = symbols[i];
+            if size == 0 && file.format() == BinaryFormat::MachO {
+                size = match symbols.get(i + 1) {
+                    Some(&(next_section, next_address, ..)) if next_section == section => {
+                        next_address - address
+                    }
+                    _ => file
+                        .section_by_index(section)
+                        .map_or(0, |s| (s.address() + s.size()).saturating_sub(address)),
+                };
+            }
+            let name = name.strip_prefix(prefix).unwrap_or(name);
+            *sizes.entry(name.to_owned()).or_default() += size;
+        }
+    }
+    sizes
+}
@@ -0,0 +1,372 @@
+//! `-Zstaticlib-localize-symbols`: merges the object files of a staticlib into
+//! a single relocatable object, like `ld -r` but without running a linker, and
+//! makes every symbol that the staticlib doesn't export local to that object.
+//!
+//! A staticlib bundles the standard library and all of its other Rust
+//! dependencies, so two staticlibs that are linked into the same binary define
+//! many of the same symbols, which clash. Once their objects are merged, the
+//! references between them no longer go through global symbols, and only the
+//! `#[no_mangle]` and `extern "C"` symbols that the staticlib exports have to
+//! stay global.
+//!
+//! Only ELF is supported. Every section is copied as it is, except that:
+//!
+//! * COMDAT groups that more than one object defines are only kept once, and
+//!   groups are dissolved into ordinary sections, since their signature symbol
+//!   may become local. References to the sections of a dropped group from other
+//!   sections, like `.eh_frame` and `.debug_info`, are resolved to the same
+//!   section of the group that was kept, or else left unrelocated, as linkers do;
+//! * sections that refer to symbols by their index, like `.llvm_addrsig`, and
+//!   other sections that only LLVM's linker understands are dropped.
+//!
+//! A global symbol that is defined more than once resolves to the strong
+//! definition, or else to the first weak one, as the linker would resolve it.
+//! Symbols that no object defines stay global undefined references.
+
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use object::read::archive::ArchiveFile;
+use object::write::{self, SectionId, SymbolId};
+use object::{
+    BinaryFormat, Object, ObjectComdat, ObjectSection, ObjectSymbol, RelocationFlags,
+    RelocationTarget, SectionFlags, SectionIndex, SectionKind, SymbolFlags, SymbolIndex,
+    SymbolKind, SymbolScope, SymbolSection, elf,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_session::Session;
+use rustc_session::config::CrateType;
+use rustc_target::spec::BinaryFormat as TargetBinaryFormat;
+
+use super::archive::ArchiveBuilderBuilder;
+use crate::{CodegenResults, errors};
+
+#[cfg(test)]
+mod tests;
+
+/// The section types that LLVM reserves for its own use, such as
+/// `SHT_LLVM_ADDRSIG`.
+const SHT_LLVM: RangeInclusive<u32> = 0x6fff4c00..=0x6fff4cff;
+
+/// Replaces the objects of the staticlib `out_filename` with a single object
+/// in which only the symbols that the staticlib exports are global.
+pub(super) fn localize_staticlib_symbols(
+    sess: &Session,
+    archive_builder_builder: &dyn ArchiveBuilderBuilder,
+    codegen_results: &CodegenResults,
+    out_filename: &Path,
+    tmpdir: &Path,
+) {
+    if sess.target.binary_format != TargetBinaryFormat::Elf {
+        sess.dcx().emit_fatal(errors::StaticlibLocalizeSymbolsUnsupported);
+    }
+
+    let data = fs::read(out_filename)
+        .unwrap_or_else(|message| sess.dcx().emit_fatal(errors::ReadFileError { message }));
+    let exported: FxHashSet<&[u8]> = codegen_results.crate_info.exported_symbols
+        [&CrateType::Staticlib]
+        .iter()
+        .map(|symbol| symbol.as_bytes())
+        .collect();
+    let merged = merge_objects(&data, &exported)
+        .unwrap_or_else(|error| sess.dcx().emit_fatal(errors::StaticlibLocalizeSymbols { error }));
+
+    let object = tmpdir.join(format!("{}.o", codegen_results.crate_info.local_crate_name));
+    if let Err(error) = fs::write(&object, merged) {
+        sess.dcx().emit_fatal(errors::FailedToWrite { path: object, error });
+    }
+    let mut ab = archive_builder_builder.new_archive_builder(sess);
+    ab.add_file(&object);
+    ab.build(out_filename);
+}
+
+/// Merges the object files of the archive `data` into one, in which only the
+/// symbols named in `exported` are global.
+fn merge_objects(data: &[u8], exported: &FxHashSet<&[u8]>) -> Result<Vec<u8>, String> {
+    let archive = ArchiveFile::parse(data).map_err(|error| error.to_string())?;
+    let mut objects = Vec::new();
+    for member in archive.members() {
+        let member = member.map_err(|error| error.to_string())?;
+        let name = String::from_utf8_lossy(member.name()).into_owned();
+        let file = member
+            .data(data)
+            .and_then(object::File::parse)
+            .map_err(|error| format!("`{name}`: {error}"))?;
+        if file.format() != BinaryFormat::Elf {
+            return Err(format!("`{name}` isn't an ELF object file"));
+        }
+        objects.push((name, file));
+    }
+    let Some((_, first)) = objects.first() else {
+        return Err("the staticlib contains no object files".to_owned());
+    };
+
+    let mut out = write::Object::new(first.format(), first.architecture(), first.endianness());
+    out.flags = first.flags();
+    out.set_mangling(write::Mangling::None);
+
+    // The sections of COMDAT groups that an earlier object already defined, with the
+    // signatures of their groups, and the object and sections of each group that is kept.
+    let mut groups: FxHashMap<&[u8], (usize, Vec<SectionIndex>)> = FxHashMap::default();
+    let mut dropped: Vec<FxHashMap<SectionIndex, &[u8]>> = Vec::with_capacity(objects.len());
+    for (i, (name, file)) in objects.iter().enumerate() {
+        let mut sections = FxHashMap::default();
+        for comdat in file.comdats() {
+            let signature = comdat.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            if groups.contains_key(signature) {
+                sections.extend(comdat.sections().map(|index| (index, signature)));
+            } else {
+                groups.insert(signature, (i, comdat.sections().collect()));
+            }
+        }
+        dropped.push(sections);
+    }
+
+    // The definition that each global symbol resolves to.
+    let mut definitions: FxHashMap<&[u8], (usize, SymbolIndex, bool)> = FxHashMap::default();
+    for (i, (name, file)) in objects.iter().enumerate() {
+        for symbol in file.symbols() {
+            if !symbol.is_global() || symbol.is_undefined() {
+                continue;
+            }
+            if let SymbolSection::Section(index) = symbol.section()
+                && dropped[i].contains_key(&index)
+            {
+                continue;
+            }
+            let symbol_name = symbol.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            let weak = symbol.is_weak() || symbol.is_common();
+            match definitions.get(symbol_name) {
+                None => {}
+                Some(&(_, _, true)) if !weak => {}
+                Some(&(j, _, false)) if !weak => {
+                    return Err(format!(
+                        "`{}` is defined in both `{}` and `{name}`",
+                        String::from_utf8_lossy(symbol_name),
+                        objects[j].0,
+                    ));
+                }
+                Some(_) => continue,
+            }
+            definitions.insert(symbol_name, (i, symbol.index(), weak));
+        }
+    }
+
+    let mut sections: Vec<FxHashMap<SectionIndex, SectionId>> = Vec::with_capacity(objects.len());
+    for (i, (name, file)) in objects.iter().enumerate() {
+        let mut ids = FxHashMap::default();
+        for section in file.sections() {
+            if dropped[i].contains_key(&section.index()) || !is_copied(section.kind()) {
+                continue;
+            }
+            let section_name =
+                section.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            let SectionFlags::Elf { sh_flags } = section.flags() else { unreachable!() };
+            let id = out.add_section(Vec::new(), section_name.to_vec(), section.kind());
+            let out_section = out.section_mut(id);
+            out_section.flags = SectionFlags::Elf {
+                sh_flags: sh_flags & !u64::from(elf::SHF_GROUP | elf::SHF_LINK_ORDER),
+            };
+            if section.kind().is_bss() {
+                out_section.append_bss(section.size(), section.align());
+            } else {
+                let data = section.data().map_err(|error| format!("`{name}`: {error}"))?;
+                out_section.set_data(data.to_vec(), section.align());
+            }
+            ids.insert(section.index(), id);
+        }
+        sections.push(ids);
+    }
+
+    // The sections that replace those of the dropped groups: the section of the same
+    // name and size in the group that was kept, which holds the same code or data.
+    let mut replacements: Vec<FxHashMap<SectionIndex, SectionId>> =
+        Vec::with_capacity(objects.len());
+    for (i, (name, file)) in objects.iter().enumerate() {
+        let mut ids = FxHashMap::default();
+        for section in file.sections() {
+            let Some(signature) = dropped[i].get(&section.index()) else { continue };
+            let section_name =
+                section.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            let (j, kept) = &groups[signature];
+            let kept_file = &objects[*j].1;
+            let replacement = kept.iter().find(|&&index| {
+                kept_file.section_by_index(index).is_ok_and(|kept_section| {
+                    kept_section.name_bytes().is_ok_and(|kept_name| kept_name == section_name)
+                        && kept_section.size() == section.size()
+                })
+            });
+            if let Some(&id) = replacement.and_then(|index| sections[*j].get(index)) {
+                ids.insert(section.index(), id);
+            }
+        }
+        replacements.push(ids);
+    }
+
+    // Definitions come first, so that references can be resolved to them by name.
+    let mut symbols: Vec<FxHashMap<SymbolIndex, SymbolId>> = Vec::with_capacity(objects.len());
+    let mut globals: FxHashMap<&[u8], SymbolId> = FxHashMap::default();
+    for (i, (name, file)) in objects.iter().enumerate() {
+        let mut ids = FxHashMap::default();
+        for symbol in file.symbols() {
+            let section = match symbol.section() {
+                SymbolSection::Section(index) => match sections[i].get(&index) {
+                    Some(&id) => write::SymbolSection::Section(id),
+                    None => continue,
+                },
+                SymbolSection::Absolute => write::SymbolSection::Absolute,
+                SymbolSection::Common => write::SymbolSection::Common,
+                _ => continue,
+            };
+            match (symbol.kind(), section) {
+                (SymbolKind::Section, write::SymbolSection::Section(id)) => {
+                    ids.insert(symbol.index(), out.section_symbol(id));
+                    continue;
+                }
+                (SymbolKind::Section | SymbolKind::File, _) => continue,
+                _ => {}
+            }
+            let symbol_name = symbol.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            let global = symbol.is_global();
+            // Only the definition that wins is added, the others resolve to it by name.
+            if global {
+                let (j, index, _) = definitions[symbol_name];
+                if (j, index) != (i, symbol.index()) {
+                    continue;
+                }
+            }
+            let SymbolFlags::Elf { st_info, st_other } = symbol.flags() else { unreachable!() };
+            let localize = global && !symbol.is_common() && !exported.contains(symbol_name);
+            let (scope, weak, st_info) = if localize {
+                (SymbolScope::Compilation, false, (elf::STB_LOCAL << 4) | (st_info & 0xf))
+            } else {
+                (symbol.scope(), symbol.is_weak(), st_info)
+            };
+            let id = out.add_symbol(write::Symbol {
+                name: symbol_name.to_vec(),
+                value: symbol.address(),
+                size: symbol.size(),
+                kind: symbol.kind(),
+                scope,
+                weak,
+                section,
+                flags: SymbolFlags::Elf { st_info, st_other },
+            });
+            if global {
+                globals.insert(symbol_name, id);
+            }
+            ids.insert(symbol.index(), id);
+        }
+        symbols.push(ids);
+    }
+
+    // References, and the definitions that lost to another one.
+    for (i, (name, file)) in objects.iter().enumerate() {
+        for symbol in file.symbols() {
+            if !symbol.is_global() || symbols[i].contains_key(&symbol.index()) {
+                continue;
+            }
+            let symbol_name = symbol.name_bytes().map_err(|error| format!("`{name}`: {error}"))?;
+            let SymbolFlags::Elf { st_info, st_other } = symbol.flags() else { unreachable!() };
+            let id = match globals.get(symbol_name) {
+                Some(&id) => {
+                    // A strong reference makes a weak undefined symbol strong.
+                    let existing = out.symbol_mut(id);
+                    if matches!(existing.section, write::SymbolSection::Undefined)
+                        && existing.weak
+                        && !symbol.is_weak()
+                    {
+                        existing.weak = false;
+                        existing.flags = SymbolFlags::Elf {
+                            st_info: (elf::STB_GLOBAL << 4) | (st_info & 0xf),
+                            st_other,
+                        };
+                    }
+                    id
+                }
+                None => {
+                    let id = out.add_symbol(write::Symbol {
+                        name: symbol_name.to_vec(),
+                        value: 0,
+                        size: 0,
+                        kind: symbol.kind(),
+                        scope: SymbolScope::Dynamic,
+                        weak: symbol.is_weak(),
+                        section: write::SymbolSection::Undefined,
+                        flags: SymbolFlags::Elf { st_info, st_other },
+                    });
+                    globals.insert(symbol_name, id);
+                    id
+                }
+            };
+            symbols[i].insert(symbol.index(), id);
+        }
+    }
+
+    for (i, (name, file)) in objects.iter().enumerate() {
+        for section in file.sections() {
+            let Some(&id) = sections[i].get(&section.index()) else { continue };
+            for (offset, relocation) in section.relocations() {
+                let symbol = match relocation.target() {
+                    RelocationTarget::Symbol(index) => symbols[i].get(&index).copied(),
+                    RelocationTarget::Section(index) => {
+                        sections[i].get(&index).map(|&section| out.section_symbol(section))
+                    }
+                    _ => None,
+                };
+                // The section, or the section symbol, of a dropped COMDAT group.
+                let dropped_section = match relocation.target() {
+                    RelocationTarget::Symbol(index) => file
+                        .symbol_by_index(index)
+                        .ok()
+                        .filter(|symbol| symbol.kind() == SymbolKind::Section)
+                        .and_then(|symbol| symbol.section_index()),
+                    RelocationTarget::Section(index) => Some(index),
+                    _ => None,
+                }
+                .filter(|index| dropped[i].contains_key(index));
+                let symbol = match (symbol, dropped_section) {
+                    (Some(symbol), _) => symbol,
+                    (None, Some(index)) => match replacements[i].get(&index) {
+                        Some(&replacement) => out.section_symbol(replacement),
+                        // The field keeps the addend, or 0, which linkers use as a
+                        // tombstone for references to discarded sections.
+                        None => continue,
+                    },
+                    // `R_*_NONE` is 0 on every architecture, and refers to nothing.
+                    (None, None)
+                        if matches!(relocation.flags(), RelocationFlags::Elf { r_type: 0 }) =>
+                    {
+                        continue;
+                    }
+                    (None, None) => {
+                        return Err(format!(
+                            "`{name}`: a relocation in `{}` refers to a symbol that was dropped",
+                            section.name().unwrap_or("<unnamed>"),
+                        ));
+                    }
+                };
+                // Implicit addends are part of the section data, which was copied.
+                let addend = if relocation.has_implicit_addend() { 0 } else { relocation.addend() };
+                out.add_relocation(
+                    id,
+                    write::Relocation { offset, symbol, addend, flags: relocation.flags() },
+                )
+                .map_err(|error| format!("`{name}`: {error}"))?;
+            }
+        }
+    }
+
+    out.write().map_err(|error| error.to_string())
+}
+
+/// Whether sections of `kind` are copied to the merged object. Symbol and
+/// string tables, relocations and groups are rebuilt instead.
+fn is_copied(kind: SectionKind) -> bool {
+    match kind {
+        SectionKind::Metadata | SectionKind::Linker | SectionKind::Unknown => false,
+        SectionKind::Elf(sh_type) => sh_type != elf::SHT_GROUP && !SHT_LLVM.contains(&sh_type),
+        _ => true,
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::Cursor;
+
+use ar_archive_writer::{
+    ArchiveKind, DEFAULT_OBJECT_READER, NewArchiveMember, write_archive_to_stream,
+};
+use object::{Architecture, ComdatKind, Endianness};
+
+use super::*;
+
+/// An object with a COMDAT group `dup`, whose `.text.dup` section is `text_size`
+/// bytes large, and a `.debug_info` section that refers to that section.
+fn object_with_comdat(text_size: usize) -> Vec<u8> {
+    let mut obj = write::Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+
+    let text = obj.add_section(Vec::new(), b".text.dup".to_vec(), SectionKind::Text);
+    obj.append_section_data(text, &vec![0xc3; text_size], 16);
+    let signature = obj.add_symbol(write::Symbol {
+        name: b"dup".to_vec(),
+        value: 0,
+        size: text_size as u64,
+        kind: SymbolKind::Text,
+        scope: SymbolScope::Linkage,
+        weak: true,
+        section: write::SymbolSection::Section(text),
+        flags: SymbolFlags::None,
+    });
+    obj.add_comdat(write::Comdat {
+        kind: ComdatKind::Any,
+        symbol: signature,
+        sections: vec![text],
+    });
+
+    let debug = obj.add_section(Vec::new(), b".debug_info".to_vec(), SectionKind::Debug);
+    obj.append_section_data(debug, &[0; 8], 1);
+    let symbol = obj.section_symbol(text);
+    obj.add_relocation(
+        debug,
+        write::Relocation {
+            offset: 0,
+            symbol,
+            addend: 4,
+            flags: RelocationFlags::Elf { r_type: elf::R_X86_64_64 },
+        },
+    )
+    .unwrap();
+
+    obj.write().unwrap()
+}
+
+fn archive(objects: Vec<Vec<u8>>) -> Vec<u8> {
+    let members: Vec<NewArchiveMember<'_>> = objects
+        .into_iter()
+        .enumerate()
+        .map(|(i, data)| NewArchiveMember {
+            buf: Box::new(data),
+            object_reader: &DEFAULT_OBJECT_READER,
+            member_name: format!("{i}.o"),
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            perms: 0o644,
+        })
+        .collect();
+    let mut data = Cursor::new(Vec::new());
+    write_archive_to_stream(&mut data, &members, ArchiveKind::Gnu, false, false).unwrap();
+    data.into_inner()
+}
+
+/// The section that the relocations of each `.debug_info` section refer to.
+fn debug_info_targets(merged: &object::File<'_>) -> Vec<Vec<SectionIndex>> {
+    merged
+        .sections()
+        .filter(|section| section.name() == Ok(".debug_info"))
+        .map(|section| {
+            section
+                .relocations()
+                .map(|(_, relocation)| {
+                    assert_eq!(relocation.addend(), 4);
+                    let RelocationTarget::Symbol(index) = relocation.target() else {
+                        panic!("unexpected relocation target {:?}", relocation.target());
+                    };
+                    merged.symbol_by_index(index).unwrap().section_index().unwrap()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn debuginfo_of_dropped_comdat_refers_to_kept_group() {
+    let data = archive(vec![object_with_comdat(8), object_with_comdat(8)]);
+    let merged = merge_objects(&data, &FxHashSet::default()).unwrap();
+    let merged = object::File::parse(&*merged).unwrap();
+
+    let texts: Vec<SectionIndex> = merged
+        .sections()
+        .filter(|section| section.name() == Ok(".text.dup"))
+        .map(|section| section.index())
+        .collect();
+    assert_eq!(texts.len(), 1);
+    assert_eq!(debug_info_targets(&merged), [texts.clone(), texts]);
+}
+
+#[test]
+fn debuginfo_of_dropped_comdat_without_match_is_unrelocated() {
+    // The groups differ in size, so the second one's sections have no counterpart.
+    let data = archive(vec![object_with_comdat(8), object_with_comdat(16)]);
+    let merged = merge_objects(&data, &FxHashSet::default()).unwrap();
+    let merged = object::File::parse(&*merged).unwrap();
+
+    let text = merged.section_by_name(".text.dup").unwrap();
+    assert_eq!(text.size(), 8);
+    assert_eq!(debug_info_targets(&merged), [vec![text.index()], vec![]]);
+}
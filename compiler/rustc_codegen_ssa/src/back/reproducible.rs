@@ -0,0 +1,606 @@
+//! `-Zverify-reproducible`: builds the crate a second time in a perturbed
+//! environment, and compares the outputs of the two builds part by part.
+//!
+//! The second build runs the same compiler with the same arguments, except that:
+//!
+//! * it runs in a different working directory, into which the relative source
+//!   files of the first build are copied, while relative library paths are made
+//!   absolute, and `--remap-path-prefix` sources in or above the working
+//!   directory are moved to the same place relative to the new one;
+//! * it writes its outputs to a different directory, including those that `-o`
+//!   and `--emit` give paths for;
+//! * it uses a different number of threads;
+//! * it gets its environment in sorted order, which usually isn't the order
+//!   rustc got it in;
+//! * it doesn't use the incremental cache of the first build.
+//!
+//! Object files are compared section by section, and archives member by member.
+//! Both builds write the regions of their metadata with `-Zdump-metadata-regions`,
+//! so that differences in it are reported as the table entries, and hence the
+//! queries and items, whose encoding differs.
+
+use std::ffi::OsStr;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::{fs, io};
+
+use object::read::archive::ArchiveFile;
+use object::{Object, ObjectSection, ObjectSymbol};
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_metadata::{METADATA_FILENAME, METADATA_HEADER, MetadataRegion, read_metadata_regions};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_session::config::{Options, OutFileName, OutputType};
+use rustc_session::output::{filename_for_metadata, out_filename};
+use rustc_span::FileName;
+use tempfile::TempDir;
+
+use crate::errors;
+
+/// How many differences are listed for each output.
+const MAX_DIFFERENCES: usize = 20;
+
+pub struct ReproducibilityCheck {
+    dir: TempDir,
+    /// Where the first build writes the regions of its metadata.
+    first_regions: PathBuf,
+}
+
+impl ReproducibilityCheck {
+    /// Prepares the second build, and makes the first build, which `opts` are the
+    /// options of, record the regions of its metadata.
+    pub fn new(opts: &mut Options) -> io::Result<ReproducibilityCheck> {
+        let dir = tempfile::Builder::new().prefix("rustc-verify-reproducible").tempdir()?;
+        let first_regions = opts
+            .unstable_opts
+            .dump_metadata_regions
+            .get_or_insert_with(|| dir.path().join("first.regions"))
+            .clone();
+        Ok(ReproducibilityCheck { dir, first_regions })
+    }
+
+    /// Builds the crate again with `args`, the arguments of the first build, and
+    /// reports the differences between the `outputs` of the two builds.
+    pub fn verify(self, sess: &Session, args: &[String], outputs: &[PathBuf]) {
+        if let Err(error) = self.build_again(sess, args) {
+            if let Some(error) = error {
+                sess.dcx().emit_err(errors::VerifyReproducibleFailed { error: error.to_string() });
+            }
+            return;
+        }
+
+        let first_regions = read_metadata_regions(&self.first_regions).ok();
+        let second_regions = read_metadata_regions(&self.second_regions()).ok();
+        let regions = first_regions.as_deref().zip(second_regions.as_deref());
+        for first in outputs {
+            let second = self.out_dir().join(first.file_name().unwrap());
+            let differences = match (fs::read(first), fs::read(&second)) {
+                (Ok(first), Ok(second)) => compare_files(&first, &second, regions),
+                (Ok(_), Err(_)) => vec!["the second build didn't write it".to_owned()],
+                (Err(error), _) => {
+                    sess.dcx().emit_err(errors::VerifyReproducibleFailed {
+                        error: format!("couldn't read `{}`: {error}", first.display()),
+                    });
+                    continue;
+                }
+            };
+            if differences.is_empty() {
+                continue;
+            }
+            let mut listed: Vec<String> =
+                differences.iter().take(MAX_DIFFERENCES).map(|d| format!("- {d}")).collect();
+            if differences.len() > MAX_DIFFERENCES {
+                listed.push(format!("and {} more", differences.len() - MAX_DIFFERENCES));
+            }
+            sess.dcx().emit_err(errors::NotReproducible {
+                path: first.clone(),
+                differences: listed.join("\n"),
+            });
+        }
+    }
+
+    /// Runs the second build. Returns `Err(None)` if it failed and that was reported.
+    fn build_again(&self, sess: &Session, args: &[String]) -> Result<(), Option<io::Error>> {
+        let cwd = std::env::current_dir()?;
+        let second_cwd = copy_relative_sources(sess, &cwd, &self.dir.path().join("cwd"))?;
+        fs::create_dir_all(self.out_dir())?;
+
+        let threads = if sess.threads() > 1 { 1 } else { 4 };
+        let args = second_build_args(
+            args,
+            &cwd,
+            &second_cwd,
+            &self.out_dir(),
+            &self.second_regions(),
+            threads,
+        );
+
+        let mut command = Command::new(std::env::current_exe()?);
+        command.args(&args).current_dir(&second_cwd);
+        // `Command` passes the environment sorted by name.
+        command.env_clear().envs(std::env::vars_os()).env("PWD", &second_cwd);
+        let output = command.output()?;
+        if !output.status.success() {
+            let mut stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            stderr.truncate(stderr.trim_end().len());
+            sess.dcx().emit_err(errors::VerifyReproducibleBuildFailed {
+                status: output.status,
+                output: stderr,
+            });
+            return Err(None);
+        }
+        Ok(())
+    }
+
+    fn out_dir(&self) -> PathBuf {
+        self.dir.path().join("out")
+    }
+
+    fn second_regions(&self) -> PathBuf {
+        self.dir.path().join("second.regions")
+    }
+}
+
+/// The files that the build of the crate wrote, which `-Zverify-reproducible` compares.
+pub fn build_outputs(tcx: TyCtxt<'_>) -> Vec<PathBuf> {
+    let sess = tcx.sess;
+    let outputs = tcx.output_filenames(());
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+
+    let mut paths = Vec::new();
+    if sess.opts.output_types.should_link() {
+        for &crate_type in tcx.crate_types() {
+            paths.push(out_filename(sess, crate_type, outputs, crate_name));
+        }
+    }
+    if sess.opts.output_types.contains_key(&OutputType::Metadata) {
+        paths.push(filename_for_metadata(sess, outputs));
+    }
+    if sess.opts.output_types.contains_key(&OutputType::Object) {
+        paths.push(outputs.path(OutputType::Object));
+    }
+    paths
+        .into_iter()
+        .filter_map(|path| match path {
+            OutFileName::Real(path) => Some(path),
+            OutFileName::Stdout => None,
+        })
+        .collect()
+}
+
+/// Copies the source files that the first build read through relative paths into
+/// `root`, so that the second build reads them through the same paths, and returns
+/// the working directory of the second build.
+///
+/// Paths can go up from the working directory with `..`, so the working directory
+/// is nested in `root` deep enough for all of them to stay inside it.
+fn copy_relative_sources(sess: &Session, cwd: &Path, root: &Path) -> io::Result<PathBuf> {
+    let mut paths: Vec<PathBuf> = sess
+        .source_map()
+        .files()
+        .iter()
+        .filter_map(|file| match &file.name {
+            FileName::Real(name) => name.local_path().map(Path::to_owned),
+            _ => None,
+        })
+        .collect();
+    paths.extend(sess.psess.file_depinfo.borrow().iter().map(|path| PathBuf::from(path.as_str())));
+    let paths: Vec<(PathBuf, usize)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let levels = levels_above(&path)?;
+            cwd.join(&path).is_file().then_some((path, levels))
+        })
+        .collect();
+
+    let levels = paths.iter().map(|&(_, levels)| levels).max().unwrap_or(0);
+    let mut second_cwd = root.to_owned();
+    for level in 0..levels {
+        second_cwd.push(level.to_string());
+    }
+    fs::create_dir_all(&second_cwd)?;
+
+    for (path, _) in paths {
+        let dest = normalize_lexically(&second_cwd.join(&path));
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(cwd.join(&path), dest)?;
+    }
+    Ok(second_cwd)
+}
+
+/// How many levels above the directory it is relative to `path` reaches with `..`,
+/// or `None` if it isn't relative.
+fn levels_above(path: &Path) -> Option<usize> {
+    let (mut depth, mut levels) = (0isize, 0);
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::ParentDir => {
+                depth -= 1;
+                if depth < 0 {
+                    levels = levels.max(depth.unsigned_abs());
+                }
+            }
+            Component::CurDir => {}
+            Component::Prefix(_) | Component::RootDir => return None,
+        }
+    }
+    Some(levels)
+}
+
+/// Resolves the `.` and `..` in `path` without looking at the file system.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The arguments of the second build: those of the first one, with its outputs
+/// redirected, its relative library paths made absolute, and the paths that it
+/// remaps moved to its working directory, `second_cwd`.
+fn second_build_args(
+    args: &[String],
+    cwd: &Path,
+    second_cwd: &Path,
+    out_dir: &Path,
+    regions: &Path,
+    threads: usize,
+) -> Vec<String> {
+    const VALUE_FLAGS: &[&str] = &[
+        "-o",
+        "-L",
+        "-Z",
+        "-C",
+        "--out-dir",
+        "--emit",
+        "--extern",
+        "--sysroot",
+        "--remap-path-prefix",
+    ];
+    const SEARCH_PATH_KINDS: &[&str] = &["native", "crate", "dependency", "framework", "all"];
+
+    let absolute = |path: &str| -> String {
+        if Path::new(path).is_absolute() {
+            path.to_owned()
+        } else {
+            cwd.join(path).to_string_lossy().into_owned()
+        }
+    };
+
+    // The same file name, in the output directory of the second build.
+    let redirect = |path: &str| -> String {
+        let name = Path::new(path).file_name().unwrap_or(OsStr::new(path));
+        out_dir.join(name).to_string_lossy().into_owned()
+    };
+
+    let mut second = Vec::with_capacity(args.len() + 6);
+    let mut has_output_file = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // Both `-L path` and `-Lpath`, and both `--extern x` and `--extern=x`.
+        let split = VALUE_FLAGS.iter().find_map(|&flag| {
+            if arg == flag {
+                Some((flag, args.next()?.clone()))
+            } else if flag.starts_with("--") {
+                Some((flag, arg.strip_prefix(flag)?.strip_prefix('=')?.to_owned()))
+            } else {
+                Some((flag, arg.strip_prefix(flag)?.to_owned()))
+            }
+        });
+        let Some((flag, value)) = split else {
+            second.push(arg.clone());
+            continue;
+        };
+        let value = match flag {
+            "-o" if value == "-" => value,
+            "-o" => {
+                has_output_file = true;
+                redirect(&value)
+            }
+            // Replaced by the output directory of the second build below.
+            "--out-dir" => continue,
+            // `--emit link,obj=path/to/file.o`
+            "--emit" => value
+                .split(',')
+                .map(|emit| match emit.split_once('=') {
+                    Some((kind, path)) if path != "-" => format!("{kind}={}", redirect(path)),
+                    _ => emit.to_owned(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            "-L" => match value.split_once('=') {
+                Some((kind, path)) if SEARCH_PATH_KINDS.contains(&kind) => {
+                    format!("{kind}={}", absolute(path))
+                }
+                _ => absolute(&value),
+            },
+            "--extern" => match value.split_once('=') {
+                Some((name, path)) => format!("{name}={}", absolute(path)),
+                None => value,
+            },
+            "--sysroot" => absolute(&value),
+            // `--remap-path-prefix=$PWD=/src` has to remap the working directory of the
+            // second build for it to embed the same paths.
+            "--remap-path-prefix" => match value.rsplit_once('=') {
+                Some((from, to)) => match rebase(Path::new(from), cwd, second_cwd) {
+                    Some(from) => format!("{}={to}", from.display()),
+                    None => value,
+                },
+                None => value,
+            },
+            "-Z" => {
+                let name = value.split('=').next().unwrap().replace('_', "-");
+                if name == "verify-reproducible" || name == "dump-metadata-regions" {
+                    continue;
+                }
+                value
+            }
+            "-C" => {
+                if value.split('=').next().unwrap() == "incremental" {
+                    continue;
+                }
+                value
+            }
+            _ => unreachable!(),
+        };
+        second.push(flag.to_owned());
+        second.push(value);
+    }
+
+    if !has_output_file {
+        second.push("--out-dir".to_owned());
+        second.push(out_dir.to_string_lossy().into_owned());
+    }
+    second.push("-Z".to_owned());
+    second.push(format!("dump-metadata-regions={}", regions.display()));
+    second.push("-Z".to_owned());
+    second.push(format!("threads={threads}"));
+    second
+}
+
+/// `path` as seen from `second_cwd` rather than `cwd`, if it is `cwd`, a directory
+/// in it, or one of its ancestors.
+fn rebase(path: &Path, cwd: &Path, second_cwd: &Path) -> Option<PathBuf> {
+    if let Ok(rest) = path.strip_prefix(cwd) {
+        if rest.as_os_str().is_empty() {
+            return Some(second_cwd.to_owned());
+        }
+        return Some(second_cwd.join(rest));
+    }
+    let levels = cwd.strip_prefix(path).ok()?.components().count();
+    second_cwd.ancestors().nth(levels).map(Path::to_owned)
+}
+
+type Regions<'a> = (&'a [MetadataRegion], &'a [MetadataRegion]);
+
+/// Describes the differences between two versions of an output file.
+fn compare_files(first: &[u8], second: &[u8], regions: Option<Regions<'_>>) -> Vec<String> {
+    if first == second {
+        Vec::new()
+    } else if let (Some(first), Some(second)) = (archive_members(first), archive_members(second)) {
+        compare_archives(&first, &second, regions)
+    } else if first.starts_with(METADATA_HEADER) {
+        compare_metadata(first, second, regions)
+    } else {
+        compare_objects(first, second)
+    }
+}
+
+/// The members of `data` by name, if it is an archive.
+fn archive_members(data: &[u8]) -> Option<FxIndexMap<String, &[u8]>> {
+    let archive = ArchiveFile::parse(data).ok()?;
+    let mut members = FxIndexMap::default();
+    for member in archive.members() {
+        let member = member.ok()?;
+        let name = String::from_utf8_lossy(member.name()).into_owned();
+        members.insert(name, member.data(data).ok()?);
+    }
+    Some(members)
+}
+
+fn compare_archives(
+    first: &FxIndexMap<String, &[u8]>,
+    second: &FxIndexMap<String, &[u8]>,
+    regions: Option<Regions<'_>>,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    for (name, &data) in first {
+        let Some(&other) = second.get(name) else {
+            differences.push(format!("member `{name}` is only in the first build"));
+            continue;
+        };
+        if data == other {
+            continue;
+        }
+        let member_differences = if name == METADATA_FILENAME {
+            match (metadata_section(data), metadata_section(other)) {
+                (Some(data), Some(other)) => compare_metadata(data, other, regions),
+                _ => compare_objects(data, other),
+            }
+        } else {
+            compare_objects(data, other)
+        };
+        differences.extend(member_differences.into_iter().map(|d| format!("in `{name}`: {d}")));
+    }
+    for name in second.keys().filter(|name| !first.contains_key(*name)) {
+        differences.push(format!("member `{name}` is only in the second build"));
+    }
+    if differences.is_empty() {
+        // The members are the same, so their order or the symbol table differs.
+        differences.push("the order of the members or the symbol table".to_owned());
+    }
+    differences
+}
+
+/// The metadata in the `lib.rmeta` member of an rlib.
+fn metadata_section(member: &[u8]) -> Option<&[u8]> {
+    if member.starts_with(METADATA_HEADER) {
+        return Some(member);
+    }
+    let file = object::File::parse(member).ok()?;
+    let section = file.sections().find(|section| section.name() == Ok(".rmeta"))?;
+    section.data().ok()
+}
+
+fn compare_objects(first: &[u8], second: &[u8]) -> Vec<String> {
+    let (Ok(first_file), Ok(second_file)) =
+        (object::File::parse(first), object::File::parse(second))
+    else {
+        return vec![format!("the contents differ{}", first_difference(first, second))];
+    };
+
+    // Sections can share names, so they are matched by name and occurrence.
+    let (first_sections, second_sections) = (sections(&first_file), sections(&second_file));
+
+    let mut differences = Vec::new();
+    for ((name, occurrence), range) in &first_sections {
+        let Some(other) = second_sections.get(&(name.clone(), *occurrence)) else {
+            differences.push(format!("section `{name}` is only in the first build"));
+            continue;
+        };
+        let data = first.get(range.clone()).unwrap_or_default();
+        let other = second.get(other.clone()).unwrap_or_default();
+        if data == other {
+            continue;
+        }
+        let offset =
+            data.iter().zip(other).position(|(a, b)| a != b).unwrap_or(data.len().min(other.len()));
+        let mut difference = format!("section `{name}` differs from offset {offset:#x}");
+        if data.len() != other.len() {
+            difference.push_str(&format!(
+                " and in size ({} and {} bytes)",
+                data.len(),
+                other.len()
+            ));
+        }
+        if let Some(symbol) = symbol_at(&first_file, name, *occurrence, offset) {
+            difference.push_str(&format!(", in `{symbol}`"));
+        }
+        differences.push(difference);
+    }
+    for (name, _) in second_sections.keys().filter(|key| !first_sections.contains_key(*key)) {
+        differences.push(format!("section `{name}` is only in the second build"));
+    }
+    if differences.is_empty() {
+        // Headers, symbol tables and the like aren't sections on every format.
+        differences
+            .push(format!("the data outside of sections{}", first_difference(first, second)));
+    }
+    differences
+}
+
+/// The file ranges of the sections of `file`, by name and occurrence.
+fn sections(file: &object::File<'_>) -> FxIndexMap<(String, usize), Range<usize>> {
+    let mut sections = FxIndexMap::default();
+    for section in file.sections() {
+        let name = section.name().unwrap_or("<unnamed>").to_owned();
+        let Some((offset, size)) = section.file_range() else { continue };
+        let occurrence = (0..).find(|&i| !sections.contains_key(&(name.clone(), i))).unwrap();
+        sections.insert((name, occurrence), offset as usize..(offset + size) as usize);
+    }
+    sections
+}
+
+/// The symbol that contains `offset` in the `occurrence`th section named `name`.
+fn symbol_at<'data>(
+    file: &object::File<'data>,
+    name: &str,
+    occurrence: usize,
+    offset: usize,
+) -> Option<&'data str> {
+    let section = file.sections().filter(|section| section.name() == Ok(name)).nth(occurrence)?;
+    let address = section.address() + offset as u64;
+    file.symbols()
+        .filter(|symbol| symbol.section_index() == Some(section.index()))
+        .filter(|symbol| {
+            symbol.address() <= address
+                && (address < symbol.address() + symbol.size() || symbol.size() == 0)
+        })
+        .max_by_key(|symbol| (symbol.size() != 0, symbol.address()))
+        .and_then(|symbol| symbol.name().ok())
+        .filter(|name| !name.is_empty())
+}
+
+fn compare_metadata(first: &[u8], second: &[u8], regions: Option<Regions<'_>>) -> Vec<String> {
+    let Some((first_regions, second_regions)) = regions else {
+        return vec![format!("the metadata differs{}", first_difference(first, second))];
+    };
+
+    // Regions are matched by what they hold, since they move when any part
+    // before them changes size.
+    let (first_keyed, second_keyed) = (keyed_regions(first_regions), keyed_regions(second_regions));
+
+    let mut differing: Vec<(&Range<usize>, String)> = Vec::new();
+    for (key, range) in &first_keyed {
+        let what = describe_region(key.0);
+        match second_keyed.get(key) {
+            None => differing.push((range, format!("{what} is only in the first build"))),
+            Some(other) if first.get(range.clone()) != second.get(other.clone()) => {
+                differing.push((range, format!("{what} differs")))
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Only report the innermost differing regions: a table entry rather than
+    // the section it is encoded in.
+    let innermost: Vec<String> = differing
+        .iter()
+        .filter(|(range, _)| {
+            !differing.iter().any(|(other, _)| {
+                *other != *range && range.start <= other.start && other.end <= range.end
+            })
+        })
+        .map(|(_, difference)| format!("in the metadata, {difference}"))
+        .collect();
+    let mut differences = innermost;
+    for (key, _) in second_keyed.keys().filter(|key| !first_keyed.contains_key(*key)) {
+        let what = describe_region(*key);
+        differences.push(format!("in the metadata, {what} is only in the second build"));
+    }
+    if differences.is_empty() {
+        // Only what isn't in any region differs, like the position of the root.
+        differences.push(format!("the metadata header differs{}", first_difference(first, second)));
+    }
+    differences
+}
+
+type RegionKey<'a> = (&'a str, Option<&'a str>);
+
+/// The ranges of `regions` by label, item and occurrence.
+fn keyed_regions(regions: &[MetadataRegion]) -> FxIndexMap<(RegionKey<'_>, usize), Range<usize>> {
+    let mut keyed = FxIndexMap::default();
+    for region in regions {
+        let key = (region.label.as_str(), region.item.as_deref());
+        let occurrence = (0..).find(|&i| !keyed.contains_key(&(key, i))).unwrap();
+        keyed.insert((key, occurrence), region.range.clone());
+    }
+    keyed
+}
+
+fn describe_region((label, item): RegionKey<'_>) -> String {
+    match item {
+        Some(item) => format!("the `{label}` of `{item}`"),
+        None => format!("`{label}`"),
+    }
+}
+
+/// Where `first` and `second` first differ.
+fn first_difference(first: &[u8], second: &[u8]) -> String {
+    match first.iter().zip(second).position(|(a, b)| a != b) {
+        Some(offset) => format!(" from offset {offset:#x}"),
+        None => format!(" in size ({} and {} bytes)", first.len(), second.len()),
+    }
+}
@@ -0,0 +1,348 @@
+//! `--print=stack-sizes`: reports the stack frame size of each function, and
+//! a worst-case bound on the stack it uses including its callees.
+//!
+//! The functions of the crate and the calls between them are recorded in the
+//! [`CrateInfo`] while the `TyCtxt` is still alive, from the calls and drops in
+//! their MIR. The frame sizes are read back from the `.stack_sizes` sections
+//! that LLVM emits into ELF object files for this print request, as it does
+//! for `-Z emit-stack-sizes`, right after codegen.
+//!
+//! The bound of a function is the largest sum of the frame sizes along any
+//! call chain starting at it. There is no bound for functions that can reach
+//! recursion, an indirect call, a function whose frame size is unknown, or a
+//! function of another crate, whose frame sizes are unknown too. The last one
+//! is common enough that the report also has the bound that the calls of
+//! functions of other crates would have if they used no stack at all.
+
+use std::fs;
+
+use object::{
+    BinaryFormat, Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionIndex, SymbolKind,
+};
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap, FxIndexSet};
+use rustc_data_structures::graph::scc::Sccs;
+use rustc_data_structures::graph::vec_graph::VecGraph;
+use rustc_hir::LangItem;
+use rustc_macros::{Decodable, Encodable};
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::{AssertKind, TerminatorKind};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceKind, Ty, TyCtxt};
+use rustc_session::Session;
+use rustc_session::config::OutFileName;
+use serde_json::json;
+
+use crate::{CompiledModule, CrateInfo, errors};
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct StackSizeInfo {
+    /// The instance, e.g. `Vec::<u8>::push`.
+    name: String,
+    symbol: String,
+    /// The symbols of the functions of this crate that the function calls,
+    /// including drop glue.
+    callees: Vec<String>,
+    /// The functions of other crates that the function calls, including the
+    /// panic functions called by failed assertions.
+    external_callees: Vec<String>,
+    /// Whether the function calls through a function pointer or a trait
+    /// object.
+    indirect_calls: bool,
+}
+
+/// Records the functions of the crate and their calls, if
+/// `--print=stack-sizes` was requested.
+pub(crate) fn collect(tcx: TyCtxt<'_>) -> Vec<StackSizeInfo> {
+    let mut items: FxIndexMap<Instance<'_>, StackSizeInfo> = Default::default();
+    for cgu in tcx.collect_and_partition_mono_items(()).codegen_units {
+        for &mono_item in cgu.items().keys() {
+            let MonoItem::Fn(instance) = mono_item else { continue };
+            items.entry(instance).or_insert_with(|| {
+                let calls = scan_calls(tcx, instance);
+                StackSizeInfo {
+                    name: with_no_trimmed_paths!(instance.to_string()),
+                    symbol: mono_item.symbol_name(tcx).name.to_owned(),
+                    callees: calls.callees.into_iter().collect(),
+                    external_callees: calls.external_callees.into_iter().collect(),
+                    indirect_calls: calls.indirect_calls,
+                }
+            });
+        }
+    }
+    items.into_values().collect()
+}
+
+#[derive(Default)]
+struct Calls {
+    callees: FxIndexSet<String>,
+    external_callees: FxIndexSet<String>,
+    indirect_calls: bool,
+}
+
+impl Calls {
+    fn add<'tcx>(&mut self, tcx: TyCtxt<'tcx>, callee: Instance<'tcx>) {
+        match callee.def {
+            InstanceKind::Virtual(..) => self.indirect_calls = true,
+            // Intrinsics are lowered in place, or to calls of lang items that
+            // are functions of their own.
+            InstanceKind::Intrinsic(_) => {}
+            // Dropping the value does nothing.
+            InstanceKind::DropGlue(_, None) => {}
+            _ if tcx.should_codegen_locally(callee) => {
+                self.callees.insert(tcx.symbol_name(callee).name.to_owned());
+            }
+            _ => {
+                self.external_callees.insert(with_no_trimmed_paths!(callee.to_string()));
+            }
+        }
+    }
+
+    fn add_lang_item(&mut self, tcx: TyCtxt<'_>, item: LangItem) {
+        self.add(tcx, Instance::mono(tcx, tcx.require_lang_item(item, None)));
+    }
+}
+
+/// Finds the functions that `instance` calls, and whether it calls through
+/// function pointers or trait objects.
+fn scan_calls<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> Calls {
+    let body = tcx.instance_mir(instance.def);
+    let typing_env = ty::TypingEnv::fully_monomorphized();
+    let monomorphize = |ty: Ty<'tcx>| {
+        instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            typing_env,
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+
+    let mut calls = Calls::default();
+    for data in body.basic_blocks.iter() {
+        match &data.terminator().kind {
+            TerminatorKind::Call { func, .. } | TerminatorKind::TailCall { func, .. } => {
+                match *monomorphize(func.ty(body, tcx)).kind() {
+                    ty::FnDef(def_id, args) => {
+                        match Instance::try_resolve(tcx, typing_env, def_id, args) {
+                            Ok(Some(callee)) => calls.add(tcx, callee),
+                            _ => calls.indirect_calls = true,
+                        }
+                    }
+                    ty::FnPtr(..) => calls.indirect_calls = true,
+                    _ => {}
+                }
+            }
+            TerminatorKind::Drop { place, .. } => {
+                let ty = monomorphize(place.ty(body, tcx).ty);
+                // Trait objects are dropped through their vtable.
+                if ty.is_trait() {
+                    calls.indirect_calls = true;
+                } else {
+                    calls.add(tcx, Instance::resolve_drop_in_place(tcx, ty));
+                }
+            }
+            TerminatorKind::Assert { msg, .. } => {
+                let item = match &**msg {
+                    AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    AssertKind::NullPointerDereference => LangItem::PanicNullPointerDereference,
+                    _ => msg.panic_function(),
+                };
+                calls.add_lang_item(tcx, item);
+            }
+            TerminatorKind::UnwindTerminate(reason) => {
+                calls.add_lang_item(tcx, reason.lang_item());
+            }
+            _ => {}
+        }
+    }
+    calls
+}
+
+/// Reads the frame sizes from the object files of `modules`, and prints the
+/// report.
+///
+/// This runs right after codegen, like `--print=mono-item-sizes`, so that it
+/// also works with `--emit=obj` and `-Zno-link`.
+pub(crate) fn print(
+    sess: &Session,
+    crate_info: &CrateInfo,
+    modules: &[CompiledModule],
+    out: &OutFileName,
+) {
+    if !modules.iter().any(|module| module.object.is_some()) {
+        sess.dcx().emit_err(errors::PrintRequiresObjects { kind: "stack-sizes" });
+        return;
+    }
+    if sess.target.binary_format != rustc_target::spec::BinaryFormat::Elf {
+        sess.dcx().emit_warn(errors::StackSizesUnsupported);
+    }
+
+    let frame_sizes = frame_sizes(modules);
+    let CrateInfo { stack_sizes, local_crate_name, .. } = crate_info;
+
+    let index: FxHashMap<&str, usize> =
+        stack_sizes.iter().enumerate().map(|(i, info)| (info.symbol.as_str(), i)).collect();
+    // Callees that are missing from the report have no known frame size.
+    let mut unknown_callee = vec![false; stack_sizes.len()];
+    let mut edges = vec![];
+    for (i, info) in stack_sizes.iter().enumerate() {
+        for callee in &info.callees {
+            match index.get(callee.as_str()) {
+                Some(&j) => edges.push((i, j)),
+                None => unknown_callee[i] = true,
+            }
+        }
+    }
+    let graph = VecGraph::<usize>::new(stack_sizes.len(), edges);
+    let sccs = Sccs::<usize, usize>::new(&graph);
+
+    let mut scc_len = vec![0; sccs.num_sccs()];
+    for i in 0..stack_sizes.len() {
+        scc_len[sccs.scc(i)] += 1;
+    }
+    let recursive: Vec<bool> = (0..stack_sizes.len())
+        .map(|i| scc_len[sccs.scc(i)] > 1 || graph.successors(i).contains(&i))
+        .collect();
+
+    // The SCCs are numbered such that callees come before their callers. The
+    // bound of a recursive function is unknown, so each SCC with a bound has a
+    // single function.
+    let mut order: Vec<usize> = (0..stack_sizes.len()).collect();
+    order.sort_by_key(|&i| sccs.scc(i));
+    let mut scc_bound: Vec<Option<u64>> = vec![None; sccs.num_sccs()];
+    let mut scc_external: Vec<bool> = vec![false; sccs.num_sccs()];
+    let mut bounds: Vec<(Option<u64>, Option<u64>)> = vec![(None, None); stack_sizes.len()];
+    for i in order {
+        let info = &stack_sizes[i];
+        let scc = sccs.scc(i);
+        scc_external[scc] |= !info.external_callees.is_empty()
+            || sccs.successors(scc).iter().any(|&callee| scc_external[callee]);
+        let bound = if recursive[i] || info.indirect_calls || unknown_callee[i] {
+            None
+        } else {
+            frame_sizes.get(info.symbol.as_str()).and_then(|&frame| {
+                sccs.successors(sccs.scc(i))
+                    .iter()
+                    .try_fold(0, |max, &callee| Some(max.max(scc_bound[callee]?)))
+                    .map(|callees| frame + callees)
+            })
+        };
+        scc_bound[scc] = bound;
+        // `scc_external` only has its final value once every function of the
+        // SCC is seen, but recursive SCCs have no bound anyway.
+        bounds[i] = (bound.filter(|_| !scc_external[scc]), bound);
+    }
+
+    let mut functions: Vec<_> = stack_sizes.iter().zip(bounds).zip(recursive).collect();
+    // Deepest first, so that the culprits are at the top.
+    functions.sort_by(|((a, (_, a_bound)), _), ((b, (_, b_bound)), _)| {
+        b_bound.cmp(a_bound).then_with(|| a.name.cmp(&b.name))
+    });
+    let functions: Vec<_> = functions
+        .into_iter()
+        .map(|((info, (bound, local_bound)), recursive)| {
+            json!({
+                "name": info.name,
+                "symbol": info.symbol,
+                "frame_bytes": frame_sizes.get(info.symbol.as_str()),
+                "max_stack_bytes": bound,
+                "max_stack_bytes_without_external_callees": local_bound,
+                "recursive": recursive,
+                "indirect_calls": info.indirect_calls,
+                "callees": info.callees,
+                "external_callees": info.external_callees,
+            })
+        })
+        .collect();
+
+    let report = json!({
+        "version": 1,
+        "crate": local_crate_name.as_str(),
+        "functions": functions,
+    });
+    let mut report = serde_json::to_string_pretty(&report).unwrap();
+    report.push('\n');
+    out.overwrite(&report, sess);
+}
+
+/// Returns the largest frame size of each function symbol over all object
+/// files, since local copies are emitted once per codegen unit.
+fn frame_sizes(modules: &[CompiledModule]) -> FxHashMap<String, u64> {
+    let mut sizes = FxHashMap::default();
+    for path in modules.iter().filter_map(|m| m.object.as_ref()) {
+        // Objects that can't be read (e.g. because they only hold bitcode for
+        // linker-plugin LTO) simply contribute nothing.
+        let Ok(data) = fs::read(path) else { continue };
+        let Ok(file) = object::File::parse(&*data) else { continue };
+        if file.format() != BinaryFormat::Elf {
+            continue;
+        }
+
+        // Relocations against local functions may refer to the section
+        // symbol, plus the offset of the function in the section.
+        let functions: FxHashMap<(SectionIndex, u64), &str> = file
+            .symbols()
+            .filter(|sym| sym.is_definition() && sym.kind() == SymbolKind::Text)
+            .filter_map(|sym| Some(((sym.section_index()?, sym.address()), sym.name().ok()?)))
+            .collect();
+
+        let address_size = if file.is_64() { 8 } else { 4 };
+        let little_endian = file.is_little_endian();
+        for section in file.sections().filter(|s| s.name() == Ok(".stack_sizes")) {
+            let Ok(data) = section.data() else { continue };
+            let relocations: FxHashMap<_, _> = section.relocations().collect();
+
+            // Each entry is the address of the function followed by its
+            // ULEB128-encoded frame size.
+            let mut offset = 0;
+            while offset + address_size < data.len() {
+                let Some((size, size_len)) = read_uleb128(&data[offset + address_size..]) else {
+                    break;
+                };
+                let name = relocations.get(&(offset as u64)).and_then(|reloc| {
+                    let RelocationTarget::Symbol(symbol) = reloc.target() else { return None };
+                    let symbol = file.symbol_by_index(symbol).ok()?;
+                    if symbol.kind() != SymbolKind::Section {
+                        return symbol.name().ok();
+                    }
+                    let addend = if reloc.has_implicit_addend() {
+                        read_address(&data[offset..offset + address_size], little_endian)
+                    } else {
+                        reloc.addend() as u64
+                    };
+                    functions.get(&(symbol.section_index()?, symbol.address() + addend)).copied()
+                });
+                if let Some(name) = name {
+                    let entry = sizes.entry(name.to_owned()).or_default();
+                    *entry = size.max(*entry);
+                }
+                offset += address_size + size_len;
+            }
+        }
+    }
+    sizes
+}
+
+fn read_address(bytes: &[u8], little_endian: bool) -> u64 {
+    let mut buf = [0; 8];
+    if little_endian {
+        buf[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(buf)
+    } else {
+        buf[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(buf)
+    }
+}
+
+/// Returns the value and the encoded length.
+fn read_uleb128(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
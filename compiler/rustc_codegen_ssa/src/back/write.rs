@@ -32,7 +32,8 @@
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
 use rustc_session::config::{
-    self, CrateType, Lto, OutFileName, OutputFilenames, OutputType, Passes, SwitchWithOptPath,
+    self, CrateType, Lto, OutFileName, OutputFilenames, OutputType, Passes, PrintKind,
+    SwitchWithOptPath,
 };
 use rustc_span::source_map::SourceMap;
 use rustc_span::{FileName, InnerSpan, Span, SpanData, sym};
@@ -42,6 +43,7 @@
 use super::link::{self, ensure_removed};
 use super::lto::{self, SerializedModule};
 use super::symbol_export::symbol_name_for_instance_in_crate;
+use super::{mono_item_sizes, stack_sizes};
 use crate::errors::{AutodiffWithoutLto, ErrorCreatingRemarkDir};
 use crate::traits::*;
 use crate::{
@@ -559,6 +561,27 @@ fn copy_all_cgu_workproducts_to_incr_comp_cache_dir(
     work_products
 }
 
+/// Prints the `--print` requests that are read back from the object files.
+///
+/// Backends call this once codegen is done, before removing the object files
+/// that are not needed for linking (e.g. with `--emit=obj`), and regardless of
+/// whether a link step follows.
+pub fn print_object_file_reports(
+    sess: &Session,
+    crate_info: &CrateInfo,
+    modules: &[CompiledModule],
+) {
+    for print in &sess.opts.prints {
+        match print.kind {
+            PrintKind::MonoItemSizes => {
+                mono_item_sizes::print(sess, crate_info, modules, &print.out)
+            }
+            PrintKind::StackSizes => stack_sizes::print(sess, crate_info, modules, &print.out),
+            _ => {}
+        }
+    }
+}
+
 fn produce_final_output_artifacts(
     sess: &Session,
     compiled_modules: &CompiledModules,
@@ -2075,6 +2098,7 @@ pub fn join(self, sess: &Session) -> (CodegenResults, FxIndexMap<WorkProductId,
 
         let work_products =
             copy_all_cgu_workproducts_to_incr_comp_cache_dir(sess, &compiled_modules);
+        print_object_file_reports(sess, &self.crate_info, &compiled_modules.modules);
         produce_final_output_artifacts(sess, &compiled_modules, &self.output_filenames);
 
         // FIXME: time_llvm_passes support - does this use a global context or
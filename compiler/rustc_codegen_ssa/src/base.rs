@@ -26,7 +26,7 @@
 use rustc_middle::ty::layout::{HasTyCtxt, HasTypingEnv, LayoutOf, TyAndLayout};
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
 use rustc_session::Session;
-use rustc_session::config::{self, CrateType, EntryFnType, OutputType};
+use rustc_session::config::{self, CrateType, EntryFnType, OutputType, PrintKind};
 use rustc_span::{DUMMY_SP, Symbol, sym};
 use rustc_symbol_mangling::mangle_internal_symbol;
 use rustc_trait_selection::infer::{BoundRegionConversionTime, TyCtxtInferExt};
@@ -40,6 +40,7 @@
     ComputedLtoType, OngoingCodegen, compute_per_cgu_lto_type, start_async_codegen,
     submit_codegened_module_to_llvm, submit_post_lto_module_to_llvm, submit_pre_lto_module_to_llvm,
 };
+use crate::back::{hot_patch, mono_item_sizes, stack_sizes};
 use crate::common::{self, IntPredicate, RealPredicate, TypeKind};
 use crate::meth::load_vtable;
 use crate::mir::operand::OperandValue;
@@ -935,6 +936,33 @@ pub fn new(tcx: TyCtxt<'_>, target_cpu: String) -> CrateInfo {
             windows_subsystem,
             natvis_debugger_visualizers: Default::default(),
             lint_levels: CodegenLintLevels::from_tcx(tcx),
+            mono_item_sizes: if tcx
+                .sess
+                .opts
+                .prints
+                .iter()
+                .any(|print| print.kind == PrintKind::MonoItemSizes)
+            {
+                mono_item_sizes::collect(tcx)
+            } else {
+                vec![]
+            },
+            stack_sizes: if tcx
+                .sess
+                .opts
+                .prints
+                .iter()
+                .any(|print| print.kind == PrintKind::StackSizes)
+            {
+                stack_sizes::collect(tcx)
+            } else {
+                vec![]
+            },
+            hot_patch_cgus: if tcx.sess.opts.unstable_opts.hot_patch {
+                hot_patch::collect(tcx)
+            } else {
+                vec![]
+            },
         };
 
         info.native_libraries.reserve(n_crates);
@@ -1091,6 +1119,12 @@ pub fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) ->
         return CguReuse::No;
     }
 
+    // Objects are only given a `.stack_sizes` section when `--print=stack-sizes` is
+    // requested, which isn't tracked, so cached objects may not have one.
+    if tcx.sess.opts.prints.iter().any(|print| print.kind == PrintKind::StackSizes) {
+        return CguReuse::No;
+    }
+
     let work_product_id = &cgu.work_product_id();
     if tcx.dep_graph.previous_work_product(work_product_id).is_none() {
         // We don't have anything cached for this CGU. This can happen
@@ -634,6 +634,20 @@ pub(crate) struct UnableToExeLinker {
 #[diag(codegen_ssa_cpu_required)]
 pub(crate) struct CpuRequired;
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_hot_patch_link_failed)]
+#[note]
+pub(crate) struct HotPatchLinkFailed {
+    pub path: PathBuf,
+    pub status: ExitStatus,
+    pub output: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_sizes_unsupported)]
+#[note]
+pub(crate) struct StackSizesUnsupported;
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_processing_dymutil_failed)]
 #[note]
@@ -648,6 +662,16 @@ pub(crate) struct UnableToRunDsymutil {
     pub error: Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_staticlib_localize_symbols)]
+pub(crate) struct StaticlibLocalizeSymbols {
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_staticlib_localize_symbols_unsupported)]
+pub(crate) struct StaticlibLocalizeSymbolsUnsupported;
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_stripping_debug_info_failed)]
 #[note]
@@ -746,6 +770,13 @@ pub(crate) enum AppleDeploymentTarget {
     TooLow { env_var: &'static str, version: String, os_min: String },
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_print_requires_objects)]
+#[help]
+pub(crate) struct PrintRequiresObjects {
+    pub kind: &'static str,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_read_file)]
 pub(crate) struct ReadFileError {
@@ -1344,3 +1375,25 @@ pub(crate) struct XcrunSdkPathWarning {
     pub sdk_name: &'static str,
     pub stderr: String,
 }
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_not_reproducible)]
+#[note]
+pub(crate) struct NotReproducible {
+    pub path: PathBuf,
+    pub differences: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_verify_reproducible_build_failed)]
+#[note]
+pub(crate) struct VerifyReproducibleBuildFailed {
+    pub status: ExitStatus,
+    pub output: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_verify_reproducible_failed)]
+pub(crate) struct VerifyReproducibleFailed {
+    pub error: String,
+}
@@ -230,6 +230,12 @@ pub struct CrateInfo {
     pub windows_subsystem: Option<String>,
     pub natvis_debugger_visualizers: BTreeSet<DebuggerVisualizerFile>,
     pub lint_levels: CodegenLintLevels,
+    /// Only recorded for `--print=mono-item-sizes`.
+    pub mono_item_sizes: Vec<back::mono_item_sizes::MonoItemSizeInfo>,
+    /// Only recorded for `--print=stack-sizes`.
+    pub stack_sizes: Vec<back::stack_sizes::StackSizeInfo>,
+    /// Only recorded for `-Zhot-patch`.
+    pub hot_patch_cgus: Vec<back::hot_patch::HotPatchCgu>,
 }
 
 #[derive(Encodable, Decodable)]
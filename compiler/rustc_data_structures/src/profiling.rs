@@ -720,6 +720,7 @@ pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
 
 struct VerboseInfo {
     start_time: Instant,
+    start_cpu_time: Option<Duration>,
     start_rss: Option<usize>,
     message: String,
     format: TimePassesFormat,
@@ -740,6 +741,7 @@ pub fn start(
             _guard,
             info: message_and_format.map(|(message, format)| VerboseInfo {
                 start_time: Instant::now(),
+                start_cpu_time: get_cpu_time(),
                 start_rss: get_resident_set_size(),
                 message,
                 format,
@@ -759,7 +761,15 @@ fn drop(&mut self) {
         if let Some(info) = &self.info {
             let end_rss = get_resident_set_size();
             let dur = info.start_time.elapsed();
-            print_time_passes_entry(&info.message, dur, info.start_rss, end_rss, info.format);
+            let cpu_time = info.start_cpu_time.zip(get_cpu_time()).map(|(start, end)| end - start);
+            print_time_passes_entry_with_cpu_time(
+                &info.message,
+                dur,
+                cpu_time,
+                info.start_rss,
+                end_rss,
+                info.format,
+            );
         }
     }
 }
@@ -767,14 +777,22 @@ fn drop(&mut self) {
 struct JsonTimePassesEntry<'a> {
     pass: &'a str,
     time: f64,
+    /// The CPU time of all threads, which is larger than `time` when the pass
+    /// ran in parallel.
+    cpu_time: Option<f64>,
     start_rss: Option<usize>,
     end_rss: Option<usize>,
 }
 
 impl Display for JsonTimePassesEntry<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self { pass: what, time, start_rss, end_rss } = self;
-        write!(f, r#"{{"pass":"{what}","time":{time},"rss_start":"#).unwrap();
+        let Self { pass: what, time, cpu_time, start_rss, end_rss } = self;
+        write!(f, r#"{{"pass":"{what}","time":{time},"cpu_time":"#).unwrap();
+        match cpu_time {
+            Some(cpu_time) => write!(f, "{cpu_time}")?,
+            None => write!(f, "null")?,
+        }
+        write!(f, r#","rss_start":"#)?;
         match start_rss {
             Some(rss) => write!(f, "{rss}")?,
             None => write!(f, "null")?,
@@ -795,11 +813,27 @@ pub fn print_time_passes_entry(
     start_rss: Option<usize>,
     end_rss: Option<usize>,
     format: TimePassesFormat,
+) {
+    print_time_passes_entry_with_cpu_time(what, dur, None, start_rss, end_rss, format)
+}
+
+fn print_time_passes_entry_with_cpu_time(
+    what: &str,
+    dur: Duration,
+    cpu_time: Option<Duration>,
+    start_rss: Option<usize>,
+    end_rss: Option<usize>,
+    format: TimePassesFormat,
 ) {
     match format {
         TimePassesFormat::Json => {
-            let entry =
-                JsonTimePassesEntry { pass: what, time: dur.as_secs_f64(), start_rss, end_rss };
+            let entry = JsonTimePassesEntry {
+                pass: what,
+                time: dur.as_secs_f64(),
+                cpu_time: cpu_time.map(|cpu_time| cpu_time.as_secs_f64()),
+                start_rss,
+                end_rss,
+            };
 
             eprintln!(r#"time: {entry}"#);
             return;
@@ -921,3 +955,22 @@ pub fn get_resident_set_size() -> Option<usize> {
 
 #[cfg(test)]
 mod tests;
+
+// CPU time reporting
+cfg_match! {
+    unix => {
+        /// Returns the CPU time used by all threads of the process so far.
+        pub fn get_cpu_time() -> Option<Duration> {
+            let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+            if unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) } != 0 {
+                return None;
+            }
+            Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+        }
+    }
+    _ => {
+        pub fn get_cpu_time() -> Option<Duration> {
+            None
+        }
+    }
+}
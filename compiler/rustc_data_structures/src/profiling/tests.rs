@@ -2,18 +2,48 @@
 
 #[test]
 fn with_rss() {
-    let entry =
-        JsonTimePassesEntry { pass: "typeck", time: 56.1, start_rss: Some(10), end_rss: Some(20) };
+    let entry = JsonTimePassesEntry {
+        pass: "typeck",
+        time: 56.1,
+        cpu_time: None,
+        start_rss: Some(10),
+        end_rss: Some(20),
+    };
 
-    assert_eq!(entry.to_string(), r#"{"pass":"typeck","time":56.1,"rss_start":10,"rss_end":20}"#)
+    assert_eq!(
+        entry.to_string(),
+        r#"{"pass":"typeck","time":56.1,"cpu_time":null,"rss_start":10,"rss_end":20}"#
+    )
 }
 
 #[test]
 fn no_rss() {
-    let entry = JsonTimePassesEntry { pass: "typeck", time: 56.1, start_rss: None, end_rss: None };
+    let entry = JsonTimePassesEntry {
+        pass: "typeck",
+        time: 56.1,
+        cpu_time: None,
+        start_rss: None,
+        end_rss: None,
+    };
+
+    assert_eq!(
+        entry.to_string(),
+        r#"{"pass":"typeck","time":56.1,"cpu_time":null,"rss_start":null,"rss_end":null}"#
+    )
+}
+
+#[test]
+fn with_cpu_time() {
+    let entry = JsonTimePassesEntry {
+        pass: "typeck",
+        time: 56.1,
+        cpu_time: Some(224.4),
+        start_rss: None,
+        end_rss: None,
+    };
 
     assert_eq!(
         entry.to_string(),
-        r#"{"pass":"typeck","time":56.1,"rss_start":null,"rss_end":null}"#
+        r#"{"pass":"typeck","time":56.1,"cpu_time":224.4,"rss_start":null,"rss_end":null}"#
     )
 }
@@ -1,3 +1,5 @@
+driver_impl_api_diff_baseline = `{$path}` is not an earlier version of `{$crate_name}`
+
 driver_impl_cant_emit_mir = could not emit MIR: {$error}
 
 driver_impl_ice = the compiler unexpectedly panicked. this is a bug.
@@ -27,3 +29,5 @@ driver_impl_rlink_unable_to_read = failed to read rlink file: `{$err}`
 driver_impl_rlink_wrong_file_type = The input does not look like a .rlink file
 
 driver_impl_unstable_feature_usage = cannot dump feature usage metrics: {$error}
+
+driver_impl_used_items_unreadable = couldn't read the items used by the crate at `{$path}`: {$reason}
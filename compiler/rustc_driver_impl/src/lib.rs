@@ -35,8 +35,10 @@
 
 use rustc_ast as ast;
 use rustc_codegen_ssa::back::apple;
+use rustc_codegen_ssa::back::reproducible::{self, ReproducibilityCheck};
 use rustc_codegen_ssa::traits::CodegenBackend;
 use rustc_codegen_ssa::{CodegenErrors, CodegenResults};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::profiling::{
     TimePassesFormat, get_resident_set_size, print_time_passes_entry,
 };
@@ -55,17 +57,23 @@
 use rustc_metadata::locator;
 use rustc_middle::ty::TyCtxt;
 use rustc_parse::{new_parser_from_file, new_parser_from_source_str, unwrap_or_emit_fatal};
+use rustc_passes::api_summary::{self, ApiSummary, Severity};
+use rustc_passes::unused_pub_items;
 use rustc_session::config::{
-    CG_OPTIONS, ErrorOutputType, Input, OptionDesc, OutFileName, OutputType, UnstableOptions,
-    Z_OPTIONS, nightly_options, parse_target_triple,
+    CG_OPTIONS, CrateType, ErrorOutputType, ExternEntry, ExternLocation, Externs, Input,
+    OptionDesc, OutFileName, OutputType, OutputTypes, UnstableOptions, Z_OPTIONS, nightly_options,
+    parse_target_triple,
 };
 use rustc_session::getopts::{self, Matches};
 use rustc_session::lint::{Lint, LintId};
 use rustc_session::output::{CRATE_TYPES, collect_crate_types, invalid_output_for_target};
+use rustc_session::utils::CanonicalizedPath;
 use rustc_session::{EarlyDiagCtxt, Session, config, filesearch};
-use rustc_span::FileName;
+use rustc_span::def_id::LOCAL_CRATE;
+use rustc_span::{FileName, Symbol};
 use rustc_target::json::ToJson;
 use rustc_target::spec::{Target, TargetTuple};
+use serde_json::json;
 use time::OffsetDateTime;
 use time::macros::format_description;
 use tracing::trace;
@@ -108,8 +116,9 @@ pub(super) fn install() {}
 }
 
 use crate::session_diagnostics::{
-    CantEmitMIR, RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch, RLinkRustcVersionMismatch,
-    RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile, RlinkUnableToRead, UnstableFeatureUsage,
+    ApiDiffBaseline, CantEmitMIR, RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch,
+    RLinkRustcVersionMismatch, RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile,
+    RlinkUnableToRead, UnstableFeatureUsage, UsedItemsUnreadable,
 };
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
@@ -234,7 +243,7 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
         return;
     };
 
-    let sopts = config::build_session_options(&mut default_early_dcx, &matches);
+    let mut sopts = config::build_session_options(&mut default_early_dcx, &matches);
     // fully initialize ice path static once unstable options are available as context
     let ice_file = ice_path_with_config(Some(&sopts.unstable_opts)).clone();
 
@@ -247,8 +256,25 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
     let has_input = input.is_some();
     let (odir, ofile) = make_output(&matches);
 
+    let reproducibility_check = sopts.unstable_opts.verify_reproducible.then(|| {
+        ReproducibilityCheck::new(&mut sopts).unwrap_or_else(|err| {
+            default_early_dcx
+                .early_fatal(format!("couldn't prepare to verify reproducibility: {err}"))
+        })
+    });
+
+    let early_sarif_log = default_early_dcx.take_sarif_log();
     drop(default_early_dcx);
 
+    // The earlier version of the crate can't be loaded next to the crate, so
+    // `-Z api-diff` summarizes it in a session of its own once this one is done.
+    let api_diff = sopts
+        .unstable_opts
+        .api_diff
+        .is_some()
+        .then(|| (sopts.clone(), ice_file.clone(), args.clone()));
+    let mut new_api = None;
+
     let mut config = interface::Config {
         opts: sopts,
         crate_cfg: matches.opt_strs("cfg"),
@@ -265,9 +291,11 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
         register_lints: None,
         override_queries: None,
         make_codegen_backend: None,
+        cache_backend: None,
         registry: diagnostics_registry(),
         using_internal_features: &USING_INTERNAL_FEATURES,
         expanded_args: args,
+        early_sarif_log,
     };
 
     callbacks.config(&mut config);
@@ -339,6 +367,7 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
             return early_exit();
         }
 
+        let mut build_outputs = None;
         let linker = create_and_enter_global_ctxt(compiler, krate, |tcx| {
             let early_exit = || {
                 sess.dcx().abort_if_errors();
@@ -370,6 +399,16 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
 
             tcx.ensure_ok().analysis(());
 
+            if sess.opts.unstable_opts.api_diff.is_some() {
+                // Symbols don't outlive the session, unlike the summary.
+                let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+                new_api = Some((crate_name, api_summary::summarize(tcx, LOCAL_CRATE)));
+            }
+
+            if let Some(paths) = &sess.opts.unstable_opts.unused_pub_items {
+                check_unused_pub_items(tcx, &*compiler.codegen_backend.metadata_loader(), paths);
+            }
+
             if callbacks.after_analysis(compiler, tcx) == Compilation::Stop {
                 return early_exit();
             }
@@ -380,6 +419,10 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
                 }
             }
 
+            if reproducibility_check.is_some() {
+                build_outputs = Some(reproducible::build_outputs(tcx));
+            }
+
             Some(Linker::codegen_and_build_linker(tcx, &*compiler.codegen_backend))
         });
 
@@ -388,7 +431,20 @@ pub fn run_compiler(at_args: &[String], callbacks: &mut (dyn Callbacks + Send))
         if let Some(linker) = linker {
             linker.link(sess, codegen_backend);
         }
-    })
+
+        if let Some(check) = reproducibility_check
+            && let Some(outputs) = build_outputs
+        {
+            sess.dcx().abort_if_errors();
+            check.verify(sess, &sess.expanded_args, &outputs);
+        }
+    });
+
+    if let Some((opts, ice_file, args)) = api_diff
+        && let Some((crate_name, new_api)) = new_api
+    {
+        print_api_diff(opts, ice_file, args, crate_name, &new_api);
+    }
 }
 
 fn dump_feature_usage_metrics(tcxt: TyCtxt<'_>, metrics_dir: &Path) {
@@ -408,6 +464,131 @@ fn dump_feature_usage_metrics(tcxt: TyCtxt<'_>, metrics_dir: &Path) {
     }
 }
 
+/// Prints the changes to the public API of the crate since the version whose
+/// rlib or metadata file is given to `-Z api-diff`, which is summarized in a
+/// session that compiles nothing but an `extern crate` of it. Both sessions
+/// render paths the same way, so the summaries can be compared as text.
+fn print_api_diff(
+    mut opts: config::Options,
+    ice_file: Option<PathBuf>,
+    expanded_args: Vec<String>,
+    crate_name: String,
+    new_api: &ApiSummary,
+) {
+    let baseline = opts.unstable_opts.api_diff.take().unwrap();
+    let mut externs: BTreeMap<_, _> =
+        opts.externs.iter().map(|(name, entry)| (name.clone(), entry.clone())).collect();
+    externs.insert(
+        crate_name.clone(),
+        ExternEntry {
+            location: ExternLocation::ExactPaths(BTreeSet::from([CanonicalizedPath::new(
+                &baseline,
+            )])),
+            is_private_dep: false,
+            add_prelude: true,
+            nounused_dep: true,
+            force: false,
+        },
+    );
+    opts.externs = Externs::new(externs);
+    // A name of its own keeps the empty crate from colliding with the
+    // baseline, which may have been built with the same `-C metadata`.
+    opts.crate_name = Some(format!("{crate_name}_api_diff_baseline"));
+    // Nothing is written, but only needing metadata lets the baseline be an
+    // rmeta file.
+    opts.crate_types = vec![CrateType::Rlib];
+    opts.output_types = OutputTypes::new(&[(OutputType::Metadata, None)]);
+    opts.incremental = None;
+
+    let config = interface::Config {
+        opts,
+        crate_cfg: vec![],
+        crate_check_cfg: vec![],
+        input: Input::Str {
+            name: FileName::Custom("api-diff-baseline".to_owned()),
+            input: format!("extern crate {crate_name};\n"),
+        },
+        output_file: None,
+        output_dir: None,
+        ice_file,
+        file_loader: None,
+        locale_resources: DEFAULT_LOCALE_RESOURCES.to_vec(),
+        lint_caps: Default::default(),
+        psess_created: None,
+        hash_untracked_state: None,
+        register_lints: None,
+        override_queries: None,
+        make_codegen_backend: None,
+        cache_backend: None,
+        registry: diagnostics_registry(),
+        using_internal_features: &USING_INTERNAL_FEATURES,
+        expanded_args,
+        early_sarif_log: None,
+    };
+
+    interface::run_compiler(config, |compiler| {
+        let krate = passes::parse(&compiler.sess);
+        create_and_enter_global_ctxt(compiler, krate, |tcx| {
+            let _ = tcx.resolver_for_lowering();
+            tcx.dcx().abort_if_errors();
+            let crate_name = Symbol::intern(&crate_name);
+            let baseline_crate = tcx.crates(()).iter().copied().find(|&cnum| {
+                tcx.crate_name(cnum) == crate_name
+                    && tcx.extern_crate(cnum).is_some_and(|extern_crate| extern_crate.is_direct())
+            });
+            let Some(baseline_crate) = baseline_crate else {
+                tcx.dcx().emit_err(ApiDiffBaseline { path: baseline, crate_name });
+                return;
+            };
+            let old_api = api_summary::summarize(tcx, baseline_crate);
+            print_api_changes(crate_name, &old_api, new_api);
+        })
+    })
+}
+
+fn print_api_changes(crate_name: Symbol, old_api: &ApiSummary, new_api: &ApiSummary) {
+    let changes = api_summary::diff(old_api, new_api);
+    let bump = changes.iter().map(|change| change.severity).max().unwrap_or(Severity::Patch);
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|change| {
+            json!({
+                "severity": change.severity.as_str(),
+                "change": change.change,
+                "kind": change.kind,
+                "path": change.path,
+                "old": change.old,
+                "new": change.new,
+            })
+        })
+        .collect();
+    safe_println!(
+        "{}",
+        json!({
+            "$message_type": "api-diff",
+            "crate": crate_name.as_str(),
+            "bump": bump.as_str(),
+            "changes": changes,
+        })
+    );
+}
+
+/// Warns about the public items of the crate that none of the crates whose
+/// metadata is at `paths` use, for `-Z unused-pub-items`.
+fn check_unused_pub_items(tcx: TyCtxt<'_>, metadata_loader: &dyn MetadataLoader, paths: &[String]) {
+    let mut used_items = FxHashSet::default();
+    for path in paths {
+        match locator::read_used_items(tcx.sess, Path::new(path), metadata_loader) {
+            Ok(items) => used_items.extend(items),
+            Err(reason) => {
+                tcx.dcx().emit_err(UsedItemsUnreadable { path: PathBuf::from(path), reason });
+                return;
+            }
+        }
+    }
+    unused_pub_items::check_unused_pub_items(tcx, &used_items, paths.len());
+}
+
 /// Extract output directory and file from matches.
 fn make_output(matches: &getopts::Matches) -> (Option<PathBuf>, Option<OutFileName>) {
     let odir = matches.opt_str("out-dir").map(|o| PathBuf::from(&o));
@@ -623,9 +804,11 @@ fn print_crate_info(
     #[allow(unused_imports)]
     use {do_not_use_safe_print as safe_print, do_not_use_safe_print as safe_println};
 
-    // NativeStaticLibs and LinkArgs are special - printed during linking
-    // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    // NativeStaticLibs and LinkArgs are special - printed during linking, and MonoItemSizes,
+    // StackSizes and TypeLayouts are printed after codegen (empty iterator returns true)
+    if sess.opts.prints.iter().all(|p| {
+        matches!(p.kind, NativeStaticLibs | LinkArgs | MonoItemSizes | StackSizes | TypeLayouts)
+    }) {
         return Compilation::Continue;
     }
 
@@ -769,6 +952,9 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            MonoItemSizes => {}
+            StackSizes => {}
+            TypeLayouts => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -2,6 +2,13 @@
 
 use rustc_macros::{Diagnostic, Subdiagnostic};
 
+#[derive(Diagnostic)]
+#[diag(driver_impl_api_diff_baseline)]
+pub(crate) struct ApiDiffBaseline {
+    pub path: std::path::PathBuf,
+    pub crate_name: rustc_span::Symbol,
+}
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_cant_emit_mir)]
 pub struct CantEmitMIR {
@@ -107,3 +114,10 @@ pub(crate) struct IceFlags {
 pub(crate) struct UnstableFeatureUsage {
     pub error: Box<dyn Error>,
 }
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_used_items_unreadable)]
+pub(crate) struct UsedItemsUnreadable {
+    pub path: std::path::PathBuf,
+    pub reason: String,
+}
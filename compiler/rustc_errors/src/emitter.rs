@@ -21,7 +21,7 @@
 use rustc_data_structures::sync::{DynSend, IntoDynSyncSend};
 use rustc_error_messages::{FluentArgs, SpanLabel};
 use rustc_lexer;
-use rustc_lint_defs::pluralize;
+use rustc_lint_defs::{Lint, pluralize};
 use rustc_span::hygiene::{ExpnKind, MacroKind};
 use rustc_span::source_map::SourceMap;
 use rustc_span::{FileLines, FileName, SourceFile, Span, char_width, str_width};
@@ -190,6 +190,10 @@ fn emit_unused_externs(
     ) {
     }
 
+    /// Makes the emitter aware of the lints known to this compilation, so it can describe them.
+    /// Currently only used by the SARIF format.
+    fn register_lints(&mut self, _lints: &[&'static Lint]) {}
+
     /// Checks if should show explanations about "rustc --explain"
     fn should_show_explain(&self) -> bool {
         true
@@ -89,6 +89,7 @@
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
@@ -609,6 +610,11 @@ struct DiagCtxtInner {
     /// The file where the ICE information is stored. This allows delayed_span_bug backtraces to be
     /// stored along side the main panic backtrace.
     ice_file: Option<PathBuf>,
+
+    /// Diagnostics that have been accounted for but not printed yet, because
+    /// they are being emitted in source order. See
+    /// [`DiagCtxt::emit_in_source_order`].
+    buffered_emission: Option<Vec<DiagInner>>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -669,6 +675,9 @@ pub struct DiagCtxtFlags {
 
 impl Drop for DiagCtxtInner {
     fn drop(&mut self) {
+        self.emit_buffered_diagnostics();
+        self.buffered_emission = None;
+
         // For tools using `interface::run_compiler` (e.g. rustc, rustdoc)
         // stashed diagnostics will have already been emitted. But for others
         // that don't use `interface::run_compiler` (e.g. rustfmt, some clippy
@@ -773,6 +782,11 @@ pub fn set_emitter(&self, emitter: Box<dyn Emitter + DynSend>) {
         self.inner.borrow_mut().emitter = emitter;
     }
 
+    /// Passes the lints known to this compilation on to the emitter.
+    pub fn register_lints(&self, lints: &[&'static rustc_lint_defs::Lint]) {
+        self.inner.borrow_mut().emitter.register_lints(lints);
+    }
+
     /// Translate `message` eagerly with `args` to `SubdiagMessage::Eager`.
     pub fn eagerly_translate<'a>(
         &self,
@@ -828,6 +842,7 @@ pub fn reset_err_count(&self) {
             future_breakage_diagnostics,
             fulfilled_expectations,
             ice_file: _,
+            buffered_emission: _,
         } = inner.deref_mut();
 
         // For the `Vec`s and `HashMap`s, we overwrite with an empty container to free the
@@ -848,6 +863,34 @@ pub fn reset_err_count(&self) {
         *fulfilled_expectations = Default::default();
     }
 
+    /// Runs `f`, holding back the diagnostics it emits until it returns, and
+    /// then printing them in source order. This makes the output of passes
+    /// that emit diagnostics from several threads at once deterministic.
+    /// Everything else, such as the error count, is updated as usual while `f`
+    /// runs. Nested calls are part of the outermost one.
+    pub fn emit_in_source_order<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct EmitOnDrop<'a>(&'a DiagCtxt);
+
+        impl Drop for EmitOnDrop<'_> {
+            fn drop(&mut self) {
+                let mut inner = self.0.inner.borrow_mut();
+                inner.emit_buffered_diagnostics();
+                inner.buffered_emission = None;
+            }
+        }
+
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.buffered_emission.is_some() {
+                drop(inner);
+                return f();
+            }
+            inner.buffered_emission = Some(Vec::new());
+        }
+        let _guard = EmitOnDrop(self);
+        f()
+    }
+
     pub fn handle<'a>(&'a self) -> DiagCtxtHandle<'a> {
         DiagCtxtHandle { dcx: self, tainted_with_errors: None }
     }
@@ -1479,6 +1522,7 @@ fn new(emitter: Box<DynEmitter>) -> Self {
             future_breakage_diagnostics: Vec::new(),
             fulfilled_expectations: Default::default(),
             ice_file: None,
+            buffered_emission: None,
         }
     }
 
@@ -1636,7 +1680,15 @@ fn emit_diagnostic(
                 }
                 self.has_printed = true;
 
-                self.emitter.emit_diagnostic(diagnostic, &self.registry);
+                if diagnostic.level == Bug {
+                    // Print whatever led up to the ICE before it.
+                    self.emit_buffered_diagnostics();
+                    self.emitter.emit_diagnostic(diagnostic, &self.registry);
+                } else if let Some(buffered) = &mut self.buffered_emission {
+                    buffered.push(diagnostic);
+                } else {
+                    self.emitter.emit_diagnostic(diagnostic, &self.registry);
+                }
             }
 
             if is_error {
@@ -1670,6 +1722,37 @@ fn emit_diagnostic(
         })
     }
 
+    /// Prints the buffered diagnostics, ordered by the location of their
+    /// primary span. Diagnostics without a location come last, and the hash
+    /// of the diagnostics breaks the remaining ties, so that the order doesn't
+    /// depend on the order in which they were emitted at all.
+    fn emit_buffered_diagnostics(&mut self) {
+        let Some(buffered) = &mut self.buffered_emission else {
+            return;
+        };
+        let mut buffered = std::mem::take(buffered);
+        let source_map = self.emitter.source_map();
+        buffered.sort_by_cached_key(|diagnostic| {
+            let span = if diagnostic.sort_span.is_dummy() {
+                diagnostic.span.primary_span()
+            } else {
+                Some(diagnostic.sort_span)
+            };
+            // Byte positions depend on the order in which the source files of
+            // other crates were imported, so compare file names and lines.
+            let location = span.zip(source_map).map(|(span, source_map)| {
+                let loc = source_map.lookup_char_pos(span.lo());
+                (loc.file.name.prefer_local().to_string(), loc.line, loc.col)
+            });
+            let mut hasher = StableHasher::new();
+            diagnostic.hash(&mut hasher);
+            (location.is_none(), location, hasher.finish::<Hash128>())
+        });
+        for diagnostic in buffered {
+            self.emitter.emit_diagnostic(diagnostic, &self.registry);
+        }
+    }
+
     fn treat_err_as_bug(&self) -> bool {
         self.flags
             .treat_err_as_bug
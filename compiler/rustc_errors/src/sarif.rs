@@ -0,0 +1,618 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] (Static Analysis Results Interchange Format) is the format consumed
+//! by code-scanning dashboards. Unlike the JSON emitter, which writes one
+//! object per diagnostic, a SARIF log is a single document, so this emitter
+//! buffers every diagnostic and writes the log when it is dropped.
+//!
+//! Diagnostics with an error code or a lint name become `results`, and the
+//! codes and lints they reference become `rules` of the tool driver. Rule
+//! metadata comes from the error code [`Registry`] and from the lints handed
+//! to [`Emitter::register_lints`]. Diagnostics with neither a code nor a
+//! location (e.g. "aborting due to 2 previous errors") are reported as tool
+//! execution notifications instead.
+//!
+//! A compilation reports diagnostics through several emitters: the short-lived
+//! ones of each `EarlyDiagCtxt` and the one of the session. So that stderr
+//! still holds a single log, early emitters (see [`SarifEmitter::early`]) collect
+//! their diagnostics in an [`EarlySarifLog`], which the driver takes from the
+//! `EarlyDiagCtxt` and passes to the session's emitter (see
+//! [`SarifEmitter::session_log`]). An early emitter writes what wasn't taken
+//! itself, for example because a command-line flag was rejected before there
+//! was a session.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use derive_setters::Setters;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_data_structures::sync::IntoDynSyncSend;
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::{Applicability, Lint};
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Span};
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{Translate, to_fluent_args};
+use crate::{CodeSuggestion, DiagInner, FluentBundle, LazyFallbackBundle, Level, Suggestions};
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Setters)]
+pub struct SarifEmitter {
+    #[setters(skip)]
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    #[setters(skip)]
+    sm: Option<Arc<SourceMap>>,
+    fluent_bundle: Option<Arc<FluentBundle>>,
+    #[setters(skip)]
+    fallback_bundle: LazyFallbackBundle,
+    /// The version reported for the tool driver, e.g. `1.87.0-nightly`.
+    tool_version: Option<String>,
+    /// Whether this emitter writes the log of a session, which is written even
+    /// if nothing was reported and includes the diagnostics of early emitters.
+    #[setters(skip)]
+    session_log: bool,
+    /// For the emitter of an `EarlyDiagCtxt`, where its diagnostics go until
+    /// they are handed to the session's log.
+    #[setters(skip)]
+    early: Option<Arc<Mutex<EarlySarifLog>>>,
+    #[setters(skip)]
+    lints: FxHashMap<String, &'static Lint>,
+    #[setters(skip)]
+    rules: FxIndexMap<String, Rule>,
+    #[setters(skip)]
+    results: Vec<SarifResult>,
+    #[setters(skip)]
+    notifications: Vec<Notification>,
+    #[setters(skip)]
+    successful: bool,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        sm: Option<Arc<SourceMap>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            sm,
+            fluent_bundle: None,
+            fallback_bundle,
+            tool_version: None,
+            session_log: false,
+            early: None,
+            lints: FxHashMap::default(),
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+            notifications: Vec::new(),
+            successful: true,
+        }
+    }
+
+    /// Makes this the emitter of an `EarlyDiagCtxt`. Its diagnostics are added
+    /// to `log`, and only written by this emitter if they are still there when
+    /// it is dropped.
+    pub fn early(mut self, log: Arc<Mutex<EarlySarifLog>>) -> Self {
+        self.early = Some(log);
+        self
+    }
+
+    /// Makes this the emitter of a session. It always writes a log, which starts
+    /// with the diagnostics of `early_log`.
+    pub fn session_log(mut self, mut early_log: EarlySarifLog) -> Self {
+        self.take_early_log(&mut early_log);
+        self.session_log = true;
+        self
+    }
+
+    /// Returns the index of the rule for `diag`, registering it on first use.
+    fn rule_index(&mut self, diag: &DiagInner, registry: &Registry) -> Option<(String, usize)> {
+        let (id, rule) = if let Some(code) = diag.code {
+            let id = code.to_string();
+            let rule = Rule {
+                help_uri: Some(format!("https://doc.rust-lang.org/error_codes/{id}.html")),
+                full_description: registry
+                    .try_find_description(code)
+                    .ok()
+                    .map(|text| Description { text: text.trim().to_owned() }),
+                id: id.clone(),
+                short_description: None,
+                default_configuration: None,
+            };
+            (id, rule)
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            let lint = self.lints.get(name);
+            let rule = Rule {
+                id: name.clone(),
+                short_description: lint.map(|lint| Description { text: lint.desc.to_owned() }),
+                full_description: None,
+                help_uri: name.strip_prefix("clippy::").map(|name| {
+                    format!("https://rust-lang.github.io/rust-clippy/master/index.html#{name}")
+                }),
+                default_configuration: lint
+                    .map(|lint| RuleConfiguration { level: lint_level(lint.default_level) }),
+            };
+            (name.clone(), rule)
+        } else {
+            return None;
+        };
+        Some((id.clone(), self.add_rule(id, rule)))
+    }
+
+    fn add_rule(&mut self, id: String, rule: Rule) -> usize {
+        let entry = self.rules.entry(id);
+        let index = entry.index();
+        entry.or_insert(rule);
+        index
+    }
+
+    /// Moves the results and notifications reported so far to `early_log`.
+    fn forward_to_early_log(&mut self, early_log: &mut EarlySarifLog) {
+        early_log.successful &= self.successful;
+        for result in self.results.drain(..) {
+            let rule = result.rule_index.map(|index| self.rules[index].clone());
+            early_log.results.push((result, rule));
+        }
+        early_log.notifications.append(&mut self.notifications);
+    }
+
+    /// Takes the diagnostics of `early_log` into this log, ahead of the ones
+    /// reported to this emitter.
+    fn take_early_log(&mut self, early_log: &mut EarlySarifLog) {
+        self.successful &= mem::replace(&mut early_log.successful, true);
+        let own_results = mem::take(&mut self.results);
+        for (mut result, rule) in mem::take(&mut early_log.results) {
+            // Early rules are appended, so indices of this log's own rules stay valid.
+            if let Some(rule) = rule {
+                result.rule_index = Some(self.add_rule(rule.id.clone(), rule));
+            }
+            self.results.push(result);
+        }
+        self.results.extend(own_results);
+        let own_notifications =
+            mem::replace(&mut self.notifications, mem::take(&mut early_log.notifications));
+        self.notifications.extend(own_notifications);
+    }
+
+    fn locations(&self, span: &crate::MultiSpan, args: &FluentArgs<'_>) -> Vec<Location> {
+        span.span_labels()
+            .into_iter()
+            .filter(|label| label.is_primary)
+            .filter_map(|label| {
+                let message = label.label.as_ref().map(|msg| Message {
+                    text: self.translate_message(msg, args).unwrap().into_owned(),
+                });
+                self.location(label.span, message)
+            })
+            .collect()
+    }
+
+    fn location(&self, span: Span, message: Option<Message>) -> Option<Location> {
+        let physical_location = self.physical_location(span)?;
+        Some(Location { physical_location, message })
+    }
+
+    fn physical_location(&self, span: Span) -> Option<PhysicalLocation> {
+        let sm = self.sm.as_deref()?;
+        if span.is_dummy() {
+            return None;
+        }
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        if !matches!(start.file.name, FileName::Real(_)) {
+            return None;
+        }
+        Some(PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: path_to_uri(&sm.filename_for_diagnostics(&start.file.name).to_string()),
+            },
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+            },
+        })
+    }
+
+    fn fixes(&self, suggestion: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description =
+            Message { text: self.translate_message(&suggestion.msg, args).unwrap().into_owned() };
+        suggestion
+            .substitutions
+            .iter()
+            .map(|substitution| {
+                // SARIF groups replacements by the file they apply to.
+                let mut changes: FxIndexMap<String, Vec<Replacement>> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    let Some(location) = self.physical_location(part.span) else { continue };
+                    changes.entry(location.artifact_location.uri).or_default().push(Replacement {
+                        deleted_region: location.region,
+                        inserted_content: InsertedContent { text: part.snippet.clone() },
+                    });
+                }
+                Fix {
+                    description: description.clone(),
+                    artifact_changes: changes
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: suggestion.applicability },
+                }
+            })
+            .filter(|fix| !fix.artifact_changes.is_empty())
+            .collect()
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        version: self.tool_version.as_deref(),
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                invocations: [Invocation {
+                    execution_successful: self.successful,
+                    tool_execution_notifications: &self.notifications,
+                }],
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *self.dst, &log)?;
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if let Some(early_log) = self.early.take() {
+            // Nothing else is going to write these, e.g. because the command
+            // line was rejected before a session was created.
+            self.take_early_log(&mut early_log.lock().unwrap());
+        }
+        if !self.session_log && self.results.is_empty() && self.notifications.is_empty() {
+            return;
+        }
+        if let Err(e) = self.write_log() {
+            panic!("failed to print diagnostics: {e:?}");
+        }
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&FluentBundle> {
+        self.fluent_bundle.as_deref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner, registry: &Registry) {
+        if diag.is_error() {
+            self.successful = false;
+        }
+        let args = to_fluent_args(diag.args.iter());
+        let level = result_level(diag.level);
+
+        // Children without a location of their own are folded into the
+        // message, the way they would be rendered below it.
+        let mut text = self.translate_messages(&diag.messages, &args).into_owned();
+        let mut related_locations = Vec::new();
+        for child in &diag.children {
+            let message = format!(
+                "{}: {}",
+                child.level.to_str(),
+                self.translate_messages(&child.messages, &args)
+            );
+            let mut spans = child.span.primary_spans().iter().peekable();
+            if spans.peek().is_none() {
+                text.push('\n');
+                text.push_str(&message);
+            }
+            for &span in spans {
+                let message = Some(Message { text: message.clone() });
+                related_locations.extend(self.location(span, message));
+            }
+        }
+        for label in diag.span.span_labels() {
+            if label.is_primary {
+                continue;
+            }
+            let message = label.label.as_ref().map(|msg| Message {
+                text: self.translate_message(msg, &args).unwrap().into_owned(),
+            });
+            related_locations.extend(self.location(label.span, message));
+        }
+
+        let locations = self.locations(&diag.span, &args);
+        let rule = self.rule_index(&diag, registry);
+        if rule.is_none() && locations.is_empty() {
+            self.notifications.push(Notification { level, message: Message { text } });
+        } else {
+            let fixes = match &diag.suggestions {
+                Suggestions::Enabled(suggestions) | Suggestions::Sealed(suggestions) => {
+                    suggestions.iter().flat_map(|sugg| self.fixes(sugg, &args)).collect()
+                }
+                Suggestions::Disabled => Vec::new(),
+            };
+            let (rule_id, rule_index) = rule.unzip();
+            self.results.push(SarifResult {
+                rule_id,
+                rule_index,
+                level,
+                message: Message { text },
+                locations,
+                related_locations,
+                fixes,
+            });
+        }
+
+        if let Some(early_log) = self.early.clone() {
+            // Forward right away, so that the log taken from the early dcx has
+            // everything reported so far.
+            self.forward_to_early_log(&mut early_log.lock().unwrap());
+        }
+    }
+
+    fn register_lints(&mut self, lints: &[&'static Lint]) {
+        self.lints.extend(lints.iter().map(|&lint| (lint.name_lower(), lint)));
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.sm.as_deref()
+    }
+
+    fn should_show_explain(&self) -> bool {
+        // The explanations are already part of the rule metadata.
+        false
+    }
+}
+
+/// Diagnostics of an early emitter that have not been written to a log yet.
+pub struct EarlySarifLog {
+    successful: bool,
+    /// Each result together with the rule it references, if any.
+    results: Vec<(SarifResult, Option<Rule>)>,
+    notifications: Vec<Notification>,
+}
+
+impl EarlySarifLog {
+    /// Adds the diagnostics of `later`, which were reported after these.
+    pub fn append(&mut self, mut later: EarlySarifLog) {
+        self.successful &= later.successful;
+        self.results.append(&mut later.results);
+        self.notifications.append(&mut later.notifications);
+    }
+}
+
+impl Default for EarlySarifLog {
+    fn default() -> Self {
+        EarlySarifLog { successful: true, results: Vec::new(), notifications: Vec::new() }
+    }
+}
+
+fn result_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+        Level::ForceWarning(_) | Level::Warning => "warning",
+        Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp | Level::FailureNote => {
+            "note"
+        }
+        Level::Allow | Level::Expect(_) => "none",
+    }
+}
+
+fn lint_level(level: rustc_lint_defs::Level) -> &'static str {
+    match level {
+        rustc_lint_defs::Level::Allow | rustc_lint_defs::Level::Expect(_) => "none",
+        rustc_lint_defs::Level::Warn | rustc_lint_defs::Level::ForceWarn(_) => "warning",
+        rustc_lint_defs::Level::Deny | rustc_lint_defs::Level::Forbid => "error",
+    }
+}
+
+/// Turns a file name as shown in diagnostics into a URI reference. Relative
+/// paths stay relative, so that consumers resolve them against the checkout.
+fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    if Path::new(&path).is_absolute() || path.as_bytes().get(1) == Some(&b':') {
+        uri.push_str("file://");
+        if !path.starts_with('/') {
+            uri.push('/');
+        }
+    }
+    for c in path.chars() {
+        match c {
+            ' ' => uri.push_str("%20"),
+            '%' => uri.push_str("%25"),
+            '#' => uri.push_str("%23"),
+            '?' => uri.push_str("%3F"),
+            c => uri.push(c),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation. Their field
+// names follow the SARIF 2.1.0 schema.
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    invocations: [Invocation<'a>; 1],
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: Driver<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code (e.g. "E0308") or the lint name (e.g. "unused_variables").
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Description>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Description>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_configuration: Option<RuleConfiguration>,
+}
+
+#[derive(Serialize, Clone)]
+struct Description {
+    text: String,
+}
+
+#[derive(Serialize, Clone)]
+struct RuleConfiguration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: &'a [Notification],
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize, Clone)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+/// A source region. Lines and columns are 1-based, and the end column is
+/// exclusive.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: InsertedContent,
+}
+
+#[derive(Serialize)]
+struct InsertedContent {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
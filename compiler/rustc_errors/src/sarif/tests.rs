@@ -0,0 +1,196 @@
+use std::sync::Mutex;
+
+use rustc_lint_defs::declare_lint;
+use rustc_span::BytePos;
+use rustc_span::source_map::FilePathMapping;
+use serde_json::Value;
+
+use super::*;
+use crate::DiagCtxt;
+use crate::codes::E0308;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+declare_lint! {
+    /// A lint used to check rule metadata.
+    pub TEST_LINT,
+    Warn,
+    "detects test code"
+}
+
+/// Runs `f` with a dcx that writes SARIF, and returns the parsed log.
+fn sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Option<Value> {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("src/test file.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter =
+            SarifEmitter::new(Box::new(Shared { data: output.clone() }), Some(sm), fallback_bundle);
+        let dcx = DiagCtxt::new(Box::new(emitter)).with_registry(Registry::new(&[(
+            E0308,
+            "Expected type did not match the received type.\n",
+        )]));
+        dcx.register_lints(&[TEST_LINT]);
+        f(&dcx);
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        if bytes.is_empty() { None } else { Some(serde_json::from_slice(&bytes).unwrap()) }
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn error_with_code() {
+    let log = sarif_log("fn main() {\n    let x: u8 = 'a';\n}\n", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(28, 31), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(23, 25), "expected due to this")
+            .with_note("a note without a span")
+            .emit();
+    })
+    .unwrap();
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], false);
+
+    let rule = &run["tool"]["driver"]["rules"][0];
+    assert_eq!(rule["id"], "E0308");
+    assert_eq!(rule["fullDescription"]["text"], "Expected type did not match the received type.");
+    assert_eq!(rule["helpUri"], "https://doc.rust-lang.org/error_codes/E0308.html");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0308");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "mismatched types\nnote: a note without a span");
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/test%20file.rs");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 17);
+    assert_eq!(location["region"]["endColumn"], 20);
+
+    let related = &result["relatedLocations"][0];
+    assert_eq!(related["message"]["text"], "expected due to this");
+    assert_eq!(related["physicalLocation"]["region"]["startColumn"], 12);
+}
+
+#[test]
+fn lint_rule_metadata() {
+    let log = sarif_log("fn test() {}\n", |dcx| {
+        for _ in 0..2 {
+            let mut diag = dcx.handle().struct_span_warn(span(3, 7), "test code");
+            diag.is_lint("test_lint".to_owned(), false);
+            diag.emit();
+        }
+        dcx.handle().struct_span_warn(span(0, 2), "a warning with a code").with_code(E0308).emit();
+    })
+    .unwrap();
+
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["id"], "test_lint");
+    assert_eq!(rules[0]["shortDescription"]["text"], "detects test code");
+    assert_eq!(rules[0]["defaultConfiguration"]["level"], "warning");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["ruleIndex"], 0);
+    assert_eq!(results[2]["ruleIndex"], 1);
+}
+
+#[test]
+fn notifications() {
+    let log = sarif_log("", |dcx| {
+        dcx.handle().err("could not find crate");
+    })
+    .unwrap();
+
+    let run = &log["runs"][0];
+    assert_eq!(run["results"].as_array().unwrap().len(), 0);
+    let notification = &run["invocations"][0]["toolExecutionNotifications"][0];
+    assert_eq!(notification["level"], "error");
+    assert_eq!(notification["message"]["text"], "could not find crate");
+}
+
+#[test]
+fn empty_log() {
+    assert!(sarif_log("", |_| {}).is_none());
+}
+
+#[test]
+fn early_diagnostics_join_session_log() {
+    rustc_span::create_default_session_globals_then(|| {
+        let fallback_bundle =
+            || crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+        let early_output = Arc::new(Mutex::new(Vec::new()));
+        let session_output = Arc::new(Mutex::new(Vec::new()));
+
+        let early_log = Arc::new(Mutex::new(EarlySarifLog::default()));
+        let early_dcx = DiagCtxt::new(Box::new(
+            SarifEmitter::new(
+                Box::new(Shared { data: early_output.clone() }),
+                None,
+                fallback_bundle(),
+            )
+            .early(early_log.clone()),
+        ));
+        early_dcx.handle().warn("early warning");
+        let session_dcx = DiagCtxt::new(Box::new(
+            SarifEmitter::new(
+                Box::new(Shared { data: session_output.clone() }),
+                None,
+                fallback_bundle(),
+            )
+            .session_log(mem::take(&mut early_log.lock().unwrap())),
+        ));
+        session_dcx.handle().warn("late warning");
+        drop(session_dcx);
+        drop(early_dcx);
+
+        assert!(early_output.lock().unwrap().is_empty());
+        let log: Value = serde_json::from_slice(&session_output.lock().unwrap()).unwrap();
+        let notifications = &log["runs"][0]["invocations"][0]["toolExecutionNotifications"];
+        assert_eq!(notifications[0]["message"]["text"], "early warning");
+        assert_eq!(notifications[1]["message"]["text"], "late warning");
+
+        // Without a session, the early emitter writes the log itself.
+        let early_dcx = DiagCtxt::new(Box::new(
+            SarifEmitter::new(
+                Box::new(Shared { data: early_output.clone() }),
+                None,
+                fallback_bundle(),
+            )
+            .early(Arc::new(Mutex::new(EarlySarifLog::default()))),
+        ));
+        early_dcx.handle().err("rejected flag");
+        drop(early_dcx);
+        let log: Value = serde_json::from_slice(&early_output.lock().unwrap()).unwrap();
+        let invocation = &log["runs"][0]["invocations"][0];
+        assert_eq!(invocation["executionSuccessful"], false);
+        assert_eq!(invocation["toolExecutionNotifications"][0]["message"]["text"], "rejected flag");
+    })
+}
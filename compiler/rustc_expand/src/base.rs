@@ -1173,6 +1173,8 @@ pub struct ExtCtxt<'a> {
     pub sess: &'a Session,
     pub ecfg: expand::ExpansionConfig<'a>,
     pub num_standard_library_imports: usize,
+    /// Whether the crate is `#![no_std]` or `#![no_core]`, so that `std` may not be linked.
+    pub no_std: bool,
     pub reduced_recursion_limit: Option<(Limit, ErrorGuaranteed)>,
     pub root_path: PathBuf,
     pub resolver: &'a mut dyn ResolverExpand,
@@ -1202,6 +1204,7 @@ pub fn new(
             sess,
             ecfg,
             num_standard_library_imports: 0,
+            no_std: false,
             reduced_recursion_limit: None,
             resolver,
             lint_store,
@@ -184,8 +184,10 @@ pub fn check_crate(tcx: TyCtxt<'_>) {
         // what we are intending to discard, to help future type-based refactoring.
         type R = Result<(), ErrorGuaranteed>;
 
-        tcx.par_hir_for_each_module(|module| {
-            let _: R = tcx.ensure_ok().check_mod_type_wf(module);
+        tcx.sess.time_in_source_order("wf_checking", || {
+            tcx.par_hir_for_each_module(|module| {
+                let _: R = tcx.ensure_ok().check_mod_type_wf(module);
+            })
         });
 
         for &trait_def_id in tcx.all_local_trait_impls(()).keys() {
@@ -209,29 +211,34 @@ pub fn check_crate(tcx: TyCtxt<'_>) {
 
     // Make sure we evaluate all static and (non-associated) const items, even if unused.
     // If any of these fail to evaluate, we do not want this crate to pass compilation.
-    tcx.par_hir_body_owners(|item_def_id| {
-        let def_kind = tcx.def_kind(item_def_id);
-        match def_kind {
-            DefKind::Static { .. } => {
-                tcx.ensure_ok().eval_static_initializer(item_def_id);
-                check::maybe_check_static_with_link_section(tcx, item_def_id);
+    tcx.sess.time_in_source_order("eval_items", || {
+        tcx.par_hir_body_owners(|item_def_id| {
+            let def_kind = tcx.def_kind(item_def_id);
+            match def_kind {
+                DefKind::Static { .. } => {
+                    tcx.ensure_ok().eval_static_initializer(item_def_id);
+                    check::maybe_check_static_with_link_section(tcx, item_def_id);
+                }
+                DefKind::Const if tcx.generics_of(item_def_id).is_empty() => {
+                    let instance = ty::Instance::new(item_def_id.into(), ty::GenericArgs::empty());
+                    let cid = GlobalId { instance, promoted: None };
+                    let typing_env = ty::TypingEnv::fully_monomorphized();
+                    tcx.ensure_ok().eval_to_const_value_raw(typing_env.as_query_input(cid));
+                }
+                _ => (),
             }
-            DefKind::Const if tcx.generics_of(item_def_id).is_empty() => {
-                let instance = ty::Instance::new(item_def_id.into(), ty::GenericArgs::empty());
-                let cid = GlobalId { instance, promoted: None };
-                let typing_env = ty::TypingEnv::fully_monomorphized();
-                tcx.ensure_ok().eval_to_const_value_raw(typing_env.as_query_input(cid));
-            }
-            _ => (),
-        }
+        })
     });
 
-    tcx.par_hir_body_owners(|item_def_id| {
-        let def_kind = tcx.def_kind(item_def_id);
-        // Skip `AnonConst`s because we feed their `type_of`.
-        if !matches!(def_kind, DefKind::AnonConst) {
-            tcx.ensure_ok().typeck(item_def_id);
-        }
+    // Type-check the bodies that others are likely to wait on first.
+    tcx.sess.time_in_source_order("type_check_bodies", || {
+        tcx.par_hir_body_owners_by_dependency(|item_def_id| {
+            let def_kind = tcx.def_kind(item_def_id);
+            // Skip `AnonConst`s because we feed their `type_of`.
+            if !matches!(def_kind, DefKind::AnonConst) {
+                tcx.ensure_ok().typeck(item_def_id);
+            }
+        })
     });
 
     tcx.ensure_ok().check_unused_traits(());
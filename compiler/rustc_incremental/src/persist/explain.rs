@@ -0,0 +1,111 @@
+//! Support for `-Z incremental-explain`, which reports why the results of the
+//! previous session could not be reused.
+//!
+//! The whole cache is discarded when a tracked command-line option changed,
+//! in which case the options that changed are reported when the dep-graph is
+//! loaded. Otherwise the red nodes that the other red nodes follow from, e.g.
+//! the HIR of an item or the hash of an upstream crate, are reported together
+//! with the codegen units that had to be recompiled because of them once all
+//! queries have completed.
+
+use std::collections::BTreeMap;
+
+use rustc_hashes::Hash64;
+use rustc_middle::dep_graph::{DepNode, DepNodeFilter, dep_kinds};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_session::config::IncrementalExplainFormat;
+use serde_json::json;
+
+pub(crate) fn explain_changed_options(
+    sess: &Session,
+    format: IncrementalExplainFormat,
+    prev_hashes: &BTreeMap<String, Hash64>,
+) {
+    let hashes = sess.opts.incr_comp_args_hashes();
+    let mut changed: Vec<&str> = hashes
+        .iter()
+        .filter(|&(name, hash)| prev_hashes.get(name) != Some(hash))
+        .map(|(name, _)| &name[..])
+        .collect();
+    changed.extend(prev_hashes.keys().filter(|name| !hashes.contains_key(*name)).map(|n| &n[..]));
+    changed.sort_unstable();
+
+    match format {
+        IncrementalExplainFormat::Human => {
+            for name in changed {
+                eprintln!("[incremental-explain] command-line option changed: {name}");
+            }
+            eprintln!("[incremental-explain] all results of the previous session were discarded");
+        }
+        IncrementalExplainFormat::Json => {
+            let report = json!({
+                "$message_type": "incremental-explain",
+                "changed_options": changed,
+            });
+            eprintln!("{report}");
+        }
+    }
+}
+
+pub(crate) fn explain_red_nodes(tcx: TyCtxt<'_>, format: IncrementalExplainFormat) {
+    let filter = tcx.sess.opts.unstable_opts.incremental_explain_filter.as_deref().unwrap_or("");
+    let explanation = tcx.dep_graph.explain_red_nodes(
+        tcx,
+        dep_kinds::CompileCodegenUnit,
+        &DepNodeFilter::new(filter),
+    );
+
+    // Refer to codegen units by their name rather than by their dep-node.
+    let cgu_name = |node: DepNode| {
+        tcx.dep_graph.dep_node_debug_str(node).unwrap_or_else(|| node.hash.to_string())
+    };
+
+    match format {
+        IncrementalExplainFormat::Human => {
+            for cause in &explanation.causes {
+                let what = if cause.is_input { "input changed" } else { "changed" };
+                eprintln!("[incremental-explain] {what}: {:?}", cause.node);
+            }
+            for (node, causes) in &explanation.invalidated {
+                let causes = causes
+                    .iter()
+                    .map(|&i| format!("{:?}", explanation.causes[i].node))
+                    .collect::<Vec<_>>();
+                if causes.is_empty() {
+                    eprintln!("[incremental-explain] codegen unit recompiled: {}", cgu_name(*node));
+                } else {
+                    eprintln!(
+                        "[incremental-explain] codegen unit recompiled: {}, because of: {}",
+                        cgu_name(*node),
+                        causes.join(", "),
+                    );
+                }
+            }
+        }
+        IncrementalExplainFormat::Json => {
+            let causes = explanation
+                .causes
+                .iter()
+                .map(|cause| {
+                    json!({
+                        "node": format!("{:?}", cause.node),
+                        "kind": format!("{:?}", cause.node.kind),
+                        "input": cause.is_input,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let invalidated = explanation
+                .invalidated
+                .iter()
+                .map(|(node, causes)| json!({ "cgu": cgu_name(*node), "causes": causes }))
+                .collect::<Vec<_>>();
+            let report = json!({
+                "$message_type": "incremental-explain",
+                "red_nodes": causes,
+                "invalidated_cgus": invalidated,
+            });
+            eprintln!("{report}");
+        }
+    }
+}
@@ -39,6 +39,24 @@
 //!    at the beginning of the session has become obsolete because we have just
 //!    published a more current version. Thus the compiler will delete it.
 //!
+//! ## Shared Caches
+//!
+//! A finalized session directory can also be copied somewhere else, e.g. into
+//! a cache shared between the worktrees of a repository or between CI runners,
+//! and be passed to the compiler with `-Z incremental-shared-cache`. If the
+//! local incremental compilation directory has no finalized session directory
+//! for a crate, the new session directory is seeded from the newest one in the
+//! shared cache instead. The shared cache is only ever read, so it is not
+//! locked or garbage collected, and it can be on a read-only file system.
+//!
+//! The crate directories are named after the crate and its `StableCrateId`,
+//! neither of which depends on the location of the source. For the seeded
+//! session to be reused, the compiler version and the command line have to
+//! match as well. With `-Z incremental-relocatable`, prefixes remapped with
+//! `--remap-path-prefix` only count relative to the working directory, so
+//! remapping the working directory makes the cache independent of where the
+//! source tree is checked out.
+//!
 //! ## Garbage Collection
 //!
 //! Naively following the above protocol might lead to old session directories
@@ -128,10 +146,10 @@
 mod tests;
 
 const LOCK_FILE_EXT: &str = ".lock";
-const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
+pub(crate) const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
-const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
-const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+pub(crate) const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
+pub(crate) const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -255,10 +273,17 @@ pub(crate) fn prepare_session_directory(sess: &Session, crate_name: Symbol) {
         create_dir(sess, &session_dir, "session");
 
         // Find a suitable source directory to copy from. Ignore those that we
-        // have already tried before.
-        let source_directory = find_source_directory(&crate_dir, &source_directories_already_tried);
+        // have already tried before. Only fall back to the shared cache if
+        // there is nothing local to start from.
+        let source_directory = find_source_directory(&crate_dir, &source_directories_already_tried)
+            .map(|dir| (dir, false))
+            .or_else(|| {
+                let shared_crate_dir = shared_crate_path(sess, crate_name)?;
+                find_source_directory(&shared_crate_dir, &source_directories_already_tried)
+                    .map(|dir| (dir, true))
+            });
 
-        let Some(source_directory) = source_directory else {
+        let Some((source_directory, is_shared)) = source_directory else {
             // There's nowhere to copy from, we're done
             debug!(
                 "no source directory found. Continuing with empty session \
@@ -272,10 +297,19 @@ pub(crate) fn prepare_session_directory(sess: &Session, crate_name: Symbol) {
         debug!("attempting to copy data from source: {}", source_directory.display());
 
         // Try copying over all files from the source directory
-        if let Ok(allows_links) = copy_files(sess, &session_dir, &source_directory) {
+        if let Ok(allows_links) = copy_files(sess, &session_dir, &source_directory, is_shared) {
             debug!("successfully copied data from: {}", source_directory.display());
 
-            if !allows_links {
+            if is_shared && sess.opts.unstable_opts.incremental_info {
+                eprintln!(
+                    "[incremental] session directory seeded from shared cache: {}",
+                    source_directory.display()
+                );
+            }
+
+            // The shared cache is often on another file system, so copying is
+            // expected there.
+            if !allows_links && !is_shared {
                 sess.dcx().emit_warn(errors::HardLinkFailed { path: &session_dir });
             }
 
@@ -375,6 +409,50 @@ pub fn finalize_session_directory(sess: &Session, svh: Option<Svh>) {
     let _ = garbage_collect_session_directories(sess);
 }
 
+/// Creates a finalized session directory for the crate from the files that
+/// `write_files` writes into it, unless there already is one to start from.
+/// This is how a cache fetched from elsewhere is used by the next session.
+///
+/// Must be called before the session directory is prepared. Returns whether
+/// a session directory was created.
+pub fn import_session_directory(
+    sess: &Session,
+    crate_name: Symbol,
+    write_files: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<bool> {
+    let crate_dir = crate_path(sess, crate_name);
+    if find_source_directory(&crate_dir, &FxHashSet::default()).is_some() {
+        return Ok(false);
+    }
+    std_fs::create_dir_all(&crate_dir)?;
+
+    let session_dir = generate_session_dir_path(&crate_dir);
+    let lock_file_path = lock_file_path(&session_dir);
+    let lock = flock::Lock::new(
+        &lock_file_path,
+        false, // don't wait
+        true,  // create the lock file
+        true,
+    )?;
+
+    let result = std_fs::create_dir(&session_dir).and_then(|()| write_files(&session_dir));
+    if let Err(err) = result {
+        let _ = safe_remove_dir_all(&session_dir);
+        let _ = safe_remove_file(&lock_file_path);
+        return Err(err);
+    }
+
+    // Publish it like `finalize_session_directory` does. There is no SVH to
+    // append, but nothing reads it back anyway.
+    let mut sub_dir_name = session_dir.file_name().unwrap().to_str().unwrap().to_string();
+    sub_dir_name.truncate(sub_dir_name.len() - "working".len());
+    sub_dir_name.push_str("imported");
+    rename_path_with_retry(&session_dir, &crate_dir.join(sub_dir_name), 3)?;
+
+    drop(lock);
+    Ok(true)
+}
+
 pub(crate) fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()> {
     let sess_dir_iterator = sess.incr_comp_session_dir().read_dir()?;
     for entry in sess_dir_iterator {
@@ -384,20 +462,31 @@ pub(crate) fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()>
     Ok(())
 }
 
-fn copy_files(sess: &Session, target_dir: &Path, source_dir: &Path) -> Result<bool, ()> {
+fn copy_files(
+    sess: &Session,
+    target_dir: &Path,
+    source_dir: &Path,
+    is_shared: bool,
+) -> Result<bool, ()> {
     // We acquire a shared lock on the lock file of the directory, so that
     // nobody deletes it out from under us while we are reading from it.
-    let lock_file_path = lock_file_path(source_dir);
-
-    // not exclusive
-    let Ok(_lock) = flock::Lock::new(
-        &lock_file_path,
-        false, // don't wait,
-        false, // don't create
-        false,
-    ) else {
-        // Could not acquire the lock, don't try to copy from here
-        return Err(());
+    // Shared caches are never garbage collected, and may be read-only.
+    let _lock = if is_shared {
+        None
+    } else {
+        let lock_file_path = lock_file_path(source_dir);
+
+        // not exclusive
+        let Ok(lock) = flock::Lock::new(
+            &lock_file_path,
+            false, // don't wait,
+            false, // don't create
+            false,
+        ) else {
+            // Could not acquire the lock, don't try to copy from here
+            return Err(());
+        };
+        Some(lock)
     };
 
     let Ok(source_dir_iterator) = source_dir.read_dir() else {
@@ -505,10 +594,9 @@ fn find_source_directory(
     crate_dir: &Path,
     source_directories_already_tried: &FxHashSet<PathBuf>,
 ) -> Option<PathBuf> {
-    let iter = crate_dir
-        .read_dir()
-        .unwrap() // FIXME
-        .filter_map(|e| e.ok().map(|e| e.path()));
+    // The local crate directory has just been created, so this can only fail
+    // for a shared cache that has no entry for this crate.
+    let iter = crate_dir.read_dir().ok()?.filter_map(|e| e.ok().map(|e| e.path()));
 
     find_source_directory_in_iter(iter, source_directories_already_tried)
 }
@@ -596,8 +684,21 @@ fn string_to_timestamp(s: &str) -> Result<SystemTime, &'static str> {
 }
 
 fn crate_path(sess: &Session, crate_name: Symbol) -> PathBuf {
-    let incr_dir = sess.opts.incremental.as_ref().unwrap().clone();
+    let incr_dir = sess.opts.incremental.as_ref().unwrap();
+    incr_dir.join(crate_dir_name(sess, crate_name))
+}
+
+/// The crate directory in the cache given with `-Z incremental-shared-cache`,
+/// if any.
+fn shared_crate_path(sess: &Session, crate_name: Symbol) -> Option<PathBuf> {
+    let shared_dir = sess.opts.unstable_opts.incremental_shared_cache.as_ref()?;
+    Some(shared_dir.join(crate_dir_name(sess, crate_name)))
+}
 
+/// Returns `{crate-name}-{stable-crate-id}`, which doesn't depend on the
+/// location of the source, so that the same name is used wherever the cache
+/// is copied to.
+fn crate_dir_name(sess: &Session, crate_name: Symbol) -> String {
     let crate_types = collect_crate_types(sess, &[]);
     let stable_crate_id = StableCrateId::new(
         crate_name,
@@ -606,9 +707,7 @@ fn crate_path(sess: &Session, crate_name: Symbol) -> PathBuf {
         sess.cfg_version,
     );
 
-    let crate_name =
-        format!("{crate_name}-{}", stable_crate_id.as_u64().to_base_fixed_len(CASE_INSENSITIVE));
-    incr_dir.join(crate_name)
+    format!("{crate_name}-{}", stable_crate_id.as_u64().to_base_fixed_len(CASE_INSENSITIVE))
 }
 
 fn is_old_enough_to_be_collected(timestamp: SystemTime) -> bool {
@@ -1,12 +1,19 @@
 //! Code to load the dep-graph from files.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::memmap::Mmap;
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
 use rustc_data_structures::unord::UnordMap;
 use rustc_hashes::Hash64;
-use rustc_middle::dep_graph::{DepGraph, DepsType, SerializedDepGraph, WorkProductMap};
+use rustc_middle::dep_graph::{
+    DepGraph, DepNode, Deps, DepsType, SerializedDepGraph, SerializedDepNodeIndex, WorkProductMap,
+    dep_kinds,
+};
 use rustc_middle::query::on_disk_cache::OnDiskCache;
 use rustc_serialize::Decodable;
 use rustc_serialize::opaque::MemDecoder;
@@ -18,7 +25,7 @@
 use super::data::*;
 use super::fs::*;
 use super::save::build_dep_graph;
-use super::{file_format, work_product};
+use super::{explain, file_format, work_product};
 use crate::errors;
 
 #[derive(Debug)]
@@ -107,7 +114,7 @@ fn load_dep_graph(
     // Calling `sess.incr_comp_session_dir()` will panic if `sess.opts.incremental.is_none()`.
     // Fortunately, we just checked that this isn't the case.
     let path = dep_graph_path(sess);
-    let expected_hash = sess.opts.dep_tracking_hash(false);
+    let expected_hash = sess.opts.incr_comp_args_hash();
 
     let mut prev_work_products = UnordMap::default();
 
@@ -159,8 +166,12 @@ fn load_dep_graph(
                 return LoadResult::DataOutOfDate;
             };
             let prev_commandline_args_hash = Hash64::decode(&mut decoder);
+            let prev_commandline_args_hashes = BTreeMap::<String, Hash64>::decode(&mut decoder);
 
             if prev_commandline_args_hash != expected_hash {
+                if let Some(format) = sess.opts.unstable_opts.incremental_explain {
+                    explain::explain_changed_options(sess, format, &prev_commandline_args_hashes);
+                }
                 if sess.opts.unstable_opts.incremental_info {
                     eprintln!(
                         "[incremental] completely ignoring cache because of \
@@ -206,6 +217,126 @@ pub fn load_query_result_cache(sess: &Session) -> Option<OnDiskCache> {
     }
 }
 
+/// The files of a session directory that make up its cache.
+pub struct SessionFiles {
+    /// The dependency graph, the work product index and the query result
+    /// cache, those of them that exist.
+    pub indices: Vec<&'static str>,
+    /// The work products listed in the work product index.
+    pub work_products: Vec<SessionWorkProduct>,
+}
+
+/// A work product listed in the work product index of a session directory.
+pub struct SessionWorkProduct {
+    /// The fingerprint of the inputs of its codegen unit, see
+    /// [`codegen_unit_inputs`]. `None` if the codegen unit depends on state
+    /// that the dependency graph doesn't track.
+    pub inputs: Option<Fingerprint>,
+    /// The names of its files in the session directory.
+    pub files: Vec<String>,
+}
+
+/// Lists the files of the session directory `session_dir`, so that they can be
+/// shared with other sessions one by one. Files that aren't part of the cache,
+/// like the lock file or leftovers of interrupted writes, are not listed.
+pub fn session_directory_files(
+    sess: &Session,
+    session_dir: &Path,
+    deps: &DepsType,
+) -> SessionFiles {
+    let indices = [DEP_GRAPH_FILENAME, WORK_PRODUCTS_FILENAME, QUERY_CACHE_FILENAME]
+        .into_iter()
+        .filter(|file_name| session_dir.join(file_name).exists())
+        .collect();
+
+    let dep_graph = match load_data(&session_dir.join(DEP_GRAPH_FILENAME), sess) {
+        LoadResult::Ok { data: (data, start_pos) } => {
+            MemDecoder::new(&data[..], start_pos).ok().map(|mut decoder| {
+                let _commandline_args_hash = Hash64::decode(&mut decoder);
+                let _commandline_args_hashes = BTreeMap::<String, Hash64>::decode(&mut decoder);
+                SerializedDepGraph::decode::<DepsType>(&mut decoder, deps)
+            })
+        }
+        _ => None,
+    };
+
+    let mut work_products = Vec::new();
+    if let LoadResult::Ok { data: (data, start_pos) } =
+        load_data(&session_dir.join(WORK_PRODUCTS_FILENAME), sess)
+        && let Ok(mut decoder) = MemDecoder::new(&data[..], start_pos)
+    {
+        let serialized: Vec<SerializedWorkProduct> = Decodable::decode(&mut decoder);
+        for swp in serialized {
+            let inputs = dep_graph
+                .as_deref()
+                .and_then(|graph| codegen_unit_inputs(graph, &swp.work_product.cgu_name));
+            let files = swp
+                .work_product
+                .saved_files
+                .to_sorted_stable_ord()
+                .into_iter()
+                .map(|(_, file_name)| file_name.clone())
+                .collect();
+            work_products.push(SessionWorkProduct { inputs, files });
+        }
+    }
+
+    SessionFiles { indices, work_products }
+}
+
+/// Returns the fingerprint of the inputs of the codegen unit named `cgu_name`
+/// in `graph`: the name, and the nodes that its node depends on together with
+/// their fingerprints. Like the red-green algorithm does, nodes without a
+/// fingerprint of their own, like anonymous ones, stand for their own
+/// dependencies. Two codegen units with the same inputs are compiled to the
+/// same work product, whichever session compiled them.
+///
+/// Returns `None` if the codegen unit isn't in `graph`, or if it depends on
+/// state that isn't tracked by the dependency graph.
+fn codegen_unit_inputs(graph: &SerializedDepGraph, cgu_name: &str) -> Option<Fingerprint> {
+    // As `make_compile_codegen_unit` makes it.
+    let mut hasher = StableHasher::new();
+    cgu_name.hash_stable(&mut (), &mut hasher);
+    let hash = hasher.finish::<Fingerprint>().into();
+    let node = DepNode { kind: dep_kinds::CompileCodegenUnit, hash };
+    let index = graph.node_to_index_opt(&node)?;
+
+    let mut inputs = Vec::new();
+    let mut visited = FxHashSet::default();
+    let mut stack: Vec<SerializedDepNodeIndex> = graph.edge_targets_from(index).collect();
+    while let Some(index) = stack.pop() {
+        if !visited.insert(index) {
+            continue;
+        }
+        let node = graph.index_to_node(index);
+        if node.kind == DepsType::DEP_KIND_RED {
+            return None;
+        }
+        let fingerprint = graph.fingerprint_by_index(index);
+        if fingerprint != Fingerprint::ZERO {
+            inputs.push((node.kind.as_inner(), Fingerprint::from(node.hash), fingerprint));
+            continue;
+        }
+        let len = stack.len();
+        stack.extend(graph.edge_targets_from(index));
+        // Without dependencies, such a node reads state that isn't tracked.
+        if stack.len() == len {
+            return None;
+        }
+    }
+    // Reads are recorded in the order they happen in, which may change.
+    inputs.sort_unstable();
+
+    let mut hasher = StableHasher::new();
+    cgu_name.hash_stable(&mut (), &mut hasher);
+    for (kind, hash, fingerprint) in inputs {
+        kind.hash_stable(&mut (), &mut hasher);
+        hash.hash_stable(&mut (), &mut hasher);
+        fingerprint.hash_stable(&mut (), &mut hasher);
+    }
+    Some(hasher.finish())
+}
+
 /// Setups the dependency graph by loading an existing graph from disk and set up streaming of a
 /// new graph to an incremental session directory.
 pub fn setup_dep_graph(sess: &Session, crate_name: Symbol, deps: &DepsType) -> DepGraph {
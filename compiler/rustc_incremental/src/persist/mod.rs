@@ -4,14 +4,20 @@
 
 mod data;
 mod dirty_clean;
+mod explain;
 mod file_format;
 mod fs;
 mod load;
 mod save;
 mod work_product;
 
-pub use fs::{finalize_session_directory, in_incr_comp_dir, in_incr_comp_dir_sess};
-pub use load::{LoadResult, load_query_result_cache, setup_dep_graph};
+pub use fs::{
+    finalize_session_directory, import_session_directory, in_incr_comp_dir, in_incr_comp_dir_sess,
+};
+pub use load::{
+    LoadResult, SessionFiles, SessionWorkProduct, load_query_result_cache, session_directory_files,
+    setup_dep_graph,
+};
 pub(crate) use save::save_dep_graph;
 pub use save::save_work_product_index;
 pub use work_product::copy_cgu_workproduct_to_incr_comp_cache_dir;
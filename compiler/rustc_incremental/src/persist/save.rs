@@ -14,7 +14,7 @@
 
 use super::data::*;
 use super::fs::*;
-use super::{dirty_clean, file_format, work_product};
+use super::{dirty_clean, explain, file_format, work_product};
 use crate::assert_dep_graph::assert_dep_graph;
 use crate::errors;
 
@@ -44,6 +44,10 @@ pub(crate) fn save_dep_graph(tcx: TyCtxt<'_>) {
         sess.time("assert_dep_graph", || assert_dep_graph(tcx));
         sess.time("check_dirty_clean", || dirty_clean::check_dirty_clean_annotations(tcx));
 
+        if let Some(format) = sess.opts.unstable_opts.incremental_explain {
+            explain::explain_red_nodes(tcx, format);
+        }
+
         if sess.opts.unstable_opts.incremental_info {
             tcx.dep_graph.print_incremental_info()
         }
@@ -169,8 +173,11 @@ pub(crate) fn build_dep_graph(
 
     file_format::write_file_header(&mut encoder, sess);
 
-    // First encode the commandline arguments hash
-    sess.opts.dep_tracking_hash(false).encode(&mut encoder);
+    // First encode the commandline arguments hash, then the hashes of the
+    // individual arguments, so that `-Z incremental-explain` can tell which
+    // of them changed.
+    sess.opts.incr_comp_args_hash().encode(&mut encoder);
+    sess.opts.incr_comp_args_hashes().encode(&mut encoder);
 
     Some(DepGraph::new(
         sess,
@@ -50,6 +50,12 @@ interface_out_dir_error =
 interface_proc_macro_crate_panic_abort =
     building proc macro crate with `panic=abort` may crash the compiler should the proc-macro panic
 
+interface_remote_cache_fetch =
+    failed to fetch the incremental compilation cache from the remote cache: {$err}
+
+interface_remote_cache_store =
+    failed to store the incremental compilation cache in the remote cache: {$err}
+
 interface_rustc_error_fatal =
     fatal error triggered by #[rustc_error]
 
@@ -0,0 +1,314 @@
+//! Sharing incremental compilation caches between machines.
+//!
+//! A [`CacheBackend`] is a content-addressed store, e.g. a directory on a
+//! network file system or a remote key-value service. When a session has no
+//! local incremental compilation cache to start from, the cache that a
+//! previous session with the same compiler, crate and command line stored in
+//! the backend is fetched. The usual incremental machinery then decides which
+//! of the serialized query results and codegen work products in it are still
+//! valid, so only the parts of the crate that changed since are recompiled.
+//!
+//! The dependency graph, the work product index and the serialized query
+//! results of a session, as well as every file of its codegen work products,
+//! are stored one by one under the fingerprint of their contents. A manifest
+//! listing the indices is stored under the fingerprint of the session key,
+//! replacing the previous manifest. Each codegen work product is recorded
+//! under the fingerprint of the inputs of its codegen unit, as found in the
+//! dependency graph, so a work product whose inputs didn't change is neither
+//! read nor stored again, and sessions that compile a codegen unit from the
+//! same inputs share its work product.
+//!
+//! The dependency graph and the other indices are needed to make any use of
+//! the cache, but work products are optional: a work product whose files
+//! can't be fetched is recompiled, as if it was invalidated by a change.
+//!
+//! `-Z incremental-remote-cache=<dir>` uses a [`DirectoryCacheBackend`]; other
+//! backends can be set by drivers through [`Config::cache_backend`].
+//!
+//! [`Config::cache_backend`]: crate::Config::cache_backend
+
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::{fs, io, process};
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::sync::{DynSend, DynSync};
+use rustc_middle::dep_graph::DepsType;
+use rustc_session::Session;
+use rustc_span::Symbol;
+use tracing::debug;
+
+#[cfg(test)]
+mod tests;
+
+/// A store of blobs, addressed by fingerprints.
+pub trait CacheBackend: DynSend + DynSync {
+    /// Returns the data stored under `key`, if any.
+    fn fetch(&self, key: Fingerprint) -> Option<Vec<u8>>;
+
+    /// Returns whether any data is stored under `key`.
+    fn contains(&self, key: Fingerprint) -> bool {
+        self.fetch(key).is_some()
+    }
+
+    /// Stores `data` under `key`, replacing what was stored there before.
+    fn store(&self, key: Fingerprint, data: &[u8]) -> io::Result<()>;
+}
+
+/// Stores every blob in a file named after its key, within a directory
+/// named after the first byte of the key.
+pub struct DirectoryCacheBackend {
+    root: PathBuf,
+}
+
+impl DirectoryCacheBackend {
+    pub fn new(root: PathBuf) -> DirectoryCacheBackend {
+        DirectoryCacheBackend { root }
+    }
+
+    fn path(&self, key: Fingerprint) -> PathBuf {
+        let hex = key_to_hex(key);
+        self.root.join(&hex[..2]).join(&hex)
+    }
+}
+
+impl CacheBackend for DirectoryCacheBackend {
+    fn fetch(&self, key: Fingerprint) -> Option<Vec<u8>> {
+        fs::read(self.path(key)).ok()
+    }
+
+    fn contains(&self, key: Fingerprint) -> bool {
+        self.path(key).exists()
+    }
+
+    fn store(&self, key: Fingerprint, data: &[u8]) -> io::Result<()> {
+        let path = self.path(key);
+        fs::create_dir_all(path.parent().unwrap())?;
+        // Other processes may be reading or writing the same key, so write to
+        // a file of our own and move it into place in one go.
+        let tmp_path = path.with_extension(format!("{}.tmp", process::id()));
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp_path);
+        })
+    }
+}
+
+/// Formats `key` with a fixed width, unlike [`Fingerprint::to_hex`].
+fn key_to_hex(key: Fingerprint) -> String {
+    key.to_le_bytes().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn key_from_hex(hex: &str) -> Option<Fingerprint> {
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+    Some(Fingerprint::from_le_bytes(bytes))
+}
+
+fn content_key(data: &[u8]) -> Fingerprint {
+    let mut hasher = StableHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The key of the manifest of the sessions of a crate. `crate_dir_name` is
+/// the name of the crate's directory in the incremental compilation
+/// directory, which is made of the crate name and its `StableCrateId`.
+fn manifest_key(sess: &Session, crate_dir_name: &str) -> Fingerprint {
+    let mut hasher = StableHasher::new();
+    "manifest".hash(&mut hasher);
+    sess.cfg_version.hash(&mut hasher);
+    crate_dir_name.hash(&mut hasher);
+    sess.opts.incr_comp_args_hash().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn crate_dir_name(session_dir: &Path) -> &str {
+    session_dir.parent().unwrap().file_name().unwrap().to_str().unwrap()
+}
+
+/// The key of the record of a codegen work product whose codegen unit has the
+/// input fingerprint `inputs`, see [`rustc_incremental::SessionWorkProduct`].
+/// It doesn't depend on the session that compiled the work product, so any
+/// session with the same compiler and command line that compiles a codegen
+/// unit with the same inputs shares the record.
+fn work_product_key(sess: &Session, inputs: Fingerprint) -> Fingerprint {
+    let mut hasher = StableHasher::new();
+    "work-product".hash(&mut hasher);
+    sess.cfg_version.hash(&mut hasher);
+    sess.opts.incr_comp_args_hash().hash(&mut hasher);
+    inputs.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Manifests and work product records list blobs, one per line, as the key of
+/// their contents and their file name in the session directory.
+fn parse_file_list(list: &[u8]) -> Option<Vec<(Fingerprint, &str)>> {
+    let list = std::str::from_utf8(list).ok()?;
+    list.lines()
+        .map(|line| {
+            let (key, file_name) = line.split_once(' ')?;
+            let key = key_from_hex(key)?;
+            // Don't let a list write outside of the session directory.
+            if Path::new(file_name).file_name()?.to_str()? != file_name {
+                return None;
+            }
+            Some((key, file_name))
+        })
+        .collect()
+}
+
+/// Stores the files `file_names` of `session_dir` one by one, and returns the
+/// list of them.
+fn store_files<'a>(
+    backend: &dyn CacheBackend,
+    session_dir: &Path,
+    file_names: impl IntoIterator<Item = &'a str>,
+    files_stored: &mut usize,
+) -> io::Result<String> {
+    let mut list = String::new();
+    for file_name in file_names {
+        let data = fs::read(session_dir.join(file_name))?;
+        let key = content_key(&data);
+        if !backend.contains(key) {
+            backend.store(key, &data)?;
+            *files_stored += 1;
+        }
+        list.push_str(&format!("{} {file_name}\n", key_to_hex(key)));
+    }
+    Ok(list)
+}
+
+/// Fetches the blob stored under `key`, checking that it wasn't corrupted.
+fn fetch_verified(backend: &dyn CacheBackend, key: Fingerprint) -> io::Result<Vec<u8>> {
+    let data = backend.fetch(key).ok_or(io::ErrorKind::NotFound)?;
+    if content_key(&data) != key {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    Ok(data)
+}
+
+/// Fetches the files of `work_product` from the record of its inputs.
+fn fetch_work_product(
+    sess: &Session,
+    backend: &dyn CacheBackend,
+    work_product: &rustc_incremental::SessionWorkProduct,
+) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let inputs = work_product.inputs.ok_or(io::ErrorKind::NotFound)?;
+    let record = backend.fetch(work_product_key(sess, inputs)).ok_or(io::ErrorKind::NotFound)?;
+    let entries = parse_file_list(&record).ok_or(io::ErrorKind::InvalidData)?;
+    // A record of the same inputs lists the same files, unless it's corrupted.
+    if !entries.iter().map(|(_, file_name)| *file_name).eq(work_product.files.iter().map(|f| &**f))
+    {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    entries
+        .into_iter()
+        .map(|(key, file_name)| Ok((file_name.to_owned(), fetch_verified(backend, key)?)))
+        .collect()
+}
+
+/// Seeds the incremental compilation directory with the cache of `crate_name`
+/// from `backend`, unless there already is a local cache to start from.
+pub(crate) fn fetch_session(sess: &Session, crate_name: Symbol, backend: &dyn CacheBackend) {
+    if sess.opts.incremental.is_none() {
+        return;
+    }
+    let _timer = sess.timer("incr_comp_fetch_remote_cache");
+
+    let deps = DepsType { dep_names: rustc_query_impl::dep_kind_names() };
+    let mut files_fetched = 0;
+    let mut work_product_files_missing = 0;
+    let result = rustc_incremental::import_session_directory(sess, crate_name, |session_dir| {
+        let manifest = backend.fetch(manifest_key(sess, crate_dir_name(session_dir)));
+        let manifest = manifest.ok_or(io::ErrorKind::NotFound)?;
+        for (key, file_name) in parse_file_list(&manifest).ok_or(io::ErrorKind::InvalidData)? {
+            fs::write(session_dir.join(file_name), fetch_verified(backend, key)?)?;
+            files_fetched += 1;
+        }
+
+        // The dependency graph no longer lists work products without all of
+        // their files, so their codegen units are recompiled.
+        let files = rustc_incremental::session_directory_files(sess, session_dir, &deps);
+        for work_product in files.work_products {
+            match fetch_work_product(sess, backend, &work_product) {
+                Ok(fetched) => {
+                    for (file_name, data) in fetched {
+                        fs::write(session_dir.join(file_name), data)?;
+                        files_fetched += 1;
+                    }
+                }
+                Err(err) => {
+                    debug!("fetch_session: work product {:?}: {err}", work_product.files);
+                    work_product_files_missing += work_product.files.len();
+                }
+            }
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(true) => {
+            if sess.opts.unstable_opts.incremental_info {
+                eprintln!(
+                    "[incremental] remote cache: {files_fetched} files fetched, \
+                     {work_product_files_missing} work product files missing"
+                );
+            }
+        }
+        Ok(false) => debug!("fetch_session: local cache exists"),
+        // The cache was never stored for this crate and command line.
+        Err(err) if err.kind() == io::ErrorKind::NotFound => debug!("fetch_session: not found"),
+        Err(err) => {
+            sess.dcx().emit_warn(crate::errors::RemoteCacheFetch { err });
+        }
+    }
+}
+
+/// Stores the finalized session directory in `backend`.
+pub(crate) fn store_session(sess: &Session, backend: &dyn CacheBackend) {
+    let Some(session_dir) = sess.finalized_incr_comp_session_dir() else {
+        // Either not incremental, or the session had errors.
+        return;
+    };
+    let _timer = sess.timer("incr_comp_store_remote_cache");
+
+    let deps = DepsType { dep_names: rustc_query_impl::dep_kind_names() };
+    let files = rustc_incremental::session_directory_files(sess, &session_dir, &deps);
+
+    let mut files_stored = 0;
+    let result: io::Result<()> = try {
+        // Work products are recorded under the fingerprint of their inputs,
+        // so unchanged ones are neither read nor stored again. Those that
+        // depend on untracked state can't be shared.
+        for work_product in &files.work_products {
+            let Some(inputs) = work_product.inputs else { continue };
+            let key = work_product_key(sess, inputs);
+            if !backend.contains(key) {
+                let file_names = work_product.files.iter().map(|file_name| &**file_name);
+                let record = store_files(backend, &session_dir, file_names, &mut files_stored)?;
+                backend.store(key, record.as_bytes())?;
+            }
+        }
+        let manifest =
+            store_files(backend, &session_dir, files.indices.iter().copied(), &mut files_stored)?;
+        backend.store(manifest_key(sess, crate_dir_name(&session_dir)), manifest.as_bytes())?;
+    };
+
+    match result {
+        Ok(()) => {
+            if sess.opts.unstable_opts.incremental_info {
+                eprintln!("[incremental] remote cache: {files_stored} files stored");
+            }
+        }
+        Err(err) => {
+            sess.dcx().emit_warn(crate::errors::RemoteCacheStore { err });
+        }
+    }
+}
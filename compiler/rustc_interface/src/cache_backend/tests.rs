@@ -0,0 +1,71 @@
+use super::*;
+
+#[test]
+fn key_hex_roundtrip() {
+    for key in [Fingerprint::ZERO, Fingerprint::new(1, 0x0123_4567_89ab_cdef), content_key(b"data")]
+    {
+        let hex = key_to_hex(key);
+        assert_eq!(hex.len(), 32);
+        assert_eq!(key_from_hex(&hex), Some(key));
+    }
+    assert_eq!(key_from_hex("0123"), None);
+    assert_eq!(key_from_hex(&"g".repeat(32)), None);
+}
+
+#[test]
+fn directory_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = DirectoryCacheBackend::new(dir.path().to_owned());
+
+    let key = content_key(b"query results");
+    assert!(!backend.contains(key));
+    assert_eq!(backend.fetch(key), None);
+
+    backend.store(key, b"query results").unwrap();
+    assert!(backend.contains(key));
+    assert_eq!(backend.fetch(key).as_deref(), Some(&b"query results"[..]));
+
+    // Manifests and work product records are replaced.
+    let manifest_key = Fingerprint::new(1, 2);
+    backend.store(manifest_key, b"first").unwrap();
+    backend.store(manifest_key, b"second").unwrap();
+    assert_eq!(backend.fetch(manifest_key).as_deref(), Some(&b"second"[..]));
+}
+
+#[test]
+fn file_list() {
+    let key = content_key(b"dep graph");
+    let list = format!("{} dep-graph.bin\n{} foo.o\n", key_to_hex(key), key_to_hex(key));
+    assert_eq!(
+        parse_file_list(list.as_bytes()),
+        Some(vec![(key, "dep-graph.bin"), (key, "foo.o")])
+    );
+    assert_eq!(parse_file_list(b""), Some(vec![]));
+
+    for line in [
+        "dep-graph.bin",
+        "0123 dep-graph.bin",
+        "{key}",
+        "{key} ../dep-graph.bin",
+        "{key} dir/dep-graph.bin",
+        "index {key} dep-graph.bin",
+    ] {
+        let list = line.replace("{key}", &key_to_hex(key));
+        assert_eq!(parse_file_list(list.as_bytes()), None, "{list}");
+    }
+}
+
+#[test]
+fn verified_fetch() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = DirectoryCacheBackend::new(dir.path().to_owned());
+
+    let key = content_key(b"object file");
+    assert_eq!(fetch_verified(&backend, key).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+    backend.store(key, b"object file").unwrap();
+    assert_eq!(fetch_verified(&backend, key).unwrap(), b"object file");
+
+    backend.store(key, b"truncated").unwrap();
+    assert_eq!(fetch_verified(&backend, key).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
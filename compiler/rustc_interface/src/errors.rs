@@ -132,3 +132,15 @@ pub(crate) struct LimitInvalid<'a> {
     pub value_span: Span,
     pub error_str: &'a str,
 }
+
+#[derive(Diagnostic)]
+#[diag(interface_remote_cache_fetch)]
+pub(crate) struct RemoteCacheFetch {
+    pub err: io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(interface_remote_cache_store)]
+pub(crate) struct RemoteCacheStore {
+    pub err: io::Error,
+}
@@ -8,6 +8,7 @@
 use rustc_data_structures::jobserver;
 use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::EarlySarifLog;
 use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed};
 use rustc_lint::LintStore;
 use rustc_middle::ty;
@@ -25,6 +26,7 @@
 use rustc_span::{FileName, sym};
 use tracing::trace;
 
+use crate::cache_backend::{self, CacheBackend, DirectoryCacheBackend};
 use crate::util;
 
 pub type Result<T> = result::Result<T, ErrorGuaranteed>;
@@ -41,6 +43,7 @@ pub struct Compiler {
     pub codegen_backend: Box<dyn CodegenBackend>,
     pub(crate) override_queries: Option<fn(&Session, &mut Providers)>,
     pub(crate) current_gcx: CurrentGcx,
+    pub(crate) cache_backend: Option<Arc<dyn CacheBackend>>,
 }
 
 /// Converts strings provided as `--cfg [cfgspec]` into a `Cfg`.
@@ -349,6 +352,10 @@ pub struct Config {
     pub make_codegen_backend:
         Option<Box<dyn FnOnce(&config::Options) -> Box<dyn CodegenBackend> + Send>>,
 
+    /// The store to share incremental compilation caches through. Defaults
+    /// to the directory given with `-Z incremental-remote-cache`, if any.
+    pub cache_backend: Option<Arc<dyn CacheBackend>>,
+
     /// Registry of diagnostics codes.
     pub registry: Registry,
 
@@ -362,6 +369,11 @@ pub struct Config {
     /// This is mainly useful for other tools that reads that debuginfo to figure out
     /// how to call the compiler with the same arguments.
     pub expanded_args: Vec<String>,
+
+    /// With `--error-format=sarif`, the diagnostics that the driver reported
+    /// before calling `run_compiler`, to be written in the session's log. See
+    /// `EarlyDiagCtxt::take_sarif_log`.
+    pub early_sarif_log: Option<EarlySarifLog>,
 }
 
 /// Initialize jobserver before getting `jobserver::client` and `build_session`.
@@ -392,6 +404,12 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
 
     let sysroot = config.opts.sysroot.clone();
     let target = config::build_target_config(&early_dcx, &config.opts.target_triple, &sysroot);
+    // The diagnostics of each early dcx join those of the driver, in order, to
+    // be written in the session's log.
+    let mut early_sarif_log = config.early_sarif_log;
+    if let Some(log) = early_dcx.take_sarif_log() {
+        early_sarif_log.get_or_insert_default().append(log);
+    }
     let file_loader = config.file_loader.unwrap_or_else(|| Box::new(RealFileLoader));
     let path_mapping = config.opts.file_path_mapping();
     let hash_kind = config.opts.unstable_opts.src_hash_algorithm(&target);
@@ -421,6 +439,11 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
                 }
             };
 
+            let cache_backend = config.cache_backend.or_else(|| {
+                let dir = config.opts.unstable_opts.incremental_remote_cache.clone()?;
+                Some(Arc::new(DirectoryCacheBackend::new(dir)) as Arc<dyn CacheBackend>)
+            });
+
             let temps_dir = config.opts.unstable_opts.temps_dir.as_deref().map(PathBuf::from);
 
             let bundle = match rustc_errors::fluent_bundle(
@@ -438,6 +461,10 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
                 }
             };
 
+            if let Some(log) = early_dcx.take_sarif_log() {
+                early_sarif_log.get_or_insert_default().append(log);
+            }
+
             let mut locale_resources = config.locale_resources;
             locale_resources.push(codegen_backend.locale_resource());
 
@@ -459,6 +486,7 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
                 config.ice_file,
                 config.using_internal_features,
                 config.expanded_args,
+                early_sarif_log,
             );
 
             codegen_backend.init(&sess);
@@ -489,6 +517,7 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
             if let Some(register_lints) = config.register_lints.as_deref() {
                 register_lints(&sess, &mut lint_store);
             }
+            sess.dcx().register_lints(lint_store.get_lints());
             sess.lint_store = Some(Arc::new(lint_store));
 
             util::check_abi_required_features(&sess);
@@ -498,6 +527,7 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
                 codegen_backend,
                 override_queries: config.override_queries,
                 current_gcx,
+                cache_backend,
             };
 
             // There are two paths out of `f`.
@@ -518,6 +548,10 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
             // had panicked.
             if res.is_ok() {
                 compiler.sess.dcx().abort_if_errors();
+
+                if let Some(backend) = &compiler.cache_backend {
+                    cache_backend::store_session(&compiler.sess, &**backend);
+                }
             }
 
             // Also make sure to flush delayed bugs as if we panicked, the
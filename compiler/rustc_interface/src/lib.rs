@@ -6,6 +6,7 @@
 #![feature(try_blocks)]
 // tidy-alphabetical-end
 
+pub mod cache_backend;
 mod callbacks;
 pub mod errors;
 pub mod interface;
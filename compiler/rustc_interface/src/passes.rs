@@ -13,6 +13,7 @@
 use rustc_expand::base::{ExtCtxt, LintStoreExpand};
 use rustc_feature::Features;
 use rustc_fs_util::try_canonicalize;
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{LOCAL_CRATE, StableCrateId, StableCrateIdMap};
 use rustc_hir::definitions::Definitions;
 use rustc_incremental::setup_dep_graph;
@@ -27,7 +28,9 @@
 };
 use rustc_passes::{abi_test, input_stats, layout_test};
 use rustc_resolve::Resolver;
-use rustc_session::config::{CrateType, Input, OutFileName, OutputFilenames, OutputType};
+use rustc_session::config::{
+    CrateType, Input, OutFileName, OutputFilenames, OutputType, PrintKind,
+};
 use rustc_session::cstore::Untracked;
 use rustc_session::output::{collect_crate_types, filename_for_input};
 use rustc_session::search_paths::PathKind;
@@ -40,7 +43,7 @@
 use tracing::{info, instrument};
 
 use crate::interface::Compiler;
-use crate::{errors, limits, proc_macro_decls, util};
+use crate::{cache_backend, errors, limits, proc_macro_decls, util};
 
 pub fn parse<'a>(sess: &'a Session) -> ast::Crate {
     let krate = sess
@@ -198,6 +201,8 @@ fn configure_and_expand(
         let lint_store = LintStoreExpandImpl(lint_store);
         let mut ecx = ExtCtxt::new(sess, cfg, resolver, Some(&lint_store));
         ecx.num_standard_library_imports = num_standard_library_imports;
+        ecx.no_std = ast::attr::contains_name(pre_configured_attrs, sym::no_std)
+            || ast::attr::contains_name(pre_configured_attrs, sym::no_core);
         // Expand macros now!
         let krate = sess.time("expand_crate", || ecx.monotonic_expander().expand_crate(krate));
 
@@ -697,6 +702,10 @@ pub fn write_dep_info(tcx: TyCtxt<'_>) {
     // the side-effect of providing a complete set of all
     // accessed files and env vars.
     let _ = tcx.resolver_for_lowering();
+    // Likewise, load the MIR inlining profile now so that it is tracked as well.
+    if tcx.sess.opts.unstable_opts.inline_mir_profile.is_some() {
+        tcx.ensure_ok().inline_mir_profile(());
+    }
 
     let sess = tcx.sess;
     let _timer = sess.timer("write_dep_info");
@@ -802,6 +811,10 @@ pub fn create_and_enter_global_ctxt<T, F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> T>(
     );
     let outputs = util::build_output_filenames(&pre_configured_attrs, sess);
 
+    if let Some(backend) = &compiler.cache_backend {
+        cache_backend::fetch_session(sess, crate_name, &**backend);
+    }
+
     let dep_type = DepsType { dep_names: rustc_query_impl::dep_kind_names() };
     let dep_graph = setup_dep_graph(sess, crate_name, &dep_type);
 
@@ -900,7 +913,7 @@ fn run_required_analyses(tcx: TyCtxt<'_>) {
     #[cfg(all(not(doc), debug_assertions))]
     rustc_passes::hir_id_validator::check_crate(tcx);
     let sess = tcx.sess;
-    sess.time("misc_checking_1", || {
+    sess.time_in_source_order("misc_checking_1", || {
         parallel!(
             {
                 sess.time("looking_for_entry_point", || tcx.ensure_ok().entry_fn(()));
@@ -950,15 +963,15 @@ fn run_required_analyses(tcx: TyCtxt<'_>) {
     // This improves performance by allowing lock-free access to them.
     tcx.untracked().definitions.freeze();
 
-    sess.time("MIR_borrow_checking", || {
-        tcx.par_hir_body_owners(|def_id| {
+    sess.time_in_source_order("MIR_borrow_checking", || {
+        tcx.par_hir_body_owners_by_dependency(|def_id| {
             // Run unsafety check because it's responsible for stealing and
             // deallocating THIR.
             tcx.ensure_ok().check_unsafety(def_id);
             tcx.ensure_ok().mir_borrowck(def_id)
         });
     });
-    sess.time("MIR_effect_checking", || {
+    sess.time_in_source_order("MIR_effect_checking", || {
         tcx.par_hir_body_owners(|def_id| {
             tcx.ensure_ok().has_ffi_unwind_calls(def_id);
 
@@ -972,7 +985,7 @@ fn run_required_analyses(tcx: TyCtxt<'_>) {
             }
         });
     });
-    sess.time("coroutine_obligations", || {
+    sess.time_in_source_order("coroutine_obligations", || {
         tcx.par_hir_body_owners(|def_id| {
             if tcx.is_coroutine(def_id.to_def_id()) {
                 tcx.ensure_ok().mir_coroutine_witnesses(def_id);
@@ -1023,7 +1036,7 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) {
         guar.raise_fatal();
     }
 
-    sess.time("misc_checking_3", || {
+    sess.time_in_source_order("misc_checking_3", || {
         parallel!(
             {
                 tcx.ensure_ok().effective_visibilities(());
@@ -1065,6 +1078,40 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) {
         // we will fail to emit overlap diagnostics. Thus we invoke it here unconditionally.
         let _ = tcx.all_diagnostic_items(());
     });
+
+    // Builds without codegen (`--emit=metadata`, `cargo check`) never monomorphize
+    // anything, so record the layouts of the crate's types and print them here
+    // instead of in `start_codegen`.
+    if !sess.opts.output_types.should_codegen()
+        && sess.opts.prints.iter().any(|print| print.kind == PrintKind::TypeLayouts)
+    {
+        record_local_type_layouts(tcx);
+        print_type_layouts(tcx);
+    }
+}
+
+/// Computes the layouts of the crate's non-generic ADTs, so that they are recorded for
+/// `--print=type-layouts` even though no codegen instantiates them.
+fn record_local_type_layouts(tcx: TyCtxt<'_>) {
+    for def_id in tcx.hir_crate_items(()).definitions() {
+        if !matches!(tcx.def_kind(def_id), DefKind::Struct | DefKind::Enum | DefKind::Union)
+            || tcx.generics_of(def_id).requires_monomorphization(tcx)
+        {
+            continue;
+        }
+        let ty = tcx.type_of(def_id).instantiate_identity();
+        // Errors are reported elsewhere; types without a layout are simply not printed.
+        let _ = tcx.layout_of(ty::TypingEnv::fully_monomorphized().as_query_input(ty));
+    }
+}
+
+fn print_type_layouts(tcx: TyCtxt<'_>) {
+    for print in &tcx.sess.opts.prints {
+        if print.kind == PrintKind::TypeLayouts {
+            let filters = &tcx.sess.opts.unstable_opts.print_type_layouts_filter;
+            print.out.overwrite(&tcx.sess.code_stats.type_layouts_json(filters), tcx.sess);
+        }
+    }
 }
 
 /// Check for the `#[rustc_error]` annotation, which forces an error in codegen. This is used
@@ -1138,6 +1185,10 @@ pub(crate) fn start_codegen<'tcx>(
     if tcx.sess.opts.unstable_opts.print_type_sizes {
         tcx.sess.code_stats.print_type_sizes();
     }
+    // Without codegen, `analysis` has printed them already.
+    if tcx.sess.opts.output_types.should_codegen() {
+        print_type_layouts(tcx);
+    }
 
     codegen
 }
@@ -11,12 +11,12 @@
 use rustc_session::config::{
     AutoDiff, BranchProtection, CFGuard, Cfg, CollapseMacroDebuginfo, CoverageLevel,
     CoverageOptions, DebugInfo, DumpMonoStatsFormat, ErrorOutputType, ExternEntry, ExternLocation,
-    Externs, FmtDebug, FunctionReturn, InliningThreshold, Input, InstrumentCoverage,
-    InstrumentXRay, LinkSelfContained, LinkerPluginLto, LocationDetail, LtoCli, MirIncludeSpans,
-    NextSolverConfig, OomStrategy, Options, OutFileName, OutputType, OutputTypes, PAuthKey, PacRet,
-    Passes, PatchableFunctionEntry, Polonius, ProcMacroExecutionStrategy, Strip, SwitchWithOptPath,
-    SymbolManglingVersion, WasiExecModel, build_configuration, build_session_options,
-    rustc_optgroups,
+    Externs, FmtDebug, FunctionReturn, IncrementalExplainFormat, InliningThreshold, Input,
+    InstrumentCoverage, InstrumentXRay, LinkSelfContained, LinkerPluginLto, LocationDetail, LtoCli,
+    MirIncludeSpans, NextSolverConfig, OomStrategy, Options, OutFileName, OutputType, OutputTypes,
+    PAuthKey, PacRet, Passes, PatchableFunctionEntry, Polonius, ProcMacroExecutionStrategy, Strip,
+    SwitchWithOptPath, SymbolManglingVersion, WasiExecModel, build_configuration,
+    build_session_options, rustc_optgroups,
 };
 use rustc_session::lint::Level;
 use rustc_session::search_paths::SearchPath;
@@ -77,6 +77,7 @@ fn sess_and_cfg<F>(args: &[&'static str], f: F)
             None,
             &USING_INTERNAL_FEATURES,
             Default::default(),
+            None,
         );
         let cfg = parse_cfg(sess.dcx(), matches.opt_strs("cfg"));
         let cfg = build_configuration(&sess, cfg);
@@ -686,9 +687,11 @@ macro_rules! untracked {
 
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // tidy-alphabetical-start
+    untracked!(api_diff, Some(PathBuf::from("old.rmeta")));
     untracked!(assert_incr_state, Some(String::from("loaded")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dump_dep_graph, true);
+    untracked!(dump_metadata_regions, Some(PathBuf::from("regions.txt")));
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
     untracked!(dump_mir_dir, String::from("abc"));
@@ -701,7 +704,12 @@ macro_rules! untracked {
     untracked!(emit_stack_sizes, true);
     untracked!(future_incompat_test, true);
     untracked!(identify_regions, true);
+    untracked!(incremental_explain, Some(IncrementalExplainFormat::Json));
+    untracked!(incremental_explain_filter, Some(String::from("hir_owner")));
     untracked!(incremental_info, true);
+    untracked!(incremental_remote_cache, Some(PathBuf::from("remote")));
+    untracked!(incremental_relocatable, true);
+    untracked!(incremental_shared_cache, Some(PathBuf::from("shared")));
     untracked!(incremental_verify_ich, true);
     untracked!(input_stats, true);
     untracked!(link_native_libraries, false);
@@ -720,6 +728,7 @@ macro_rules! untracked {
     untracked!(print_codegen_stats, true);
     untracked!(print_llvm_passes, true);
     untracked!(print_mono_items, Some(String::from("abc")));
+    untracked!(print_type_layouts_filter, vec![String::from("core::")]);
     untracked!(print_type_sizes, true);
     untracked!(proc_macro_backtrace, true);
     untracked!(proc_macro_execution_strategy, ProcMacroExecutionStrategy::CrossThread);
@@ -741,7 +750,9 @@ macro_rules! untracked {
     untracked!(ui_testing, true);
     untracked!(unpretty, Some("expanded".to_string()));
     untracked!(unstable_options, true);
+    untracked!(unused_pub_items, Some(vec![String::from("libdownstream.rmeta")]));
     untracked!(validate_mir, true);
+    untracked!(verify_reproducible, true);
     untracked!(write_long_types_to_disk, false);
     // tidy-alphabetical-end
 
@@ -798,10 +809,13 @@ macro_rules! tracked {
     tracked!(force_unstable_if_unmarked, true);
     tracked!(function_return, FunctionReturn::ThunkExtern);
     tracked!(function_sections, Some(false));
+    tracked!(hot_patch, true);
     tracked!(human_readable_cgu_names, true);
     tracked!(incremental_ignore_spans, true);
     tracked!(inline_mir, Some(true));
     tracked!(inline_mir_hint_threshold, Some(123));
+    tracked!(inline_mir_hot_threshold, Some(123));
+    tracked!(inline_mir_profile, Some(PathBuf::from("abc")));
     tracked!(inline_mir_threshold, Some(123));
     tracked!(instrument_mcount, true);
     tracked!(instrument_xray, Some(InstrumentXRay::default()));
@@ -843,6 +857,7 @@ macro_rules! tracked {
     tracked!(precise_enum_drop_elaboration, false);
     tracked!(profile_sample_use, Some(PathBuf::from("abc")));
     tracked!(profiler_runtime, "abc".to_string());
+    tracked!(record_uses, true);
     tracked!(reg_struct_return, true);
     tracked!(regparm, Some(3));
     tracked!(relax_elf_relocations, Some(true));
@@ -861,6 +876,7 @@ macro_rules! tracked {
     tracked!(split_lto_unit, Some(true));
     tracked!(src_hash_algorithm, Some(SourceFileHashAlgorithm::Sha1));
     tracked!(stack_protector, StackProtector::All);
+    tracked!(staticlib_localize_symbols, true);
     tracked!(teach, true);
     tracked!(thinlto, Some(true));
     tracked!(tiny_const_eval_limit, true);
@@ -985,3 +985,6 @@ lint_uses_power_alignment = repr(C) does not follow the power alignment rule. Th
 
 lint_variant_size_differences =
     enum variant is more than three times larger ({$largest} bytes) than the next largest
+
+lint_variant_size_differences_boxed =
+    boxing the fields of this variant would shrink the enum from {$size} to {$boxed_size} bytes
@@ -1909,6 +1909,15 @@ fn decorate_lint<'b>(self, diag: &'b mut Diag<'a, ()>) {
 #[diag(lint_variant_size_differences)]
 pub(crate) struct VariantSizeDifferencesDiag {
     pub largest: u64,
+    #[subdiagnostic]
+    pub boxed: Option<VariantSizeDifferencesBoxed>,
+}
+
+#[derive(Subdiagnostic)]
+#[help(lint_variant_size_differences_boxed)]
+pub(crate) struct VariantSizeDifferencesBoxed {
+    pub size: u64,
+    pub boxed_size: u64,
 }
 
 #[derive(LintDiagnostic)]
@@ -26,7 +26,7 @@
     AtomicOrderingStore, ImproperCTypes, InvalidAtomicOrderingDiag, InvalidNanComparisons,
     InvalidNanComparisonsSuggestion, UnpredictableFunctionPointerComparisons,
     UnpredictableFunctionPointerComparisonsSuggestion, UnusedComparisons, UsesPowerAlignment,
-    VariantSizeDifferencesDiag,
+    VariantSizeDifferencesBoxed, VariantSizeDifferencesDiag,
 };
 use crate::{LateContext, LateLintPass, LintContext, fluent_generated as fluent};
 
@@ -104,7 +104,13 @@
     /// second-largest variant.
     ///
     /// Consider placing the large variant's contents on the heap (for example
-    /// via [`Box`]) to keep the overall size of the enum itself down.
+    /// via [`Box`]) to keep the overall size of the enum itself down. The lint
+    /// reports the size the enum would have then, and
+    /// `-Z print-enum-layout-hints` estimates how much that would save across
+    /// the monomorphized code of a crate.
+    ///
+    /// Neither the lint nor that flag changes the layout of the enum, so the
+    /// fields have to be moved into a `Box` by hand.
     ///
     /// This lint is "allow" by default because it can be noisy, and may not be
     /// an actual problem. Decisions about this should be guided with
@@ -1802,10 +1808,16 @@ fn check_item(&mut self, cx: &LateContext<'_>, it: &hir::Item<'_>) {
             // We only warn if the largest variant is at least thrice as large as
             // the second-largest.
             if largest > slargest * 3 && slargest > 0 {
+                let boxed = layout.oversized_variant(&cx.tcx).map(|oversized| {
+                    VariantSizeDifferencesBoxed {
+                        size: layout.size.bytes(),
+                        boxed_size: oversized.boxed_size.bytes(),
+                    }
+                });
                 cx.emit_span_lint(
                     VARIANT_SIZE_DIFFERENCES,
                     enum_definition.variants[largest_index].span,
-                    VariantSizeDifferencesDiag { largest },
+                    VariantSizeDifferencesDiag { largest, boxed },
                 );
             }
         }
@@ -38,6 +38,9 @@
     NativeLibSearchFallback, find_native_static_library, try_find_native_dynamic_library,
     try_find_native_static_library, walk_native_lib_search_dirs,
 };
-pub use rmeta::{EncodedMetadata, METADATA_HEADER, encode_metadata, rendered_const};
+pub use rmeta::{
+    EncodedMetadata, METADATA_HEADER, MetadataRegion, encode_metadata, read_metadata_regions,
+    rendered_const,
+};
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
@@ -224,6 +224,7 @@
 use rustc_data_structures::svh::Svh;
 use rustc_errors::{DiagArgValue, IntoDiagArg};
 use rustc_fs_util::try_canonicalize;
+use rustc_hir::def_id::DefPathHash;
 use rustc_session::Session;
 use rustc_session::cstore::CrateSource;
 use rustc_session::filesearch::FileSearch;
@@ -879,6 +880,24 @@ pub fn list_file_metadata(
     }
 }
 
+/// Reads the items of other crates that the crate whose metadata is at `path`
+/// uses, as recorded by `-Z record-uses`, for `-Z unused-pub-items`.
+pub fn read_used_items(
+    sess: &Session,
+    path: &Path,
+    metadata_loader: &dyn MetadataLoader,
+) -> Result<Vec<DefPathHash>, String> {
+    let flavor = get_flavor_from_path(path);
+    let metadata =
+        get_metadata_section(&sess.target, flavor, path, metadata_loader, sess.cfg_version)
+            .map_err(|err| err.to_string())?;
+    let root = metadata.get_root();
+    let used_items = root
+        .decode_used_items(&metadata)
+        .ok_or_else(|| "it was built without `-Z record-uses`".to_owned())?;
+    Ok(used_items.collect())
+}
+
 fn get_flavor_from_path(path: &Path) -> CrateFlavor {
     let filename = path.file_name().unwrap().to_str().unwrap();
 
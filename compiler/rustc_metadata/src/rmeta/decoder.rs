@@ -964,6 +964,13 @@ pub(crate) fn decode_crate_deps<'a>(
         self.crate_deps.decode(metadata)
     }
 
+    pub(crate) fn decode_used_items<'a>(
+        &self,
+        metadata: &'a MetadataBlob,
+    ) -> Option<impl ExactSizeIterator<Item = DefPathHash>> {
+        self.used_items.map(|used_items| used_items.decode(metadata))
+    }
+
     pub(crate) fn decode_target_modifiers<'a>(
         &self,
         metadata: &'a MetadataBlob,
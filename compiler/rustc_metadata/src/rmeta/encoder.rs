@@ -2,6 +2,7 @@
 use std::collections::hash_map::Entry;
 use std::fs::File;
 use std::io::{Read, Seek, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -23,6 +24,7 @@
 use rustc_middle::traits::specialization_graph;
 use rustc_middle::ty::codec::TyEncoder;
 use rustc_middle::ty::fast_reject::{self, TreatParams};
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::{AssocItemContainer, SymbolName};
 use rustc_middle::{bug, span_bug};
 use rustc_serialize::{Decodable, Decoder, Encodable, Encoder, opaque};
@@ -64,6 +66,28 @@ pub(super) struct EncodeContext<'a, 'tcx> {
     is_proc_macro: bool,
     hygiene_ctxt: &'a HygieneEncodeContext,
     symbol_table: FxHashMap<Symbol, usize>,
+    // The parts of the metadata encoded so far, with `-Z dump-metadata-regions`.
+    regions: Option<Vec<EncodedRegion>>,
+}
+
+/// A part of the metadata, as recorded while encoding it for `-Z dump-metadata-regions`.
+pub(super) struct EncodedRegion {
+    range: Range<usize>,
+    label: &'static str,
+    def_index: Option<DefIndex>,
+}
+
+impl EncodedRegion {
+    pub(super) fn push(
+        regions: &mut Option<Vec<EncodedRegion>>,
+        range: Range<usize>,
+        label: &'static str,
+        def_index: Option<DefIndex>,
+    ) {
+        if let Some(regions) = regions {
+            regions.push(EncodedRegion { range, label, def_index });
+        }
+    }
 }
 
 /// If the current crate is a proc-macro, returns early with `LazyArray::default()`.
@@ -385,6 +409,12 @@ fn position(&self) -> usize {
         self.opaque.position()
     }
 
+    /// Records that what was encoded since `start` is `label`, with `-Z dump-metadata-regions`.
+    fn record_region(&mut self, start: usize, label: &'static str, def_index: Option<DefIndex>) {
+        let range = start..self.position();
+        EncodedRegion::push(&mut self.regions, range, label, def_index);
+    }
+
     fn type_shorthands(&mut self) -> &mut FxHashMap<Ty<'tcx>, usize> {
         &mut self.type_shorthands
     }
@@ -406,7 +436,9 @@ macro_rules! record {
     ($self:ident.$tables:ident.$table:ident[$def_id:expr] <- $value:expr) => {{
         {
             let value = $value;
+            let start = $self.position();
             let lazy = $self.lazy(value);
+            $self.record_region(start, stringify!($table), Some($def_id.index));
             $self.$tables.$table.set_some($def_id.index, lazy);
         }
     }};
@@ -418,7 +450,9 @@ macro_rules! record_array {
     ($self:ident.$tables:ident.$table:ident[$def_id:expr] <- $value:expr) => {{
         {
             let value = $value;
+            let start = $self.position();
             let lazy = $self.lazy_array(value);
+            $self.record_region(start, stringify!($table), Some($def_id.index));
             $self.$tables.$table.set_some($def_id.index, lazy);
         }
     }};
@@ -428,7 +462,9 @@ macro_rules! record_defaulted_array {
     ($self:ident.$tables:ident.$table:ident[$def_id:expr] <- $value:expr) => {{
         {
             let value = $value;
+            let start = $self.position();
             let lazy = $self.lazy_array(value);
+            $self.record_region(start, stringify!($table), Some($def_id.index));
             $self.$tables.$table.set($def_id.index, lazy);
         }
     }};
@@ -599,6 +635,7 @@ macro_rules! stat {
                 let orig_pos = self.position();
                 let res = $f();
                 stats.push(($label, self.position() - orig_pos));
+                self.record_region(orig_pos, $label, None);
                 res
             }};
         }
@@ -668,11 +705,15 @@ macro_rules! stat {
         // encode_def_path_table.
         let proc_macro_data = stat!("proc-macro-data", || self.encode_proc_macros());
 
-        let tables = stat!("tables", || self.tables.encode(&mut self.opaque));
+        let tables = stat!("tables", || self.tables.encode(&mut self.opaque, &mut self.regions));
 
         let debugger_visualizers =
             stat!("debugger-visualizers", || self.encode_debugger_visualizers());
 
+        let used_items = stat!("used-items", || {
+            tcx.sess.opts.unstable_opts.record_uses.then(|| self.encode_used_items())
+        });
+
         // Encode exported symbols info. This is prefetched in `encode_metadata`.
         let exported_symbols = stat!("exported-symbols", || {
             self.encode_exported_symbols(tcx.exported_symbols(LOCAL_CRATE))
@@ -716,6 +757,7 @@ macro_rules! stat {
                 ),
                 proc_macro_data,
                 debugger_visualizers,
+                used_items,
                 compiler_builtins: ast::attr::contains_name(attrs, sym::compiler_builtins),
                 needs_allocator: ast::attr::contains_name(attrs, sym::needs_allocator),
                 needs_panic_runtime: ast::attr::contains_name(attrs, sym::needs_panic_runtime),
@@ -1972,6 +2014,18 @@ fn encode_debugger_visualizers(&mut self) -> LazyArray<DebuggerVisualizerFile> {
         )
     }
 
+    fn encode_used_items(&mut self) -> LazyArray<DefPathHash> {
+        let tcx = self.tcx;
+        let mut used_items: Vec<_> = tcx
+            .used_items(())
+            .items()
+            .filter(|def_id| !def_id.is_local())
+            .map(|&def_id| tcx.def_path_hash(def_id))
+            .into_sorted_stable_ord();
+        used_items.dedup();
+        self.lazy_array(used_items)
+    }
+
     fn encode_crate_deps(&mut self) -> LazyArray<CrateDep> {
         empty_proc_macro!(self);
 
@@ -2321,6 +2375,7 @@ pub fn encode_metadata(tcx: TyCtxt<'_>, path: &Path) {
         is_proc_macro: tcx.crate_types().contains(&CrateType::ProcMacro),
         hygiene_ctxt: &hygiene_ctxt,
         symbol_table: Default::default(),
+        regions: tcx.sess.opts.unstable_opts.dump_metadata_regions.as_ref().map(|_| Vec::new()),
     };
 
     // Encode the rustc version string in a predictable location.
@@ -2337,6 +2392,25 @@ pub fn encode_metadata(tcx: TyCtxt<'_>, path: &Path) {
         tcx.dcx().emit_fatal(FailWriteFile { path: &path, err });
     }
 
+    if let Some(path) = &tcx.sess.opts.unstable_opts.dump_metadata_regions
+        && let Some(regions) = ecx.regions.take()
+    {
+        let regions: Vec<_> = regions
+            .into_iter()
+            .map(|region| MetadataRegion {
+                range: region.range,
+                label: region.label.to_owned(),
+                item: region.def_index.map(|index| {
+                    let def_id = DefId { krate: LOCAL_CRATE, index };
+                    with_no_trimmed_paths!(tcx.def_path_str(def_id))
+                }),
+            })
+            .collect();
+        if let Err(err) = regions::write_metadata_regions(path, &regions) {
+            tcx.dcx().emit_fatal(FailWriteFile { path, err });
+        }
+    }
+
     let file = ecx.opaque.file();
     if let Err(err) = encode_root_position(file, root.position.get()) {
         tcx.dcx().emit_fatal(FailWriteFile { path: ecx.opaque.path(), err });
@@ -4,8 +4,9 @@
 pub(crate) use decoder::{CrateMetadata, CrateNumMap, MetadataBlob, TargetModifiers};
 use decoder::{DecodeContext, Metadata};
 use def_path_hash_map::DefPathHashMapRef;
-use encoder::EncodeContext;
+use encoder::{EncodeContext, EncodedRegion};
 pub use encoder::{EncodedMetadata, encode_metadata, rendered_const};
+pub use regions::{MetadataRegion, read_metadata_regions};
 use rustc_abi::{FieldIdx, ReprOptions, VariantIdx};
 use rustc_ast::expand::StrippedCfgItem;
 use rustc_data_structures::fx::FxHashMap;
@@ -47,6 +48,7 @@
 mod decoder;
 mod def_path_hash_map;
 mod encoder;
+mod regions;
 mod table;
 
 pub(crate) fn rustc_version(cfg_version: &'static str) -> String {
@@ -273,6 +275,9 @@ pub(crate) struct CrateRoot {
 
     tables: LazyTables,
     debugger_visualizers: LazyArray<DebuggerVisualizerFile>,
+    /// Only encoded with `-Z record-uses`: the items of other crates that this
+    /// crate uses, sorted.
+    used_items: Option<LazyArray<DefPathHash>>,
 
     exported_symbols: LazyArray<(ExportedSymbol<'static>, SymbolExportInfo)>,
 
@@ -365,10 +370,24 @@ struct TableBuilders {
         }
 
         impl TableBuilders {
-            fn encode(&self, buf: &mut FileEncoder) -> LazyTables {
+            fn encode(
+                &self,
+                buf: &mut FileEncoder,
+                regions: &mut Option<Vec<EncodedRegion>>,
+            ) -> LazyTables {
                 LazyTables {
-                    $($name1: self.$name1.encode(buf),)+
-                    $($name2: self.$name2.encode(buf),)+
+                    $($name1: {
+                        let start = buf.position();
+                        let table = self.$name1.encode(buf);
+                        EncodedRegion::push(regions, start..buf.position(), stringify!($name1), None);
+                        table
+                    },)+
+                    $($name2: {
+                        let start = buf.position();
+                        let table = self.$name2.encode(buf);
+                        EncodedRegion::push(regions, start..buf.position(), stringify!($name2), None);
+                        table
+                    },)+
                 }
             }
         }
@@ -0,0 +1,53 @@
+//! The byte ranges of the crate metadata that its parts were encoded to, written
+//! with `-Z dump-metadata-regions`. `-Z verify-reproducible` compares the regions
+//! of two builds to tell which section, query or item of the metadata differs.
+//!
+//! The file has one region per line: its start, its end, its label and, for
+//! the entries of tables, the path of the item, separated by spaces.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// A part of the crate metadata: a section of it, the table of a query, or the
+/// value that such a table holds for an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataRegion {
+    /// Where the part starts and ends in the metadata.
+    pub range: Range<usize>,
+    /// The name of the section or table, e.g. `def-ids` or `optimized_mir`.
+    pub label: String,
+    /// For the entries of tables, the path of the item the entry is for.
+    pub item: Option<String>,
+}
+
+pub(super) fn write_metadata_regions(path: &Path, regions: &[MetadataRegion]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    for region in regions {
+        write!(file, "{} {} {}", region.range.start, region.range.end, region.label)?;
+        if let Some(item) = &region.item {
+            write!(file, " {item}")?;
+        }
+        writeln!(file)?;
+    }
+    file.flush()
+}
+
+/// Reads the regions that `-Z dump-metadata-regions` wrote to `path`.
+pub fn read_metadata_regions(path: &Path) -> io::Result<Vec<MetadataRegion>> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed metadata regions");
+    let mut regions = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let mut parts = line.splitn(4, ' ');
+        let mut position = || parts.next().and_then(|part| part.parse::<usize>().ok());
+        let (Some(start), Some(end)) = (position(), position()) else {
+            return Err(malformed());
+        };
+        let label = parts.next().ok_or_else(malformed)?.to_owned();
+        let item = parts.next().map(str::to_owned);
+        regions.push(MetadataRegion { range: start..end, label, item });
+    }
+    Ok(regions)
+}
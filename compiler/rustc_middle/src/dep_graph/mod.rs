@@ -9,7 +9,9 @@
 
 pub use dep_node::{DepKind, DepNode, DepNodeExt, dep_kinds, label_strs};
 pub(crate) use dep_node::{make_compile_codegen_unit, make_compile_mono_item};
-pub use rustc_query_system::dep_graph::debug::{DepNodeFilter, EdgeFilter};
+pub use rustc_query_system::dep_graph::debug::{
+    DepNodeFilter, EdgeFilter, RedNodeCause, RedNodeExplanation,
+};
 pub use rustc_query_system::dep_graph::{
     DepContext, DepGraphQuery, DepNodeIndex, Deps, SerializedDepGraph, SerializedDepNodeIndex,
     TaskDepsRef, WorkProduct, WorkProductId, WorkProductMap, hash_result,
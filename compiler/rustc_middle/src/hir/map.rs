@@ -1,6 +1,7 @@
 use rustc_abi::ExternAbi;
 use rustc_ast::visit::{VisitorResult, walk_list};
 use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::{DynSend, DynSync, par_for_each_in, try_par_for_each_in};
@@ -345,6 +346,32 @@ pub fn par_hir_body_owners(self, f: impl Fn(LocalDefId) + DynSend + DynSync) {
         par_for_each_in(&self.hir_crate_items(()).body_owners[..], |&def_id| f(def_id));
     }
 
+    /// Runs `f` on every body owner in parallel, starting with the bodies that the queries of
+    /// other bodies are likely to wait on: the ones of constants and statics, which are evaluated
+    /// by the bodies using them, and the ones of functions returning `impl Trait`, whose hidden
+    /// types are inferred by type-checking and borrow-checking them. With a single thread, nothing
+    /// waits, so the bodies are visited in source order.
+    pub fn par_hir_body_owners_by_dependency(self, f: impl Fn(LocalDefId) + DynSend + DynSync) {
+        if self.sess.threads() == 1 {
+            return self.par_hir_body_owners(f);
+        }
+        let defines_opaques: FxHashSet<LocalDefId> =
+            self.hir_crate_items(()).opaques().map(|opaque| self.local_parent(opaque)).collect();
+        let (first, rest): (Vec<_>, Vec<_>) = self.hir_body_owners().partition(|&def_id| {
+            defines_opaques.contains(&def_id)
+                || matches!(
+                    self.def_kind(def_id),
+                    DefKind::Const
+                        | DefKind::AssocConst
+                        | DefKind::Static { .. }
+                        | DefKind::AnonConst
+                        | DefKind::InlineConst
+                )
+        });
+        par_for_each_in(&first[..], |&def_id| f(def_id));
+        par_for_each_in(&rest[..], |&def_id| f(def_id));
+    }
+
     pub fn hir_ty_param_owner(self, def_id: LocalDefId) -> LocalDefId {
         let def_kind = self.def_kind(def_id);
         match def_kind {
@@ -203,6 +203,12 @@ pub fn instantiation_mode(&self, tcx: TyCtxt<'tcx>) -> InstantiationMode {
             return InstantiationMode::GloballyShared { may_conflict: true };
         }
 
+        // With -Zhot-patch, a function can only be replaced if every use of it goes through
+        // the one definition in the executable, which a patch can then redirect to its own.
+        if tcx.sess.opts.unstable_opts.hot_patch {
+            return InstantiationMode::GloballyShared { may_conflict: true };
+        }
+
         // To ensure that #[inline(always)] can be inlined as much as possible, especially in unoptimized
         // builds, we always select LocalCopy.
         if codegen_fn_attrs.inline.always() {
@@ -4,6 +4,7 @@
 
 use rustc_abi::{FieldIdx, VariantIdx};
 use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::unord::UnordMap;
 use rustc_errors::ErrorGuaranteed;
 use rustc_hir::def_id::LocalDefId;
 use rustc_index::bit_set::BitMatrix;
@@ -319,3 +320,55 @@ pub struct DestructuredConstant<'tcx> {
     pub variant: Option<VariantIdx>,
     pub fields: &'tcx [(ConstValue<'tcx>, Ty<'tcx>)],
 }
+
+/// Execution counts read from `-Z inline-mir-profile`, which guide the MIR inliner
+/// and `cross_crate_inlinable`.
+///
+/// Functions are keyed by their crate-qualified path without generic arguments,
+/// such as `alloc::vec::Vec::push` or `<mycrate::Foo as core::clone::Clone>::clone`.
+#[derive(Debug, Default, HashStable)]
+pub struct InlineProfile {
+    /// How many times each function was entered.
+    pub functions: UnordMap<String, u64>,
+    /// How many times each function called another one, keyed by `(caller, callee)`.
+    pub calls: UnordMap<(String, String), u64>,
+    /// Counts at or above this are hot.
+    pub hot_count: u64,
+    /// Counts at or below this are cold.
+    pub cold_count: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProfileHotness {
+    Hot,
+    Cold,
+    Neutral,
+}
+
+impl InlineProfile {
+    pub fn hotness(&self, count: u64) -> ProfileHotness {
+        if count >= self.hot_count {
+            ProfileHotness::Hot
+        } else if count <= self.cold_count {
+            ProfileHotness::Cold
+        } else {
+            ProfileHotness::Neutral
+        }
+    }
+
+    /// The hotness of `function`, or `None` if the profile doesn't mention it.
+    pub fn function_hotness(&self, function: &str) -> Option<ProfileHotness> {
+        self.functions.get(function).map(|&count| self.hotness(count))
+    }
+
+    /// The hotness of calls from `caller` to `callee`. Falls back to the hotness of
+    /// `callee` itself if the profile has no count for this particular call edge.
+    pub fn call_hotness(&self, caller: &str, callee: &str) -> Option<ProfileHotness> {
+        if !self.calls.is_empty()
+            && let Some(&count) = self.calls.get(&(caller.to_owned(), callee.to_owned()))
+        {
+            return Some(self.hotness(count));
+        }
+        self.function_hotness(callee)
+    }
+}
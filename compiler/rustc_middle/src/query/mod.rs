@@ -1113,6 +1113,15 @@
         desc { "finding live symbols in crate" }
     }
 
+    /// The items, of the local crate or others, that the local crate refers to
+    /// anywhere: in its bodies, signatures, types or trait impls. This is what
+    /// `-Z record-uses` records in the metadata, and what `-Z unused-pub-items`
+    /// compares the public items of the crate with.
+    query used_items(_: ()) -> &'tcx UnordSet<DefId> {
+        arena_cache
+        desc { "collecting the items used by the crate" }
+    }
+
     query check_mod_deathness(key: LocalModDefId) {
         desc { |tcx| "checking deathness of variables in {}", describe_as_module(key, tcx) }
     }
@@ -1208,6 +1217,13 @@
         }
     }
 
+    /// The execution counts read from `-Z inline-mir-profile`, if it was passed.
+    query inline_mir_profile(_: ()) -> Option<&'tcx mir::InlineProfile> {
+        arena_cache
+        eval_always
+        desc { "loading the MIR inlining profile" }
+    }
+
     /// Obtain all the calls into other local functions
     query mir_inliner_callees(key: ty::InstanceKind<'tcx>) -> &'tcx [(DefId, GenericArgsRef<'tcx>)] {
         fatal_cycle
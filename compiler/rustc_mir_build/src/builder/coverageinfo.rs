@@ -155,6 +155,18 @@ fn register_two_way_branch<'tcx>(
             return;
         }
 
+        self.register_plain_branch(cfg, source_info, true_block, false_block);
+    }
+
+    /// Like [`Self::register_two_way_branch`], but never treats the branch as
+    /// a condition of an MC/DC decision.
+    fn register_plain_branch(
+        &mut self,
+        cfg: &mut CFG<'_>,
+        source_info: SourceInfo,
+        true_block: BasicBlock,
+        false_block: BasicBlock,
+    ) {
         // Bail out if branch coverage is not enabled.
         let Some(branch_info) = self.branch_info.as_mut() else { return };
 
@@ -285,6 +297,30 @@ pub(crate) fn visit_coverage_branch_condition(
         );
     }
 
+    /// If branch coverage is enabled, inject marker statements into the arms of
+    /// the `match` that a `?` expression desugars to, and record their IDs in
+    /// the table of branches. The branch is taken when `?` produces a value, and
+    /// not taken when it returns early.
+    ///
+    /// A `?` is never a condition of an MC/DC decision, even if it is nested in
+    /// one, so it always gets plain branch coverage.
+    pub(crate) fn visit_coverage_try_branch(
+        &mut self,
+        source_info: SourceInfo, // Source info of the whole `?` expression
+        continue_block: BasicBlock,
+        break_block: BasicBlock,
+    ) {
+        // Bail out if coverage is not enabled for this function.
+        let Some(coverage_info) = self.coverage_info.as_mut() else { return };
+
+        coverage_info.register_plain_branch(
+            &mut self.cfg,
+            source_info,
+            continue_block,
+            break_block,
+        );
+    }
+
     /// If branch coverage is enabled, inject marker statements into `true_block`
     /// and `false_block`, and record their IDs in the table of branches.
     ///
@@ -55,11 +55,12 @@ pub(crate) fn expr_into_dest(
             ExprKind::Block { block: ast_block } => {
                 this.ast_block(destination, block, ast_block, source_info)
             }
-            ExprKind::Match { scrutinee, ref arms, .. } => this.match_expr(
+            ExprKind::Match { scrutinee, ref arms, match_source } => this.match_expr(
                 destination,
                 block,
                 scrutinee,
                 arms,
+                match_source,
                 expr_span,
                 this.thir[scrutinee].span,
             ),
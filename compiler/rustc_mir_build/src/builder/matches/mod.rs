@@ -13,7 +13,7 @@
 use rustc_abi::VariantIdx;
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_data_structures::stack::ensure_sufficient_stack;
-use rustc_hir::{BindingMode, ByRef, LetStmt, LocalSource, Node};
+use rustc_hir::{BindingMode, ByRef, LetStmt, LocalSource, MatchSource, Node};
 use rustc_middle::bug;
 use rustc_middle::middle::region;
 use rustc_middle::mir::{self, *};
@@ -369,6 +369,7 @@ pub(crate) fn match_expr(
         mut block: BasicBlock,
         scrutinee_id: ExprId,
         arms: &[ArmId],
+        match_source: MatchSource,
         span: Span,
         scrutinee_span: Span,
     ) -> BlockAnd<()> {
@@ -399,6 +400,7 @@ pub(crate) fn match_expr(
             scrutinee_place,
             scrutinee_span,
             arms,
+            match_source,
             built_tree,
             self.source_info(span),
         )
@@ -432,9 +434,11 @@ fn lower_match_arms(
         scrutinee_place_builder: PlaceBuilder<'tcx>,
         scrutinee_span: Span,
         arms: &[ArmId],
+        match_source: MatchSource,
         built_match_tree: BuiltMatchTree<'tcx>,
         outer_source_info: SourceInfo,
     ) -> BlockAnd<()> {
+        let mut arm_start_blocks = Vec::with_capacity(arms.len());
         let arm_end_blocks: Vec<BasicBlock> = arms
             .iter()
             .map(|&arm| &self.thir[arm])
@@ -486,12 +490,21 @@ fn lower_match_arms(
                         this.source_scope = source_scope;
                     }
 
+                    arm_start_blocks.push(arm_block);
                     this.expr_into_dest(destination, arm_block, arm.body)
                 })
                 .into_block()
             })
             .collect();
 
+        // The `match` of a `?` expression has an arm for the output, and an arm
+        // that returns the residual.
+        if let MatchSource::TryDesugar(_) = match_source
+            && let [continue_block, break_block] = arm_start_blocks[..]
+        {
+            self.visit_coverage_try_branch(outer_source_info, continue_block, break_block);
+        }
+
         // all the arm blocks will rejoin here
         let end_block = self.cfg.start_new_block();
 
@@ -2365,7 +2378,11 @@ pub(crate) fn lower_let_expr(
         emit_storage_live: EmitStorageLive,
     ) -> BlockAnd<()> {
         let expr_span = self.thir[expr_id].span;
+        // Boolean expressions in the scrutinee are separate decisions from the
+        // `let` chain that the pattern is a condition of.
+        self.mcdc_increment_depth_if_enabled();
         let scrutinee = unpack!(block = self.lower_scrutinee(block, expr_id, expr_span));
+        self.mcdc_decrement_depth_if_enabled();
         let built_tree = self.lower_match_tree(
             block,
             expr_span,
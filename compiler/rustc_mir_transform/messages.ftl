@@ -36,6 +36,11 @@ mir_transform_force_inline_attr =
 mir_transform_force_inline_justification =
     `{$callee}` is required to be inlined to: {$sym}
 
+mir_transform_inline_profile_malformed = malformed line {$line} in MIR inlining profile `{$path}`
+    .help = each line must be `<count> <function path>` or `<count> <caller path> -> <callee path>`
+
+mir_transform_inline_profile_read = failed to read MIR inlining profile `{$path}`: {$err}
+
 mir_transform_must_not_suspend = {$pre}`{$def_path}`{$post} held across a suspend point, but should not be
     .label = the value is held across this suspend point
     .note = {$reason}
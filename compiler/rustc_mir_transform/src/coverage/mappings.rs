@@ -12,7 +12,7 @@
 use crate::coverage::ExtractedHirInfo;
 use crate::coverage::graph::{BasicCoverageBlock, CoverageGraph, START_BCB};
 use crate::coverage::spans::extract_refined_covspans;
-use crate::coverage::unexpand::unexpand_into_body_span;
+use crate::coverage::unexpand::unexpand_branch_span;
 use crate::errors::MCDCExceedsTestVectorLimit;
 
 /// Associates an ordinary executable code span with its corresponding BCB.
@@ -160,12 +160,7 @@ pub(super) fn extract_branch_pairs(
         .branch_spans
         .iter()
         .filter_map(|&BranchSpan { span: raw_span, true_marker, false_marker }| {
-            // For now, ignore any branch span that was introduced by
-            // expansion. This makes things like assert macros less noisy.
-            if !raw_span.ctxt().outer_expn_data().is_root() {
-                return None;
-            }
-            let span = unexpand_into_body_span(raw_span, hir_info.body_span)?;
+            let span = unexpand_branch_span(raw_span, hir_info.body_span)?;
 
             let bcb_from_marker = |marker: BlockMarkerId| graph.bcb_from_bb(block_markers[marker]?);
 
@@ -194,12 +189,7 @@ pub(super) fn extract_mcdc_mappings(
 
     let check_branch_bcb =
         |raw_span: Span, true_marker: BlockMarkerId, false_marker: BlockMarkerId| {
-            // For now, ignore any branch span that was introduced by
-            // expansion. This makes things like assert macros less noisy.
-            if !raw_span.ctxt().outer_expn_data().is_root() {
-                return None;
-            }
-            let span = unexpand_into_body_span(raw_span, body_span)?;
+            let span = unexpand_branch_span(raw_span, body_span)?;
 
             let true_bcb = bcb_from_marker(true_marker)?;
             let false_bcb = bcb_from_marker(false_marker)?;
@@ -238,18 +228,23 @@ pub(super) fn extract_mcdc_mappings(
         if branches.len() == 0 {
             return None;
         }
-        let decision_span = unexpand_into_body_span(decision.span, body_span)?;
+        let mut branch_mappings: Vec<_> = branches.into_iter().filter_map(to_mcdc_branch).collect();
+        // Decisions are reported where their conditions are, so a decision in
+        // a local macro is reported at the macro invocation. If the decision
+        // or any of its conditions can't be reported, degrade the conditions
+        // to plain branches.
+        let Some(decision_span) = unexpand_branch_span(decision.span, body_span)
+            .filter(|_| branch_mappings.len() == branches.len())
+        else {
+            mcdc_degraded_branches.extend(branch_mappings);
+            return None;
+        };
 
         let end_bcbs = decision
             .end_markers
             .iter()
             .map(|&marker| bcb_from_marker(marker))
             .collect::<Option<_>>()?;
-        let mut branch_mappings: Vec<_> = branches.into_iter().filter_map(to_mcdc_branch).collect();
-        if branch_mappings.len() != branches.len() {
-            mcdc_degraded_branches.extend(branch_mappings);
-            return None;
-        }
         let num_test_vectors = calc_test_vectors_index(&mut branch_mappings);
         let Some(bitmap_idx) = get_bitmap_idx(num_test_vectors) else {
             tcx.dcx().emit_warn(MCDCExceedsTestVectorLimit {
@@ -162,12 +162,9 @@ fn create_mappings(extracted_mappings: &ExtractedMappings) -> Vec<Mapping> {
     ));
 
     for (decision, branches) in mcdc_mappings {
-        // FIXME(#134497): Previously it was possible for some of these branch
-        // conversions to fail, in which case the remaining branches in the
-        // decision would be degraded to plain `MappingKind::Branch`.
-        // The changes in #134497 made that failure impossible, because the
-        // fallible step was deferred to codegen. But the corresponding code
-        // in codegen wasn't updated to detect the need for a degrade step.
+        // The conditions must directly follow their decision. If codegen can't
+        // map the span of the decision or of any of its conditions, it degrades
+        // the conditions to plain `MappingKind::Branch` (see #134497).
         let conditions = branches
             .into_iter()
             .map(
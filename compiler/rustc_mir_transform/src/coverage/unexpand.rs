@@ -1,11 +1,33 @@
-use rustc_span::{ExpnKind, Span};
+use rustc_span::{DesugaringKind, ExpnKind, MacroKind, Span};
 
-/// Walks through the expansion ancestors of `original_span` to find a span that
-/// is contained in `body_span` and has the same [syntax context] as `body_span`.
-pub(crate) fn unexpand_into_body_span(original_span: Span, body_span: Span) -> Option<Span> {
-    // Because we don't need to return any extra ancestor information,
-    // we can just delegate directly to `find_ancestor_inside_same_ctxt`.
-    original_span.find_ancestor_inside_same_ctxt(body_span)
+/// Finds the span that a branch, MC/DC condition or MC/DC decision with span
+/// `raw_span` should be reported at, if any.
+///
+/// Conditions written in the body are reported at their own span. Conditions
+/// introduced by the `?` desugaring, or written in a `macro_rules!` macro of
+/// the current crate, are reported at the enclosing `?` expression or macro
+/// invocation in the body. Conditions introduced by other expansions, such as
+/// the `!` that `assert!` wraps around its condition, would only be noise, so
+/// they are not reported.
+pub(crate) fn unexpand_branch_span(raw_span: Span, body_span: Span) -> Option<Span> {
+    let mut curr = raw_span;
+
+    while !body_span.contains(curr) || !curr.eq_ctxt(body_span) {
+        let expn_data = curr.ctxt().outer_expn_data();
+        let is_reported = match expn_data.kind {
+            ExpnKind::Desugaring(DesugaringKind::QuestionMark) => true,
+            ExpnKind::Macro(MacroKind::Bang, _) => {
+                expn_data.macro_def_id.is_some_and(|def_id| def_id.is_local())
+            }
+            _ => false,
+        };
+        if !is_reported {
+            return None;
+        }
+        curr = curr.parent_callsite()?;
+    }
+
+    Some(curr)
 }
 
 /// Walks through the expansion ancestors of `original_span` to find a span that
@@ -85,6 +85,13 @@ fn cross_crate_inlinable(tcx: TyCtxt<'_>, def_id: LocalDefId) -> bool {
         InliningThreshold::Never => return false,
     };
 
+    // Make hot functions available for inlining everywhere, and leave cold ones alone.
+    match inline::profile::function_hotness(tcx, def_id.to_def_id()) {
+        Some(ProfileHotness::Hot) => return true,
+        Some(ProfileHotness::Cold) => return false,
+        Some(ProfileHotness::Neutral) | None => {}
+    }
+
     let mir = tcx.optimized_mir(def_id);
     let mut checker =
         CostChecker { tcx, callee_body: mir, calls: 0, statements: 0, landing_pads: 0, resumes: 0 };
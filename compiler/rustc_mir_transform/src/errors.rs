@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use rustc_errors::codes::*;
 use rustc_errors::{Diag, LintDiagnostic};
 use rustc_macros::{Diagnostic, LintDiagnostic, Subdiagnostic};
@@ -66,6 +68,21 @@ pub(crate) struct UnknownPassName<'a> {
     pub(crate) name: &'a str,
 }
 
+#[derive(Diagnostic)]
+#[diag(mir_transform_inline_profile_read)]
+pub(crate) struct InlineProfileRead<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) err: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(mir_transform_inline_profile_malformed)]
+#[help]
+pub(crate) struct InlineProfileMalformed<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) line: usize,
+}
+
 pub(crate) struct AssertLint<P> {
     pub span: Span,
     pub assert_kind: AssertKind<P>,
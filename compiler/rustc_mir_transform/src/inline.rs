@@ -26,6 +26,7 @@
 use crate::{check_inline, util};
 
 pub(crate) mod cycle;
+pub(crate) mod profile;
 
 const HISTORY_DEPTH_LIMIT: usize = 20;
 const TOP_DOWN_DEPTH_LIMIT: usize = 5;
@@ -377,8 +378,19 @@ fn check_callee_mir_body(
             return Err("Not inlining multi-block body as we're past a depth limit");
         }
 
+        // Call sites in inlined bodies were, at profiling time, in the inlined function.
+        let profiled_caller = self.history.last().copied().unwrap_or(self.def_id);
+        let hotness = profile::call_hotness(tcx, profiled_caller, callsite.callee.def_id());
+        if hotness == Some(ProfileHotness::Cold)
+            && !matches!(callee_attrs.inline, InlineAttr::Always)
+        {
+            return Err("call is cold according to the profile");
+        }
+
         let mut threshold = if self.caller_is_inline_forwarder || self.past_depth_limit() {
             tcx.sess.opts.unstable_opts.inline_mir_forwarder_threshold.unwrap_or(30)
+        } else if hotness == Some(ProfileHotness::Hot) {
+            tcx.sess.opts.unstable_opts.inline_mir_hot_threshold.unwrap_or(250)
         } else if tcx.cross_crate_inlinable(callsite.callee.def_id()) {
             tcx.sess.opts.unstable_opts.inline_mir_hint_threshold.unwrap_or(100)
         } else {
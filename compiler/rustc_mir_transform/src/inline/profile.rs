@@ -0,0 +1,181 @@
+//! Execution counts from `-Z inline-mir-profile`, used to guide inlining.
+//!
+//! The profile is a text file. Each line is either `<count> <function>`, giving
+//! how many times a function was entered, or `<count> <caller> -> <callee>`, giving
+//! how many times one function called another. Blank lines and lines starting with
+//! `#` are ignored. Functions are written as crate-qualified paths, the way
+//! demanglers print symbol names without their hashes; generic arguments are
+//! ignored, so all instantiations of a generic function share one entry.
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{InlineProfile, ProfileHotness};
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::print::{with_crate_prefix, with_no_trimmed_paths, with_no_visible_paths};
+use rustc_span::Symbol;
+use tracing::debug;
+
+use crate::errors;
+
+/// Counts that make up this share (in millionths) of the total count are hot.
+/// Same as LLVM's default `-profile-summary-cutoff-hot`.
+const HOT_CUTOFF: u128 = 990_000;
+/// Counts outside of this share (in millionths) of the total count are cold.
+/// Same as LLVM's default `-profile-summary-cutoff-cold`.
+const COLD_CUTOFF: u128 = 999_999;
+
+pub(crate) fn inline_mir_profile(tcx: TyCtxt<'_>, (): ()) -> Option<InlineProfile> {
+    let path = tcx.sess.opts.unstable_opts.inline_mir_profile.as_deref()?;
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            tcx.dcx().emit_err(errors::InlineProfileRead { path, err });
+            return None;
+        }
+    };
+    // Rebuild when the profile changes.
+    tcx.sess.psess.file_depinfo.borrow_mut().insert(Symbol::intern(&path.to_string_lossy()));
+
+    let mut functions = FxIndexMap::<String, u64>::default();
+    let mut calls = FxIndexMap::<(String, String), u64>::default();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((count, key)) = line
+            .split_once(char::is_whitespace)
+            .and_then(|(count, key)| Some((count.parse::<u64>().ok()?, key.trim())))
+        else {
+            tcx.dcx().emit_err(errors::InlineProfileMalformed { path, line: index + 1 });
+            continue;
+        };
+        if let Some((caller, callee)) = key.split_once(" -> ") {
+            let key = (normalize_path(caller.trim()), normalize_path(callee.trim()));
+            *calls.entry(key).or_default() += count;
+        } else {
+            *functions.entry(normalize_path(key)).or_default() += count;
+        }
+    }
+
+    let counts: Vec<u64> = functions.values().chain(calls.values()).copied().collect();
+    let (hot_count, cold_count) = count_cutoffs(counts);
+    debug!(?hot_count, ?cold_count, "loaded MIR inlining profile");
+
+    Some(InlineProfile {
+        functions: functions.into_iter().collect(),
+        calls: calls.into_iter().collect(),
+        hot_count,
+        cold_count,
+    })
+}
+
+/// How hot calls from `caller` to `callee` are, according to `-Z inline-mir-profile`.
+/// Returns `None` if there is no profile or it has nothing to say about `callee`.
+pub(crate) fn call_hotness(
+    tcx: TyCtxt<'_>,
+    caller: DefId,
+    callee: DefId,
+) -> Option<ProfileHotness> {
+    let profile = tcx.inline_mir_profile(())?;
+    profile.call_hotness(&profile_path(tcx, caller), &profile_path(tcx, callee))
+}
+
+/// How hot `def_id` is, according to `-Z inline-mir-profile`.
+pub(crate) fn function_hotness(tcx: TyCtxt<'_>, def_id: DefId) -> Option<ProfileHotness> {
+    let profile = tcx.inline_mir_profile(())?;
+    profile.function_hotness(&profile_path(tcx, def_id))
+}
+
+/// The minimum counts of hot functions and the maximum counts of cold ones.
+fn count_cutoffs(mut counts: Vec<u64>) -> (u64, u64) {
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    let total: u128 = counts.iter().map(|&count| u128::from(count)).sum();
+    if total == 0 {
+        // Nothing ran: everything is cold and nothing is hot.
+        return (u64::MAX, 0);
+    }
+    let cutoff = |share: u128| {
+        let target = total * share / 1_000_000;
+        let mut sum = 0;
+        for &count in &counts {
+            sum += u128::from(count);
+            if sum >= target {
+                return count;
+            }
+        }
+        0
+    };
+    (cutoff(HOT_CUTOFF), cutoff(COLD_CUTOFF))
+}
+
+/// The path of `def_id` as it appears in the profile.
+fn profile_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let path = with_crate_prefix!(with_no_visible_paths!(with_no_trimmed_paths!(
+        tcx.def_path_str(def_id)
+    )));
+    let crate_name = tcx.crate_name(def_id.krate);
+
+    // Replace the `crate::` that local paths are printed with by the crate's name, so
+    // that the paths match between the crate that is profiled and its dependents.
+    let mut qualified = String::with_capacity(path.len());
+    let mut rest = path.as_str();
+    while let Some(pos) = rest.find("crate::") {
+        let (before, after) = rest.split_at(pos);
+        qualified.push_str(before);
+        if qualified.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            qualified.push_str("crate");
+        } else {
+            qualified.push_str(crate_name.as_str());
+        }
+        rest = &after["crate".len()..];
+    }
+    qualified.push_str(rest);
+
+    // Before Rust 2018, local paths aren't prefixed at all.
+    if def_id.is_local() && !tcx.sess.at_least_rust_2018() && !qualified.starts_with('<') {
+        qualified = format!("{crate_name}::{qualified}");
+    }
+
+    normalize_path(&qualified)
+}
+
+/// Removes generic arguments from `path`, and turns the `<Type>::method` that demanglers
+/// print for inherent methods into `Type::method`, which is how rustc prints them.
+fn normalize_path(path: &str) -> String {
+    let mut normalized = String::with_capacity(path.len());
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_args = c == '<'
+            && (normalized.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                || normalized.ends_with("::"));
+        if !starts_args {
+            normalized.push(c);
+            continue;
+        }
+        // Skip the arguments, including any `->` in function types within them.
+        let mut depth = 1;
+        while depth > 0 {
+            match chars.next() {
+                Some('<') => depth += 1,
+                Some('>') => depth -= 1,
+                Some('-') if chars.peek() == Some(&'>') => {
+                    chars.next();
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        if let Some(stripped) = normalized.strip_suffix("::") {
+            normalized.truncate(stripped.len());
+        }
+    }
+
+    if let Some(inner) = normalized.strip_prefix('<')
+        && let Some((self_ty, rest)) = inner.split_once(">::")
+        && !self_ty.contains(['<', ' '])
+    {
+        return format!("{self_ty}::{rest}");
+    }
+    normalized
+}
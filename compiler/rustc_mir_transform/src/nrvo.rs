@@ -1,12 +1,18 @@
 //! See the docs for [`RenameReturnPlace`].
 
+use either::{Left, Right};
+use rustc_abi::{BackendRepr, FieldIdx};
 use rustc_hir::Mutability;
 use rustc_index::bit_set::DenseBitSet;
 use rustc_middle::bug;
-use rustc_middle::mir::visit::{MutVisitor, NonUseContext, PlaceContext, Visitor};
-use rustc_middle::mir::{self, BasicBlock, Local, Location};
-use rustc_middle::ty::TyCtxt;
-use tracing::{debug, trace};
+use rustc_middle::mir::visit::{
+    MutVisitor, MutatingUseContext, NonMutatingUseContext, NonUseContext, PlaceContext, Visitor,
+};
+use rustc_middle::mir::{self, AggregateKind, Local, Location, Place};
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_mir_dataflow::Analysis;
+use rustc_mir_dataflow::impls::MaybeBorrowedLocals;
+use tracing::debug;
 
 /// This pass looks for MIR that always copies the same local into the return place and eliminates
 /// the copy by renaming all uses of that local to `_0`.
@@ -24,6 +30,19 @@
 /// }
 /// ```
 ///
+/// The local may also be returned as the only field of an aggregate, as in `Ok(buf)`. It is then
+/// renamed to that field of `_0`, and the aggregate is replaced by setting the discriminant of `_0`.
+///
+/// Renaming is only sound if the local and the return place never need to hold different values.
+/// The return place is never read before returning, so this is the case when the copies of the
+/// local are the only writes to the return place, and nothing modifies the local once it has been
+/// copied. If the local may be borrowed when it is copied, or is borrowed afterwards, it may also
+/// be modified through a pointer, so the pass then only allows statements that can't write through
+/// a pointer to follow the copy (#111005).
+///
+/// Values that are passed around in registers aren't copied with a `memcpy`, so this only renames
+/// locals whose layout keeps them in memory.
+///
 /// For now, this pass is very simple and only capable of eliminating a single copy. A more general
 /// version of copy propagation, such as the one based on non-overlapping live ranges in [#47954] and
 /// [#71003], could yield even more benefits.
@@ -34,42 +53,40 @@
 
 impl<'tcx> crate::MirPass<'tcx> for RenameReturnPlace {
     fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
-        // unsound: #111005
-        sess.mir_opt_level() > 0 && sess.opts.unstable_opts.unsound_mir_opts
+        sess.mir_opt_level() > 0
     }
 
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut mir::Body<'tcx>) {
         let def_id = body.source.def_id();
-        let Some(returned_local) = local_eligible_for_nrvo(body) else {
+        let Some(returned) = local_eligible_for_nrvo(tcx, body) else {
             debug!("`{:?}` was ineligible for NRVO", def_id);
             return;
         };
+        let returned_local = returned.local();
 
-        debug!(
-            "`{:?}` was eligible for NRVO, making {:?} the return place",
-            def_id, returned_local
-        );
+        debug!("`{:?}` was eligible for NRVO, making {:?} the return place", def_id, returned);
 
-        RenameToReturnPlace { tcx, to_rename: returned_local }.visit_body_preserves_cfg(body);
+        let Some((into, write)) = returned.rename(tcx, body) else {
+            bug!("NRVO target {returned:?} can't be renamed");
+        };
+        RenameToReturnPlace { tcx, returned, into, write }.visit_body_preserves_cfg(body);
 
         // Clean up the `NOP`s we inserted for statements made useless by our renaming.
         for block_data in body.basic_blocks.as_mut_preserves_cfg() {
             block_data.statements.retain(|stmt| stmt.kind != mir::StatementKind::Nop);
         }
 
-        // Overwrite the debuginfo of `_0` with that of the renamed local.
-        let (renamed_decl, ret_decl) =
-            body.local_decls.pick2_mut(returned_local, mir::RETURN_PLACE);
-
-        // Sometimes, the return place is assigned a local of a different but coercible type, for
-        // example `&mut T` instead of `&T`. Overwriting the `LocalInfo` for the return place means
-        // its type may no longer match the return type of its function. This doesn't cause a
-        // problem in codegen because these two types are layout-compatible, but may be unexpected.
-        debug!("_0: {:?} = {:?}: {:?}", ret_decl.ty, returned_local, renamed_decl.ty);
-        ret_decl.clone_from(renamed_decl);
+        // When `_0` takes the place of the renamed local, overwrite its debuginfo with that of the
+        // local.
+        if let Returned::Local(_) = returned {
+            let (renamed_decl, ret_decl) =
+                body.local_decls.pick2_mut(returned_local, mir::RETURN_PLACE);
+            debug!("_0: {:?} = {:?}: {:?}", ret_decl.ty, returned_local, renamed_decl.ty);
+            ret_decl.clone_from(renamed_decl);
+        }
 
         // The return place is always mutable.
-        ret_decl.mutability = Mutability::Mut;
+        body.local_decls[mir::RETURN_PLACE].mutability = Mutability::Mut;
     }
 
     fn is_required(&self) -> bool {
@@ -77,100 +94,227 @@ fn is_required(&self) -> bool {
     }
 }
 
-/// MIR that is eligible for the NRVO must fulfill two conditions:
+/// A write of a local to the return place.
+#[derive(Clone, Debug, PartialEq)]
+enum Returned<'tcx> {
+    /// `_0 = _1`
+    Local(Local),
+    /// `_0 = Ok(move _1)`, an aggregate of which the local is the only field.
+    Wrapped(Local, AggregateKind<'tcx>),
+}
+
+impl<'tcx> Returned<'tcx> {
+    fn local(&self) -> Local {
+        match *self {
+            Returned::Local(local) | Returned::Wrapped(local, _) => local,
+        }
+    }
+
+    /// Returns the place in `_0` that the local is renamed to, and the statement that replaces
+    /// the writes to the return place. Returns `None` if the local can't be renamed to a place
+    /// of its type.
+    fn rename(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        body: &mir::Body<'tcx>,
+    ) -> Option<(Place<'tcx>, mir::StatementKind<'tcx>)> {
+        let ret_ty = body.local_decls[mir::RETURN_PLACE].ty;
+        let (into, write, field_ty) = match self {
+            Returned::Local(_) => (Place::return_place(), mir::StatementKind::Nop, ret_ty),
+            Returned::Wrapped(_, AggregateKind::Tuple) => {
+                let ty::Tuple(fields) = ret_ty.kind() else { return None };
+                let field_ty = fields[0];
+                let into = tcx.mk_place_field(Place::return_place(), FieldIdx::ZERO, field_ty);
+                (into, mir::StatementKind::Nop, field_ty)
+            }
+            // Unions are left alone.
+            &Returned::Wrapped(_, AggregateKind::Adt(def_id, variant_index, args, _, None)) => {
+                let adt_def = tcx.adt_def(def_id);
+                let field_ty = adt_def.variant(variant_index).fields[FieldIdx::ZERO].ty(tcx, args);
+                let field_ty =
+                    tcx.try_normalize_erasing_regions(body.typing_env(tcx), field_ty).ok()?;
+                if adt_def.is_enum() {
+                    let variant =
+                        tcx.mk_place_downcast(Place::return_place(), adt_def, variant_index);
+                    let into = tcx.mk_place_field(variant, FieldIdx::ZERO, field_ty);
+                    let write = mir::StatementKind::SetDiscriminant {
+                        place: Box::new(Place::return_place()),
+                        variant_index,
+                    };
+                    (into, write, field_ty)
+                } else {
+                    let into = tcx.mk_place_field(Place::return_place(), FieldIdx::ZERO, field_ty);
+                    (into, mir::StatementKind::Nop, field_ty)
+                }
+            }
+            Returned::Wrapped(..) => return None,
+        };
+
+        // Renaming a local of a subtype would change the types that its uses see.
+        if body.local_decls[self.local()].ty != field_ty {
+            return None;
+        }
+        Some((into, write))
+    }
+}
+
+/// MIR that is eligible for the NRVO must fulfill these conditions:
 ///   1. The return place must not be read prior to the `Return` terminator.
-///   2. A simple assignment of a whole local to the return place (e.g., `_0 = _1`) must be the
-///      only definition of the return place reaching the `Return` terminator.
+///   2. The only writes to the return place must be the same simple assignment of a local of the
+///      same type (e.g., `_0 = _1`), or of an aggregate whose only field is that local (e.g.,
+///      `_0 = Ok(move _1)`).
+///   3. That local must not be modified after it has been copied to the return place, directly or,
+///      if it is borrowed, through a pointer.
 ///
-/// If the MIR fulfills both these conditions, this function returns the `Local` that is assigned
-/// to the return place along all possible paths through the control-flow graph.
-fn local_eligible_for_nrvo(body: &mir::Body<'_>) -> Option<Local> {
+/// If the MIR fulfills these conditions, this function returns the write to the return place.
+fn local_eligible_for_nrvo<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mir::Body<'tcx>,
+) -> Option<Returned<'tcx>> {
     if IsReturnPlaceRead::run(body) {
         return None;
     }
 
-    let mut copied_to_return_place = None;
-    for block in body.basic_blocks.indices() {
-        // Look for blocks with a `Return` terminator.
-        if !matches!(body[block].terminator().kind, mir::TerminatorKind::Return) {
-            continue;
-        }
+    let returned = FindReturnedLocal::run(body)?;
+    let returned_local = returned.local();
+    match body.local_kind(returned_local) {
+        // FIXME: Can we do this for arguments as well?
+        mir::LocalKind::Arg => return None,
 
-        // Look for an assignment of a single local to the return place prior to the `Return`.
-        let returned_local = find_local_assigned_to_return_place(block, body)?;
-        match body.local_kind(returned_local) {
-            // FIXME: Can we do this for arguments as well?
-            mir::LocalKind::Arg => return None,
+        mir::LocalKind::ReturnPointer => bug!("Return place was assigned to itself?"),
+        mir::LocalKind::Temp => {}
+    }
 
-            mir::LocalKind::ReturnPointer => bug!("Return place was assigned to itself?"),
-            mir::LocalKind::Temp => {}
-        }
+    returned.rename(tcx, body)?;
 
-        // If multiple different locals are copied to the return place. We can't pick a
-        // single one to rename.
-        if copied_to_return_place.is_some_and(|old| old != returned_local) {
-            return None;
-        }
+    let ty = body.local_decls[returned_local].ty;
+    if !is_in_memory(tcx, body, ty) {
+        return None;
+    }
 
-        copied_to_return_place = Some(returned_local);
+    if is_modified_after_copy(tcx, body, &returned) {
+        return None;
     }
 
-    copied_to_return_place
+    Some(returned)
 }
 
-fn find_local_assigned_to_return_place(start: BasicBlock, body: &mir::Body<'_>) -> Option<Local> {
-    let mut block = start;
-    let mut seen = DenseBitSet::new_empty(body.basic_blocks.len());
-
-    // Iterate as long as `block` has exactly one predecessor that we have not yet visited.
-    while seen.insert(block) {
-        trace!("Looking for assignments to `_0` in {:?}", block);
+/// Whether values of type `ty` are passed around in memory, and so copied with a `memcpy`.
+fn is_in_memory<'tcx>(tcx: TyCtxt<'tcx>, body: &mir::Body<'tcx>, ty: Ty<'tcx>) -> bool {
+    let typing_env = body.typing_env(tcx);
+    tcx.layout_of(typing_env.as_query_input(ty)).is_ok_and(|layout| {
+        matches!(layout.backend_repr, BackendRepr::Memory { .. }) && !layout.is_zst()
+    })
+}
 
-        let local = body[block].statements.iter().rev().find_map(as_local_assigned_to_return_place);
-        if local.is_some() {
-            return local;
+/// Whether the local of `returned` may be modified after it has been copied to the return place,
+/// on any path through the control-flow graph.
+fn is_modified_after_copy<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mir::Body<'tcx>,
+    returned: &Returned<'tcx>,
+) -> bool {
+    let local = returned.local();
+    let mut maybe_borrowed =
+        MaybeBorrowedLocals.iterate_to_fixpoint(tcx, body, None).into_results_cursor(body);
+
+    // The locations that may be reached after `local` has been copied to the return place, and
+    // whether it may be borrowed by then.
+    let mut after_copy = Vec::new();
+    let mut borrowed = false;
+    let mut copied_on_entry = DenseBitSet::new_empty(body.basic_blocks.len());
+    let mut worklist: Vec<_> = body.basic_blocks.indices().collect();
+    while let Some(block) = worklist.pop() {
+        let data = &body.basic_blocks[block];
+        let mut copied = copied_on_entry.contains(block);
+        for (statement_index, stmt) in data.statements.iter().enumerate() {
+            let location = Location { block, statement_index };
+            if as_returned_local(stmt).as_ref() == Some(returned) {
+                maybe_borrowed.seek_before_primary_effect(location);
+                borrowed |= maybe_borrowed.get().contains(local);
+                copied = true;
+            } else if copied {
+                after_copy.push(location);
+            }
+        }
+        if !copied {
+            continue;
+        }
+        after_copy.push(body.terminator_loc(block));
+        for successor in data.terminator().successors() {
+            if copied_on_entry.insert(successor) {
+                worklist.push(successor);
+            }
         }
+    }
 
-        match body.basic_blocks.predecessors()[block].as_slice() {
-            &[pred] => block = pred,
-            _ => return None,
+    let mut visitor = IsLocalModified { local, borrowed, modified: false };
+    // A borrow taken after the copy can be written through later on.
+    if !borrowed {
+        let mut is_borrowed = IsLocalBorrowed { local, borrowed: false };
+        for &location in &after_copy {
+            match body.stmt_at(location) {
+                Left(stmt) => is_borrowed.visit_statement(stmt, location),
+                Right(terminator) => is_borrowed.visit_terminator(terminator, location),
+            }
+        }
+        visitor.borrowed = is_borrowed.borrowed;
+    }
+    for &location in &after_copy {
+        match body.stmt_at(location) {
+            Left(stmt) => visitor.visit_statement(stmt, location),
+            Right(terminator) => visitor.visit_terminator(terminator, location),
+        }
+        if visitor.modified {
+            return true;
         }
     }
 
-    None
+    false
 }
 
-// If this statement is an assignment of an unprojected local to the return place,
-// return that local.
-fn as_local_assigned_to_return_place(stmt: &mir::Statement<'_>) -> Option<Local> {
-    if let mir::StatementKind::Assign(box (lhs, rhs)) = &stmt.kind {
-        if lhs.as_local() == Some(mir::RETURN_PLACE) {
-            if let mir::Rvalue::Use(mir::Operand::Copy(rhs) | mir::Operand::Move(rhs)) = rhs {
-                return rhs.as_local();
-            }
+/// If this statement writes an unprojected local to the return place, returns the write.
+fn as_returned_local<'tcx>(stmt: &mir::Statement<'tcx>) -> Option<Returned<'tcx>> {
+    let mir::StatementKind::Assign(box (lhs, rhs)) = &stmt.kind else { return None };
+    if lhs.as_local() != Some(mir::RETURN_PLACE) {
+        return None;
+    }
+    match rhs {
+        mir::Rvalue::Use(mir::Operand::Copy(rhs) | mir::Operand::Move(rhs)) => {
+            rhs.as_local().map(Returned::Local)
         }
+        mir::Rvalue::Aggregate(kind, fields) => match &fields.raw[..] {
+            [mir::Operand::Copy(rhs) | mir::Operand::Move(rhs)] => {
+                rhs.as_local().map(|local| Returned::Wrapped(local, (**kind).clone()))
+            }
+            _ => None,
+        },
+        _ => None,
     }
-
-    None
 }
 
 struct RenameToReturnPlace<'tcx> {
-    to_rename: Local,
+    returned: Returned<'tcx>,
+    /// The place in `_0` that the local is renamed to.
+    into: Place<'tcx>,
+    /// The statement that replaces the writes to the return place.
+    write: mir::StatementKind<'tcx>,
     tcx: TyCtxt<'tcx>,
 }
 
-/// Replaces all uses of `self.to_rename` with `_0`.
+/// Replaces all uses of the returned local with `self.into`.
 impl<'tcx> MutVisitor<'tcx> for RenameToReturnPlace<'tcx> {
     fn tcx(&self) -> TyCtxt<'tcx> {
         self.tcx
     }
 
     fn visit_statement(&mut self, stmt: &mut mir::Statement<'tcx>, loc: Location) {
-        // Remove assignments of the local being replaced to the return place, since it is now the
-        // return place:
+        // Replace writes of the local being replaced to the return place, since it is now part of
+        // the return place:
         //     _0 = _1
-        if as_local_assigned_to_return_place(stmt) == Some(self.to_rename) {
-            stmt.kind = mir::StatementKind::Nop;
+        //     _0 = Ok(move _1)
+        if as_returned_local(stmt).as_ref() == Some(&self.returned) {
+            stmt.kind = self.write.clone();
             return;
         }
 
@@ -179,7 +323,7 @@ fn visit_statement(&mut self, stmt: &mut mir::Statement<'tcx>, loc: Location) {
         if let mir::StatementKind::StorageLive(local) | mir::StatementKind::StorageDead(local) =
             stmt.kind
         {
-            if local == self.to_rename {
+            if local == self.returned.local() {
                 stmt.kind = mir::StatementKind::Nop;
                 return;
             }
@@ -197,11 +341,21 @@ fn visit_terminator(&mut self, terminator: &mut mir::Terminator<'tcx>, loc: Loca
         self.super_terminator(terminator, loc);
     }
 
+    fn visit_place(&mut self, place: &mut Place<'tcx>, ctxt: PlaceContext, loc: Location) {
+        if place.local == self.returned.local() {
+            *place = self.into.project_deeper(place.projection, self.tcx);
+            return;
+        }
+
+        self.super_place(place, ctxt, loc)
+    }
+
     fn visit_local(&mut self, l: &mut Local, ctxt: PlaceContext, _: Location) {
         if *l == mir::RETURN_PLACE {
             assert_eq!(ctxt, PlaceContext::NonUse(NonUseContext::VarDebugInfo));
-        } else if *l == self.to_rename {
-            *l = mir::RETURN_PLACE;
+        } else if *l == self.returned.local() {
+            // Only places can use the returned local, which has a type that can't index.
+            bug!("use of {l:?} outside of a place in {ctxt:?}");
         }
     }
 }
@@ -232,3 +386,134 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, loc: Location
         self.super_terminator(terminator, loc);
     }
 }
+
+/// Finds the local that all writes to the return place copy, if there is one.
+struct FindReturnedLocal<'tcx> {
+    returned: Option<Returned<'tcx>>,
+    eligible: bool,
+}
+
+impl<'tcx> FindReturnedLocal<'tcx> {
+    fn run(body: &mir::Body<'tcx>) -> Option<Returned<'tcx>> {
+        let mut vis = FindReturnedLocal { returned: None, eligible: true };
+        vis.visit_body(body);
+        if vis.eligible { vis.returned } else { None }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for FindReturnedLocal<'tcx> {
+    fn visit_statement(&mut self, stmt: &mir::Statement<'tcx>, loc: Location) {
+        if let Some(returned) = as_returned_local(stmt) {
+            // If multiple different locals, or the same local in different ways, are written to
+            // the return place, we can't pick a single place to rename it to.
+            if self.returned.as_ref().is_some_and(|old| *old != returned) {
+                self.eligible = false;
+            }
+            self.returned = Some(returned);
+            return;
+        }
+
+        self.super_statement(stmt, loc);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, loc: Location) {
+        // Ignore the implicit "use" of the return place in a `Return` statement.
+        if let mir::TerminatorKind::Return = terminator.kind {
+            return;
+        }
+
+        self.super_terminator(terminator, loc);
+    }
+
+    fn visit_local(&mut self, l: Local, ctxt: PlaceContext, _: Location) {
+        if l == mir::RETURN_PLACE && ctxt.is_mutating_use() {
+            self.eligible = false;
+        }
+    }
+}
+
+/// Checks whether statements and terminators borrow `local`.
+struct IsLocalBorrowed {
+    local: Local,
+    borrowed: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for IsLocalBorrowed {
+    fn visit_place(&mut self, place: &Place<'tcx>, ctxt: PlaceContext, loc: Location) {
+        if place.local == self.local
+            && !place.is_indirect()
+            && matches!(
+                ctxt,
+                PlaceContext::MutatingUse(
+                    MutatingUseContext::Borrow | MutatingUseContext::RawBorrow
+                ) | PlaceContext::NonMutatingUse(
+                    NonMutatingUseContext::SharedBorrow
+                        | NonMutatingUseContext::FakeBorrow
+                        | NonMutatingUseContext::RawBorrow
+                )
+            )
+        {
+            self.borrowed = true;
+        }
+
+        self.super_place(place, ctxt, loc);
+    }
+}
+
+/// Checks whether statements and terminators may modify `local`.
+struct IsLocalModified {
+    local: Local,
+    /// If `local` is borrowed, anything that writes through a pointer may modify it.
+    borrowed: bool,
+    modified: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for IsLocalModified {
+    fn visit_statement(&mut self, stmt: &mir::Statement<'tcx>, loc: Location) {
+        // Intrinsics like `copy_nonoverlapping` write through their pointer operands.
+        if self.borrowed && matches!(stmt.kind, mir::StatementKind::Intrinsic(_)) {
+            self.modified = true;
+        }
+
+        self.super_statement(stmt, loc);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, loc: Location) {
+        match terminator.kind {
+            // Ignore the implicit "use" of the return place in a `Return` statement.
+            mir::TerminatorKind::Return => return,
+            mir::TerminatorKind::Call { .. }
+            | mir::TerminatorKind::TailCall { .. }
+            | mir::TerminatorKind::InlineAsm { .. }
+            | mir::TerminatorKind::Drop { .. }
+            | mir::TerminatorKind::Yield { .. }
+                if self.borrowed =>
+            {
+                self.modified = true;
+            }
+            _ => {}
+        }
+
+        self.super_terminator(terminator, loc);
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, ctxt: PlaceContext, loc: Location) {
+        // Assignments, `SetDiscriminant`, `Deinit` and any other mutation of a place behind a
+        // pointer may be one of `local`.
+        if self.borrowed && place.is_indirect() && ctxt.is_mutating_use() {
+            self.modified = true;
+        }
+
+        self.super_place(place, ctxt, loc);
+    }
+
+    fn visit_local(&mut self, l: Local, ctxt: PlaceContext, _: Location) {
+        // The callee may modify the arguments that are moved into a call in place.
+        if l == self.local
+            && (ctxt.is_mutating_use()
+                || ctxt == PlaceContext::NonMutatingUse(NonMutatingUseContext::Move))
+        {
+            self.modified = true;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Implements `-Z print-enum-layout-hints`, which reports the enums that one
+//! variant makes much larger than the others, and estimates how many bytes
+//! moving the fields of that variant into a `Box` would save across the
+//! monomorphized functions and statics of the crate.
+
+use rustc_abi::Size;
+use rustc_data_structures::fx::{FxIndexMap, FxIndexSet};
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_span::Symbol;
+
+struct EnumUses {
+    size: Size,
+    boxed_size: Size,
+    variant: Symbol,
+    locals: usize,
+    functions: usize,
+    statics: usize,
+}
+
+impl EnumUses {
+    fn saved_bytes(&self) -> u64 {
+        let uses = (self.locals + self.statics) as u64;
+        uses * (self.size - self.boxed_size).bytes()
+    }
+}
+
+struct EnumLayoutHints<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    enums: FxIndexMap<Ty<'tcx>, EnumUses>,
+}
+
+impl<'tcx> EnumLayoutHints<'tcx> {
+    /// The uses of `ty` so far, or `None` if it isn't an enum with an oversized variant.
+    fn uses(&mut self, ty: Ty<'tcx>) -> Option<&mut EnumUses> {
+        let ty::Adt(adt_def, _) = *ty.kind() else { return None };
+        if !adt_def.is_enum() {
+            return None;
+        }
+        if !self.enums.contains_key(&ty) {
+            let typing_env = ty::TypingEnv::fully_monomorphized();
+            let layout = self.tcx.layout_of(typing_env.as_query_input(ty)).ok()?;
+            let oversized = layout.oversized_variant(&self.tcx)?;
+            self.enums.insert(
+                ty,
+                EnumUses {
+                    size: layout.size,
+                    boxed_size: oversized.boxed_size,
+                    variant: adt_def.variant(oversized.variant).name,
+                    locals: 0,
+                    functions: 0,
+                    statics: 0,
+                },
+            );
+        }
+        self.enums.get_mut(&ty)
+    }
+}
+
+pub(crate) fn print_enum_layout_hints<'tcx>(tcx: TyCtxt<'tcx>, items: &[MonoItem<'tcx>]) {
+    let typing_env = ty::TypingEnv::fully_monomorphized();
+    let mut hints = EnumLayoutHints { tcx, enums: Default::default() };
+
+    for item in items {
+        match *item {
+            MonoItem::Fn(instance) => {
+                let body = tcx.instance_mir(instance.def);
+                let mut used = FxIndexSet::default();
+                for decl in body.local_decls.iter() {
+                    let ty = instance.instantiate_mir_and_normalize_erasing_regions(
+                        tcx,
+                        typing_env,
+                        ty::EarlyBinder::bind(decl.ty),
+                    );
+                    if let Some(uses) = hints.uses(ty) {
+                        uses.locals += 1;
+                        used.insert(ty);
+                    }
+                }
+                for ty in used {
+                    hints.uses(ty).unwrap().functions += 1;
+                }
+            }
+            MonoItem::Static(def_id) => {
+                let ty = tcx.type_of(def_id).instantiate_identity();
+                if let Some(uses) = hints.uses(tcx.normalize_erasing_regions(typing_env, ty)) {
+                    uses.statics += 1;
+                }
+            }
+            MonoItem::GlobalAsm(_) => {}
+        }
+    }
+
+    let mut enums: Vec<_> = hints.enums.into_iter().collect();
+    enums.sort_by_key(|(_, uses)| std::cmp::Reverse(uses.saved_bytes()));
+    for (ty, uses) in enums {
+        let ty = with_no_trimmed_paths!(ty.to_string());
+        println!(
+            "print-enum-layout-hint enum `{ty}`: {} bytes, {} bytes with the fields of `{}` boxed",
+            uses.size.bytes(),
+            uses.boxed_size.bytes(),
+            uses.variant,
+        );
+        println!(
+            "print-enum-layout-hint     used by {} locals in {} functions and by {} statics, \
+            boxing saves about {} bytes",
+            uses.locals,
+            uses.functions,
+            uses.statics,
+            uses.saved_bytes(),
+        );
+    }
+}
@@ -16,6 +16,7 @@
 use rustc_span::ErrorGuaranteed;
 
 mod collector;
+mod enum_layout_hints;
 mod errors;
 mod mono_checks;
 mod partitioning;
@@ -124,6 +124,7 @@
 use tracing::debug;
 
 use crate::collector::{self, MonoItemCollectionStrategy, UsageMap};
+use crate::enum_layout_hints;
 use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
 
 struct PartitioningCx<'a, 'tcx> {
@@ -172,8 +173,9 @@ fn partition<'tcx, I>(
     }
 
     // Make as many symbols "internal" as possible, so LLVM has more freedom to
-    // optimize.
-    if !tcx.sess.link_dead_code() {
+    // optimize. The patches of -Zhot-patch link against every symbol of the
+    // executable, so nothing can be internalized then.
+    if !tcx.sess.link_dead_code() && !tcx.sess.opts.unstable_opts.hot_patch {
         let _prof_timer = tcx.prof.generic_activity("cgu_partitioning_internalize_symbols");
         internalize_symbols(cx, &mut codegen_units, internalization_candidates);
 
@@ -756,6 +758,12 @@ fn mono_item_linkage_and_visibility<'tcx>(
         can_export_generics,
         always_export_generics,
     );
+    if tcx.sess.opts.unstable_opts.hot_patch {
+        // Patches are linked against the executable, so they must be able to
+        // see all of its symbols, and must be able to replace all of its functions.
+        *can_be_internalized = false;
+        return (Linkage::External, Visibility::Default);
+    }
     (Linkage::External, vis)
 }
 
@@ -1153,6 +1161,10 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> MonoItemPartitio
     // (codegen relies on this and ICEs will happen if this is violated.)
     tcx.dcx().abort_if_errors();
 
+    if tcx.sess.opts.unstable_opts.print_enum_layout_hints {
+        enum_layout_hints::print_enum_layout_hints(tcx, &items);
+    }
+
     let (codegen_units, _) = tcx.sess.time("partition_and_assert_distinct_symbols", || {
         sync::join(
             || {
@@ -822,6 +822,12 @@ passes_unused_multiple =
 passes_unused_no_lints_note =
     attribute `{$name}` without any lints has no effect
 
+passes_unused_pub_item = public {$descr} `{$name}` is never used
+    .note = neither this crate nor {$crates ->
+        [one] the crate
+        *[other] any of the {$crates} crates
+    } given to `-Z unused-pub-items` use it
+
 passes_unused_var_assigned_only = variable `{$name}` is assigned to, but never used
     .note = consider using `_{$name}` instead
 
@@ -0,0 +1,645 @@
+//! Summarizing the public API of a crate, and comparing the summaries of two
+//! versions of it for `-Z api-diff`.
+//!
+//! The summary lists every item that can be named from outside the crate,
+//! under each of the public paths it can be named by, along with the public
+//! items of its inherent impls and the items of public traits. Trait impls are
+//! listed as `impl Trait for Type` when both the trait and the type are
+//! public. Everything is rendered as text with full paths, starting with the
+//! crate name even for the local crate, so that the summary of the crate being
+//! compiled can be compared with the summary of an earlier version of it that
+//! is loaded from its metadata as a dependency of another session.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def::{CtorKind, DefKind, Res};
+use rustc_hir::def_id::{CRATE_DEF_INDEX, CrateNum, DefId};
+use rustc_middle::ty::print::{
+    PrintTraitRefExt as _, with_local_crate_name, with_no_trimmed_paths, with_no_visible_paths,
+};
+use rustc_middle::ty::{self, GenericArgs, GenericParamDefKind, ImplPolarity, TyCtxt, TypingMode};
+use rustc_span::{kw, sym};
+use rustc_trait_selection::infer::{InferCtxtExt, TyCtxtInferExt};
+use rustc_trait_selection::traits::query::evaluate_obligation::InferCtxtExt as _;
+
+/// The public API of a crate, as compared by `-Z api-diff`.
+#[derive(Clone, Debug)]
+pub struct ApiSummary {
+    /// Sorted by path and kind.
+    pub items: Vec<ApiItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiItem {
+    /// The public path of the item, e.g. `mycrate::io::Reader::read`, or
+    /// `impl Trait for Type` for a trait impl.
+    pub path: String,
+    /// What the item is, e.g. `fn`, `struct` or `trait impl`. The items of
+    /// traits are either `required` or `provided`, e.g. `provided method`.
+    pub kind: String,
+    /// The generic parameters of the item, with their defaults, e.g. `'a`,
+    /// `T = u32` or `const N: usize`.
+    pub generics: Vec<String>,
+    /// The where clauses of the item, including the supertraits of a trait,
+    /// sorted.
+    pub predicates: Vec<String>,
+    /// The rest of what its users may depend on: the signature of a function,
+    /// the type of a const, the impl header of an inherent impl item, etc.
+    pub signature: String,
+    /// The variants of an ADT, of which structs and unions have one.
+    pub variants: Vec<ApiVariant>,
+    /// Whether the ADT is an enum that is `#[non_exhaustive]`.
+    pub non_exhaustive: bool,
+    /// The auto traits that a type implements when its type parameters do.
+    pub auto_traits: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiVariant {
+    /// Empty for structs and unions.
+    pub name: String,
+    pub ctor_kind: Option<CtorKind>,
+    /// The public fields, as their name, or their index for tuple fields, and
+    /// their type.
+    pub fields: Vec<(String, String)>,
+    /// Whether some fields are private or the variant is `#[non_exhaustive]`,
+    /// which both keep the fields from being exhaustively matched and the
+    /// value from being built.
+    pub hidden_fields: bool,
+}
+
+impl ApiItem {
+    /// Renders everything about the item that its users may depend on.
+    pub fn render(&self) -> String {
+        let mut rendered = String::new();
+        if self.kind == "trait impl" {
+            rendered.push_str("impl");
+        }
+        if !self.generics.is_empty() {
+            write!(rendered, "<{}>", self.generics.join(", ")).unwrap();
+        }
+        if !self.signature.is_empty() {
+            write!(rendered, " {}", self.signature).unwrap();
+        }
+        let mut variants: Vec<_> = self.variants.iter().map(ApiVariant::render).collect();
+        if self.non_exhaustive {
+            variants.push("..".to_owned());
+        }
+        if !variants.is_empty() {
+            write!(rendered, " {}", variants.join(" | ")).unwrap();
+        }
+        if !self.predicates.is_empty() {
+            write!(rendered, " where {}", self.predicates.join(", ")).unwrap();
+        }
+        rendered.trim().to_owned()
+    }
+}
+
+impl ApiVariant {
+    fn render(&self) -> String {
+        let mut fields: Vec<_> = self
+            .fields
+            .iter()
+            .map(|(name, ty)| match self.ctor_kind {
+                Some(CtorKind::Fn) => ty.clone(),
+                _ => format!("{name}: {ty}"),
+            })
+            .collect();
+        if self.hidden_fields {
+            fields.push("..".to_owned());
+        }
+        let fields = match self.ctor_kind {
+            Some(CtorKind::Fn) => format!("({})", fields.join(", ")),
+            Some(CtorKind::Const) => String::new(),
+            None => format!("{{ {} }}", fields.join(", ")),
+        };
+        format!("{}{fields}", self.name)
+    }
+}
+
+/// Summarizes the public API of `krate`, which may be the local crate or a
+/// dependency.
+pub fn summarize(tcx: TyCtxt<'_>, krate: CrateNum) -> ApiSummary {
+    let mut collector = ApiCollector { tcx, krate, items: vec![], visited: Default::default() };
+    with_no_trimmed_paths!(with_no_visible_paths!(with_local_crate_name!({
+        let root = DefId { krate, index: CRATE_DEF_INDEX };
+        collector.visit_module(root, tcx.crate_name(krate).as_str());
+        collector.visit_trait_impls();
+    })));
+
+    let mut items = collector.items;
+    items.sort_by(|a, b| (&a.path, &a.kind).cmp(&(&b.path, &b.kind)));
+    items.dedup();
+    ApiSummary { items }
+}
+
+struct ApiCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    krate: CrateNum,
+    items: Vec<ApiItem>,
+    /// The modules and other items of the crate that were reached through
+    /// public paths.
+    visited: FxHashSet<DefId>,
+}
+
+impl<'tcx> ApiCollector<'tcx> {
+    fn visit_module(&mut self, module: DefId, path: &str) {
+        if !self.visited.insert(module) {
+            return;
+        }
+        let children = match module.as_local() {
+            Some(module) => self.tcx.module_children_local(module),
+            None => self.tcx.module_children(module),
+        };
+        for child in children {
+            if !child.vis.is_public() || child.ident.name == kw::Underscore {
+                continue;
+            }
+            let Res::Def(kind, def_id) = child.res else {
+                continue;
+            };
+            if self.tcx.is_doc_hidden(def_id) {
+                continue;
+            }
+            self.visit_item(kind, def_id, format!("{path}::{}", child.ident));
+        }
+    }
+
+    fn visit_item(&mut self, kind: DefKind, def_id: DefId, path: String) {
+        match kind {
+            // Covered by the fields of their struct or variant.
+            DefKind::Ctor(..) => return,
+            // The public API of other crates is theirs to summarize.
+            DefKind::Mod if def_id.krate == self.krate => self.visit_module(def_id, &path),
+            DefKind::Trait => self.visit_trait_items(def_id, &path),
+            DefKind::Struct | DefKind::Enum | DefKind::Union => {
+                self.visit_inherent_impls(def_id, &path)
+            }
+            _ => {}
+        }
+        self.visited.insert(def_id);
+
+        let mut item = self.item(kind, def_id, path, self.tcx.def_descr(def_id).to_owned());
+        if let DefKind::Struct | DefKind::Enum | DefKind::Union = kind {
+            item.auto_traits = self.auto_traits(def_id);
+        }
+        self.items.push(item);
+    }
+
+    fn visit_trait_items(&mut self, trait_def_id: DefId, path: &str) {
+        for &def_id in self.tcx.associated_item_def_ids(trait_def_id) {
+            let item = self.tcx.associated_item(def_id);
+            if item.is_impl_trait_in_trait() || self.tcx.is_doc_hidden(def_id) {
+                continue;
+            }
+            let requirement =
+                if item.defaultness(self.tcx).has_value() { "provided" } else { "required" };
+            self.items.push(self.item(
+                self.tcx.def_kind(def_id),
+                def_id,
+                format!("{path}::{}", item.name),
+                format!("{requirement} {}", item.descr()),
+            ));
+        }
+    }
+
+    fn visit_inherent_impls(&mut self, ty_def_id: DefId, path: &str) {
+        for &impl_def_id in self.tcx.inherent_impls(ty_def_id) {
+            let header = self.generics(impl_def_id);
+            let predicates = self.predicates(impl_def_id);
+            for &def_id in self.tcx.associated_item_def_ids(impl_def_id) {
+                if !self.tcx.visibility(def_id).is_public() || self.tcx.is_doc_hidden(def_id) {
+                    continue;
+                }
+                let assoc = self.tcx.associated_item(def_id);
+                let mut item = self.item(
+                    self.tcx.def_kind(def_id),
+                    def_id,
+                    format!("{path}::{}", assoc.name),
+                    assoc.descr().to_owned(),
+                );
+                // Changes to the impl header don't count as relaxing the item.
+                if !header.is_empty() || !predicates.is_empty() {
+                    let mut impl_header = "impl".to_owned();
+                    if !header.is_empty() {
+                        write!(impl_header, "<{}>", header.join(", ")).unwrap();
+                    }
+                    if !predicates.is_empty() {
+                        write!(impl_header, " where {}", predicates.join(", ")).unwrap();
+                    }
+                    item.signature = format!("{impl_header}: {}", item.signature);
+                }
+                self.items.push(item);
+            }
+        }
+    }
+
+    fn visit_trait_impls(&mut self) {
+        let tcx = self.tcx;
+        for &impl_def_id in tcx.trait_impls_in_crate(self.krate) {
+            let trait_ref = tcx.impl_trait_ref(impl_def_id).unwrap().instantiate_identity();
+            if !self.is_public(trait_ref.def_id) {
+                continue;
+            }
+            let self_ty = trait_ref.self_ty();
+            if let Some(adt) = self_ty.ty_adt_def()
+                && !self.is_public(adt.did())
+            {
+                continue;
+            }
+            if tcx.is_doc_hidden(impl_def_id) {
+                continue;
+            }
+            let polarity = match tcx.impl_polarity(impl_def_id) {
+                ImplPolarity::Negative => "!",
+                ImplPolarity::Positive | ImplPolarity::Reservation => "",
+            };
+            self.items.push(ApiItem {
+                path: format!("impl {polarity}{} for {self_ty}", trait_ref.print_only_trait_path()),
+                kind: "trait impl".to_owned(),
+                generics: self.generics(impl_def_id),
+                predicates: self.predicates(impl_def_id),
+                signature: String::new(),
+                variants: vec![],
+                non_exhaustive: false,
+                auto_traits: vec![],
+            });
+        }
+    }
+
+    /// Whether the item can be named from outside of its crate. The items of
+    /// the summarized crate must have been reached through its public paths.
+    fn is_public(&self, def_id: DefId) -> bool {
+        if def_id.krate == self.krate {
+            self.visited.contains(&def_id)
+        } else {
+            self.tcx.visibility(def_id).is_public()
+        }
+    }
+
+    fn item(&self, kind: DefKind, def_id: DefId, path: String, descr: String) -> ApiItem {
+        let tcx = self.tcx;
+        let mut signature = String::new();
+        let mut variants = vec![];
+        let mut non_exhaustive = false;
+        match kind {
+            DefKind::Fn | DefKind::AssocFn => {
+                if tcx.is_const_fn(def_id) {
+                    signature.push_str("const ");
+                }
+                write!(signature, "{}", tcx.fn_sig(def_id).instantiate_identity()).unwrap();
+            }
+            DefKind::Const | DefKind::AssocConst => {
+                write!(signature, ": {}", tcx.type_of(def_id).instantiate_identity()).unwrap();
+            }
+            DefKind::Static { mutability, .. } => {
+                let ty = tcx.type_of(def_id).instantiate_identity();
+                write!(signature, ": {}{ty}", mutability.prefix_str()).unwrap();
+            }
+            DefKind::TyAlias => {
+                write!(signature, "= {}", tcx.type_of(def_id).instantiate_identity()).unwrap();
+            }
+            DefKind::AssocTy if tcx.defaultness(def_id).has_value() => {
+                write!(signature, "= {}", tcx.type_of(def_id).instantiate_identity()).unwrap();
+            }
+            DefKind::Struct | DefKind::Enum | DefKind::Union => {
+                let adt = tcx.adt_def(def_id);
+                variants =
+                    adt.variants().iter().map(|variant| self.variant(adt, variant)).collect();
+                non_exhaustive = adt.is_variant_list_non_exhaustive();
+            }
+            DefKind::Trait => {
+                signature.push_str(tcx.trait_def(def_id).safety.prefix_str());
+                if tcx.is_dyn_compatible(def_id) {
+                    signature.push_str(" dyn-compatible");
+                }
+            }
+            _ => {}
+        }
+        ApiItem {
+            path,
+            kind: descr,
+            generics: self.generics(def_id),
+            predicates: self.predicates(def_id),
+            signature: signature.trim().to_owned(),
+            variants,
+            non_exhaustive,
+            auto_traits: vec![],
+        }
+    }
+
+    /// Renders the generic parameters of the item, e.g. `'a`, `T = u32` and
+    /// `const N: usize`.
+    fn generics(&self, def_id: DefId) -> Vec<String> {
+        if !has_generics(self.tcx.def_kind(def_id)) {
+            return vec![];
+        }
+        self.tcx
+            .generics_of(def_id)
+            .own_params
+            .iter()
+            .filter(|param| param.name != kw::SelfUpper)
+            .map(|param| {
+                let mut rendered = match param.kind {
+                    GenericParamDefKind::Const { .. } => {
+                        let ty = self.tcx.type_of(param.def_id).instantiate_identity();
+                        format!("const {}: {ty}", param.name)
+                    }
+                    GenericParamDefKind::Lifetime | GenericParamDefKind::Type { .. } => {
+                        param.name.to_string()
+                    }
+                };
+                if let Some(default) = param.default_value(self.tcx) {
+                    write!(rendered, " = {}", default.instantiate_identity()).unwrap();
+                }
+                rendered
+            })
+            .collect()
+    }
+
+    /// Renders the where clauses of the item, including the supertraits of
+    /// a trait, in a stable order.
+    fn predicates(&self, def_id: DefId) -> Vec<String> {
+        let tcx = self.tcx;
+        if !has_generics(tcx.def_kind(def_id)) {
+            return vec![];
+        }
+        let mut predicates: Vec<_> = tcx
+            .explicit_predicates_of(def_id)
+            .predicates
+            .iter()
+            .map(|(clause, _)| clause.to_string())
+            .collect();
+        if tcx.def_kind(def_id) == DefKind::Trait {
+            predicates.extend(
+                tcx.explicit_super_predicates_of(def_id)
+                    .iter_identity_copied()
+                    .map(|(clause, _)| clause.to_string()),
+            );
+        }
+        predicates.sort();
+        predicates.dedup();
+        predicates
+    }
+
+    fn variant(&self, adt: ty::AdtDef<'tcx>, variant: &ty::VariantDef) -> ApiVariant {
+        let fields: Vec<_> = variant
+            .fields
+            .iter_enumerated()
+            .filter(|(_, field)| field.vis.is_public())
+            .map(|(index, field)| {
+                let ty = self.tcx.type_of(field.did).instantiate_identity().to_string();
+                match variant.ctor_kind() {
+                    Some(CtorKind::Fn) => (index.as_usize().to_string(), ty),
+                    _ => (field.name.to_string(), ty),
+                }
+            })
+            .collect();
+        ApiVariant {
+            name: if adt.is_enum() { variant.name.to_string() } else { String::new() },
+            ctor_kind: variant.ctor_kind(),
+            hidden_fields: variant.is_field_list_non_exhaustive()
+                || fields.len() < variant.fields.len(),
+            fields,
+        }
+    }
+
+    /// Returns the auto traits that the ADT implements when its type
+    /// parameters implement all of them.
+    fn auto_traits(&self, def_id: DefId) -> Vec<String> {
+        let tcx = self.tcx;
+        let args = GenericArgs::for_item(tcx, def_id, |param, _| match param.kind {
+            GenericParamDefKind::Lifetime => tcx.lifetimes.re_static.into(),
+            GenericParamDefKind::Type { .. } => tcx.types.unit.into(),
+            GenericParamDefKind::Const { .. } => tcx.mk_param_from_def(param),
+        });
+        let ty = tcx.type_of(def_id).instantiate(tcx, args);
+        let infcx = tcx.infer_ctxt().build(TypingMode::non_body_analysis());
+        [
+            tcx.get_diagnostic_item(sym::Send),
+            tcx.get_diagnostic_item(sym::Sync),
+            tcx.lang_items().unpin_trait(),
+            tcx.get_diagnostic_item(sym::unwind_safe_trait),
+            tcx.get_diagnostic_item(sym::ref_unwind_safe_trait),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|&trait_def_id| {
+            infcx
+                .type_implements_trait(trait_def_id, [ty], ty::ParamEnv::empty())
+                .must_apply_modulo_regions()
+        })
+        .map(|trait_def_id| tcx.item_name(trait_def_id).to_string())
+        .collect()
+    }
+}
+
+fn has_generics(kind: DefKind) -> bool {
+    matches!(
+        kind,
+        DefKind::Fn
+            | DefKind::AssocFn
+            | DefKind::Const
+            | DefKind::AssocConst
+            | DefKind::TyAlias
+            | DefKind::AssocTy
+            | DefKind::Struct
+            | DefKind::Enum
+            | DefKind::Union
+            | DefKind::Trait
+            | DefKind::TraitAlias
+            | DefKind::Impl { .. }
+    )
+}
+
+/// The part of the version of a crate that a change to its API requires
+/// bumping, following the SemVer conventions of [RFC 1105].
+///
+/// [RFC 1105]: https://rust-lang.github.io/rfcs/1105-api-evolution.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Patch => "patch",
+            Severity::Minor => "minor",
+            Severity::Major => "major",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiChange<'a> {
+    pub severity: Severity,
+    /// What changed, e.g. `added`, `removed` or `signature changed`.
+    pub change: &'static str,
+    pub path: &'a str,
+    pub kind: &'a str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Compares the public API of two versions of a crate. The changes are
+/// sorted by path.
+pub fn diff<'a>(old: &'a ApiSummary, new: &'a ApiSummary) -> Vec<ApiChange<'a>> {
+    // Trait items are matched regardless of whether they are required.
+    let key = |item: &'a ApiItem| (item.path.as_str(), trait_item_kind(&item.kind));
+    let old_items: BTreeMap<_, _> = old.items.iter().map(|item| (key(item), item)).collect();
+    let new_items: BTreeMap<_, _> = new.items.iter().map(|item| (key(item), item)).collect();
+
+    let mut changes = vec![];
+    for (key, &old_item) in &old_items {
+        let Some(&new_item) = new_items.get(key) else {
+            changes.push(ApiChange {
+                severity: Severity::Major,
+                change: "removed",
+                path: &old_item.path,
+                kind: &old_item.kind,
+                old: Some(old_item.render()),
+                new: None,
+            });
+            continue;
+        };
+
+        let change = |severity, change, old, new| ApiChange {
+            severity,
+            change,
+            path: &new_item.path,
+            kind: &new_item.kind,
+            old,
+            new,
+        };
+        if old_item.kind != new_item.kind {
+            // Only trait items change kind while keeping their key.
+            let severity = if new_item.kind.starts_with("required ") {
+                Severity::Major
+            } else {
+                Severity::Minor
+            };
+            changes.push(change(
+                severity,
+                "kind changed",
+                Some(old_item.kind.clone()),
+                Some(new_item.kind.clone()),
+            ));
+        }
+        let (old_rendered, new_rendered) = (old_item.render(), new_item.render());
+        if old_rendered != new_rendered {
+            let severity =
+                if is_compatible(old_item, new_item) { Severity::Minor } else { Severity::Major };
+            changes.push(change(
+                severity,
+                "signature changed",
+                Some(old_rendered),
+                Some(new_rendered),
+            ));
+        }
+        for auto_trait in &old_item.auto_traits {
+            if !new_item.auto_traits.contains(auto_trait) {
+                changes.push(change(
+                    Severity::Major,
+                    "auto trait removed",
+                    Some(auto_trait.clone()),
+                    None,
+                ));
+            }
+        }
+        for auto_trait in &new_item.auto_traits {
+            if !old_item.auto_traits.contains(auto_trait) {
+                changes.push(change(
+                    Severity::Minor,
+                    "auto trait added",
+                    None,
+                    Some(auto_trait.clone()),
+                ));
+            }
+        }
+    }
+
+    for (key, &new_item) in &new_items {
+        if old_items.contains_key(key) {
+            continue;
+        }
+        // Implementors of a trait have to define the items it newly requires.
+        let severity =
+            if new_item.kind.starts_with("required ") { Severity::Major } else { Severity::Minor };
+        changes.push(ApiChange {
+            severity,
+            change: "added",
+            path: &new_item.path,
+            kind: &new_item.kind,
+            old: None,
+            new: Some(new_item.render()),
+        });
+    }
+
+    changes.sort_by(|a, b| (a.path, a.kind).cmp(&(b.path, b.kind)));
+    changes
+}
+
+/// The kind of a trait item regardless of whether it is required.
+fn trait_item_kind(kind: &str) -> &str {
+    kind.strip_prefix("required ").or(kind.strip_prefix("provided ")).unwrap_or(kind)
+}
+
+/// Whether every use of the old version of the item keeps compiling with the
+/// new one, as the changes that RFC 1105 calls minor do: adding generic
+/// parameters with defaults, relaxing where clauses, adding public fields to a
+/// struct or variant that already has hidden ones, and adding variants to a
+/// `#[non_exhaustive]` enum.
+fn is_compatible(old: &ApiItem, new: &ApiItem) -> bool {
+    if old.signature != new.signature {
+        return false;
+    }
+
+    // Inference falls back to the defaults of the new parameters wherever the
+    // old ones were enough.
+    let generics_extended = new.generics.len() >= old.generics.len()
+        && new.generics[..old.generics.len()] == old.generics[..]
+        && new.generics[old.generics.len()..].iter().all(|param| param.contains(" = "));
+    if !generics_extended {
+        return false;
+    }
+
+    // The where clauses of traits and of their items are also relied upon by
+    // the code that uses their implementations, and required by those that
+    // implement them, so they can't be relaxed.
+    let is_trait = old.kind == "trait" || trait_item_kind(&old.kind) != old.kind;
+    let predicates_relaxed = if is_trait {
+        new.predicates == old.predicates
+    } else {
+        new.predicates.iter().all(|predicate| old.predicates.contains(predicate))
+    };
+    if !predicates_relaxed {
+        return false;
+    }
+
+    // Matches on an enum that isn't `#[non_exhaustive]` must cover every
+    // variant, so it can't become `#[non_exhaustive]` or gain variants.
+    if new.non_exhaustive && !old.non_exhaustive {
+        return false;
+    }
+    if new.variants.len() > old.variants.len() && !old.non_exhaustive {
+        return false;
+    }
+    old.variants.iter().all(|old_variant| {
+        new.variants.iter().any(|new_variant| {
+            new_variant.name == old_variant.name
+                && new_variant.ctor_kind == old_variant.ctor_kind
+                && if old_variant.hidden_fields {
+                    // Neither patterns nor struct expressions can list every
+                    // field, so more of them can be made public.
+                    old_variant.fields.iter().all(|field| new_variant.fields.contains(field))
+                } else {
+                    !new_variant.hidden_fields && new_variant.fields == old_variant.fields
+                }
+        })
+    })
+}
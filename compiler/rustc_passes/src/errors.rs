@@ -1923,3 +1923,12 @@ pub(crate) struct UnsupportedAttributesInWhere {
     #[primary_span]
     pub span: MultiSpan,
 }
+
+#[derive(LintDiagnostic)]
+#[diag(passes_unused_pub_item)]
+#[note]
+pub(crate) struct UnusedPubItem {
+    pub descr: &'static str,
+    pub name: Symbol,
+    pub crates: usize,
+}
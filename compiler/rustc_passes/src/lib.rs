@@ -18,6 +18,7 @@
 use rustc_middle::query::Providers;
 
 pub mod abi_test;
+pub mod api_summary;
 mod check_attr;
 pub mod dead;
 mod debugger_visualizer;
@@ -35,6 +36,7 @@
 mod naked_functions;
 mod reachable;
 pub mod stability;
+pub mod unused_pub_items;
 mod upvars;
 mod weak_lang_items;
 
@@ -53,5 +55,6 @@ pub fn provide(providers: &mut Providers) {
     liveness::provide(providers);
     reachable::provide(providers);
     stability::provide(providers);
+    unused_pub_items::provide(providers);
     upvars::provide(providers);
 }
@@ -0,0 +1,199 @@
+//! Finding the public items that no crate uses, for `-Z unused-pub-items`.
+//!
+//! The dead code lint only sees the uses within the crate, so it has to assume
+//! that other crates use whatever is public. With `-Z record-uses`, the
+//! metadata of a crate records which items of other crates it uses. Given the
+//! metadata of the crates that depend on this one, e.g. of every other crate
+//! in a workspace, `-Z unused-pub-items` reports the public items that neither
+//! they nor this crate use, under the `dead_code` lint.
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::unord::UnordSet;
+use rustc_hir::def::{CtorOf, DefKind, Res};
+use rustc_hir::def_id::{DefId, DefPathHash, LocalDefId};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{self as hir, HirId};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::query::Providers;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::lint::builtin::DEAD_CODE;
+use rustc_span::{Span, sym};
+
+use crate::errors::UnusedPubItem;
+
+struct UsedItemsVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    maybe_typeck_results: Option<&'tcx ty::TypeckResults<'tcx>>,
+    used_items: UnordSet<DefId>,
+}
+
+impl<'tcx> UsedItemsVisitor<'tcx> {
+    fn handle_res(&mut self, res: Res) {
+        match res {
+            Res::Def(DefKind::Ctor(CtorOf::Variant, ..), ctor_def_id) => {
+                let variant_id = self.tcx.parent(ctor_def_id);
+                self.used_items.insert(self.tcx.parent(variant_id));
+                self.used_items.insert(variant_id);
+                self.used_items.insert(ctor_def_id);
+            }
+            Res::Def(DefKind::Ctor(CtorOf::Struct, ..), ctor_def_id) => {
+                self.used_items.insert(self.tcx.parent(ctor_def_id));
+                self.used_items.insert(ctor_def_id);
+            }
+            Res::Def(DefKind::Variant, variant_id) => {
+                self.used_items.insert(self.tcx.parent(variant_id));
+                self.used_items.insert(variant_id);
+            }
+            Res::Def(_, def_id) => {
+                self.used_items.insert(def_id);
+            }
+            Res::SelfTyParam { trait_ } => {
+                self.used_items.insert(trait_);
+            }
+            Res::SelfTyAlias { alias_to, .. } => {
+                self.used_items.insert(alias_to);
+            }
+            Res::PrimTy(..)
+            | Res::SelfCtor(..)
+            | Res::Local(..)
+            | Res::ToolMod
+            | Res::NonMacroAttr(..)
+            | Res::Err => {}
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for UsedItemsVisitor<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn maybe_tcx(&mut self) -> Self::MaybeTyCtxt {
+        self.tcx
+    }
+
+    fn visit_nested_body(&mut self, body: hir::BodyId) {
+        let old_maybe_typeck_results =
+            self.maybe_typeck_results.replace(self.tcx.typeck_body(body));
+        let body = self.tcx.hir_body(body);
+        self.visit_body(body);
+        self.maybe_typeck_results = old_maybe_typeck_results;
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        // Implementing something for a type, e.g. deriving `Debug`, isn't a
+        // use of the type.
+        if let hir::ItemKind::Impl(impl_) = item.kind {
+            self.visit_generics(impl_.generics);
+            if let Some(trait_ref) = &impl_.of_trait {
+                self.visit_trait_ref(trait_ref);
+            }
+            return;
+        }
+        intravisit::walk_item(self, item);
+    }
+
+    fn visit_impl_item(&mut self, impl_item: &'tcx hir::ImplItem<'tcx>) {
+        // Implementing a trait uses the items it declares.
+        if let Some(trait_item) = self.tcx.associated_item(impl_item.owner_id).trait_item_def_id {
+            self.used_items.insert(trait_item);
+        }
+        intravisit::walk_impl_item(self, impl_item);
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        // Method calls and overloaded operators.
+        if let Some(def_id) =
+            self.maybe_typeck_results.and_then(|results| results.type_dependent_def_id(expr.hir_id))
+        {
+            self.used_items.insert(def_id);
+        }
+        intravisit::walk_expr(self, expr);
+    }
+
+    fn visit_qpath(&mut self, qpath: &'tcx hir::QPath<'tcx>, id: HirId, span: Span) {
+        // Resolved paths are handled by `visit_path`, but paths relative to a
+        // type are only resolved by type checking.
+        if let hir::QPath::TypeRelative(..) = qpath
+            && let Some(typeck_results) = self.maybe_typeck_results
+        {
+            self.handle_res(typeck_results.qpath_res(qpath, id));
+        }
+        intravisit::walk_qpath(self, qpath, id, span);
+    }
+
+    fn visit_path(&mut self, path: &hir::Path<'tcx>, _: HirId) {
+        self.handle_res(path.res);
+        intravisit::walk_path(self, path);
+    }
+}
+
+fn used_items(tcx: TyCtxt<'_>, (): ()) -> UnordSet<DefId> {
+    let mut visitor =
+        UsedItemsVisitor { tcx, maybe_typeck_results: None, used_items: Default::default() };
+    tcx.hir_visit_all_item_likes_in_crate(&mut visitor);
+    visitor.used_items
+}
+
+/// Whether the item is used by something other than Rust code, which makes it
+/// live regardless of what uses it.
+fn has_external_uses(tcx: TyCtxt<'_>, def_id: LocalDefId) -> bool {
+    tcx.has_attr(def_id, sym::lang)
+        || tcx.entry_fn(()).is_some_and(|(entry, _)| entry == def_id.to_def_id())
+        || tcx.def_kind(def_id).has_codegen_attrs()
+            && tcx.codegen_fn_attrs(def_id).contains_extern_indicator()
+}
+
+/// Warns about the public items of the crate that neither the crate itself nor
+/// any of the `crates` crates that use `used_items` use.
+pub fn check_unused_pub_items(tcx: TyCtxt<'_>, used_items: &FxHashSet<DefPathHash>, crates: usize) {
+    let local_uses = tcx.used_items(());
+    let effective_visibilities = tcx.effective_visibilities(());
+    let crate_items = tcx.hir_crate_items(());
+
+    let free_items = crate_items.free_items().map(|item| item.owner_id.def_id);
+    // The items of trait impls are used through the trait.
+    let inherent_impl_items = crate_items
+        .impl_items()
+        .map(|item| item.owner_id.def_id)
+        .filter(|&def_id| tcx.trait_id_of_impl(tcx.local_parent(def_id).to_def_id()).is_none());
+
+    for def_id in free_items.chain(inherent_impl_items) {
+        if !matches!(
+            tcx.def_kind(def_id),
+            DefKind::Fn
+                | DefKind::AssocFn
+                | DefKind::Const
+                | DefKind::AssocConst
+                | DefKind::Static { .. }
+                | DefKind::Struct
+                | DefKind::Enum
+                | DefKind::Union
+                | DefKind::Trait
+                | DefKind::TraitAlias
+                | DefKind::TyAlias
+        ) {
+            continue;
+        }
+        if !effective_visibilities.is_exported(def_id)
+            || local_uses.contains(&def_id.to_def_id())
+            || used_items.contains(&tcx.def_path_hash(def_id.to_def_id()))
+            || has_external_uses(tcx, def_id)
+        {
+            continue;
+        }
+
+        tcx.emit_node_span_lint(
+            DEAD_CODE,
+            tcx.local_def_id_to_hir_id(def_id),
+            tcx.def_ident_span(def_id).unwrap_or_else(|| tcx.def_span(def_id)),
+            UnusedPubItem {
+                descr: tcx.def_descr(def_id.to_def_id()),
+                name: tcx.item_name(def_id.to_def_id()),
+                crates,
+            },
+        );
+    }
+}
+
+pub(crate) fn provide(providers: &mut Providers) {
+    providers.used_items = used_items;
+}
@@ -36,6 +36,25 @@ pub fn test(&self, node: &DepNode) -> bool {
     }
 }
 
+/// Why the results of the previous session could not be reused, see
+/// [`DepGraph::explain_red_nodes`](super::DepGraph::explain_red_nodes).
+#[derive(Debug, Default)]
+pub struct RedNodeExplanation {
+    /// The red nodes whose red dependencies, if any, are all inputs. These are
+    /// the changes that the other red nodes follow from.
+    pub causes: Vec<RedNodeCause>,
+    /// The red nodes of the output kind, with the indices of the `causes` that
+    /// they could not be marked green because of.
+    pub invalidated: Vec<(DepNode, Vec<usize>)>,
+}
+
+#[derive(Debug)]
+pub struct RedNodeCause {
+    pub node: DepNode,
+    /// Whether the node is an input, i.e. `eval_always`.
+    pub is_input: bool,
+}
+
 /// A filter like `F -> G` where `F` and `G` are valid dep-node
 /// filters. This can be used to test the source/target independently.
 pub struct EdgeFilter {
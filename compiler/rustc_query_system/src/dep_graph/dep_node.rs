@@ -129,14 +129,14 @@ pub fn construct<Tcx, Key>(tcx: Tcx, kind: DepKind, arg: &Key) -> DepNode
         let hash = arg.to_fingerprint(tcx);
         let dep_node = DepNode { kind, hash: hash.into() };
 
-        #[cfg(debug_assertions)]
+        // This is on the hot path of every query, so look at the options
+        // before the fingerprint style.
+        let opts = &tcx.sess().opts.unstable_opts;
+        if (opts.incremental_explain.is_some()
+            || (cfg!(debug_assertions) && (opts.incremental_info || opts.query_dep_graph)))
+            && !tcx.fingerprint_style(kind).reconstructible()
         {
-            if !tcx.fingerprint_style(kind).reconstructible()
-                && (tcx.sess().opts.unstable_opts.incremental_info
-                    || tcx.sess().opts.unstable_opts.query_dep_graph)
-            {
-                tcx.dep_graph().register_dep_node_debug_str(dep_node, || arg.to_debug_str(tcx));
-            }
+            tcx.dep_graph().register_dep_node_debug_str(dep_node, || arg.to_debug_str(tcx));
         }
 
         dep_node
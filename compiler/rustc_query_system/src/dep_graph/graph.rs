@@ -22,6 +22,7 @@
 #[cfg(debug_assertions)]
 use {super::debug::EdgeFilter, std::env};
 
+use super::debug::{DepNodeFilter, RedNodeCause, RedNodeExplanation};
 use super::query::DepGraphQuery;
 use super::serialized::{GraphEncoder, SerializedDepGraph, SerializedDepNodeIndex};
 use super::{DepContext, DepKind, DepNode, Deps, HasDepContext, WorkProductId};
@@ -766,13 +767,13 @@ pub fn debug_was_loaded_from_disk(&self, dep_node: DepNode) -> bool {
         self.data.as_ref().unwrap().debug_loaded_from_disk.lock().contains(&dep_node)
     }
 
-    #[cfg(debug_assertions)]
     #[inline(always)]
     pub(crate) fn register_dep_node_debug_str<F>(&self, dep_node: DepNode, debug_str_gen: F)
     where
         F: FnOnce() -> String,
     {
-        let dep_node_debug = &self.data.as_ref().unwrap().dep_node_debug;
+        let Some(data) = &self.data else { return };
+        let dep_node_debug = &data.dep_node_debug;
 
         if dep_node_debug.borrow().contains_key(&dep_node) {
             return;
@@ -1012,6 +1013,72 @@ pub fn exec_cache_promotions<Tcx: DepContext>(&self, tcx: Tcx) {
         }
     }
 
+    /// Explains which nodes of the previous session turned red, and which
+    /// nodes of `output_kind` could not be reused because of them, for
+    /// `-Z incremental-explain`. Only the causes that match `filter` are
+    /// reported.
+    ///
+    /// Must be called once all nodes that will be marked have been.
+    pub fn explain_red_nodes<Tcx: DepContext<Deps = D>>(
+        &self,
+        tcx: Tcx,
+        output_kind: DepKind,
+        filter: &DepNodeFilter,
+    ) -> RedNodeExplanation {
+        let mut explanation = RedNodeExplanation::default();
+        let Some(data) = &self.data else { return explanation };
+        let is_red = |index| matches!(data.colors.get(index), Some(DepNodeColor::Red));
+
+        let mut cause_indices = FxHashMap::default();
+        for index in data.colors.values.indices() {
+            if !is_red(index) {
+                continue;
+            }
+            let is_cause = data.previous.edge_targets_from(index).all(|dep_index| {
+                !is_red(dep_index) || tcx.is_eval_always(data.prev_node_of(dep_index).kind)
+            });
+            let node = data.prev_node_of(index);
+            if is_cause && filter.test(&node) {
+                cause_indices.insert(index, explanation.causes.len());
+                let is_input = tcx.is_eval_always(node.kind);
+                explanation.causes.push(RedNodeCause { node, is_input });
+            }
+        }
+
+        for index in data.colors.values.indices() {
+            let node = data.prev_node_of(index);
+            if node.kind != output_kind || !is_red(index) {
+                continue;
+            }
+
+            // The causes are found among the dependencies that couldn't be
+            // marked green, whether they turned red or were never marked.
+            let mut causes = Vec::new();
+            let mut visited = FxHashSet::default();
+            let mut stack = vec![index];
+            while let Some(index) = stack.pop() {
+                if !visited.insert(index) {
+                    continue;
+                }
+                if let Some(&cause) = cause_indices.get(&index) {
+                    causes.push(cause);
+                }
+                stack.extend(
+                    data.previous
+                        .edge_targets_from(index)
+                        .filter(|&dep_index| !data.is_index_green(dep_index)),
+                );
+            }
+
+            if !causes.is_empty() || filter.accepts_all() {
+                causes.sort_unstable();
+                explanation.invalidated.push((node, causes));
+            }
+        }
+
+        explanation
+    }
+
     pub fn print_incremental_info(&self) {
         if let Some(data) = &self.data {
             data.current.encoder.print_incremental_info(
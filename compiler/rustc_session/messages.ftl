@@ -40,9 +40,19 @@ session_function_return_thunk_extern_requires_non_large_code_model = `-Zfunction
 
 session_hexadecimal_float_literal_not_supported = hexadecimal float literal is not supported
 
+session_hot_patch_incompatible = `-Zhot-patch` is incompatible with `{$flag}`
+
+session_hot_patch_requires_incremental = `-Zhot-patch` requires incremental compilation
+    .help = enable it with `-Cincremental=<dir>`
+
+session_hot_patch_unsupported_target = `-Zhot-patch` is not supported for target {$target_triple}
+
 session_incompatible_linker_flavor = linker flavor `{$flavor}` is incompatible with the current target
     .note = compatible flavors are: {$compatible_list}
 
+session_incremental_not_relocatable = `-Zincremental-relocatable` has no effect because the working directory is not remapped
+    .help = remap it with `--remap-path-prefix={$working_dir}=<path>` or `-Zremap-cwd-prefix=<path>`
+
 session_instrumentation_not_supported = {$us} instrumentation is not supported for this target
 
 session_int_literal_too_large = integer literal is too large
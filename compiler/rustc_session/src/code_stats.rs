@@ -4,6 +4,7 @@
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::Lock;
 use rustc_span::Symbol;
+use serde::Serialize;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -59,6 +60,19 @@ pub enum DataTypeKind {
     Coroutine,
 }
 
+/// The largest niche of a type, i.e. the invalid values of a scalar inside it
+/// that enclosing enums can use to store their discriminant.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// The valid values of the scalar, as a wrapping range.
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+    /// How many invalid values are available.
+    pub available: u128,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TypeSizeInfo {
     pub kind: DataTypeKind,
@@ -67,9 +81,35 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub opt_niche: Option<NicheInfo>,
+    /// In declaration order, except for coroutines whose variants are sorted
+    /// according to their yield points.
     pub variants: Vec<VariantInfo>,
 }
 
+impl TypeSizeInfo {
+    fn struct_like(&self) -> bool {
+        match self.kind {
+            DataTypeKind::Struct | DataTypeKind::Closure => true,
+            DataTypeKind::Enum | DataTypeKind::Union | DataTypeKind::Coroutine => false,
+        }
+    }
+
+    /// Returns the variants in the order they are printed by `-Zprint-type-sizes`.
+    fn variants_by_size(&self) -> Vec<&VariantInfo> {
+        let mut variants: Vec<_> = self.variants.iter().collect();
+        // Sort variants so the largest ones are shown first. A stable sort is
+        // used here so that source code order is preserved for all variants
+        // that have the same size.
+        // Except for Coroutines, whose variants are already sorted according to
+        // their yield points in `variant_info_for_coroutine`.
+        if self.kind != DataTypeKind::Coroutine {
+            variants.sort_by_key(|info| cmp::Reverse(info.size));
+        }
+        variants
+    }
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
@@ -84,16 +124,9 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
-        mut variants: Vec<VariantInfo>,
+        opt_niche: Option<NicheInfo>,
+        variants: Vec<VariantInfo>,
     ) {
-        // Sort variants so the largest ones are shown first. A stable sort is
-        // used here so that source code order is preserved for all variants
-        // that have the same size.
-        // Except for Coroutines, whose variants are already sorted according to
-        // their yield points in `variant_info_for_coroutine`.
-        if kind != DataTypeKind::Coroutine {
-            variants.sort_by_key(|info| cmp::Reverse(info.size));
-        }
         let info = TypeSizeInfo {
             kind,
             type_description: type_desc.to_string(),
@@ -101,6 +134,7 @@ pub fn record_type_size<S: ToString>(
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            opt_niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
@@ -117,7 +151,7 @@ pub fn print_type_sizes(&self) {
         sorted.sort_by_key(|info| (cmp::Reverse(info.overall_size), &info.type_description));
 
         for info in sorted {
-            let TypeSizeInfo { type_description, overall_size, align, kind, variants, .. } = info;
+            let TypeSizeInfo { type_description, overall_size, align, .. } = info;
             println!(
                 "print-type-size type: `{type_description}`: {overall_size} bytes, alignment: {align} bytes"
             );
@@ -136,11 +170,8 @@ pub fn print_type_sizes(&self) {
             // to reflect the presence of the discriminant.
             let mut max_variant_size = discr_size;
 
-            let struct_like = match kind {
-                DataTypeKind::Struct | DataTypeKind::Closure => true,
-                DataTypeKind::Enum | DataTypeKind::Union | DataTypeKind::Coroutine => false,
-            };
-            for (i, variant_info) in variants.into_iter().enumerate() {
+            let struct_like = info.struct_like();
+            for (i, variant_info) in info.variants_by_size().into_iter().enumerate() {
                 let VariantInfo { ref name, kind: _, align: _, size, ref fields } = *variant_info;
                 let indent = if !struct_like {
                     let name = match name.as_ref() {
@@ -209,4 +240,143 @@ pub fn print_type_sizes(&self) {
             }
         }
     }
+
+    /// Renders the recorded layouts as JSON, for `--print=type-layouts`.
+    ///
+    /// Only types whose description starts with one of `filters` are included,
+    /// unless `filters` is empty. Types are sorted by description and variants
+    /// are kept in declaration order, so that reports of two builds can be
+    /// diffed line by line.
+    pub fn type_layouts_json(&self, filters: &[String]) -> String {
+        #[derive(Serialize)]
+        struct Report<'a> {
+            version: u32,
+            types: Vec<TypeLayout<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct TypeLayout<'a> {
+            name: &'a str,
+            kind: &'static str,
+            size: u64,
+            align: u64,
+            packed: bool,
+            discriminant_size: Option<u64>,
+            niche: Option<NicheInfo>,
+            variants: Vec<VariantLayout>,
+            end_padding: u64,
+        }
+
+        #[derive(Serialize)]
+        struct VariantLayout {
+            name: Option<String>,
+            size: u64,
+            /// Whether `size` is only a lower bound, because the variant is unsized.
+            size_is_min: bool,
+            align: u64,
+            fields: Vec<FieldLayout>,
+            padding: Vec<Padding>,
+        }
+
+        #[derive(Serialize)]
+        struct FieldLayout {
+            name: String,
+            kind: String,
+            offset: u64,
+            size: u64,
+            align: u64,
+            #[serde(rename = "type")]
+            type_name: Option<String>,
+        }
+
+        #[derive(Serialize)]
+        struct Padding {
+            offset: u64,
+            size: u64,
+        }
+
+        let type_sizes = self.type_sizes.borrow();
+        // We sort below, so the initial order does not matter.
+        #[allow(rustc::potential_query_instability)]
+        let mut types: Vec<_> = type_sizes
+            .iter()
+            .filter(|info| {
+                filters.is_empty()
+                    || filters.iter().any(|filter| info.type_description.starts_with(&**filter))
+            })
+            .collect();
+        types.sort_by(|a, b| a.type_description.cmp(&b.type_description));
+
+        let types = types
+            .into_iter()
+            .map(|info| {
+                let discr_size = info.opt_discr_size.unwrap_or(0);
+                let mut max_variant_size = discr_size;
+                let variants = info
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        max_variant_size = cmp::max(max_variant_size, variant.size);
+
+                        // Same as in `print_type_sizes`: fields by increasing
+                        // offset, with zero-sized fields first.
+                        let mut fields = variant.fields.clone();
+                        fields.sort_by_key(|f| (f.offset, f.size));
+                        let mut padding = vec![];
+                        let mut min_offset = discr_size;
+                        for field in &fields {
+                            if field.offset > min_offset {
+                                padding.push(Padding {
+                                    offset: min_offset,
+                                    size: field.offset - min_offset,
+                                });
+                            }
+                            min_offset = cmp::max(min_offset, field.offset + field.size);
+                        }
+
+                        VariantLayout {
+                            name: variant.name.map(|name| name.to_string()),
+                            size: variant.size,
+                            size_is_min: variant.kind == SizeKind::Min,
+                            align: variant.align,
+                            fields: fields
+                                .into_iter()
+                                .map(|field| FieldLayout {
+                                    name: field.name.to_string(),
+                                    kind: field.kind.to_string(),
+                                    offset: field.offset,
+                                    size: field.size,
+                                    align: field.align,
+                                    type_name: field.type_name.map(|ty| ty.to_string()),
+                                })
+                                .collect(),
+                            padding,
+                        }
+                    })
+                    .collect();
+
+                TypeLayout {
+                    name: &info.type_description,
+                    kind: match info.kind {
+                        DataTypeKind::Struct => "struct",
+                        DataTypeKind::Union => "union",
+                        DataTypeKind::Enum => "enum",
+                        DataTypeKind::Closure => "closure",
+                        DataTypeKind::Coroutine => "coroutine",
+                    },
+                    size: info.overall_size,
+                    align: info.align,
+                    packed: info.packed,
+                    discriminant_size: info.opt_discr_size,
+                    niche: info.opt_niche,
+                    variants,
+                    end_padding: info.overall_size.saturating_sub(max_variant_size),
+                }
+            })
+            .collect();
+
+        let mut json = serde_json::to_string_pretty(&Report { version: 1, types }).unwrap();
+        json.push('\n');
+        json
+    }
 }
@@ -3,6 +3,7 @@
 
 #![allow(rustc::untranslatable_diagnostic)] // FIXME: make this translatable
 
+use std::borrow::Cow;
 use std::collections::btree_map::{
     Iter as BTreeMapIter, Keys as BTreeMapKeysIter, Values as BTreeMapValuesIter,
 };
@@ -19,6 +20,7 @@
 use rustc_errors::emitter::HumanReadableErrorType;
 use rustc_errors::{ColorConfig, DiagArgValue, DiagCtxtFlags, IntoDiagArg};
 use rustc_feature::UnstableFeatures;
+use rustc_hashes::Hash64;
 use rustc_macros::{Decodable, Encodable, HashStable_Generic};
 use rustc_span::edition::{DEFAULT_EDITION, EDITION_NAME_LIST, Edition, LATEST_STABLE_EDITION};
 use rustc_span::source_map::FilePathMapping;
@@ -54,10 +56,12 @@
     ("file-names", PrintKind::FileNames),
     ("host-tuple", PrintKind::HostTuple),
     ("link-args", PrintKind::LinkArgs),
+    ("mono-item-sizes", PrintKind::MonoItemSizes),
     ("native-static-libs", PrintKind::NativeStaticLibs),
     ("relocation-models", PrintKind::RelocationModels),
     ("split-debuginfo", PrintKind::SplitDebuginfo),
     ("stack-protector-strategies", PrintKind::StackProtectorStrategies),
+    ("stack-sizes", PrintKind::StackSizes),
     ("supported-crate-types", PrintKind::SupportedCrateTypes),
     ("sysroot", PrintKind::Sysroot),
     ("target-cpus", PrintKind::TargetCPUs),
@@ -66,6 +70,7 @@
     ("target-list", PrintKind::TargetList),
     ("target-spec-json", PrintKind::TargetSpecJson),
     ("tls-models", PrintKind::TlsModels),
+    ("type-layouts", PrintKind::TypeLayouts),
     // tidy-alphabetical-end
 ];
 
@@ -171,6 +176,10 @@ pub enum InstrumentCoverage {
     No,
     /// `-C instrument-coverage` or `-C instrument-coverage=yes`
     Yes,
+    /// `-C instrument-coverage=branch`
+    Branch,
+    /// `-C instrument-coverage=mcdc`
+    Mcdc,
 }
 
 /// Individual flag values controlled by `-Zcoverage-options`.
@@ -191,9 +200,18 @@ pub struct CoverageOptions {
     /// regression tests for #133606, because we don't have an easy way to
     /// reproduce it from actual source code.
     pub discard_all_spans_in_codegen: bool,
+
+    /// `-Zcoverage-options=discard-last-condition-spans-in-codegen`: During
+    /// codegen, discard the span of the last condition of each MC/DC decision
+    /// as though it were invalid. Needed by tests of the degrading of such
+    /// decisions to branch coverage, which is hard to trigger from source code.
+    pub discard_last_condition_spans_in_codegen: bool,
 }
 
 /// Controls whether branch coverage or MC/DC coverage is enabled.
+///
+/// `Branch` and `Mcdc` can be requested with `-C instrument-coverage`, the
+/// other levels only with `-Zcoverage-options`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub enum CoverageLevel {
     /// Instrument for coverage at the MIR block level.
@@ -699,6 +717,9 @@ pub enum ErrorOutputType {
         json_rendered: HumanReadableErrorType,
         color_config: ColorConfig,
     },
+    /// A SARIF 2.1.0 log, written once compilation is done, that's consumed by
+    /// code-scanning tools.
+    Sarif,
 }
 
 #[derive(Clone, Hash, Debug)]
@@ -885,10 +906,12 @@ pub enum PrintKind {
     FileNames,
     HostTuple,
     LinkArgs,
+    MonoItemSizes,
     NativeStaticLibs,
     RelocationModels,
     SplitDebuginfo,
     StackProtectorStrategies,
+    StackSizes,
     SupportedCrateTypes,
     Sysroot,
     TargetCPUs,
@@ -897,6 +920,7 @@ pub enum PrintKind {
     TargetList,
     TargetSpecJson,
     TlsModels,
+    TypeLayouts,
     // tidy-alphabetical-end
 }
 
@@ -1266,6 +1290,37 @@ pub fn file_path_mapping(&self) -> FilePathMapping {
         file_path_mapping(self.remap_path_prefix.clone(), &self.unstable_opts)
     }
 
+    /// Returns the hash of the command line that the incremental compilation
+    /// cache is keyed by.
+    ///
+    /// With `-Z incremental-relocatable`, the prefixes remapped by
+    /// `--remap-path-prefix` are hashed relative to the working directory, so
+    /// that the cache can be reused from a copy of the source tree elsewhere.
+    pub fn incr_comp_args_hash(&self) -> Hash64 {
+        self.incr_comp_relocated().dep_tracking_hash(false)
+    }
+
+    /// Returns the hashes of the options that make up [`Self::incr_comp_args_hash`],
+    /// by name.
+    pub fn incr_comp_args_hashes(&self) -> BTreeMap<String, Hash64> {
+        self.incr_comp_relocated().dep_tracking_hashes()
+    }
+
+    fn incr_comp_relocated(&self) -> Cow<'_, Options> {
+        let working_dir = match self.working_dir.local_path() {
+            Some(working_dir) if self.unstable_opts.incremental_relocatable => working_dir,
+            _ => return Cow::Borrowed(self),
+        };
+
+        let mut opts = self.clone();
+        for (from, _) in &mut opts.remap_path_prefix {
+            if let Ok(relative) = from.strip_prefix(working_dir) {
+                *from = Path::new(".").join(relative);
+            }
+        }
+        Cow::Owned(opts)
+    }
+
     /// Returns `true` if there will be an output file generated.
     pub fn will_create_output_file(&self) -> bool {
         !self.unstable_opts.parse_crate_root_only && // The file is just being parsed
@@ -1850,11 +1905,13 @@ pub fn parse_error_format(
                 kind: HumanReadableErrorType::Unicode,
                 color_config,
             },
+            Some("sarif") => ErrorOutputType::Sarif,
             Some(arg) => {
                 early_dcx.set_error_format(ErrorOutputType::HumanReadable { color_config, .. });
                 early_dcx.early_fatal(format!(
                     "argument for `--error-format` must be `human`, `human-annotate-rs`, \
-                    `human-unicode`, `json`, `pretty-json` or `short` (instead was `{arg}`)"
+                    `human-unicode`, `json`, `pretty-json`, `sarif` or `short` \
+                    (instead was `{arg}`)"
                 ))
             }
         }
@@ -1914,6 +1971,7 @@ fn check_error_format_stability(
     }
     let format = match format {
         ErrorOutputType::Json { pretty: true, .. } => "pretty-json",
+        ErrorOutputType::Sarif => "sarif",
         ErrorOutputType::HumanReadable { kind, .. } => match kind {
             HumanReadableErrorType::AnnotateSnippet => "human-annotate-rs",
             HumanReadableErrorType::Unicode => "human-unicode",
@@ -2067,8 +2125,11 @@ fn check_print_request_stability(
     match print_kind {
         PrintKind::AllTargetSpecsJson
         | PrintKind::CheckCfg
+        | PrintKind::MonoItemSizes
+        | PrintKind::StackSizes
         | PrintKind::SupportedCrateTypes
         | PrintKind::TargetSpecJson
+        | PrintKind::TypeLayouts
             if !unstable_opts.unstable_options =>
         {
             early_dcx.early_fatal(format!(
@@ -3223,6 +3284,15 @@ pub fn extension(self) -> &'static str {
     }
 }
 
+/// Which format to use for `-Z incremental-explain`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum IncrementalExplainFormat {
+    /// Human-readable lines on stderr
+    Human,
+    /// One JSON object on stderr
+    Json,
+}
+
 /// `-Z patchable-function-entry` representation - how many nops to put before and after function
 /// entry.
 #[derive(Clone, Copy, PartialEq, Hash, Debug, Default)]
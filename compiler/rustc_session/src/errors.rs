@@ -176,6 +176,30 @@ pub(crate) struct EmbedSourceInsufficientDwarfVersion {
 #[diag(session_embed_source_requires_debug_info)]
 pub(crate) struct EmbedSourceRequiresDebugInfo;
 
+#[derive(Diagnostic)]
+#[diag(session_hot_patch_incompatible)]
+pub(crate) struct HotPatchIncompatible {
+    pub(crate) flag: &'static str,
+}
+
+#[derive(Diagnostic)]
+#[diag(session_hot_patch_requires_incremental)]
+#[help]
+pub(crate) struct HotPatchRequiresIncremental;
+
+#[derive(Diagnostic)]
+#[diag(session_hot_patch_unsupported_target)]
+pub(crate) struct HotPatchUnsupportedTarget<'a> {
+    pub(crate) target_triple: &'a TargetTuple,
+}
+
+#[derive(Diagnostic)]
+#[diag(session_incremental_not_relocatable)]
+#[help]
+pub(crate) struct IncrementalNotRelocatable<'a> {
+    pub(crate) working_dir: &'a std::path::Path,
+}
+
 #[derive(Diagnostic)]
 #[diag(session_target_stack_protector_not_supported)]
 pub(crate) struct StackProtectorNotSupportedForTarget<'a> {
@@ -46,6 +46,29 @@ macro_rules! hash_opt {
     ($opt_name:ident, $opt_expr:expr, $sub_hashes:expr, $_for_crate_hash: ident, [SUBSTRUCT]) => {{}};
 }
 
+macro_rules! hashes_substruct {
+    ($opt_name:ident, $opt_expr:expr, $error_format:expr, $hashes:expr, [UNTRACKED]) => {{}};
+    ($opt_name:ident, $opt_expr:expr, $error_format:expr, $hashes:expr, [TRACKED]) => {{}};
+    ($opt_name:ident, $opt_expr:expr, $error_format:expr, $hashes:expr, [TRACKED_NO_CRATE_HASH]) => {{}};
+    ($opt_name:ident, $opt_expr:expr, $error_format:expr, $hashes:expr, [SUBSTRUCT]) => {
+        $opt_expr.dep_tracking_hashes($error_format, $hashes);
+    };
+}
+
+/// Hashes every option in `sub_hashes` separately, for `dep_tracking_hashes`.
+fn separate_hashes(
+    sub_hashes: BTreeMap<&'static str, &dyn dep_tracking::DepTrackingHash>,
+    prefix: &str,
+    error_format: ErrorOutputType,
+    hashes: &mut BTreeMap<String, Hash64>,
+) {
+    for (key, sub_hash) in sub_hashes {
+        let mut hasher = StableHasher::new();
+        sub_hash.hash(&mut hasher, error_format, false);
+        hashes.insert(format!("{prefix}{}", key.replace('_', "-")), hasher.finish());
+    }
+}
+
 macro_rules! hash_substruct {
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [UNTRACKED]) => {{}};
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [TRACKED]) => {{}};
@@ -280,6 +303,29 @@ pub fn dep_tracking_hash(&self, for_crate_hash: bool) -> Hash64 {
                 hasher.finish()
             }
 
+            /// Returns the hash of every option that `dep_tracking_hash(false)`
+            /// covers, by name, so that it can be told which of them changed.
+            pub fn dep_tracking_hashes(&self) -> BTreeMap<String, Hash64> {
+                let mut sub_hashes = BTreeMap::new();
+                $({
+                    hash_opt!($opt,
+                                &self.$opt,
+                                &mut sub_hashes,
+                                false,
+                                [$dep_tracking_marker]);
+                })*
+                let mut hashes = BTreeMap::new();
+                separate_hashes(sub_hashes, "", self.error_format, &mut hashes);
+                $({
+                    hashes_substruct!($opt,
+                        &self.$opt,
+                        self.error_format,
+                        &mut hashes,
+                        [$dep_tracking_marker]);
+                })*
+                hashes
+            }
+
             pub fn gather_target_modifiers(&self) -> Vec<TargetModifier> {
                 let mut mods = Vec::<TargetModifier>::new();
                 $({
@@ -568,6 +614,22 @@ fn dep_tracking_hash(&self, for_crate_hash: bool, error_format: ErrorOutputType)
             hasher.finish()
         }
 
+        fn dep_tracking_hashes(
+            &self,
+            error_format: ErrorOutputType,
+            hashes: &mut BTreeMap<String, Hash64>,
+        ) {
+            let mut sub_hashes = BTreeMap::new();
+            $({
+                hash_opt!($opt,
+                            &self.$opt,
+                            &mut sub_hashes,
+                            false,
+                            [$dep_tracking_marker]);
+            })*
+            separate_hashes(sub_hashes, concat!("-", $prefix, " "), error_format, hashes);
+        }
+
         pub fn gather_target_modifiers(
             &self,
             _mods: &mut Vec<TargetModifier>,
@@ -739,7 +801,9 @@ mod desc {
     pub(crate) const parse_strip: &str = "either `none`, `debuginfo`, or `symbols`";
     pub(crate) const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavorCli::one_of();
     pub(crate) const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
-    pub(crate) const parse_instrument_coverage: &str = parse_bool;
+    pub(crate) const parse_incremental_explain: &str = "`human` (default) or `json`";
+    pub(crate) const parse_instrument_coverage: &str =
+        "either a boolean (`yes`, `no`, `on`, `off`, etc), `branch`, or `mcdc`";
     pub(crate) const parse_coverage_options: &str =
         "`block` | `branch` | `condition` | `mcdc` | `no-mir-spans`";
     pub(crate) const parse_instrument_xray: &str = "either a boolean (`yes`, `no`, `on`, `off`, etc), or a comma separated list of settings: `always` or `never` (mutually exclusive), `ignore-loops`, `instruction-threshold=N`, `skip-entry`, `skip-exit`";
@@ -1343,6 +1407,18 @@ pub(crate) fn parse_dump_mono_stats(slot: &mut DumpMonoStatsFormat, v: Option<&s
         }
     }
 
+    pub(crate) fn parse_incremental_explain(
+        slot: &mut Option<IncrementalExplainFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            None | Some("human") => Some(IncrementalExplainFormat::Human),
+            Some("json") => Some(IncrementalExplainFormat::Json),
+            Some(_) => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_autodiff(slot: &mut Vec<AutoDiff>, v: Option<&str>) -> bool {
         let Some(v) = v else {
             *slot = vec![];
@@ -1394,6 +1470,8 @@ pub(crate) fn parse_instrument_coverage(
         *slot = match v {
             "all" => InstrumentCoverage::Yes,
             "0" => InstrumentCoverage::No,
+            "branch" => InstrumentCoverage::Branch,
+            "mcdc" => InstrumentCoverage::Mcdc,
             _ => return false,
         };
         true
@@ -1410,6 +1488,9 @@ pub(crate) fn parse_coverage_options(slot: &mut CoverageOptions, v: Option<&str>
                 "mcdc" => slot.level = CoverageLevel::Mcdc,
                 "no-mir-spans" => slot.no_mir_spans = true,
                 "discard-all-spans-in-codegen" => slot.discard_all_spans_in_codegen = true,
+                "discard-last-condition-spans-in-codegen" => {
+                    slot.discard_last_condition_spans_in_codegen = true
+                }
                 _ => return false,
             }
         }
@@ -1973,7 +2054,8 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         deprecated_do_nothing: true),
     #[rustc_lint_opt_deny_field_access("use `Session::instrument_coverage` instead of this field")]
     instrument_coverage: InstrumentCoverage = (InstrumentCoverage::No, parse_instrument_coverage, [TRACKED],
-        "instrument the generated code to support LLVM source-based code coverage reports \
+        "instrument the generated code to support LLVM source-based code coverage reports, \
+        optionally with branch or MC/DC coverage \
         (note, the compiler build config must include `profiler = true`); \
         implies `-C symbol-mangling-version=v0`"),
     link_arg: (/* redirected to link_args */) = ((), parse_string_push, [UNTRACKED],
@@ -2077,6 +2159,10 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         "only allow the listed language features to be enabled in code (comma separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    api_diff: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "compare the public API of the crate with the one of the earlier version of it whose \
+        rlib or metadata file is given, and print the changes and the SemVer version bump \
+        they require as JSON"),
     assert_incr_state: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "assert that the incremental cache is in given state: \
          either `loaded` or `not-loaded`."),
@@ -2142,6 +2228,9 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
     dump_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv) \
         (default: no)"),
+    dump_metadata_regions: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the byte ranges of the crate metadata that each of its sections, tables and \
+        table entries was encoded to, to this file"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump MIR state to file.
         `val` is used to select which passes and functions to dump. For example:
@@ -2218,17 +2307,34 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         environment variable `RUSTC_GRAPHVIZ_FONT` (default: `Courier, monospace`)"),
     has_thread_local: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "explicitly enable the `cfg(target_thread_local)` directive"),
+    hot_patch: bool = (false, parse_bool, [TRACKED],
+        "build executables whose functions can be replaced while they run, by shared objects \
+        built from the codegen units that changed since (default: no)"),
     human_readable_cgu_names: bool = (false, parse_bool, [TRACKED],
         "generate human-readable, predictable names for codegen units (default: no)"),
     identify_regions: bool = (false, parse_bool, [UNTRACKED],
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
     ignore_directory_in_diagnostics_source_blocks: Vec<String> = (Vec::new(), parse_string_push, [UNTRACKED],
         "do not display the source code block in diagnostics for files in the directory"),
+    incremental_explain: Option<IncrementalExplainFormat> = (None, parse_incremental_explain, [UNTRACKED],
+        "explain why incremental compilation could not reuse its previous results, as `human` \
+        readable text or `json` (default: no)"),
+    incremental_explain_filter: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "only explain the red dep nodes that match this filter, e.g. `hir_owner & my_fn`"),
     incremental_ignore_spans: bool = (false, parse_bool, [TRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
         "print high-level information about incremental reuse (or the lack thereof) \
         (default: no)"),
+    incremental_remote_cache: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "a directory to fetch incremental compilation caches from and store them in, shared \
+        with other machines"),
+    incremental_relocatable: bool = (false, parse_bool, [UNTRACKED],
+        "key the incremental compilation cache by the remapped working directory, so that it \
+        can be reused from another location of the source tree (default: no)"),
+    incremental_shared_cache: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "a read-only incremental compilation cache to start from if the local one has nothing \
+        for the crate"),
     incremental_verify_ich: bool = (false, parse_bool, [UNTRACKED],
         "verify extended properties for incr. comp. (default: no):
         - hashes of green query instances
@@ -2242,9 +2348,14 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         "inlining threshold when the caller is a simple forwarding function (default: 30)"),
     inline_mir_hint_threshold: Option<usize> = (None, parse_opt_number, [TRACKED],
         "inlining threshold for functions with inline hint (default: 100)"),
+    inline_mir_hot_threshold: Option<usize> = (None, parse_opt_number, [TRACKED],
+        "inlining threshold for callees that `-Z inline-mir-profile` marks as hot (default: 250)"),
     inline_mir_preserve_debug: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "when MIR inlining, whether to preserve debug info for callee variables \
         (default: preserve for debuginfo != None, otherwise remove)"),
+    inline_mir_profile: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "use the execution counts in this file to guide MIR inlining and cross-crate \
+        inlinability: hot callees are inlined more eagerly, cold ones are not inlined"),
     inline_mir_threshold: Option<usize> = (None, parse_opt_number, [TRACKED],
         "a default MIR inlining threshold (default: 50)"),
     input_stats: bool = (false, parse_bool, [UNTRACKED],
@@ -2389,12 +2500,18 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
     #[rustc_lint_opt_deny_field_access("use `Session::print_codegen_stats` instead of this field")]
     print_codegen_stats: bool = (false, parse_bool, [UNTRACKED],
         "print codegen statistics (default: no)"),
+    print_enum_layout_hints: bool = (false, parse_bool, [UNTRACKED],
+        "print the enums that one variant makes much larger than the others, and how many \
+        bytes boxing that variant would save in the monomorphized code (default: no)"),
     print_llvm_passes: bool = (false, parse_bool, [UNTRACKED],
         "print the LLVM optimization passes being run (default: no)"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass. \
          Value `lazy` means to use normal collection; `eager` means to collect all items.
          Note that this overwrites the effect `-Clink-dead-code` has on collection!"),
+    print_type_layouts_filter: Vec<String> = (Vec::new(), parse_comma_list, [UNTRACKED],
+        "only report types whose path starts with one of these prefixes in \
+        `--print=type-layouts` (default: all types)"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print layout information for each type encountered (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
@@ -2412,6 +2529,9 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         "enable queries of the dependency graph for regression testing (default: no)"),
     randomize_layout: bool = (false, parse_bool, [TRACKED],
         "randomize the layout of types (default: no)"),
+    record_uses: bool = (false, parse_bool, [TRACKED],
+        "record in the metadata which items of other crates the crate uses, for \
+        `-Z unused-pub-items` (default: no)"),
     reg_struct_return: bool = (false, parse_bool, [TRACKED TARGET_MODIFIER],
         "On x86-32 targets, it overrides the default ABI to return small structs in registers.
         It is UNSOUND to link together crates that use different values for this flag!"),
@@ -2495,6 +2615,9 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
         "control stack smash protection strategy (`rustc --print stack-protector-strategies` for details)"),
     staticlib_allow_rdylib_deps: bool = (false, parse_bool, [TRACKED],
         "allow staticlibs to have rust dylib dependencies"),
+    staticlib_localize_symbols: bool = (false, parse_bool, [TRACKED],
+        "merge the objects of staticlibs into one, and make the symbols that they don't export \
+        local to it (ELF only) (default: no)"),
     staticlib_prefer_dynamic: bool = (false, parse_bool, [TRACKED],
         "prefer dynamic linking to static linking for staticlibs (default: no)"),
     strict_init_checks: bool = (false, parse_bool, [TRACKED],
@@ -2586,6 +2709,10 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
     #[rustc_lint_opt_deny_field_access("use `Session::unstable_options` instead of this field")]
     unstable_options: bool = (false, parse_no_value, [UNTRACKED],
         "adds unstable command line options to rustc interface (default: no)"),
+    unused_pub_items: Option<Vec<String>> = (None, parse_opt_comma_list, [UNTRACKED],
+        "warn about the public items of the crate that neither it nor any of the crates whose \
+        metadata is at the given paths (comma separated) use; those crates must be built with \
+        `-Z record-uses`"),
     use_ctors_section: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "use legacy .ctors section for initializers rather than .init_array"),
     use_sync_unwind: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -2600,6 +2727,9 @@ pub(crate) fn parse_align(slot: &mut Option<Align>, v: Option<&str>) -> bool {
     #[rustc_lint_opt_deny_field_access("use `Session::verify_llvm_ir` instead of this field")]
     verify_llvm_ir: bool = (false, parse_bool, [TRACKED],
         "verify LLVM IR (default: no)"),
+    verify_reproducible: bool = (false, parse_bool, [UNTRACKED],
+        "build the crate a second time in a different working directory and environment, \
+        and report the parts of the outputs that differ (default: no)"),
     virtual_function_elimination: bool = (false, parse_bool, [TRACKED],
         "enables dead virtual function elimination optimization. \
         Requires `-Clto[=[fat,yes]]`"),
@@ -2,9 +2,9 @@
 use std::ops::{Div, Mul};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
-use std::{env, fmt, io};
+use std::sync::{Arc, Mutex};
+use std::{env, fmt, io, mem};
 
 use rustc_data_structures::flock;
 use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
@@ -16,6 +16,7 @@
     DynEmitter, HumanEmitter, HumanReadableErrorType, OutputTheme, stderr_destination,
 };
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::{EarlySarifLog, SarifEmitter};
 use rustc_errors::{
     Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic, ErrorGuaranteed, FatalAbort,
     FluentBundle, LazyFallbackBundle, TerminalUrl, fallback_fluent_bundle,
@@ -27,17 +28,17 @@
 use rustc_span::{FileNameDisplayPreference, RealFileName, Span, Symbol};
 use rustc_target::asm::InlineAsmArch;
 use rustc_target::spec::{
-    CodeModel, DebuginfoKind, PanicStrategy, RelocModel, RelroLevel, SanitizerSet,
+    BinaryFormat, CodeModel, DebuginfoKind, PanicStrategy, RelocModel, RelroLevel, SanitizerSet,
     SmallDataThresholdSupport, SplitDebuginfo, StackProtector, SymbolVisibility, Target,
     TargetTuple, TlsModel,
 };
 
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+pub use crate::code_stats::{DataTypeKind, FieldInfo, FieldKind, NicheInfo, SizeKind, VariantInfo};
 use crate::config::{
     self, CoverageLevel, CrateType, DebugInfo, ErrorOutputType, FunctionReturn, Input,
-    InstrumentCoverage, OptLevel, OutFileName, OutputType, RemapPathScopeComponents,
-    SwitchWithOptPath,
+    InstrumentCoverage, OptLevel, OutFileName, OutputType, PatchableFunctionEntry, PrintKind,
+    RemapPathScopeComponents, SwitchWithOptPath,
 };
 use crate::filesearch::FileSearch;
 use crate::parse::{ParseSess, add_feature_diagnostics};
@@ -302,7 +303,7 @@ pub fn create_feature_err<'a>(&'a self, err: impl Diagnostic<'a>, feature: Symbo
     /// Record the fact that we called `trimmed_def_paths`, and do some
     /// checking about whether its cost was justified.
     pub fn record_trimmed_def_paths(&self) {
-        if self.opts.unstable_opts.print_type_sizes
+        if self.record_type_layouts()
             || self.opts.unstable_opts.query_dep_graph
             || self.opts.unstable_opts.dump_mir.is_some()
             || self.opts.unstable_opts.unpretty.is_some()
@@ -336,19 +337,27 @@ pub fn instrument_coverage(&self) -> bool {
         self.opts.cg.instrument_coverage() != InstrumentCoverage::No
     }
 
+    /// The coverage level requested by `-C instrument-coverage` or by
+    /// `-Zcoverage-options`, whichever is higher.
+    fn coverage_level(&self) -> CoverageLevel {
+        let level = match self.opts.cg.instrument_coverage() {
+            InstrumentCoverage::No | InstrumentCoverage::Yes => CoverageLevel::Block,
+            InstrumentCoverage::Branch => CoverageLevel::Branch,
+            InstrumentCoverage::Mcdc => CoverageLevel::Mcdc,
+        };
+        level.max(self.opts.unstable_opts.coverage_options.level)
+    }
+
     pub fn instrument_coverage_branch(&self) -> bool {
-        self.instrument_coverage()
-            && self.opts.unstable_opts.coverage_options.level >= CoverageLevel::Branch
+        self.instrument_coverage() && self.coverage_level() >= CoverageLevel::Branch
     }
 
     pub fn instrument_coverage_condition(&self) -> bool {
-        self.instrument_coverage()
-            && self.opts.unstable_opts.coverage_options.level >= CoverageLevel::Condition
+        self.instrument_coverage() && self.coverage_level() >= CoverageLevel::Condition
     }
 
     pub fn instrument_coverage_mcdc(&self) -> bool {
-        self.instrument_coverage()
-            && self.opts.unstable_opts.coverage_options.level >= CoverageLevel::Mcdc
+        self.instrument_coverage() && self.coverage_level() >= CoverageLevel::Mcdc
     }
 
     /// True if `-Zcoverage-options=no-mir-spans` was passed.
@@ -361,6 +370,11 @@ pub fn coverage_discard_all_spans_in_codegen(&self) -> bool {
         self.opts.unstable_opts.coverage_options.discard_all_spans_in_codegen
     }
 
+    /// True if `-Zcoverage-options=discard-last-condition-spans-in-codegen` was passed.
+    pub fn coverage_discard_last_condition_spans_in_codegen(&self) -> bool {
+        self.opts.unstable_opts.coverage_options.discard_last_condition_spans_in_codegen
+    }
+
     pub fn is_sanitizer_cfi_enabled(&self) -> bool {
         self.opts.unstable_opts.sanitizer.contains(SanitizerSet::CFI)
     }
@@ -518,6 +532,14 @@ pub fn incr_comp_session_dir_opt(&self) -> Option<MappedReadGuard<'_, PathBuf>>
         self.opts.incremental.as_ref().map(|_| self.incr_comp_session_dir())
     }
 
+    /// Returns the session directory once it has been successfully finalized.
+    pub fn finalized_incr_comp_session_dir(&self) -> Option<PathBuf> {
+        match *self.incr_comp_session.borrow() {
+            IncrCompSession::Finalized { ref session_directory } => Some(session_directory.clone()),
+            _ => None,
+        }
+    }
+
     /// Is this edition 2015?
     pub fn is_rust_2015(&self) -> bool {
         self.edition().is_rust_2015()
@@ -607,6 +629,13 @@ pub fn print_llvm_stats(&self) -> bool {
         self.opts.unstable_opts.print_codegen_stats
     }
 
+    /// Whether type layouts should be recorded in `code_stats`, for `-Zprint-type-sizes` or
+    /// `--print=type-layouts`.
+    pub fn record_type_layouts(&self) -> bool {
+        self.opts.unstable_opts.print_type_sizes
+            || self.opts.prints.iter().any(|print| print.kind == PrintKind::TypeLayouts)
+    }
+
     pub fn verify_llvm_ir(&self) -> bool {
         self.opts.unstable_opts.verify_llvm_ir || option_env!("RUSTC_VERIFY_LLVM_IR").is_some()
     }
@@ -663,6 +692,12 @@ pub fn lto(&self) -> config::Lto {
             return config::Lto::No;
         }
 
+        // ThinLTO would inline functions across codegen units, so that a patch
+        // built from a changed codegen unit couldn't replace all of their uses.
+        if self.opts.unstable_opts.hot_patch {
+            return config::Lto::No;
+        }
+
         // If `-Z thinlto` specified process that, but note that this is mostly
         // a deprecated option now that `-C lto=thin` exists.
         if let Some(enabled) = self.opts.unstable_opts.thinlto {
@@ -877,6 +912,19 @@ pub fn link_dead_code(&self) -> bool {
         self.opts.cg.link_dead_code.unwrap_or(false)
     }
 
+    /// The number of nops to put around function entries, so that
+    /// `-Zhot-patch` has room to write a jump to the new definition.
+    pub fn patchable_function_entry(&self) -> PatchableFunctionEntry {
+        let config = self.opts.unstable_opts.patchable_function_entry;
+        if !self.opts.unstable_opts.hot_patch || config != PatchableFunctionEntry::default() {
+            return config;
+        }
+        // Enough for an absolute jump: `movabs` and `jmp` on x86, `ldr`, `br` and
+        // the address elsewhere.
+        let nops = if matches!(&*self.target.arch, "x86" | "x86_64") { 16 } else { 4 };
+        PatchableFunctionEntry::from_total_and_prefix_nops(nops, 0).unwrap()
+    }
+
     pub fn filename_display_preference(
         &self,
         scope: RemapPathScopeComponents,
@@ -900,6 +948,8 @@ fn default_emitter(
     source_map: Arc<SourceMap>,
     bundle: Option<Arc<FluentBundle>>,
     fallback_bundle: LazyFallbackBundle,
+    cfg_version: &'static str,
+    early_sarif_log: Option<EarlySarifLog>,
 ) -> Box<DynEmitter> {
     let macro_backtrace = sopts.unstable_opts.macro_backtrace;
     let track_diagnostics = sopts.unstable_opts.track_diagnostics;
@@ -971,6 +1021,16 @@ fn default_emitter(
             .track_diagnostics(track_diagnostics)
             .terminal_url(terminal_url),
         ),
+        config::ErrorOutputType::Sarif => Box::new(
+            SarifEmitter::new(
+                Box::new(io::BufWriter::new(io::stderr())),
+                source_map,
+                fallback_bundle,
+            )
+            .fluent_bundle(bundle)
+            .tool_version(Some(cfg_version.to_owned()))
+            .session_log(early_sarif_log.unwrap_or_default()),
+        ),
     }
 }
 
@@ -990,6 +1050,7 @@ pub fn build_session(
     ice_file: Option<PathBuf>,
     using_internal_features: &'static AtomicBool,
     expanded_args: Vec<String>,
+    early_sarif_log: Option<EarlySarifLog>,
 ) -> Session {
     // FIXME: This is not general enough to make the warning lint completely override
     // normal diagnostic warnings, since the warning lint can also be denied and changed
@@ -1007,7 +1068,14 @@ pub fn build_session(
         sopts.unstable_opts.translate_directionality_markers,
     );
     let source_map = rustc_span::source_map::get_source_map().unwrap();
-    let emitter = default_emitter(&sopts, Arc::clone(&source_map), bundle, fallback_bundle);
+    let emitter = default_emitter(
+        &sopts,
+        Arc::clone(&source_map),
+        bundle,
+        fallback_bundle,
+        cfg_version,
+        early_sarif_log,
+    );
 
     let mut dcx = DiagCtxt::new(emitter)
         .with_flags(sopts.unstable_opts.dcx_flags(can_emit_warnings))
@@ -1288,6 +1356,34 @@ fn validate_commandline_args_with_session_available(sess: &Session) {
         }
     }
 
+    // The absolute working directory is part of the cache key unless it's remapped.
+    if sess.opts.unstable_opts.incremental_relocatable
+        && let RealFileName::LocalPath(working_dir) = &sess.opts.working_dir
+    {
+        sess.dcx().emit_warn(errors::IncrementalNotRelocatable { working_dir });
+    }
+
+    if sess.opts.unstable_opts.hot_patch {
+        if sess.opts.incremental.is_none() {
+            sess.dcx().emit_err(errors::HotPatchRequiresIncremental);
+        }
+        // Patches are built from the object files of single codegen units.
+        if sess.lto() != config::Lto::No || sess.opts.cg.linker_plugin_lto.enabled() {
+            sess.dcx().emit_err(errors::HotPatchIncompatible { flag: "-Clto" });
+        }
+        // The objects are linked into shared objects as well as into the executable.
+        if sess.relocation_model() != RelocModel::Pic {
+            sess.dcx().emit_err(errors::HotPatchIncompatible { flag: "-Crelocation-model" });
+        }
+        // The patches rely on ELF symbol interposition to share the statics of
+        // the executable, and the loader is only implemented for Linux.
+        if sess.target.binary_format != BinaryFormat::Elf || sess.target.os != "linux" {
+            sess.dcx().emit_err(errors::HotPatchUnsupportedTarget {
+                target_triple: &sess.opts.target_triple,
+            });
+        }
+    }
+
     if sess.opts.unstable_opts.instrument_xray.is_some() && !sess.target.options.supports_xray {
         sess.dcx().emit_err(errors::InstrumentationNotSupported { us: "XRay".to_string() });
     }
@@ -1369,12 +1465,14 @@ enum IncrCompSession {
 /// A wrapper around an [`DiagCtxt`] that is used for early error emissions.
 pub struct EarlyDiagCtxt {
     dcx: DiagCtxt,
+    /// With `--error-format=sarif`, the diagnostics that belong in the session's log.
+    sarif_log: Option<Arc<Mutex<EarlySarifLog>>>,
 }
 
 impl EarlyDiagCtxt {
     pub fn new(output: ErrorOutputType) -> Self {
-        let emitter = mk_emitter(output);
-        Self { dcx: DiagCtxt::new(emitter) }
+        let (emitter, sarif_log) = mk_emitter(output);
+        Self { dcx: DiagCtxt::new(emitter), sarif_log }
     }
 
     /// Swap out the underlying dcx once we acquire the user's preference on error emission
@@ -1382,8 +1480,20 @@ pub fn new(output: ErrorOutputType) -> Self {
     pub fn set_error_format(&mut self, output: ErrorOutputType) {
         assert!(self.dcx.handle().has_errors().is_none());
 
-        let emitter = mk_emitter(output);
+        let (emitter, sarif_log) = mk_emitter(output);
+        // Warnings reported so far stay on their way to the session's log.
+        if let (Some(earlier), Some(log)) = (self.take_sarif_log(), &sarif_log) {
+            *log.lock().unwrap() = earlier;
+        }
         self.dcx = DiagCtxt::new(emitter);
+        self.sarif_log = sarif_log;
+    }
+
+    /// With `--error-format=sarif`, takes the diagnostics reported so far, to be
+    /// passed to `build_session` so that they are written in the session's log.
+    /// Otherwise this dcx writes them as a log of their own when it is dropped.
+    pub fn take_sarif_log(&self) -> Option<EarlySarifLog> {
+        self.sarif_log.as_ref().map(|log| mem::take(&mut *log.lock().unwrap()))
     }
 
     #[allow(rustc::untranslatable_diagnostic)]
@@ -1430,7 +1540,9 @@ pub fn early_struct_warn(&self, msg: impl Into<DiagMessage>) -> Diag<'_, ()> {
     }
 }
 
-fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
+/// The emitter of an `EarlyDiagCtxt`, and for SARIF output, the log that its
+/// diagnostics go to.
+fn mk_emitter(output: ErrorOutputType) -> (Box<DynEmitter>, Option<Arc<Mutex<EarlySarifLog>>>) {
     // FIXME(#100717): early errors aren't translated at the moment, so this is fine, but it will
     // need to reference every crate that might emit an early error for translation to work.
     let fallback_bundle =
@@ -1458,8 +1570,18 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
                 color_config,
             ))
         }
+        config::ErrorOutputType::Sarif => {
+            let log = Arc::new(Mutex::new(EarlySarifLog::default()));
+            let emitter = SarifEmitter::new(
+                Box::new(io::BufWriter::new(io::stderr())),
+                Some(Arc::new(SourceMap::new(FilePathMapping::empty()))),
+                fallback_bundle,
+            )
+            .early(Arc::clone(&log));
+            return (Box::new(emitter), Some(log));
+        }
     };
-    emitter
+    (emitter, None)
 }
 
 pub trait RemapFileNameExt {
@@ -15,6 +15,15 @@ pub fn timer(&self, what: &'static str) -> VerboseTimingGuard<'_> {
     pub fn time<R>(&self, what: &'static str, f: impl FnOnce() -> R) -> R {
         self.prof.verbose_generic_activity(what).run(f)
     }
+    /// Like [`Session::time`], for passes that emit diagnostics from several threads at once when
+    /// the frontend runs in parallel. Those are then printed in source order once the pass is
+    /// done, so that the output doesn't depend on how the threads were scheduled.
+    pub fn time_in_source_order<R>(&self, what: &'static str, f: impl FnOnce() -> R) -> R {
+        self.time(
+            what,
+            || if self.threads() > 1 { self.dcx().emit_in_source_order(f) } else { f() },
+        )
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Encodable, Decodable)]
@@ -460,6 +460,7 @@
         arbitrary_enum_discriminant,
         arbitrary_self_types,
         arbitrary_self_types_pointers,
+        arch,
         areg,
         args,
         arith_offset,
@@ -1381,6 +1382,9 @@
         mul_assign,
         mul_with_overflow,
         multiple_supertrait_upcastable,
+        multiversion,
+        multiversion_expand,
+        multiversion_internals,
         must_not_suspend,
         must_use,
         mut_preserve_binding_mode_2024,
@@ -2057,6 +2061,7 @@
         target_pointer_width,
         target_thread_local,
         target_vendor,
+        targets,
         tbm_target_feature,
         termination,
         termination_trait,
@@ -2228,6 +2233,7 @@
         usize_legacy_fn_max_value,
         usize_legacy_fn_min_value,
         usize_legacy_mod,
+        v1,
         v8plus,
         va_arg,
         va_copy,
@@ -17,7 +17,7 @@
 use rustc_middle::ty::{
     self, AdtDef, CoroutineArgsExt, EarlyBinder, PseudoCanonicalInput, Ty, TyCtxt, TypeVisitableExt,
 };
-use rustc_session::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+use rustc_session::{DataTypeKind, FieldInfo, FieldKind, NicheInfo, SizeKind, VariantInfo};
 use rustc_span::{Symbol, sym};
 use tracing::{debug, instrument};
 use {rustc_abi as abi, rustc_hir as hir};
@@ -67,9 +67,9 @@ fn layout_of<'tcx>(
     let layout = layout_of_uncached(&cx, ty)?;
     let layout = TyAndLayout { ty, layout };
 
-    // If we are running with `-Zprint-type-sizes`, maybe record layouts
-    // for dumping later.
-    if cx.tcx().sess.opts.unstable_opts.print_type_sizes {
+    // If we are running with `-Zprint-type-sizes` or `--print=type-layouts`,
+    // maybe record layouts for dumping later.
+    if cx.tcx().sess.record_type_layouts() {
         record_layout_for_printing(&cx, layout);
     }
 
@@ -646,6 +646,13 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx>, layout: TyAndLayout<'tc
     // (delay format until we actually need it)
     let record = |kind, packed, opt_discr_size, variants| {
         let type_desc = with_no_trimmed_paths!(format!("{}", layout.ty));
+        let opt_niche = layout.largest_niche.map(|niche| NicheInfo {
+            offset: niche.offset.bytes(),
+            size: niche.value.size(cx).bytes(),
+            valid_range_start: niche.valid_range.start,
+            valid_range_end: niche.valid_range.end,
+            available: niche.available(cx),
+        });
         cx.tcx().sess.code_stats.record_type_size(
             kind,
             type_desc,
@@ -653,6 +660,7 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx>, layout: TyAndLayout<'tc
             layout.size,
             packed,
             opt_discr_size,
+            opt_niche,
             variants,
         );
     };
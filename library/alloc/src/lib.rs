@@ -102,6 +102,7 @@
 #![feature(assert_matches)]
 #![feature(async_fn_traits)]
 #![feature(async_iterator)]
+#![feature(bigint_helper_methods)]
 #![feature(bstr)]
 #![feature(bstr_internals)]
 #![feature(char_max_len)]
@@ -219,6 +220,8 @@
 #[cfg(all(not(no_rc), not(no_sync), not(no_global_oom_handling)))]
 pub mod ffi;
 pub mod fmt;
+#[cfg(not(no_global_oom_handling))]
+pub mod num;
 #[cfg(not(no_rc))]
 pub mod rc;
 pub mod slice;
@@ -0,0 +1,271 @@
+//! Algorithms on little-endian slices of `u64` limbs.
+//!
+//! Unless stated otherwise, inputs and outputs are *normalized*: they have no
+//! high zero limbs, so zero is the empty slice.
+
+use core::cmp::Ordering;
+
+use crate::vec::Vec;
+
+/// Below this many limbs in the shorter operand, schoolbook multiplication
+/// beats Karatsuba.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Removes high zero limbs.
+pub(super) fn trim(v: &mut Vec<u64>) {
+    while let Some(&0) = v.last() {
+        v.pop();
+    }
+}
+
+pub(super) fn cmp(a: &[u64], b: &[u64]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+/// `a += b`, returning the carry out of `a`. Requires `a.len() >= b.len()`.
+fn add_in_place(a: &mut [u64], b: &[u64]) -> bool {
+    let (lo, hi) = a.split_at_mut(b.len());
+    let mut carry = false;
+    for (x, &y) in lo.iter_mut().zip(b) {
+        (*x, carry) = x.carrying_add(y, carry);
+    }
+    for x in hi {
+        if !carry {
+            break;
+        }
+        (*x, carry) = x.overflowing_add(1);
+    }
+    carry
+}
+
+/// `a -= b`, returning the borrow out of `a`. Requires `a.len() >= b.len()`.
+fn sub_in_place(a: &mut [u64], b: &[u64]) -> bool {
+    let (lo, hi) = a.split_at_mut(b.len());
+    let mut borrow = false;
+    for (x, &y) in lo.iter_mut().zip(b) {
+        (*x, borrow) = x.borrowing_sub(y, borrow);
+    }
+    for x in hi {
+        if !borrow {
+            break;
+        }
+        (*x, borrow) = x.overflowing_sub(1);
+    }
+    borrow
+}
+
+pub(super) fn add_assign(a: &mut Vec<u64>, b: &[u64]) {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0);
+    }
+    if add_in_place(a, b) {
+        a.push(1);
+    }
+}
+
+/// `a -= b`. Requires `a >= b`.
+pub(super) fn sub_assign(a: &mut Vec<u64>, b: &[u64]) {
+    let borrow = sub_in_place(a, b);
+    debug_assert!(!borrow);
+    trim(a);
+}
+
+/// `a = a * m + add`.
+pub(super) fn mul_limb_add_assign(a: &mut Vec<u64>, m: u64, add: u64) {
+    let mut carry = add;
+    for x in a.iter_mut() {
+        (*x, carry) = x.carrying_mul_add(m, carry, 0);
+    }
+    if carry != 0 {
+        a.push(carry);
+    }
+    trim(a);
+}
+
+/// `acc += a * m`. Requires `acc.len() > a.len()` and that the sum fits.
+fn mac_limb(acc: &mut [u64], a: &[u64], m: u64) {
+    let (lo, hi) = acc.split_at_mut(a.len());
+    let mut carry = 0;
+    for (x, &y) in lo.iter_mut().zip(a) {
+        (*x, carry) = y.carrying_mul_add(m, carry, *x);
+    }
+    let overflow = add_in_place(hi, &[carry]);
+    debug_assert!(!overflow);
+}
+
+/// `acc += a * b`. Requires `acc.len() >= a.len() + b.len()` and that the sum
+/// fits. Inputs need not be normalized.
+fn mul_into(acc: &mut [u64], a: &[u64], b: &[u64]) {
+    let (a, b) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if b.len() < KARATSUBA_THRESHOLD {
+        for (i, &m) in b.iter().enumerate() {
+            if m != 0 {
+                mac_limb(&mut acc[i..], a, m);
+            }
+        }
+        return;
+    }
+
+    if 2 * b.len() <= a.len() {
+        // Very unbalanced operands: split the longer one into pieces the size
+        // of the shorter one, so that each partial product is balanced.
+        for (i, chunk) in a.chunks(b.len()).enumerate() {
+            mul_into(&mut acc[i * b.len()..], chunk, b);
+        }
+        return;
+    }
+
+    // Karatsuba: with `a = a1·B^m + a0` and `b = b1·B^m + b0`,
+    // `a·b = z2·B^2m + (z1 - z2 - z0)·B^m + z0` where `z0 = a0·b0`,
+    // `z2 = a1·b1` and `z1 = (a0 + a1)·(b0 + b1)`.
+    let m = b.len() / 2;
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = mul(a0, b0);
+    let z2 = mul(a1, b1);
+
+    let mut sa = a0.to_vec();
+    add_assign(&mut sa, a1);
+    let mut sb = b0.to_vec();
+    add_assign(&mut sb, b1);
+    let mut z1 = mul(&sa, &sb);
+    sub_assign(&mut z1, &z0);
+    sub_assign(&mut z1, &z2);
+
+    let overflow = add_in_place(acc, &z0)
+        | add_in_place(&mut acc[m..], &z1)
+        | add_in_place(&mut acc[2 * m..], &z2);
+    debug_assert!(!overflow);
+}
+
+pub(super) fn mul(a: &[u64], b: &[u64]) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0; a.len() + b.len()];
+    mul_into(&mut out, a, b);
+    trim(&mut out);
+    out
+}
+
+/// Divides `a` in place by `d`, returning the remainder.
+pub(super) fn div_rem_limb(a: &mut Vec<u64>, d: u64) -> u64 {
+    let mut rem = 0u64;
+    for x in a.iter_mut().rev() {
+        let n = ((rem as u128) << 64) | *x as u128;
+        *x = (n / d as u128) as u64;
+        rem = (n % d as u128) as u64;
+    }
+    trim(a);
+    rem
+}
+
+/// Returns `(a / b, a % b)`.
+///
+/// # Panics
+///
+/// Panics if `b` is zero.
+pub(super) fn div_rem(a: &[u64], b: &[u64]) -> (Vec<u64>, Vec<u64>) {
+    assert!(!b.is_empty(), "attempt to divide by zero");
+    if cmp(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if let [d] = *b {
+        let mut q = a.to_vec();
+        let r = div_rem_limb(&mut q, d);
+        return (q, if r == 0 { Vec::new() } else { vec![r] });
+    }
+
+    // Knuth, TAOCP vol. 2, 4.3.1, Algorithm D. Shift both operands so the top
+    // bit of the divisor is set, which keeps each quotient estimate within two
+    // of the true digit.
+    let shift = b[b.len() - 1].leading_zeros() as u64;
+    let v = shl(b, shift);
+    let mut u = shl(a, shift);
+    u.resize(a.len() + 1, 0);
+
+    let n = v.len();
+    let v_top = v[n - 1] as u128;
+    let v_next = v[n - 2] as u128;
+    let mut q = vec![0; u.len() - n];
+    for j in (0..q.len()).rev() {
+        let num = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+        let mut q_hat = num / v_top;
+        let mut r_hat = num % v_top;
+        while q_hat >> 64 != 0 || q_hat * v_next > ((r_hat << 64) | u[j + n - 2] as u128) {
+            q_hat -= 1;
+            r_hat += v_top;
+            if r_hat >> 64 != 0 {
+                break;
+            }
+        }
+
+        // Multiply and subtract `q_hat·v` from the current window of `u`.
+        let mut carry = 0;
+        let mut borrow = false;
+        for i in 0..n {
+            let lo;
+            (lo, carry) = v[i].carrying_mul_add(q_hat as u64, carry, 0);
+            (u[j + i], borrow) = u[j + i].borrowing_sub(lo, borrow);
+        }
+        (u[j + n], borrow) = u[j + n].borrowing_sub(carry, borrow);
+
+        if borrow {
+            // The estimate was one too large; add the divisor back. The carry
+            // out of the window cancels the borrow above.
+            q_hat -= 1;
+            add_in_place(&mut u[j..=j + n], &v);
+        }
+        q[j] = q_hat as u64;
+    }
+
+    u.truncate(n);
+    let mut r = shr(&u, shift);
+    trim(&mut r);
+    trim(&mut q);
+    (q, r)
+}
+
+/// Returns `a << bits`.
+pub(super) fn shl(a: &[u64], bits: u64) -> Vec<u64> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let limbs = usize::try_from(bits / 64).expect("shift amount too large");
+    let bits = (bits % 64) as u32;
+    let mut out = Vec::with_capacity(limbs + a.len() + 1);
+    out.resize(limbs, 0);
+    if bits == 0 {
+        out.extend_from_slice(a);
+    } else {
+        let mut carry = 0;
+        for &x in a {
+            out.push((x << bits) | carry);
+            carry = x >> (64 - bits);
+        }
+        out.push(carry);
+    }
+    trim(&mut out);
+    out
+}
+
+/// Returns `a >> bits`.
+pub(super) fn shr(a: &[u64], bits: u64) -> Vec<u64> {
+    let limbs = usize::try_from(bits / 64).unwrap_or(usize::MAX);
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let bits = (bits % 64) as u32;
+    let a = &a[limbs..];
+    let mut out = Vec::with_capacity(a.len());
+    if bits == 0 {
+        out.extend_from_slice(a);
+    } else {
+        for (i, &x) in a.iter().enumerate() {
+            let hi = a.get(i + 1).map_or(0, |&y| y << (64 - bits));
+            out.push((x >> bits) | hi);
+        }
+    }
+    trim(&mut out);
+    out
+}
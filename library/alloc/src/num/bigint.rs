@@ -0,0 +1,511 @@
+use core::cmp::Ordering;
+use core::iter::{Product, Sum};
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Neg, Not, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
+};
+use core::str::FromStr;
+use core::{fmt, iter};
+
+use super::{BigUint, ParseBigIntError, forward_binop, radix};
+use crate::string::String;
+use crate::vec::Vec;
+
+/// An arbitrary-precision signed integer.
+///
+/// A `BigInt` is stored as a sign and a [`BigUint`] magnitude. Arithmetic
+/// follows the primitive signed integers wherever they are defined: division
+/// truncates towards zero, the remainder takes the sign of the dividend,
+/// right shifts round towards negative infinity, and the bitwise operators act
+/// as if on an infinitely sign-extended two's complement representation.
+///
+/// Formatting in a non-decimal radix (`{:x}`, `{:b}`, ...) prints the sign
+/// followed by the magnitude, e.g. `-ff`, since there is no finite two's
+/// complement representation to print.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(bigint)]
+/// use std::num::BigInt;
+///
+/// let a = BigInt::from(i128::MIN);
+/// let b = &a * &a;
+/// assert_eq!(b.to_string(), "28948022309329048855892746252171976963317496166410141009864396001978282409984");
+/// assert_eq!(-(&b / &a), BigInt::from(i128::MAX) + BigInt::from(1));
+/// assert_eq!(BigInt::from(-7) / BigInt::from(2), BigInt::from(-3));
+/// assert_eq!(BigInt::from(-7) >> 1, BigInt::from(-4));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[unstable(feature = "bigint", issue = "none")]
+pub struct BigInt {
+    // Zero is never negative, so equal values have equal representations.
+    negative: bool,
+    magnitude: BigUint,
+}
+
+impl BigInt {
+    /// Creates a `BigInt` equal to zero, without allocating.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub const fn new() -> BigInt {
+        BigInt { negative: false, magnitude: BigUint::new() }
+    }
+
+    /// Creates a `BigInt` from a sign and a magnitude.
+    ///
+    /// If `magnitude` is zero, the result is zero regardless of `negative`.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn from_parts(negative: bool, magnitude: BigUint) -> BigInt {
+        BigInt { negative: negative && !magnitude.is_zero(), magnitude }
+    }
+
+    /// Splits `self` into its sign and magnitude. The sign is `true` if `self`
+    /// is negative.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn into_parts(self) -> (bool, BigUint) {
+        (self.negative, self.magnitude)
+    }
+
+    /// Returns the absolute value of `self` as a [`BigUint`].
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    /// Returns `true` if `self` is zero.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    /// Returns `true` if `self` is less than zero.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns `true` if `self` is greater than zero.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn is_positive(&self) -> bool {
+        !self.negative && !self.magnitude.is_zero()
+    }
+
+    /// Returns a number representing the sign of `self`: `-1`, `0` or `1`.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn signum(&self) -> BigInt {
+        match (self.negative, self.is_zero()) {
+            (true, _) => BigInt::from(-1),
+            (false, true) => BigInt::new(),
+            (false, false) => BigInt::from(1),
+        }
+    }
+
+    /// Returns the absolute value of `self`.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, magnitude: self.magnitude.clone() }
+    }
+
+    /// Returns the number of bits needed to represent the magnitude of `self`.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn bits(&self) -> u64 {
+        self.magnitude.bits()
+    }
+
+    /// Parses a `BigInt` from a string of digits in the given radix, with an
+    /// optional leading `+` or `-` sign.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigInt;
+    ///
+    /// assert_eq!(BigInt::from_str_radix("-ff", 16), Ok(BigInt::from(-255)));
+    /// ```
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<BigInt, ParseBigIntError> {
+        let (negative, digits) = match src.as_bytes() {
+            [b'-', rest @ ..] => (true, rest),
+            [b'+', rest @ ..] => (false, rest),
+            digits => (false, digits),
+        };
+        let limbs = radix::parse(digits, radix)?;
+        Ok(BigInt::from_parts(negative, BigUint::from_limbs(limbs)))
+    }
+
+    /// Formats `self` as a string of lowercase digits in the given radix,
+    /// preceded by `-` if it is negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        let digits = self.magnitude.to_str_radix(radix);
+        if self.negative { ["-", &digits].concat() } else { digits }
+    }
+
+    /// Returns `self` as an `i64`, or `None` if it does not fit.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.magnitude.to_u64()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// Returns `self` as an `i128`, or `None` if it does not fit.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_i128(&self) -> Option<i128> {
+        let magnitude = self.magnitude.to_u128()?;
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    /// Raises `self` to the power of `exp`, using exponentiation by squaring.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn pow(&self, exp: u32) -> BigInt {
+        BigInt::from_parts(self.negative && exp % 2 == 1, self.magnitude.pow(exp))
+    }
+
+    /// Computes the truncated quotient and remainder of `self / rhs` at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` is zero.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn div_rem(&self, rhs: &BigInt) -> (BigInt, BigInt) {
+        let (q, r) = self.magnitude.div_rem(&rhs.magnitude);
+        (BigInt::from_parts(self.negative != rhs.negative, q), BigInt::from_parts(self.negative, r))
+    }
+
+    /// The two's complement of `self`, sign-extended to `len` limbs.
+    fn to_twos_complement(&self, len: usize) -> Vec<u64> {
+        let mut limbs = self.magnitude.limbs().to_vec();
+        limbs.resize(len, 0);
+        if self.negative {
+            negate(&mut limbs);
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u64>) -> BigInt {
+        let negative = limbs.last().is_some_and(|&top| top >> 63 == 1);
+        if negative {
+            negate(&mut limbs);
+        }
+        BigInt::from_parts(negative, BigUint::from_limbs(limbs))
+    }
+}
+
+/// Negates a two's complement number in place.
+fn negate(limbs: &mut [u64]) {
+    let mut carry = true;
+    for x in limbs {
+        (*x, carry) = (!*x).overflowing_add(carry as u64);
+    }
+}
+
+fn add_signed(a_negative: bool, a: &BigUint, b_negative: bool, b: &BigUint) -> BigInt {
+    if a_negative == b_negative {
+        return BigInt::from_parts(a_negative, a + b);
+    }
+    match a.cmp(b) {
+        Ordering::Less => BigInt::from_parts(b_negative, b - a),
+        Ordering::Equal => BigInt::new(),
+        Ordering::Greater => BigInt::from_parts(a_negative, a - b),
+    }
+}
+
+fn add(a: &BigInt, b: &BigInt) -> BigInt {
+    add_signed(a.negative, &a.magnitude, b.negative, &b.magnitude)
+}
+
+fn sub(a: &BigInt, b: &BigInt) -> BigInt {
+    add_signed(a.negative, &a.magnitude, !b.negative, &b.magnitude)
+}
+
+fn mul(a: &BigInt, b: &BigInt) -> BigInt {
+    BigInt::from_parts(a.negative != b.negative, &a.magnitude * &b.magnitude)
+}
+
+fn div(a: &BigInt, b: &BigInt) -> BigInt {
+    a.div_rem(b).0
+}
+
+fn rem(a: &BigInt, b: &BigInt) -> BigInt {
+    a.div_rem(b).1
+}
+
+fn bitwise(a: &BigInt, b: &BigInt, op: impl Fn(u64, u64) -> u64) -> BigInt {
+    // One extra limb guarantees room for the sign bit.
+    let len = a.magnitude.limbs().len().max(b.magnitude.limbs().len()) + 1;
+    let (x, y) = (a.to_twos_complement(len), b.to_twos_complement(len));
+    BigInt::from_twos_complement(iter::zip(x, y).map(|(x, y)| op(x, y)).collect())
+}
+
+fn bitand(a: &BigInt, b: &BigInt) -> BigInt {
+    bitwise(a, b, |x, y| x & y)
+}
+
+fn bitor(a: &BigInt, b: &BigInt) -> BigInt {
+    bitwise(a, b, |x, y| x | y)
+}
+
+fn bitxor(a: &BigInt, b: &BigInt) -> BigInt {
+    bitwise(a, b, |x, y| x ^ y)
+}
+
+forward_binop!(BigInt, Add::add, AddAssign::add_assign, add);
+forward_binop!(BigInt, Sub::sub, SubAssign::sub_assign, sub);
+forward_binop!(BigInt, Mul::mul, MulAssign::mul_assign, mul);
+forward_binop!(BigInt, Div::div, DivAssign::div_assign, div);
+forward_binop!(BigInt, Rem::rem, RemAssign::rem_assign, rem);
+forward_binop!(BigInt, BitAnd::bitand, BitAndAssign::bitand_assign, bitand);
+forward_binop!(BigInt, BitOr::bitor, BitOrAssign::bitor_assign, bitor);
+forward_binop!(BigInt, BitXor::bitxor, BitXorAssign::bitxor_assign, bitxor);
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Not for BigInt {
+    type Output = BigInt;
+
+    fn not(self) -> BigInt {
+        !&self
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Not for &BigInt {
+    type Output = BigInt;
+
+    /// Returns `-self - 1`, the two's complement bitwise negation.
+    fn not(self) -> BigInt {
+        add_signed(!self.negative, &self.magnitude, true, &BigUint::from(1u8))
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shl<u64> for &BigInt {
+    type Output = BigInt;
+
+    fn shl(self, bits: u64) -> BigInt {
+        BigInt::from_parts(self.negative, &self.magnitude << bits)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shl<u64> for BigInt {
+    type Output = BigInt;
+
+    fn shl(self, bits: u64) -> BigInt {
+        &self << bits
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl ShlAssign<u64> for BigInt {
+    fn shl_assign(&mut self, bits: u64) {
+        *self = &*self << bits;
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shr<u64> for &BigInt {
+    type Output = BigInt;
+
+    /// Arithmetic right shift, rounding towards negative infinity.
+    fn shr(self, bits: u64) -> BigInt {
+        if self.negative {
+            // floor(-m / 2^n) == -(((m - 1) >> n) + 1)
+            let one = BigUint::from(1u8);
+            BigInt::from_parts(true, ((&self.magnitude - &one) >> bits) + one)
+        } else {
+            BigInt::from_parts(false, &self.magnitude >> bits)
+        }
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shr<u64> for BigInt {
+    type Output = BigInt;
+
+    fn shr(self, bits: u64) -> BigInt {
+        &self >> bits
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl ShrAssign<u64> for BigInt {
+    fn shr_assign(&mut self, bits: u64) {
+        *self = &*self >> bits;
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl PartialOrd for BigInt {
+    #[inline]
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Sum for BigInt {
+    fn sum<I: Iterator<Item = BigInt>>(iter: I) -> BigInt {
+        iter.fold(BigInt::new(), |acc, x| acc + x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl<'a> Sum<&'a BigInt> for BigInt {
+    fn sum<I: Iterator<Item = &'a BigInt>>(iter: I) -> BigInt {
+        iter.fold(BigInt::new(), |acc, x| acc + x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Product for BigInt {
+    fn product<I: Iterator<Item = BigInt>>(iter: I) -> BigInt {
+        iter.fold(BigInt::from(1), |acc, x| acc * x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl<'a> Product<&'a BigInt> for BigInt {
+    fn product<I: Iterator<Item = &'a BigInt>>(iter: I) -> BigInt {
+        iter.fold(BigInt::from(1), |acc, x| acc * x)
+    }
+}
+
+macro_rules! from_primitive {
+    ($($t:ty)*) => {$(
+        #[unstable(feature = "bigint", issue = "none")]
+        impl From<$t> for BigInt {
+            #[inline]
+            #[allow(unused_comparisons)]
+            fn from(n: $t) -> BigInt {
+                BigInt::from_parts(n < 0, BigUint::from(n.unsigned_abs()))
+            }
+        }
+    )*};
+}
+
+from_primitive!(i8 i16 i32 i64 i128 isize);
+
+macro_rules! from_unsigned {
+    ($($t:ty)*) => {$(
+        #[unstable(feature = "bigint", issue = "none")]
+        impl From<$t> for BigInt {
+            #[inline]
+            fn from(n: $t) -> BigInt {
+                BigInt::from(BigUint::from(n))
+            }
+        }
+    )*};
+}
+
+from_unsigned!(u8 u16 u32 u64 u128 usize);
+
+#[unstable(feature = "bigint", issue = "none")]
+impl From<BigUint> for BigInt {
+    #[inline]
+    fn from(magnitude: BigUint) -> BigInt {
+        BigInt { negative: false, magnitude }
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(src: &str) -> Result<BigInt, ParseBigIntError> {
+        BigInt::from_str_radix(src, 10)
+    }
+}
+
+macro_rules! fmt_radix {
+    ($($Trait:ident, $radix:literal, $prefix:literal, $upper:literal;)*) => {$(
+        #[unstable(feature = "bigint", issue = "none")]
+        impl fmt::$Trait for BigInt {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let digits = radix::to_string(self.magnitude.limbs(), $radix, $upper);
+                f.pad_integral(!self.negative, $prefix, &digits)
+            }
+        }
+    )*};
+}
+
+fmt_radix! {
+    Display, 10, "", false;
+    Binary, 2, "0b", false;
+    Octal, 8, "0o", false;
+    LowerHex, 16, "0x", false;
+    UpperHex, 16, "0x", true;
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
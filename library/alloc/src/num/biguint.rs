@@ -0,0 +1,585 @@
+use core::cmp::Ordering;
+use core::iter::{Product, Sum};
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
+};
+use core::str::FromStr;
+use core::{fmt, iter};
+
+use super::{ParseBigIntError, arith, forward_binop, radix};
+use crate::string::String;
+use crate::vec::Vec;
+
+/// An arbitrary-precision unsigned integer.
+///
+/// The value is stored on the heap as little-endian 64-bit limbs, without
+/// leading zeros, so equal values always have equal representations.
+///
+/// Arithmetic operators are implemented for every combination of owned and
+/// borrowed operands. Borrowing avoids consuming values that are still
+/// needed, e.g. `&a + &b`.
+///
+/// # Panics
+///
+/// Subtraction panics if the result would be negative, and division and
+/// remainder panic if the divisor is zero. Use [`checked_sub`] and
+/// [`checked_div`] for non-panicking variants.
+///
+/// [`checked_sub`]: BigUint::checked_sub
+/// [`checked_div`]: BigUint::checked_div
+///
+/// # Examples
+///
+/// ```
+/// #![feature(bigint)]
+/// use std::num::BigUint;
+///
+/// let a: BigUint = "123456789012345678901234567890".parse().unwrap();
+/// let b = BigUint::from(987654321u32);
+/// let product = &a * &b;
+/// assert_eq!(product.to_string(), "121932631124828532112482853211126352690");
+/// assert_eq!(product / b, a);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[unstable(feature = "bigint", issue = "none")]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    /// Creates a `BigUint` equal to zero, without allocating.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub const fn new() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub(super) fn from_limbs(mut limbs: Vec<u64>) -> BigUint {
+        arith::trim(&mut limbs);
+        BigUint { limbs }
+    }
+
+    pub(super) fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+
+    /// Returns `true` if `self` is zero.
+    #[inline]
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns the number of bits needed to represent `self`, which is zero
+    /// for zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::new().bits(), 0);
+    /// assert_eq!(BigUint::from(255u8).bits(), 8);
+    /// assert_eq!((BigUint::from(1u8) << 100).bits(), 101);
+    /// ```
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 64 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Returns the value of bit `n`, where bit 0 is the least significant.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn bit(&self, n: u64) -> bool {
+        usize::try_from(n / 64)
+            .ok()
+            .and_then(|i| self.limbs.get(i))
+            .is_some_and(|limb| limb >> (n % 64) & 1 == 1)
+    }
+
+    /// Returns the number of trailing zero bits, or `None` if `self` is zero.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn trailing_zeros(&self) -> Option<u64> {
+        let i = self.limbs.iter().position(|&limb| limb != 0)?;
+        Some(i as u64 * 64 + self.limbs[i].trailing_zeros() as u64)
+    }
+
+    /// Returns the number of ones in the binary representation of `self`.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn count_ones(&self) -> u64 {
+        self.limbs.iter().map(|limb| limb.count_ones() as u64).sum()
+    }
+
+    /// Creates a `BigUint` from its little-endian byte representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::from_bytes_le(&[0x34, 0x12]), BigUint::from(0x1234u16));
+    /// ```
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn from_bytes_le(bytes: &[u8]) -> BigUint {
+        let limbs = bytes
+            .chunks(8)
+            .map(|chunk| {
+                let mut buf = [0; 8];
+                buf[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(buf)
+            })
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Creates a `BigUint` from its big-endian byte representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::from_bytes_be(&[0x12, 0x34]), BigUint::from(0x1234u16));
+    /// ```
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn from_bytes_be(bytes: &[u8]) -> BigUint {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| {
+                let mut buf = [0; 8];
+                buf[8 - chunk.len()..].copy_from_slice(chunk);
+                u64::from_be_bytes(buf)
+            })
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns the little-endian byte representation of `self`, without
+    /// trailing zero bytes. Zero is represented by an empty vector.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        while let Some(&0) = bytes.last() {
+            bytes.pop();
+        }
+        bytes
+    }
+
+    /// Returns the big-endian byte representation of `self`, without leading
+    /// zero bytes. Zero is represented by an empty vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::from(0x1234u16).to_bytes_be(), [0x12, 0x34]);
+    /// ```
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Parses a `BigUint` from a string of digits in the given radix.
+    ///
+    /// The string may start with a `+` sign. Digits are `0-9`, `a-z` and
+    /// `A-Z`, as for [`u64::from_str_radix`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// let n = BigUint::from_str_radix("ffffffffffffffffffffffffffffffff", 16).unwrap();
+    /// assert_eq!(n, BigUint::from(u128::MAX));
+    /// assert!(BigUint::from_str_radix("-1", 10).is_err());
+    /// ```
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<BigUint, ParseBigIntError> {
+        let digits = src.as_bytes();
+        let digits = match digits {
+            [b'+', rest @ ..] => rest,
+            _ => digits,
+        };
+        radix::parse(digits, radix).map(BigUint::from_limbs)
+    }
+
+    /// Formats `self` as a string of lowercase digits in the given radix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::from(35u8).to_str_radix(36), "z");
+    /// assert_eq!(BigUint::from(u128::MAX).to_str_radix(32), "7vvvvvvvvvvvvvvvvvvvvvvvvv");
+    /// ```
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        radix::to_string(&self.limbs, radix, false)
+    }
+
+    /// Returns `self` as a `u64`, or `None` if it does not fit.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_u64(&self) -> Option<u64> {
+        match *self.limbs {
+            [] => Some(0),
+            [a] => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns `self` as a `u128`, or `None` if it does not fit.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn to_u128(&self) -> Option<u128> {
+        match *self.limbs {
+            [] => Some(0),
+            [a] => Some(a as u128),
+            [a, b] => Some((b as u128) << 64 | a as u128),
+            _ => None,
+        }
+    }
+
+    /// Raises `self` to the power of `exp`, using exponentiation by squaring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// assert_eq!(BigUint::from(10u8).pow(30).to_string(), "1000000000000000000000000000000");
+    /// ```
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn pow(&self, mut exp: u32) -> BigUint {
+        let mut base = self.clone();
+        let mut acc = BigUint::from(1u8);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = &acc * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        acc
+    }
+
+    /// Computes `self.pow(exponent) % modulus` without materializing the full
+    /// power.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// let base = BigUint::from(4u8);
+    /// let exp = BigUint::from(13u8);
+    /// let modulus = BigUint::from(497u16);
+    /// assert_eq!(base.modpow(&exp, &modulus), BigUint::from(445u16));
+    /// ```
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn modpow(&self, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "attempt to calculate the remainder with a divisor of zero");
+        let mut base = self % modulus;
+        let mut acc = BigUint::from(1u8) % modulus;
+        for i in 0..exponent.bits() {
+            if exponent.bit(i) {
+                acc = &acc * &base % modulus;
+            }
+            if i + 1 < exponent.bits() {
+                base = &base * &base % modulus;
+            }
+        }
+        acc
+    }
+
+    /// Computes the quotient and remainder of `self / rhs` at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(bigint)]
+    /// use std::num::BigUint;
+    ///
+    /// let (q, r) = BigUint::from(17u8).div_rem(&BigUint::from(5u8));
+    /// assert_eq!((q, r), (BigUint::from(3u8), BigUint::from(2u8)));
+    /// ```
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn div_rem(&self, rhs: &BigUint) -> (BigUint, BigUint) {
+        let (q, r) = arith::div_rem(&self.limbs, &rhs.limbs);
+        (BigUint { limbs: q }, BigUint { limbs: r })
+    }
+
+    /// Checked subtraction. Returns `None` if `rhs` is greater than `self`.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn checked_sub(&self, rhs: &BigUint) -> Option<BigUint> {
+        if *self < *rhs {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        arith::sub_assign(&mut limbs, &rhs.limbs);
+        Some(BigUint { limbs })
+    }
+
+    /// Checked division. Returns `None` if `rhs` is zero.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn checked_div(&self, rhs: &BigUint) -> Option<BigUint> {
+        if rhs.is_zero() { None } else { Some(self / rhs) }
+    }
+}
+
+fn add(a: &BigUint, b: &BigUint) -> BigUint {
+    let mut limbs = a.limbs.clone();
+    arith::add_assign(&mut limbs, &b.limbs);
+    BigUint { limbs }
+}
+
+fn sub(a: &BigUint, b: &BigUint) -> BigUint {
+    a.checked_sub(b).expect("attempt to subtract with overflow")
+}
+
+fn mul(a: &BigUint, b: &BigUint) -> BigUint {
+    BigUint { limbs: arith::mul(&a.limbs, &b.limbs) }
+}
+
+fn div(a: &BigUint, b: &BigUint) -> BigUint {
+    a.div_rem(b).0
+}
+
+fn rem(a: &BigUint, b: &BigUint) -> BigUint {
+    a.div_rem(b).1
+}
+
+fn bitand(a: &BigUint, b: &BigUint) -> BigUint {
+    BigUint::from_limbs(iter::zip(&a.limbs, &b.limbs).map(|(x, y)| x & y).collect())
+}
+
+fn bitor(a: &BigUint, b: &BigUint) -> BigUint {
+    let (long, short) = if a.limbs.len() >= b.limbs.len() { (a, b) } else { (b, a) };
+    let mut limbs = long.limbs.clone();
+    iter::zip(&mut limbs, &short.limbs).for_each(|(x, y)| *x |= y);
+    BigUint { limbs }
+}
+
+fn bitxor(a: &BigUint, b: &BigUint) -> BigUint {
+    let (long, short) = if a.limbs.len() >= b.limbs.len() { (a, b) } else { (b, a) };
+    let mut limbs = long.limbs.clone();
+    iter::zip(&mut limbs, &short.limbs).for_each(|(x, y)| *x ^= y);
+    BigUint::from_limbs(limbs)
+}
+
+forward_binop!(BigUint, Add::add, AddAssign::add_assign, add);
+forward_binop!(BigUint, Sub::sub, SubAssign::sub_assign, sub);
+forward_binop!(BigUint, Mul::mul, MulAssign::mul_assign, mul);
+forward_binop!(BigUint, Div::div, DivAssign::div_assign, div);
+forward_binop!(BigUint, Rem::rem, RemAssign::rem_assign, rem);
+forward_binop!(BigUint, BitAnd::bitand, BitAndAssign::bitand_assign, bitand);
+forward_binop!(BigUint, BitOr::bitor, BitOrAssign::bitor_assign, bitor);
+forward_binop!(BigUint, BitXor::bitxor, BitXorAssign::bitxor_assign, bitxor);
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shl<u64> for &BigUint {
+    type Output = BigUint;
+
+    fn shl(self, bits: u64) -> BigUint {
+        BigUint { limbs: arith::shl(&self.limbs, bits) }
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shl<u64> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, bits: u64) -> BigUint {
+        &self << bits
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl ShlAssign<u64> for BigUint {
+    fn shl_assign(&mut self, bits: u64) {
+        *self = &*self << bits;
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shr<u64> for &BigUint {
+    type Output = BigUint;
+
+    fn shr(self, bits: u64) -> BigUint {
+        BigUint { limbs: arith::shr(&self.limbs, bits) }
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Shr<u64> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, bits: u64) -> BigUint {
+        &self >> bits
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl ShrAssign<u64> for BigUint {
+    fn shr_assign(&mut self, bits: u64) {
+        *self = &*self >> bits;
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl PartialOrd for BigUint {
+    #[inline]
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Ord for BigUint {
+    #[inline]
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        arith::cmp(&self.limbs, &other.limbs)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::new(), |acc, x| acc + x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl<'a> Sum<&'a BigUint> for BigUint {
+    fn sum<I: Iterator<Item = &'a BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::new(), |acc, x| acc + x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Product for BigUint {
+    fn product<I: Iterator<Item = BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::from(1u8), |acc, x| acc * x)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl<'a> Product<&'a BigUint> for BigUint {
+    fn product<I: Iterator<Item = &'a BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::from(1u8), |acc, x| acc * x)
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty)*) => {$(
+        #[unstable(feature = "bigint", issue = "none")]
+        impl From<$t> for BigUint {
+            #[inline]
+            fn from(n: $t) -> BigUint {
+                BigUint::from(n as u64)
+            }
+        }
+    )*};
+}
+
+from_unsigned!(u8 u16 u32 usize);
+
+#[unstable(feature = "bigint", issue = "none")]
+impl From<u64> for BigUint {
+    #[inline]
+    fn from(n: u64) -> BigUint {
+        BigUint::from_limbs(vec![n])
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl From<u128> for BigUint {
+    #[inline]
+    fn from(n: u128) -> BigUint {
+        BigUint::from_limbs(vec![n as u64, (n >> 64) as u64])
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl FromStr for BigUint {
+    type Err = ParseBigIntError;
+
+    fn from_str(src: &str) -> Result<BigUint, ParseBigIntError> {
+        BigUint::from_str_radix(src, 10)
+    }
+}
+
+macro_rules! fmt_radix {
+    ($($Trait:ident, $radix:literal, $prefix:literal, $upper:literal;)*) => {$(
+        #[unstable(feature = "bigint", issue = "none")]
+        impl fmt::$Trait for BigUint {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad_integral(true, $prefix, &radix::to_string(&self.limbs, $radix, $upper))
+            }
+        }
+    )*};
+}
+
+fmt_radix! {
+    Display, 10, "", false;
+    Binary, 2, "0b", false;
+    Octal, 8, "0o", false;
+    LowerHex, 16, "0x", false;
+    UpperHex, 16, "0x", true;
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,146 @@
+//! Arbitrary-precision integers.
+//!
+//! [`BigUint`] and [`BigInt`] store their magnitude on the heap and grow as
+//! needed, so their arithmetic never overflows. They support the usual
+//! arithmetic, comparison, bitwise and shift operators, and can be parsed from
+//! and formatted to strings in any radix from 2 to 36.
+//!
+//! Multiplication switches from the schoolbook method to Karatsuba's algorithm
+//! for large operands, and division uses Knuth's Algorithm D.
+//!
+//! # Examples
+//!
+//! ```
+//! #![feature(bigint)]
+//! use std::num::BigUint;
+//!
+//! // 2^128 does not fit in any primitive integer.
+//! let big = BigUint::from(u128::MAX) + BigUint::from(1u8);
+//! assert_eq!(big.to_string(), "340282366920938463463374607431768211456");
+//! assert_eq!(big, BigUint::from(1u8) << 128);
+//! ```
+
+#![unstable(feature = "bigint", issue = "none")]
+
+use core::error::Error;
+use core::fmt;
+use core::num::IntErrorKind;
+
+#[unstable(feature = "bigint", issue = "none")]
+pub use self::bigint::BigInt;
+#[unstable(feature = "bigint", issue = "none")]
+pub use self::biguint::BigUint;
+
+mod arith;
+mod bigint;
+mod biguint;
+mod radix;
+
+/// Implements a binary operator and its assigning form for every combination
+/// of owned and borrowed operands, in terms of a function taking two borrows.
+macro_rules! forward_binop {
+    ($T:ident, $Op:ident :: $op:ident, $OpAssign:ident :: $op_assign:ident, $imp:path) => {
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $Op<&$T> for &$T {
+            type Output = $T;
+
+            #[inline]
+            fn $op(self, rhs: &$T) -> $T {
+                $imp(self, rhs)
+            }
+        }
+
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $Op<$T> for &$T {
+            type Output = $T;
+
+            #[inline]
+            fn $op(self, rhs: $T) -> $T {
+                $imp(self, &rhs)
+            }
+        }
+
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $Op<&$T> for $T {
+            type Output = $T;
+
+            #[inline]
+            fn $op(self, rhs: &$T) -> $T {
+                $imp(&self, rhs)
+            }
+        }
+
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $Op<$T> for $T {
+            type Output = $T;
+
+            #[inline]
+            fn $op(self, rhs: $T) -> $T {
+                $imp(&self, &rhs)
+            }
+        }
+
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $OpAssign<&$T> for $T {
+            #[inline]
+            fn $op_assign(&mut self, rhs: &$T) {
+                *self = $imp(self, rhs);
+            }
+        }
+
+        #[unstable(feature = "bigint", issue = "none")]
+        impl $OpAssign<$T> for $T {
+            #[inline]
+            fn $op_assign(&mut self, rhs: $T) {
+                *self = $imp(self, &rhs);
+            }
+        }
+    };
+}
+use forward_binop;
+
+/// An error which can be returned when parsing a [`BigUint`] or [`BigInt`].
+///
+/// This error is used as the error type for the `from_str_radix` functions
+/// and the [`FromStr`](core::str::FromStr) implementations of both types.
+///
+/// # Example
+///
+/// ```
+/// #![feature(bigint)]
+/// use std::num::{BigInt, IntErrorKind};
+///
+/// let err = "12a".parse::<BigInt>().unwrap_err();
+/// assert_eq!(err.kind(), &IntErrorKind::InvalidDigit);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[unstable(feature = "bigint", issue = "none")]
+pub struct ParseBigIntError {
+    kind: IntErrorKind,
+}
+
+impl ParseBigIntError {
+    /// Outputs the detailed cause of parsing a big integer failing.
+    ///
+    /// This is either [`IntErrorKind::Empty`] or [`IntErrorKind::InvalidDigit`];
+    /// big integers cannot overflow.
+    #[must_use]
+    #[unstable(feature = "bigint", issue = "none")]
+    pub fn kind(&self) -> &IntErrorKind {
+        &self.kind
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            IntErrorKind::Empty => "cannot parse integer from empty string",
+            _ => "invalid digit found in string",
+        }
+        .fmt(f)
+    }
+}
+
+#[unstable(feature = "bigint", issue = "none")]
+impl Error for ParseBigIntError {}
@@ -0,0 +1,101 @@
+//! Conversion between limbs and digit strings.
+
+use core::num::IntErrorKind;
+
+use super::{ParseBigIntError, arith};
+use crate::string::String;
+use crate::vec::Vec;
+
+/// Returns the largest power of `radix` that fits in a limb, and its exponent.
+fn big_base(radix: u32) -> (u64, usize) {
+    let radix = radix as u64;
+    let mut base = radix;
+    let mut digits = 1;
+    while let Some(next) = base.checked_mul(radix) {
+        base = next;
+        digits += 1;
+    }
+    (base, digits)
+}
+
+fn check_radix(radix: u32) {
+    assert!((2..=36).contains(&radix), "radix must lie in the range `[2, 36]` - found {radix}");
+}
+
+/// Parses unsigned digits (without a sign) into normalized limbs.
+pub(super) fn parse(digits: &[u8], radix: u32) -> Result<Vec<u64>, ParseBigIntError> {
+    check_radix(radix);
+    if digits.is_empty() {
+        return Err(ParseBigIntError { kind: IntErrorKind::Empty });
+    }
+
+    let (base, chunk_len) = big_base(radix);
+    let mut limbs = Vec::new();
+    // The first chunk is short so that every later chunk is exactly
+    // `chunk_len` digits, i.e. scales the accumulator by `base`.
+    let first = match digits.len() % chunk_len {
+        0 => chunk_len,
+        n => n,
+    };
+    let (head, tail) = digits.split_at(first);
+    for chunk in [head].into_iter().chain(tail.chunks(chunk_len)) {
+        let mut value = 0u64;
+        for &c in chunk {
+            let digit = (c as char)
+                .to_digit(radix)
+                .ok_or(ParseBigIntError { kind: IntErrorKind::InvalidDigit })?;
+            value = value * radix as u64 + digit as u64;
+        }
+        let scale =
+            if chunk.len() == chunk_len { base } else { (radix as u64).pow(chunk.len() as u32) };
+        arith::mul_limb_add_assign(&mut limbs, scale, value);
+    }
+    Ok(limbs)
+}
+
+/// Formats normalized limbs as digits in `radix`, without a sign or prefix.
+pub(super) fn to_string(limbs: &[u64], radix: u32, upper: bool) -> String {
+    check_radix(radix);
+    if limbs.is_empty() {
+        return String::from("0");
+    }
+
+    let digit = |d: u64| {
+        let c = char::from_digit(d as u32, radix).unwrap();
+        if upper { c.to_ascii_uppercase() } else { c }
+    };
+
+    let mut out = Vec::new();
+    if radix.is_power_of_two() {
+        // Every digit covers a fixed number of bits, so read them off directly.
+        let bits_per_digit = radix.trailing_zeros() as u64;
+        let mask = radix as u64 - 1;
+        let total_bits = limbs.len() as u64 * 64 - limbs[limbs.len() - 1].leading_zeros() as u64;
+        let mut bit = 0;
+        while bit < total_bits {
+            let (limb, offset) = ((bit / 64) as usize, (bit % 64) as u32);
+            let mut d = limbs[limb] >> offset;
+            if offset as u64 + bits_per_digit > 64 && limb + 1 < limbs.len() {
+                d |= limbs[limb + 1] << (64 - offset);
+            }
+            out.push(digit(d & mask));
+            bit += bits_per_digit;
+        }
+    } else {
+        // Peel off chunks of `chunk_len` digits, least significant first.
+        let (base, chunk_len) = big_base(radix);
+        let mut rest = limbs.to_vec();
+        while !rest.is_empty() {
+            let mut chunk = arith::div_rem_limb(&mut rest, base);
+            for _ in 0..chunk_len {
+                if rest.is_empty() && chunk == 0 {
+                    break;
+                }
+                out.push(digit(chunk % radix as u64));
+                chunk /= radix as u64;
+            }
+        }
+    }
+
+    out.iter().rev().collect()
+}
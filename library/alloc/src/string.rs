@@ -56,6 +56,9 @@
 use core::str::pattern::{Pattern, Utf8Pattern};
 use core::{fmt, hash, ptr, slice};
 
+#[cfg(not(no_global_oom_handling))]
+#[unstable(feature = "small_string", issue = "none")]
+pub use self::small_string::SmallString;
 #[cfg(not(no_global_oom_handling))]
 use crate::alloc::Allocator;
 #[cfg(not(no_global_oom_handling))]
@@ -67,6 +70,9 @@
 use crate::str::{FromStr, from_boxed_utf8_unchecked};
 use crate::vec::{self, Vec};
 
+#[cfg(not(no_global_oom_handling))]
+pub mod small_string;
+
 /// A UTF-8–encoded, growable string.
 ///
 /// `String` is the most common string type. It has ownership over the contents
@@ -0,0 +1,729 @@
+//! A UTF-8–encoded string that stores short contents inline, written
+//! `SmallString<N>`.
+//!
+//! A `SmallString<N>` keeps up to `N` bytes inside the value itself and only
+//! moves them to a heap allocation once that capacity is exceeded. It is backed
+//! by a [`SmallVec<u8, N>`] the same way [`String`] is backed by a [`Vec<u8>`].
+//!
+//! # Examples
+//!
+//! ```
+//! #![feature(small_string)]
+//! use std::string::SmallString;
+//!
+//! let mut s: SmallString<8> = SmallString::from("hello");
+//! assert!(!s.spilled());
+//!
+//! s.push_str(", world");
+//! assert!(s.spilled());
+//! assert_eq!(s, "hello, world");
+//! ```
+
+#![unstable(feature = "small_string", issue = "none")]
+
+use core::borrow::{Borrow, BorrowMut};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::{self, Range, RangeBounds};
+use core::str::{Chars, FromStr};
+use core::{fmt, ptr, slice, str};
+
+use super::String;
+use crate::vec::SmallVec;
+
+/// A UTF-8–encoded, growable string that stores up to `N` bytes inline.
+///
+/// See the [module-level documentation](self) for more.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SmallString<const N: usize> {
+    vec: SmallVec<u8, N>,
+}
+
+impl<const N: usize> SmallString<N> {
+    /// Creates a new empty `SmallString<N>` using its inline storage.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        SmallString { vec: SmallVec::new() }
+    }
+
+    /// Creates a new empty `SmallString<N>` with at least the specified capacity in bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        SmallString { vec: SmallVec::with_capacity(capacity) }
+    }
+
+    /// Converts a vector of bytes to a `SmallString` without checking that the
+    /// bytes are valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The bytes passed in must be valid UTF-8.
+    #[inline]
+    #[must_use]
+    pub unsafe fn from_utf8_unchecked(bytes: SmallVec<u8, N>) -> Self {
+        SmallString { vec: bytes }
+    }
+
+    /// Converts a vector of bytes to a `SmallString`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the slice is not UTF-8, with a description as to why the
+    /// provided bytes are not UTF-8. The vector is dropped in that case.
+    pub fn from_utf8(bytes: SmallVec<u8, N>) -> Result<Self, str::Utf8Error> {
+        str::from_utf8(&bytes)?;
+        Ok(SmallString { vec: bytes })
+    }
+
+    /// Converts a `SmallString` into its underlying bytes.
+    #[inline]
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_bytes(self) -> SmallVec<u8, N> {
+        self.vec
+    }
+
+    /// Converts the string into a [`String`], reusing the heap allocation if the
+    /// string has spilled.
+    #[inline]
+    #[must_use = "`self` will be dropped if the result is not used"]
+    pub fn into_string(self) -> String {
+        // SAFETY: the bytes of a `SmallString` are always valid UTF-8.
+        unsafe { String::from_utf8_unchecked(self.vec.into_vec()) }
+    }
+
+    /// Extracts a string slice containing the entire `SmallString`.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes of a `SmallString` are always valid UTF-8.
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+
+    /// Converts a `SmallString` into a mutable string slice.
+    #[inline]
+    #[must_use]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        // SAFETY: the bytes of a `SmallString` are always valid UTF-8.
+        unsafe { str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    /// Returns a byte slice of this `SmallString`'s contents.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.vec
+    }
+
+    /// Returns a mutable reference to the contents of this `SmallString`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the contents remain valid UTF-8.
+    #[inline]
+    pub unsafe fn as_mut_vec(&mut self) -> &mut SmallVec<u8, N> {
+        &mut self.vec
+    }
+
+    /// Returns the length of this string, in bytes.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns `true` if this string has a length of zero.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Returns this string's capacity, in bytes.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Returns `true` if the contents are stored in a heap allocation rather than inline.
+    #[inline]
+    #[must_use]
+    pub fn spilled(&self) -> bool {
+        self.vec.spilled()
+    }
+
+    /// Reserves capacity for at least `additional` bytes more than the current length.
+    #[inline]
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional)
+    }
+
+    /// Shrinks the capacity of this string to match its length, moving the
+    /// contents back inline if they fit.
+    #[inline]
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit()
+    }
+
+    /// Appends a given string slice onto the end of this `SmallString`.
+    #[inline]
+    #[track_caller]
+    pub fn push_str(&mut self, string: &str) {
+        self.vec.extend_from_slice(string.as_bytes())
+    }
+
+    /// Appends the given [`char`] to the end of this `SmallString`.
+    #[inline]
+    #[track_caller]
+    pub fn push(&mut self, ch: char) {
+        match ch.len_utf8() {
+            1 => self.vec.push(ch as u8),
+            _ => self.vec.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    /// Removes the last character from the string buffer and returns it, or
+    /// [`None`] if this string is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().rev().next()?;
+        let newlen = self.len() - ch.len_utf8();
+        // SAFETY: `newlen` is the start of the last character, a char boundary.
+        unsafe { self.vec.set_len(newlen) };
+        Some(ch)
+    }
+
+    /// Shortens this `SmallString` to the specified length.
+    ///
+    /// If `new_len` is greater than or equal to the string's current length, this
+    /// has no effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` does not lie on a [`char`] boundary.
+    #[inline]
+    #[track_caller]
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(self.is_char_boundary(new_len));
+            self.vec.truncate(new_len)
+        }
+    }
+
+    /// Truncates this `SmallString`, removing all contents.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vec.clear()
+    }
+
+    /// Removes a [`char`] from this `SmallString` at a byte position and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than or equal to the string's length,
+    /// or if it does not lie on a [`char`] boundary.
+    #[track_caller]
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match self[idx..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+
+        let next = idx + ch.len_utf8();
+        let len = self.len();
+        unsafe {
+            let base = self.vec.as_mut_ptr();
+            ptr::copy(base.add(next), base.add(idx), len - next);
+            self.vec.set_len(len - (next - idx));
+        }
+        ch
+    }
+
+    /// Retains only the characters specified by the predicate.
+    ///
+    /// See [`String::retain`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_string)]
+    /// use std::string::SmallString;
+    ///
+    /// let mut s: SmallString<8> = SmallString::from("f_o_ob_ar");
+    /// s.retain(|c| c != '_');
+    /// assert_eq!(s, "foobar");
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(char) -> bool,
+    {
+        struct SetLenOnDrop<'a, const N: usize> {
+            s: &'a mut SmallString<N>,
+            idx: usize,
+            del_bytes: usize,
+        }
+
+        impl<const N: usize> Drop for SetLenOnDrop<'_, N> {
+            fn drop(&mut self) {
+                let new_len = self.idx - self.del_bytes;
+                debug_assert!(new_len <= self.s.len());
+                unsafe { self.s.vec.set_len(new_len) };
+            }
+        }
+
+        let len = self.len();
+        let mut guard = SetLenOnDrop { s: self, idx: 0, del_bytes: 0 };
+
+        while guard.idx < len {
+            // SAFETY: `guard.idx` is a char boundary below `len` and the bytes are valid
+            // UTF-8, so `Chars` always returns one character.
+            let ch =
+                unsafe { guard.s.get_unchecked(guard.idx..len).chars().next().unwrap_unchecked() };
+            let ch_len = ch.len_utf8();
+
+            if !f(ch) {
+                guard.del_bytes += ch_len;
+            } else if guard.del_bytes > 0 {
+                // SAFETY: `guard.del_bytes >= ch_len`, so the destination lies within the
+                // bytes already processed.
+                ch.encode_utf8(unsafe {
+                    slice::from_raw_parts_mut(
+                        guard.s.vec.as_mut_ptr().add(guard.idx - guard.del_bytes),
+                        ch_len,
+                    )
+                });
+            }
+
+            guard.idx += ch_len;
+        }
+
+        drop(guard);
+    }
+
+    /// Inserts a character into this `SmallString` at a byte position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a [`char`] boundary.
+    #[inline]
+    #[track_caller]
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        assert!(self.is_char_boundary(idx));
+        let mut bits = [0; 4];
+        let bits = ch.encode_utf8(&mut bits).as_bytes();
+        unsafe { self.insert_bytes(idx, bits) };
+    }
+
+    /// Inserts a string slice into this `SmallString` at a byte position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a [`char`] boundary.
+    #[inline]
+    #[track_caller]
+    pub fn insert_str(&mut self, idx: usize, string: &str) {
+        assert!(self.is_char_boundary(idx));
+        unsafe { self.insert_bytes(idx, string.as_bytes()) };
+    }
+
+    #[track_caller]
+    unsafe fn insert_bytes(&mut self, idx: usize, bytes: &[u8]) {
+        let len = self.len();
+        let amt = bytes.len();
+        self.vec.reserve(amt);
+
+        unsafe {
+            let base = self.vec.as_mut_ptr();
+            ptr::copy(base.add(idx), base.add(idx + amt), len - idx);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), base.add(idx), amt);
+            self.vec.set_len(len + amt);
+        }
+    }
+
+    /// Splits the string into two at the given byte index.
+    ///
+    /// See [`String::split_off`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is not on a [`char`] boundary, or if it is beyond the last
+    /// code point of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_string)]
+    /// use std::string::SmallString;
+    ///
+    /// let mut hello: SmallString<16> = SmallString::from("Hello, World!");
+    /// let world = hello.split_off(7);
+    /// assert_eq!(hello, "Hello, ");
+    /// assert_eq!(world, "World!");
+    /// ```
+    #[inline]
+    #[track_caller]
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    pub fn split_off(&mut self, at: usize) -> SmallString<N> {
+        assert!(self.is_char_boundary(at));
+        let other = self.vec.split_off(at);
+        // `at` is a char boundary, so both halves stay valid UTF-8.
+        SmallString { vec: other }
+    }
+
+    /// Removes the specified range in the string, and replaces it with the given
+    /// string. The given string doesn't need to be the same length as the range.
+    ///
+    /// See [`String::replace_range`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a [`char`]
+    /// boundary, or if they're out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_string)]
+    /// use std::string::SmallString;
+    ///
+    /// let mut s: SmallString<16> = SmallString::from("α is alpha, β is beta");
+    /// let beta_offset = s.find('β').unwrap_or(s.len());
+    /// s.replace_range(..beta_offset, "Α is capital alpha; ");
+    /// assert_eq!(s, "Α is capital alpha; β is beta");
+    /// ```
+    #[track_caller]
+    pub fn replace_range<R>(&mut self, range: R, replace_with: &str)
+    where
+        R: RangeBounds<usize>,
+    {
+        let Range { start, end } = slice::range(range, ..self.len());
+        assert!(self.is_char_boundary(start));
+        assert!(self.is_char_boundary(end));
+
+        // Both ends of the replaced range are char boundaries and the
+        // replacement is a `&str`, so the result is valid UTF-8.
+        self.vec.splice(start..end, replace_with.bytes());
+    }
+
+    /// Removes the specified range from the string in bulk, returning all
+    /// removed characters as an iterator.
+    ///
+    /// See [`String::drain`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a [`char`]
+    /// boundary, or if they're out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_string)]
+    /// use std::string::SmallString;
+    ///
+    /// let mut s: SmallString<16> = SmallString::from("α is alpha");
+    /// let t: String = s.drain(..s.find(' ').unwrap()).collect();
+    /// assert_eq!(t, "α");
+    /// assert_eq!(s, " is alpha");
+    /// ```
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, N>
+    where
+        R: RangeBounds<usize>,
+    {
+        // As with `String::drain`, the removal happens in `Drop`, so leaking the
+        // iterator leaves the string untouched.
+        let Range { start, end } = slice::range(range, ..self.len());
+        assert!(self.is_char_boundary(start));
+        assert!(self.is_char_boundary(end));
+
+        // Take out two simultaneous borrows. The &mut SmallString won't be accessed
+        // until iteration is over, in Drop.
+        let self_ptr = self as *mut _;
+        // SAFETY: `slice::range` and `is_char_boundary` do the appropriate bounds checks.
+        let chars_iter = unsafe { self.get_unchecked(start..end) }.chars();
+
+        Drain { start, end, iter: chars_iter, string: self_ptr }
+    }
+}
+
+impl<const N: usize> ops::Deref for SmallString<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> ops::DerefMut for SmallString<N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for SmallString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for SmallString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<const N: usize> fmt::Write for SmallString<N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<const N: usize> Hash for SmallString<N> {
+    #[inline]
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        (**self).hash(hasher)
+    }
+}
+
+macro_rules! impl_eq {
+    ($lhs:ty, $rhs: ty) => {
+        #[allow(unused_lifetimes)]
+        impl<'a, 'b, const N: usize> PartialEq<$rhs> for $lhs {
+            #[inline]
+            fn eq(&self, other: &$rhs) -> bool {
+                PartialEq::eq(&self[..], &other[..])
+            }
+        }
+
+        #[allow(unused_lifetimes)]
+        impl<'a, 'b, const N: usize> PartialEq<$lhs> for $rhs {
+            #[inline]
+            fn eq(&self, other: &$lhs) -> bool {
+                PartialEq::eq(&self[..], &other[..])
+            }
+        }
+    };
+}
+
+impl_eq! { SmallString<N>, str }
+impl_eq! { SmallString<N>, &'a str }
+impl_eq! { SmallString<N>, String }
+
+impl<const N: usize> PartialOrd<str> for SmallString<N> {
+    #[inline]
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        self.as_str().partial_cmp(other)
+    }
+}
+
+impl<const N: usize> AsRef<str> for SmallString<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsMut<str> for SmallString<N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for SmallString<N> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<const N: usize> Borrow<str> for SmallString<N> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self[..]
+    }
+}
+
+impl<const N: usize> BorrowMut<str> for SmallString<N> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut str {
+        &mut self[..]
+    }
+}
+
+impl<const N: usize> From<&str> for SmallString<N> {
+    #[track_caller]
+    fn from(s: &str) -> Self {
+        let mut string = SmallString::with_capacity(s.len());
+        string.push_str(s);
+        string
+    }
+}
+
+impl<const N: usize> From<char> for SmallString<N> {
+    #[inline]
+    fn from(c: char) -> Self {
+        let mut string = SmallString::new();
+        string.push(c);
+        string
+    }
+}
+
+impl<const N: usize> From<String> for SmallString<N> {
+    /// Converts a `String` into a spilled `SmallString<N>` without reallocating.
+    #[inline]
+    fn from(s: String) -> Self {
+        SmallString { vec: SmallVec::from(s.into_bytes()) }
+    }
+}
+
+impl<const N: usize> From<SmallString<N>> for String {
+    #[inline]
+    fn from(s: SmallString<N>) -> Self {
+        s.into_string()
+    }
+}
+
+impl<const N: usize> FromStr for SmallString<N> {
+    type Err = core::convert::Infallible;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(SmallString::from(s))
+    }
+}
+
+impl<const N: usize> Extend<char> for SmallString<N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iterator = iter.into_iter();
+        let (lower_bound, _) = iterator.size_hint();
+        self.reserve(lower_bound);
+        iterator.for_each(move |c| self.push(c));
+    }
+}
+
+impl<'a, const N: usize> Extend<&'a char> for SmallString<N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a char>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<'a, const N: usize> Extend<&'a str> for SmallString<N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(move |s| self.push_str(s));
+    }
+}
+
+impl<const N: usize> FromIterator<char> for SmallString<N> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut buf = SmallString::new();
+        buf.extend(iter);
+        buf
+    }
+}
+
+impl<'a, const N: usize> FromIterator<&'a str> for SmallString<N> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut buf = SmallString::new();
+        buf.extend(iter);
+        buf
+    }
+}
+
+/// A draining iterator for `SmallString`.
+///
+/// This struct is created by the [`drain`] method on [`SmallString`]. See its
+/// documentation for more.
+///
+/// [`drain`]: SmallString::drain
+pub struct Drain<'a, const N: usize> {
+    /// Will be used as &'a mut SmallString in the destructor
+    string: *mut SmallString<N>,
+    /// Start of part to remove
+    start: usize,
+    /// End of part to remove
+    end: usize,
+    /// Current remaining range to remove
+    iter: Chars<'a>,
+}
+
+impl<const N: usize> fmt::Debug for Drain<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_str()).finish()
+    }
+}
+
+unsafe impl<const N: usize> Sync for Drain<'_, N> {}
+unsafe impl<const N: usize> Send for Drain<'_, N> {}
+
+impl<const N: usize> Drop for Drain<'_, N> {
+    fn drop(&mut self) {
+        unsafe {
+            let self_vec = (*self.string).as_mut_vec();
+            if self.start <= self.end && self.end <= self_vec.len() {
+                self_vec.drain(self.start..self.end);
+            }
+        }
+    }
+}
+
+impl<'a, const N: usize> Drain<'a, N> {
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.iter.as_str()
+    }
+}
+
+impl<const N: usize> Iterator for Drain<'_, N> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<char> {
+        self.next_back()
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for Drain<'_, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        self.iter.next_back()
+    }
+}
+
+impl<const N: usize> FusedIterator for Drain<'_, N> {}
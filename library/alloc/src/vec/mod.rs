@@ -109,6 +109,13 @@
 
 mod partial_eq;
 
+#[cfg(not(no_global_oom_handling))]
+#[unstable(feature = "small_vec", issue = "none")]
+pub use self::small_vec::SmallVec;
+
+#[cfg(not(no_global_oom_handling))]
+pub mod small_vec;
+
 #[cfg(not(no_global_oom_handling))]
 use self::spec_from_elem::SpecFromElem;
 
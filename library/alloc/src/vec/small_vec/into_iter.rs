@@ -0,0 +1,84 @@
+use core::iter::FusedIterator;
+use core::{fmt, ptr, slice};
+
+use super::SmallVec;
+
+/// An iterator that moves out of a `SmallVec<T, N>`.
+///
+/// This `struct` is created by the `into_iter` method on [`SmallVec`]
+/// (provided by the [`IntoIterator`] trait).
+pub struct IntoIter<T, const N: usize> {
+    /// The vector whose buffer is being iterated; its length is zero, so that
+    /// only the elements in `start..end` are owned by the iterator.
+    pub(super) vec: SmallVec<T, N>,
+    pub(super) start: usize,
+    pub(super) end: usize,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    /// Returns the remaining items of this iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.start), self.end - self.start) }
+    }
+
+    /// Returns the remaining items of this iterator as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.vec.as_mut_ptr().add(self.start), self.end - self.start)
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        let elem = unsafe { ptr::read(self.vec.as_ptr().add(self.start)) };
+        self.start += 1;
+        Some(elem)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { ptr::read(self.vec.as_ptr().add(self.end)) })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T: Clone, const N: usize> Clone for IntoIter<T, N> {
+    fn clone(&self) -> Self {
+        self.as_slice().iter().cloned().collect::<SmallVec<T, N>>().into_iter()
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        // The buffer itself is freed when `self.vec` is dropped.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
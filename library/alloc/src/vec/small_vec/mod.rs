@@ -0,0 +1,1053 @@
+//! A vector that stores a small number of elements inline, written
+//! `SmallVec<T, N>`.
+//!
+//! A `SmallVec<T, N>` keeps up to `N` elements inside the value itself and
+//! only moves them to a heap allocation from the global allocator once that
+//! capacity is exceeded ("spills"). Once spilled, it behaves like a [`Vec`].
+//!
+//! # Examples
+//!
+//! ```
+//! #![feature(small_vec)]
+//! use std::vec::SmallVec;
+//!
+//! let mut v: SmallVec<i32, 2> = SmallVec::new();
+//! v.push(1);
+//! v.push(2);
+//! assert!(!v.spilled());
+//!
+//! v.push(3);
+//! assert!(v.spilled());
+//! assert_eq!(v, [1, 2, 3]);
+//! ```
+
+#![unstable(feature = "small_vec", issue = "none")]
+
+use core::borrow::{Borrow, BorrowMut};
+use core::cmp::{self, Ordering};
+use core::hash::{Hash, Hasher};
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ops::{self, Range, RangeBounds};
+use core::ptr::{self, NonNull};
+use core::{fmt, slice};
+
+pub use self::drain::Drain;
+pub use self::into_iter::IntoIter;
+pub use self::splice::Splice;
+use super::Vec;
+use crate::raw_vec::RawVec;
+
+mod drain;
+mod into_iter;
+mod splice;
+
+/// A contiguous growable array type that stores up to `N` elements inline.
+///
+/// See the [module-level documentation](self) for more.
+pub struct SmallVec<T, const N: usize> {
+    len: usize,
+    buf: Buf<T, N>,
+}
+
+/// Backing storage of a [`SmallVec`].
+enum Buf<T, const N: usize> {
+    Inline([MaybeUninit<T>; N]),
+    Heap(RawVec<T>),
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    /// Constructs a new, empty `SmallVec<T, N>` using its inline storage.
+    ///
+    /// The vector will not allocate until more than `N` elements are pushed onto it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 16> = SmallVec::new();
+    /// assert_eq!(v.capacity(), 16);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        SmallVec { len: 0, buf: Buf::Inline([const { MaybeUninit::uninit() }; N]) }
+    }
+
+    /// Constructs a new, empty `SmallVec<T, N>` with at least the specified capacity.
+    ///
+    /// If `capacity` is at most `N`, the inline storage is used and nothing is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity exceeds `isize::MAX` _bytes_.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 4> = SmallVec::with_capacity(4);
+    /// assert!(!v.spilled());
+    ///
+    /// let v: SmallVec<u8, 4> = SmallVec::with_capacity(10);
+    /// assert!(v.spilled());
+    /// assert!(v.capacity() >= 10);
+    /// ```
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            SmallVec { len: 0, buf: Buf::Heap(RawVec::with_capacity(capacity)) }
+        }
+    }
+
+    /// Returns the number of elements the vector can hold without spilling to the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// assert_eq!(SmallVec::<String, 3>::inline_capacity(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn inline_capacity() -> usize {
+        N
+    }
+
+    /// Returns the total number of elements the vector can hold without reallocating.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        match &self.buf {
+            Buf::Inline(_) => N,
+            Buf::Heap(buf) => buf.capacity(),
+        }
+    }
+
+    /// Returns `true` if the elements are stored in a heap allocation rather than inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 1> = SmallVec::new();
+    /// v.push(1);
+    /// assert!(!v.spilled());
+    /// v.push(2);
+    /// assert!(v.spilled());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn spilled(&self) -> bool {
+        matches!(self.buf, Buf::Heap(_))
+    }
+
+    /// Returns the number of elements in the vector.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a raw pointer to the vector's buffer.
+    ///
+    /// Unlike with [`Vec`], the pointer is invalidated whenever the vector is moved
+    /// while its elements are stored inline.
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        match &self.buf {
+            Buf::Inline(buf) => buf.as_ptr().cast(),
+            Buf::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    /// Returns a raw mutable pointer to the vector's buffer.
+    ///
+    /// Unlike with [`Vec`], the pointer is invalidated whenever the vector is moved
+    /// while its elements are stored inline.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        match &mut self.buf {
+            Buf::Inline(buf) => buf.as_mut_ptr().cast(),
+            Buf::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    /// Extracts a slice containing the entire vector.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    /// Extracts a mutable slice of the entire vector.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    /// Forces the length of the vector to `new_len`.
+    ///
+    /// # Safety
+    ///
+    /// - `new_len` must be less than or equal to [`capacity()`].
+    /// - The elements at `old_len..new_len` must be initialized.
+    ///
+    /// [`capacity()`]: SmallVec::capacity
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        self.len = new_len;
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// If the inline storage is too small, the elements are moved to the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity exceeds `isize::MAX` _bytes_.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1]);
+    /// v.reserve(3);
+    /// assert!(!v.spilled());
+    /// v.reserve(4);
+    /// assert!(v.spilled());
+    /// assert!(v.capacity() >= 5);
+    /// ```
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.reserve_for(self.len, additional);
+    }
+
+    /// Makes room for `len + additional` elements, preserving the first `len`
+    /// slots of the buffer (initialized or not) if the buffer has to move.
+    #[track_caller]
+    fn reserve_for(&mut self, len: usize, additional: usize) {
+        match &mut self.buf {
+            Buf::Inline(inline) => {
+                let required = len.checked_add(additional).expect("capacity overflow");
+                if required <= N {
+                    return;
+                }
+                let heap = RawVec::with_capacity(cmp::max(required, N.saturating_mul(2)));
+                unsafe { ptr::copy_nonoverlapping(inline.as_ptr().cast::<T>(), heap.ptr(), len) };
+                self.buf = Buf::Heap(heap);
+            }
+            Buf::Heap(heap) => heap.reserve(len, additional),
+        }
+    }
+
+    /// Shrinks the capacity of the vector as much as possible.
+    ///
+    /// If the elements fit in the inline storage again, they are moved back and the
+    /// heap allocation is freed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 2> = SmallVec::from([1, 2, 3]);
+    /// assert!(v.spilled());
+    /// v.pop();
+    /// v.shrink_to_fit();
+    /// assert!(!v.spilled());
+    /// assert_eq!(v, [1, 2]);
+    /// ```
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        let Buf::Heap(heap) = &mut self.buf else { return };
+        if self.len <= N {
+            let mut inline = [const { MaybeUninit::uninit() }; N];
+            unsafe {
+                ptr::copy_nonoverlapping(heap.ptr(), inline.as_mut_ptr().cast::<T>(), self.len)
+            };
+            // Dropping the `RawVec` frees the allocation without touching the elements.
+            self.buf = Buf::Inline(inline);
+        } else {
+            heap.shrink_to_fit(self.len);
+        }
+    }
+
+    /// Appends an element to the back of the vector, spilling to the heap if the
+    /// inline storage is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity exceeds `isize::MAX` _bytes_.
+    #[inline]
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
+        let len = self.len;
+        if len == self.capacity() {
+            self.reserve_for(len, 1);
+        }
+        unsafe { ptr::write(self.as_mut_ptr().add(len), value) };
+        self.len = len + 1;
+    }
+
+    /// Removes the last element from the vector and returns it, or [`None`] if it
+    /// is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { ptr::read(self.as_ptr().add(self.len)) })
+        }
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, element: T) {
+        let len = self.len;
+        if index > len {
+            panic!("insertion index (is {index}) should be <= len (is {len})");
+        }
+        if len == self.capacity() {
+            self.reserve_for(len, 1);
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            if index < len {
+                ptr::copy(p, p.add(1), len - index);
+            }
+            ptr::write(p, element);
+        }
+        self.len = len + 1;
+    }
+
+    /// Removes and returns the element at position `index`, shifting all elements
+    /// after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        if index >= len {
+            panic!("removal index (is {index}) should be < len (is {len})");
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let ret = ptr::read(p);
+            ptr::copy(p.add(1), p, len - index - 1);
+            self.len = len - 1;
+            ret
+        }
+    }
+
+    /// Removes an element from the vector and returns it, replacing it with the
+    /// last element.
+    ///
+    /// This does not preserve ordering of the remaining elements, but is *O*(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[track_caller]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        if index >= len {
+            panic!("swap_remove index (is {index}) should be < len (is {len})");
+        }
+        unsafe {
+            let base = self.as_mut_ptr();
+            let value = ptr::read(base.add(index));
+            ptr::copy(base.add(len - 1), base.add(index), 1);
+            self.len = len - 1;
+            value
+        }
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest.
+    ///
+    /// If `len` is greater or equal to the vector's current length, this has no effect.
+    /// The storage is not moved back inline; use [`shrink_to_fit`] for that.
+    ///
+    /// [`shrink_to_fit`]: SmallVec::shrink_to_fit
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let remaining = self.len - len;
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.as_mut_ptr().add(len), remaining);
+            self.len = len;
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Clears the vector, removing all values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`Vec::retain`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3, 4]);
+    /// v.retain(|&x| x % 2 == 0);
+    /// assert_eq!(v, [2, 4]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    /// Retains only the elements specified by the predicate, passing a mutable
+    /// reference to it.
+    ///
+    /// See [`Vec::retain_mut`] for details.
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        struct BackshiftOnDrop<'a, T, const N: usize> {
+            v: &'a mut SmallVec<T, N>,
+            processed: usize,
+            deleted: usize,
+            original_len: usize,
+        }
+
+        impl<T, const N: usize> Drop for BackshiftOnDrop<'_, T, N> {
+            fn drop(&mut self) {
+                if self.deleted > 0 {
+                    // SAFETY: Trailing unchecked items must be valid since we never touch them.
+                    unsafe {
+                        let base = self.v.as_mut_ptr();
+                        ptr::copy(
+                            base.add(self.processed),
+                            base.add(self.processed - self.deleted),
+                            self.original_len - self.processed,
+                        );
+                    }
+                }
+                self.v.len = self.original_len - self.deleted;
+            }
+        }
+
+        let original_len = self.len;
+        // Avoid double drop if `f` or a destructor panics: the guard restores the
+        // length once the retained elements have been compacted.
+        self.len = 0;
+        let mut g = BackshiftOnDrop { v: self, processed: 0, deleted: 0, original_len };
+        // Every element pointer below is derived from this one base. For inline
+        // storage each `as_mut_ptr` call reborrows the buffer, which would invalidate
+        // pointers taken from an earlier call.
+        let base = g.v.as_mut_ptr();
+
+        while g.processed != original_len {
+            // SAFETY: Unchecked element must be valid.
+            let cur = unsafe { &mut *base.add(g.processed) };
+            if !f(cur) {
+                // Advance early to avoid double drop if `drop_in_place` panicked.
+                g.processed += 1;
+                g.deleted += 1;
+                // SAFETY: We never touch this element again after dropped.
+                unsafe { ptr::drop_in_place(cur) };
+                continue;
+            }
+            if g.deleted > 0 {
+                // SAFETY: `deleted > 0`, so the hole slot must not overlap with current element.
+                unsafe {
+                    let hole = base.add(g.processed - g.deleted);
+                    ptr::copy_nonoverlapping(cur, hole, 1);
+                }
+            }
+            g.processed += 1;
+        }
+
+        drop(g);
+    }
+
+    /// Removes all but the first of consecutive elements in the vector that resolve
+    /// to the same key.
+    ///
+    /// See [`Vec::dedup_by_key`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 8> = SmallVec::from([10, 20, 21, 30, 20]);
+    /// v.dedup_by_key(|i| *i / 10);
+    /// assert_eq!(v, [10, 20, 30, 20]);
+    /// ```
+    #[inline]
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes all but the first of consecutive elements in the vector satisfying a
+    /// given equality relation.
+    ///
+    /// See [`Vec::dedup_by`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<&str, 8> = SmallVec::from(["foo", "bar", "Bar", "baz", "bar"]);
+    /// v.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    /// assert_eq!(v, ["foo", "bar", "baz", "bar"]);
+    /// ```
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+
+        /* INVARIANT: vec.len > read > write > write-1 >= 0 */
+        struct FillGapOnDrop<'a, T, const N: usize> {
+            /* Offset of the element we want to check if it is duplicate */
+            read: usize,
+
+            /* Offset of the place where we want to place the non-duplicate
+             * when we find it. */
+            write: usize,
+
+            /* The SmallVec that would need correction if `same_bucket` panicked */
+            vec: &'a mut SmallVec<T, N>,
+        }
+
+        impl<T, const N: usize> Drop for FillGapOnDrop<'_, T, N> {
+            fn drop(&mut self) {
+                // This code gets executed when `same_bucket` panics. Move the
+                // unchecked elements down over the gap and fix up the length.
+                unsafe {
+                    let len = self.vec.len;
+                    let base = self.vec.as_mut_ptr();
+                    let items_left = len.wrapping_sub(self.read);
+                    ptr::copy(base.add(self.read), base.add(self.write), items_left);
+                    self.vec.len = len - (self.read - self.write);
+                }
+            }
+        }
+
+        let mut gap = FillGapOnDrop { read: 1, write: 1, vec: self };
+        let base = gap.vec.as_mut_ptr();
+
+        unsafe {
+            while gap.read < len {
+                let read_ptr = base.add(gap.read);
+                let prev_ptr = base.add(gap.write - 1);
+
+                if same_bucket(&mut *read_ptr, &mut *prev_ptr) {
+                    // Increase `gap.read` now since the drop may panic.
+                    gap.read += 1;
+                    ptr::drop_in_place(read_ptr);
+                } else {
+                    // `read` and `write` coincide until the first duplicate, so
+                    // the ranges may overlap.
+                    ptr::copy(read_ptr, base.add(gap.write), 1);
+                    gap.write += 1;
+                    gap.read += 1;
+                }
+            }
+
+            gap.vec.len = gap.write;
+            mem::forget(gap);
+        }
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2]);
+    /// let mut w: SmallVec<i32, 4> = SmallVec::from([3, 4, 5]);
+    /// v.append(&mut w);
+    /// assert_eq!(v, [1, 2, 3, 4, 5]);
+    /// assert!(w.is_empty());
+    /// ```
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        let count = other.len;
+        self.reserve(count);
+        let len = self.len;
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(len), count);
+            other.len = 0;
+        }
+        self.len = len + count;
+    }
+
+    /// Splits the vector into two at the given index.
+    ///
+    /// Returns a new vector containing the elements in the range `[at, len)`,
+    /// stored inline if they fit. After the call, the original vector will be
+    /// left containing the elements `[0, at)` with its previous capacity unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3]);
+    /// let w = v.split_off(1);
+    /// assert_eq!(v, [1]);
+    /// assert_eq!(w, [2, 3]);
+    /// ```
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len;
+        assert!(at <= len, "`at` split index (is {at}) should be <= len (is {len})");
+
+        let other_len = len - at;
+        let mut other = SmallVec::with_capacity(other_len);
+        unsafe {
+            self.len = at;
+            ptr::copy_nonoverlapping(self.as_ptr().add(at), other.as_mut_ptr(), other_len);
+            other.len = other_len;
+        }
+        other
+    }
+
+    /// Removes the specified range from the vector in bulk, returning all removed
+    /// elements as an iterator.
+    ///
+    /// See [`Vec::drain`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end
+    /// point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3]);
+    /// let u: Vec<_> = v.drain(1..).collect();
+    /// assert_eq!(v, [1]);
+    /// assert_eq!(u, [2, 3]);
+    /// ```
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, N>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = self.len;
+        let Range { start, end } = slice::range(range, ..len);
+
+        let vec = NonNull::from(self);
+        unsafe {
+            // Set the length to `start` first, so that the drained elements and the
+            // tail are forgotten if the `Drain` is leaked.
+            (*vec.as_ptr()).len = start;
+            let range_slice =
+                slice::from_raw_parts((*vec.as_ptr()).as_ptr().add(start), end - start);
+            Drain { tail_start: end, tail_len: len - end, iter: range_slice.iter(), vec }
+        }
+    }
+
+    /// Creates a splicing iterator that replaces the specified range in the vector
+    /// with the given `replace_with` iterator and yields the removed items.
+    ///
+    /// See [`Vec::splice`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end
+    /// point is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3, 4]);
+    /// let removed: Vec<_> = v.splice(1..3, [7, 8, 9]).collect();
+    /// assert_eq!(v, [1, 7, 8, 9, 4]);
+    /// assert_eq!(removed, [2, 3]);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, N>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        Splice { drain: self.drain(range), replace_with: replace_with.into_iter() }
+    }
+
+    /// Converts the vector into a [`Vec<T>`], reusing the heap allocation if the
+    /// vector has spilled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let v: SmallVec<i32, 2> = SmallVec::from([1, 2, 3]);
+    /// assert_eq!(v.into_vec(), vec![1, 2, 3]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        let mut this = ManuallyDrop::new(self);
+        let len = this.len;
+        match &mut this.buf {
+            // SAFETY: `this` is never used or dropped again, so ownership of the
+            // buffer and its `len` initialized elements moves to the `Vec`.
+            Buf::Heap(buf) => Vec { buf: unsafe { ptr::read(buf) }, len },
+            Buf::Inline(buf) => {
+                let mut vec = Vec::with_capacity(len);
+                unsafe {
+                    ptr::copy_nonoverlapping(buf.as_ptr().cast::<T>(), vec.as_mut_ptr(), len);
+                    vec.set_len(len);
+                }
+                vec
+            }
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> SmallVec<T, N> {
+    /// Clones and appends all elements in a slice to the vector.
+    #[track_caller]
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.reserve(other.len());
+        for elem in other {
+            self.push(elem.clone());
+        }
+    }
+
+    /// Resizes the vector in-place so that `len` is equal to `new_len`.
+    ///
+    /// If `new_len` is greater than `len`, the vector is extended by the
+    /// difference, with each additional slot filled with `value`. If `new_len`
+    /// is less than `len`, the vector is simply truncated.
+    #[track_caller]
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len;
+        if new_len > len {
+            self.reserve(new_len - len);
+            for _ in 1..new_len - len {
+                self.push(value.clone());
+            }
+            self.push(value);
+        } else {
+            self.truncate(new_len);
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> SmallVec<T, N> {
+    /// Removes consecutive repeated elements in the vector according to the
+    /// [`PartialEq`] trait implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(small_vec)]
+    /// use std::vec::SmallVec;
+    ///
+    /// let mut v: SmallVec<i32, 8> = SmallVec::from([1, 2, 2, 3, 2]);
+    /// v.dedup();
+    /// assert_eq!(v, [1, 2, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        // The buffer itself is freed when `self.buf` is dropped.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> ops::Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for SmallVec<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    /// Creates an empty `SmallVec<T, N>`.
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    #[track_caller]
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for SmallVec<T, N> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+impl<T, U, const N: usize, const M: usize> PartialEq<SmallVec<U, M>> for SmallVec<T, N>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &SmallVec<U, M>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T, U, const N: usize> PartialEq<[U]> for SmallVec<T, N>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T, U, const N: usize> PartialEq<&[U]> for SmallVec<T, N>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &&[U]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T, U, const N: usize, const M: usize> PartialEq<[U; M]> for SmallVec<T, N>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &[U; M]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T, U, const N: usize> PartialEq<Vec<U>> for SmallVec<T, N>
+where
+    T: PartialEq<U>,
+{
+    #[inline]
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for SmallVec<T, N> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for SmallVec<T, N> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for SmallVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for SmallVec<T, N> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> Borrow<[T]> for SmallVec<T, N> {
+    fn borrow(&self) -> &[T] {
+        &self[..]
+    }
+}
+
+impl<T, const N: usize> BorrowMut<[T]> for SmallVec<T, N> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        &mut self[..]
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, const N: usize> Extend<&'a T> for SmallVec<T, N> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = SmallVec::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    /// Creates a consuming iterator, that is, one that moves each value out of
+    /// the vector (from start to end).
+    fn into_iter(mut self) -> Self::IntoIter {
+        let end = self.len;
+        // The iterator now owns the elements; the vector only owns the buffer.
+        self.len = 0;
+        IntoIter { vec: self, start: 0, end }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    /// Converts a `Vec<T>` into a spilled `SmallVec<T, N>` without reallocating.
+    fn from(vec: Vec<T>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        // SAFETY: `vec` is never used or dropped again, so ownership of its buffer
+        // and elements moves to the `SmallVec`.
+        let buf = unsafe { ptr::read(&vec.buf) };
+        SmallVec { len: vec.len, buf: Buf::Heap(buf) }
+    }
+}
+
+impl<T, const N: usize> From<SmallVec<T, N>> for Vec<T> {
+    fn from(v: SmallVec<T, N>) -> Self {
+        v.into_vec()
+    }
+}
+
+impl<T, const N: usize, const M: usize> From<[T; M]> for SmallVec<T, N> {
+    #[track_caller]
+    fn from(array: [T; M]) -> Self {
+        array.into_iter().collect()
+    }
+}
+
+impl<T: Clone, const N: usize> From<&[T]> for SmallVec<T, N> {
+    #[track_caller]
+    fn from(s: &[T]) -> Self {
+        let mut v = SmallVec::with_capacity(s.len());
+        v.extend_from_slice(s);
+        v
+    }
+}
@@ -0,0 +1,172 @@
+use std::num::{BigInt, BigUint, IntErrorKind};
+
+/// A small deterministic generator, so that failures are reproducible.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn biguint(&mut self, limbs: usize) -> BigUint {
+        let bytes: Vec<u8> = (0..limbs).flat_map(|_| self.next().to_le_bytes()).collect();
+        BigUint::from_bytes_le(&bytes)
+    }
+}
+
+fn big(n: u128) -> BigUint {
+    BigUint::from(n)
+}
+
+#[test]
+fn biguint_matches_u128() {
+    let mut rng = XorShift(0x9e3779b97f4a7c15);
+    for _ in 0..500 {
+        let a = rng.next() as u128 | ((rng.next() as u128 >> (rng.next() % 64)) << 64);
+        let b = (rng.next() >> (rng.next() % 64)) as u128 | 1;
+
+        let sum = big(a) + big(b);
+        assert_eq!(&sum - big(b), big(a));
+        assert_eq!(sum.to_u128(), a.checked_add(b));
+        assert_eq!(big(a) * big(b), big(a.wrapping_mul(b)) + ((big(a) * big(b)) >> 128 << 128));
+        assert_eq!(big(a) / big(b), big(a / b));
+        assert_eq!(big(a) % big(b), big(a % b));
+        assert_eq!(big(a) & big(b), big(a & b));
+        assert_eq!(big(a) | big(b), big(a | b));
+        assert_eq!(big(a) ^ big(b), big(a ^ b));
+        assert_eq!(big(a) >> 7, big(a >> 7));
+        assert_eq!(big(a).cmp(&big(b)), a.cmp(&b));
+        assert_eq!(big(a).to_string(), a.to_string());
+        assert_eq!(format!("{:x}", big(a)), format!("{a:x}"));
+        assert_eq!(format!("{:#b}", big(a)), format!("{a:#b}"));
+        assert_eq!(format!("{:o}", big(a)), format!("{a:o}"));
+        assert_eq!(big(a).to_u128(), Some(a));
+        if a >= b {
+            assert_eq!(big(a) - big(b), big(a - b));
+        } else {
+            assert_eq!(big(a).checked_sub(&big(b)), None);
+        }
+    }
+}
+
+#[test]
+fn biguint_large_arithmetic() {
+    let mut rng = XorShift(0x2545f4914f6cdd1d);
+    // Sizes on both sides of the Karatsuba threshold, balanced and not.
+    for &(la, lb) in &[(3, 2), (40, 33), (100, 64), (150, 31), (257, 129), (64, 200)] {
+        let a = rng.biguint(la);
+        let b = rng.biguint(lb);
+        let c = rng.biguint(lb / 2 + 1);
+
+        let ab = &a * &b;
+        assert_eq!(ab, &b * &a);
+        assert_eq!(&ab / &b, a);
+        assert_eq!(&ab % &b, BigUint::new());
+        // Distributivity exercises unrelated partial products.
+        assert_eq!(&a * (&b + &c), &ab + &a * &c);
+
+        let (q, r) = (&ab + &c).div_rem(&b);
+        assert!(r < b);
+        assert_eq!(&q * &b + &r, &ab + &c);
+
+        assert_eq!(BigUint::from_str_radix(&ab.to_str_radix(10), 10).unwrap(), ab);
+        assert_eq!(BigUint::from_str_radix(&ab.to_str_radix(7), 7).unwrap(), ab);
+        assert_eq!(BigUint::from_str_radix(&ab.to_str_radix(32), 32).unwrap(), ab);
+        assert_eq!(BigUint::from_bytes_be(&ab.to_bytes_be()), ab);
+        assert_eq!((&ab << 77) >> 77, ab);
+    }
+}
+
+#[test]
+fn biguint_pow_and_modpow() {
+    let two = BigUint::from(2u8);
+    assert_eq!(two.pow(200), BigUint::from(1u8) << 200);
+    assert_eq!(BigUint::from(3u8).pow(0), BigUint::from(1u8));
+
+    // Fermat's little theorem for the Mersenne prime 2^127 - 1.
+    let p = (BigUint::from(1u8) << 127) - BigUint::from(1u8);
+    let a = BigUint::from(0xdead_beef_u32);
+    assert_eq!(a.modpow(&(&p - BigUint::from(1u8)), &p), BigUint::from(1u8));
+    assert_eq!(a.modpow(&BigUint::new(), &BigUint::from(1u8)), BigUint::new());
+}
+
+#[test]
+fn biguint_parse_errors() {
+    assert_eq!("".parse::<BigUint>().unwrap_err().kind(), &IntErrorKind::Empty);
+    assert_eq!("+".parse::<BigUint>().unwrap_err().kind(), &IntErrorKind::Empty);
+    assert_eq!("12a".parse::<BigUint>().unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+    assert_eq!("-1".parse::<BigUint>().unwrap_err().kind(), &IntErrorKind::InvalidDigit);
+    assert_eq!("+00042".parse::<BigUint>(), Ok(BigUint::from(42u8)));
+    assert_eq!(BigUint::from_str_radix("Zz", 36), Ok(BigUint::from(35u16 * 36 + 35)));
+}
+
+#[test]
+#[should_panic(expected = "attempt to subtract with overflow")]
+fn biguint_sub_overflow() {
+    let _ = BigUint::from(1u8) - BigUint::from(2u8);
+}
+
+#[test]
+#[should_panic(expected = "attempt to divide by zero")]
+fn biguint_div_by_zero() {
+    let _ = BigUint::from(1u8) / BigUint::new();
+}
+
+#[test]
+fn bigint_matches_i128() {
+    let mut rng = XorShift(0x853c49e6748fea9b);
+    for _ in 0..500 {
+        let a = (rng.next() as i64 >> (rng.next() % 64)) as i128;
+        let b = (rng.next() as i64 >> (rng.next() % 64)) as i128 | 1;
+        let (x, y) = (BigInt::from(a), BigInt::from(b));
+
+        assert_eq!(&x + &y, BigInt::from(a + b));
+        assert_eq!(&x - &y, BigInt::from(a - b));
+        assert_eq!(&x * &y, BigInt::from(a * b));
+        assert_eq!(&x / &y, BigInt::from(a / b));
+        assert_eq!(&x % &y, BigInt::from(a % b));
+        assert_eq!(&x & &y, BigInt::from(a & b));
+        assert_eq!(&x | &y, BigInt::from(a | b));
+        assert_eq!(&x ^ &y, BigInt::from(a ^ b));
+        assert_eq!(!&x, BigInt::from(!a));
+        assert_eq!(-&x, BigInt::from(-a));
+        assert_eq!(&x >> 5, BigInt::from(a >> 5));
+        assert_eq!(&x << 5, BigInt::from(a << 5));
+        assert_eq!(x.cmp(&y), a.cmp(&b));
+        assert_eq!(x.to_string(), a.to_string());
+        assert_eq!(x.to_string().parse::<BigInt>(), Ok(x.clone()));
+        assert_eq!(x.to_i128(), Some(a));
+    }
+}
+
+#[test]
+fn bigint_edges() {
+    assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+    assert_eq!((BigInt::from(i64::MAX) + BigInt::from(1)).to_i64(), None);
+    assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+    assert_eq!((BigInt::from(i128::MIN) - BigInt::from(1)).to_i128(), None);
+
+    assert_eq!(-BigInt::new(), BigInt::new());
+    assert!(!BigInt::from_parts(true, BigUint::new()).is_negative());
+    assert_eq!(BigInt::from(-5).signum(), BigInt::from(-1));
+    assert_eq!(BigInt::from(-3).pow(3), BigInt::from(-27));
+    assert_eq!(BigInt::from(-1) >> 100, BigInt::from(-1));
+
+    assert_eq!(format!("{:x}", BigInt::from(-255)), "-ff");
+    assert_eq!(format!("{:+}", BigInt::from(7)), "+7");
+    assert_eq!(format!("{:>6}", BigInt::from(-42)), "   -42");
+    assert_eq!(format!("{:06}", BigInt::from(-42)), "-00042");
+    assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::new()));
+    assert_eq!("-".parse::<BigInt>().unwrap_err().kind(), &IntErrorKind::Empty);
+}
+
+#[test]
+fn sum_and_product() {
+    let factorial: BigUint = (1..=30u32).map(BigUint::from).product();
+    assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+    let total: BigInt = [-3, 5, -7].into_iter().map(BigInt::from).sum();
+    assert_eq!(total, BigInt::from(-5));
+}
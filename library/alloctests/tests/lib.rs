@@ -2,6 +2,7 @@
 #![feature(alloc_layout_extra)]
 #![feature(iter_array_chunks)]
 #![feature(assert_matches)]
+#![feature(bigint)]
 #![feature(btree_extract_if)]
 #![feature(char_max_len)]
 #![feature(cow_is_borrowed)]
@@ -24,6 +25,8 @@
 #![feature(iter_next_chunk)]
 #![feature(round_char_boundary)]
 #![feature(slice_partition_dedup)]
+#![feature(small_string)]
+#![feature(small_vec)]
 #![feature(string_from_utf8_lossy_owned)]
 #![feature(string_remove_matches)]
 #![feature(const_btree_len)]
@@ -51,6 +54,7 @@
 mod alloc_test;
 mod arc;
 mod autotraits;
+mod bigint;
 mod borrow;
 mod boxed;
 mod btree_set_hash;
@@ -65,6 +69,8 @@
 mod misc_tests;
 mod rc;
 mod slice;
+mod small_string;
+mod small_vec;
 mod sort;
 mod str;
 mod string;
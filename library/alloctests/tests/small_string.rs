@@ -0,0 +1,123 @@
+use std::fmt::Write;
+use std::string::SmallString;
+
+#[test]
+fn test_push_spills() {
+    let mut s: SmallString<4> = SmallString::new();
+    s.push_str("abc");
+    s.push('d');
+    assert!(!s.spilled());
+    s.push('é');
+    assert!(s.spilled());
+    assert_eq!(s, "abcdé");
+    assert_eq!(s.pop(), Some('é'));
+    assert_eq!(s.len(), 4);
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut s: SmallString<8> = SmallString::from("hllo");
+    s.insert(1, 'e');
+    s.insert_str(5, ", wörld");
+    assert_eq!(s, "hello, wörld");
+    assert_eq!(s.remove(8), 'ö');
+    assert_eq!(s, "hello, wrld");
+}
+
+#[test]
+#[should_panic]
+fn test_insert_not_char_boundary() {
+    let mut s: SmallString<8> = SmallString::from("é");
+    s.insert(1, 'a');
+}
+
+#[test]
+fn test_retain() {
+    let mut s: SmallString<4> = SmallString::from("α_β_γ_δ");
+    s.retain(|c| c != '_');
+    assert_eq!(s, "αβγδ");
+}
+
+#[test]
+fn test_inline_insert_remove() {
+    // Stays inline throughout, so Miri checks the in-place moves on the inline buffer.
+    let mut s: SmallString<32> = SmallString::from("hllo");
+    s.insert_str(1, "e");
+    s.insert(5, '!');
+    assert_eq!(s.remove(0), 'h');
+    assert!(!s.spilled());
+    assert_eq!(s, "ello!");
+}
+
+#[test]
+fn test_replace_range() {
+    let mut s: SmallString<32> = SmallString::from("α is alpha");
+    s.replace_range(..2, "Α");
+    assert!(!s.spilled());
+    assert_eq!(s, "Α is alpha");
+    s.replace_range(6.., "capital alpha");
+    assert_eq!(s, "Α is capital alpha");
+
+    let mut s: SmallString<4> = SmallString::from("abc");
+    s.replace_range(1..2, "long");
+    assert!(s.spilled());
+    assert_eq!(s, "alongc");
+}
+
+#[test]
+#[should_panic]
+fn test_replace_range_not_char_boundary() {
+    let mut s: SmallString<8> = SmallString::from("é");
+    s.replace_range(..1, "e");
+}
+
+#[test]
+fn test_split_off() {
+    let mut s: SmallString<16> = SmallString::from("Hello, wörld");
+    let t = s.split_off(7);
+    assert!(!t.spilled());
+    assert_eq!(s, "Hello, ");
+    assert_eq!(t, "wörld");
+}
+
+#[test]
+fn test_drain() {
+    let mut s: SmallString<32> = SmallString::from("αβγ and more");
+    let t: String = s.drain(..6).collect();
+    assert_eq!(t, "αβγ");
+    assert_eq!(s, " and more");
+
+    let mut drain = s.drain(1..4);
+    assert_eq!(drain.next(), Some('a'));
+    assert_eq!(drain.as_str(), "nd");
+    drop(drain);
+    assert_eq!(s, "  more");
+}
+
+#[test]
+fn test_string_conversions() {
+    let string = String::from("a fairly long string");
+    let ptr = string.as_ptr();
+    let s: SmallString<4> = SmallString::from(string);
+    assert!(s.spilled());
+    assert_eq!(s.as_ptr(), ptr);
+    assert_eq!(s.into_string().as_ptr(), ptr);
+
+    let s: SmallString<8> = "short".parse().unwrap();
+    assert_eq!(String::from(s), "short");
+}
+
+#[test]
+fn test_traits() {
+    let mut s: SmallString<8> = ['a', 'b'].into_iter().collect();
+    write!(s, "{}-{}", 1, 2).unwrap();
+    s.extend(["x", "y"]);
+    assert_eq!(s, "ab1-2xy");
+    assert_eq!(format!("{s}|{s:?}"), "ab1-2xy|\"ab1-2xy\"");
+    assert_eq!(crate::hash(&s), crate::hash(&String::from("ab1-2xy")));
+    assert!(s.starts_with("ab"));
+    s.truncate(2);
+    s.shrink_to_fit();
+    assert!(!s.spilled());
+    assert_eq!(s, String::from("ab"));
+}
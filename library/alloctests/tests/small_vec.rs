@@ -0,0 +1,340 @@
+use std::cell::Cell;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::rc::Rc;
+use std::vec::SmallVec;
+
+struct DropCounter<'a> {
+    count: &'a Cell<usize>,
+}
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.count.set(self.count.get() + 1);
+    }
+}
+
+#[test]
+fn test_push_spills() {
+    let mut v: SmallVec<i32, 3> = SmallVec::new();
+    assert_eq!(v.capacity(), 3);
+    for i in 0..3 {
+        v.push(i);
+    }
+    assert!(!v.spilled());
+    v.push(3);
+    assert!(v.spilled());
+    assert!(v.capacity() >= 4);
+    assert_eq!(v, [0, 1, 2, 3]);
+    assert_eq!(v.pop(), Some(3));
+    assert_eq!(v.len(), 3);
+}
+
+#[test]
+fn test_zero_inline_capacity() {
+    let mut v: SmallVec<String, 0> = SmallVec::new();
+    assert!(!v.spilled());
+    v.push("a".to_string());
+    assert!(v.spilled());
+    assert_eq!(v, ["a"]);
+}
+
+#[test]
+fn test_zero_sized() {
+    let mut v: SmallVec<(), 2> = SmallVec::new();
+    for _ in 0..10 {
+        v.push(());
+    }
+    assert_eq!(v.len(), 10);
+    assert_eq!(v.drain(2..5).count(), 3);
+    assert_eq!(v.len(), 7);
+    assert_eq!(v.into_iter().count(), 7);
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut v: SmallVec<i32, 4> = SmallVec::from([1, 3]);
+    v.insert(1, 2);
+    v.insert(3, 4);
+    v.insert(0, 0);
+    assert_eq!(v, [0, 1, 2, 3, 4]);
+    assert_eq!(v.remove(0), 0);
+    assert_eq!(v.swap_remove(0), 1);
+    assert_eq!(v, [4, 2, 3]);
+}
+
+#[test]
+#[should_panic]
+fn test_insert_out_of_bounds() {
+    let mut v: SmallVec<i32, 4> = SmallVec::new();
+    v.insert(1, 0);
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let mut v: SmallVec<i32, 2> = SmallVec::with_capacity(10);
+    assert!(v.spilled());
+    v.extend([1, 2]);
+    v.shrink_to_fit();
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 2]);
+
+    v.extend([3, 4, 5]);
+    v.shrink_to_fit();
+    assert!(v.spilled());
+    assert_eq!(v.capacity(), 5);
+}
+
+#[test]
+fn test_drop_counts() {
+    let count = Cell::new(0);
+    {
+        let mut v: SmallVec<DropCounter<'_>, 2> = SmallVec::new();
+        v.push(DropCounter { count: &count });
+        v.push(DropCounter { count: &count });
+        v.truncate(1);
+        assert_eq!(count.get(), 1);
+        for _ in 0..3 {
+            v.push(DropCounter { count: &count });
+        }
+    }
+    assert_eq!(count.get(), 5);
+}
+
+#[test]
+fn test_retain() {
+    let mut v: SmallVec<i32, 8> = (0..8).collect();
+    v.retain(|&x| x % 3 != 0);
+    assert_eq!(v, [1, 2, 4, 5, 7]);
+
+    let mut v: SmallVec<i32, 2> = (0..8).collect();
+    v.retain_mut(|x| {
+        *x *= 10;
+        *x > 30
+    });
+    assert_eq!(v, [40, 50, 60, 70]);
+}
+
+#[test]
+fn test_retain_predicate_panic() {
+    let count = Cell::new(0);
+    let mut v: SmallVec<DropCounter<'_>, 4> = SmallVec::new();
+    for _ in 0..6 {
+        v.push(DropCounter { count: &count });
+    }
+    let mut calls = 0;
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        v.retain(|_| {
+            calls += 1;
+            if calls == 4 {
+                panic!("predicate panic");
+            }
+            calls % 2 == 0
+        })
+    }));
+    assert!(res.is_err());
+    // Elements 1 and 3 were dropped, the other four are still in the vector.
+    assert_eq!(count.get(), 2);
+    assert_eq!(v.len(), 4);
+    drop(v);
+    assert_eq!(count.get(), 6);
+}
+
+#[test]
+fn test_drain() {
+    let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3, 4]);
+    let drained: Vec<_> = v.drain(1..3).collect();
+    assert_eq!(drained, [2, 3]);
+    assert_eq!(v, [1, 4]);
+
+    let mut v: SmallVec<i32, 2> = (0..10).collect();
+    assert_eq!(v.drain(..8).rev().collect::<Vec<_>>(), [7, 6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(v, [8, 9]);
+}
+
+#[test]
+fn test_drain_partially_consumed() {
+    let count = Cell::new(0);
+    let mut v: SmallVec<DropCounter<'_>, 4> = SmallVec::new();
+    for _ in 0..4 {
+        v.push(DropCounter { count: &count });
+    }
+    let mut drain = v.drain(1..3);
+    drop(drain.next());
+    drop(drain);
+    assert_eq!(count.get(), 2);
+    assert_eq!(v.len(), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_drain_out_of_bounds() {
+    let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3]);
+    v.drain(2..5);
+}
+
+#[test]
+fn test_splice() {
+    let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2, 3, 4]);
+    let removed: Vec<_> = v.splice(1..3, [10, 11, 12, 13]).collect();
+    assert_eq!(removed, [2, 3]);
+    assert!(v.spilled());
+    assert_eq!(v, [1, 10, 11, 12, 13, 4]);
+
+    let mut v: SmallVec<i32, 8> = SmallVec::from([1, 2, 3, 4]);
+    v.splice(1..3, [5]);
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 5, 4]);
+
+    // An iterator with a lower size hint of zero forces the collecting path.
+    let mut v: SmallVec<i32, 2> = SmallVec::from([1, 2]);
+    v.splice(..1, (7..10).filter(|_| true));
+    assert_eq!(v, [7, 8, 9, 2]);
+
+    let mut v: SmallVec<i32, 2> = SmallVec::from([1, 2]);
+    v.splice(2.., [3, 4]);
+    assert_eq!(v, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_into_iter() {
+    let v: SmallVec<String, 2> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+    let mut it = v.into_iter();
+    assert_eq!(it.next().as_deref(), Some("a"));
+    assert_eq!(it.next_back().as_deref(), Some("c"));
+    assert_eq!(it.as_slice(), ["b"]);
+
+    let rc = Rc::new(());
+    let v: SmallVec<Rc<()>, 4> = SmallVec::from([rc.clone(), rc.clone(), rc.clone()]);
+    let mut it = v.into_iter();
+    it.next();
+    drop(it);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn test_vec_conversions() {
+    let vec = vec![1, 2, 3];
+    let ptr = vec.as_ptr();
+    let v: SmallVec<i32, 8> = SmallVec::from(vec);
+    assert!(v.spilled());
+    assert_eq!(v.as_ptr(), ptr);
+    let vec = v.into_vec();
+    assert_eq!(vec.as_ptr(), ptr);
+
+    let v: SmallVec<i32, 8> = SmallVec::from(&[1, 2][..]);
+    assert_eq!(Vec::from(v), vec![1, 2]);
+}
+
+#[test]
+fn test_clone_eq_hash() {
+    let a: SmallVec<i32, 2> = SmallVec::from([1, 2, 3]);
+    let b: SmallVec<i32, 8> = SmallVec::from([1, 2, 3]);
+    assert_eq!(a, b);
+    assert_eq!(a.clone(), a);
+    assert_eq!(crate::hash(&a), crate::hash(&vec![1, 2, 3]));
+    assert!(a < SmallVec::from([1, 2, 4]));
+    assert_eq!(format!("{a:?}"), "[1, 2, 3]");
+}
+
+#[test]
+fn test_resize_extend_from_slice() {
+    let mut v: SmallVec<i32, 4> = SmallVec::new();
+    v.extend_from_slice(&[1, 2]);
+    v.resize(5, 0);
+    assert_eq!(v, [1, 2, 0, 0, 0]);
+    v.resize(1, 0);
+    assert_eq!(v, [1]);
+    v.extend(&[4, 5]);
+    assert_eq!(v, [1, 4, 5]);
+}
+
+// The tests below keep the contents inline (`N > len`) so that Miri checks the
+// pointer juggling against the inline buffer, not just the heap path.
+
+#[test]
+fn test_inline_retain_drain_splice() {
+    let mut v: SmallVec<i32, 16> = (0..8).collect();
+    v.retain_mut(|x| *x % 2 == 1);
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 3, 5, 7]);
+
+    let drained: Vec<_> = v.drain(1..2).collect();
+    assert_eq!(drained, [3]);
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 5, 7]);
+
+    v.splice(1..1, [2, 3, 4]);
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 2, 3, 4, 5, 7]);
+
+    // A zero lower size hint makes `Splice` collect and move the tail again.
+    v.splice(5..5, (6..7).filter(|_| true));
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_dedup() {
+    let mut v: SmallVec<i32, 16> = SmallVec::from([1, 1, 2, 3, 3, 3, 1]);
+    v.dedup();
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 2, 3, 1]);
+
+    let mut v: SmallVec<i32, 2> = SmallVec::from([10, 11, 20, 21, 30]);
+    v.dedup_by_key(|x| *x / 10);
+    assert_eq!(v, [10, 20, 30]);
+}
+
+#[test]
+fn test_dedup_by_panic() {
+    let count = Cell::new(0);
+    let mut v: SmallVec<DropCounter<'_>, 8> = SmallVec::new();
+    for _ in 0..6 {
+        v.push(DropCounter { count: &count });
+    }
+    let mut calls = 0;
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        v.dedup_by(|_, _| {
+            calls += 1;
+            if calls == 3 {
+                panic!("same_bucket panic");
+            }
+            true
+        })
+    }));
+    assert!(res.is_err());
+    // The first two comparisons dropped elements 1 and 2.
+    assert_eq!(count.get(), 2);
+    assert_eq!(v.len(), 4);
+    drop(v);
+    assert_eq!(count.get(), 6);
+}
+
+#[test]
+fn test_append_split_off() {
+    let mut v: SmallVec<i32, 8> = SmallVec::from([1, 2]);
+    let mut w: SmallVec<i32, 8> = SmallVec::from([3, 4]);
+    v.append(&mut w);
+    assert!(!v.spilled());
+    assert_eq!(v, [1, 2, 3, 4]);
+    assert!(w.is_empty());
+
+    let tail = v.split_off(1);
+    assert!(!tail.spilled());
+    assert_eq!(v, [1]);
+    assert_eq!(tail, [2, 3, 4]);
+
+    let mut v: SmallVec<i32, 2> = SmallVec::from([1, 2]);
+    let mut w: SmallVec<i32, 2> = SmallVec::from([3]);
+    v.append(&mut w);
+    assert!(v.spilled());
+    assert_eq!(v, [1, 2, 3]);
+    assert!(v.split_off(3).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_split_off_out_of_bounds() {
+    let mut v: SmallVec<i32, 4> = SmallVec::from([1, 2]);
+    let _ = v.split_off(3);
+}
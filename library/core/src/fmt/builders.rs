@@ -1,6 +1,7 @@
 #![allow(unused_imports)]
 
-use crate::fmt::{self, Debug, Formatter};
+use crate::fmt::visit::debug_visitor;
+use crate::fmt::{self, Debug, DebugNode, DebugVisitor, Formatter};
 
 struct PadAdapter<'buf, 'state> {
     buf: &'buf mut (dyn fmt::Write + 'buf),
@@ -27,6 +28,26 @@ fn wrap<'slot, 'fmt: 'buf + 'slot>(
     }
 }
 
+/// Returns whether `fmt` is being driven by [`fmt::visit_debug`], in which case
+/// builders report their structure to the visitor instead of writing text.
+///
+/// This only reads the formatter's options, so ordinary `{:?}` output does not
+/// pay for more than a comparison.
+#[inline]
+fn is_visiting(fmt: &Formatter<'_>) -> bool {
+    fmt.options.is_visiting()
+}
+
+fn visit<F>(fmt: &mut Formatter<'_>, f: F) -> fmt::Result
+where
+    F: FnOnce(&mut dyn DebugVisitor) -> fmt::Result,
+{
+    match debug_visitor(fmt) {
+        Some(visitor) => f(visitor),
+        None => Ok(()),
+    }
+}
+
 impl fmt::Write for PadAdapter<'_, '_> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for s in s.split_inclusive('\n') {
@@ -89,14 +110,20 @@ pub struct DebugStruct<'a, 'b: 'a> {
     fmt: &'a mut fmt::Formatter<'b>,
     result: fmt::Result,
     has_fields: bool,
+    visiting: bool,
 }
 
 pub(super) fn debug_struct_new<'a, 'b>(
     fmt: &'a mut fmt::Formatter<'b>,
     name: &str,
 ) -> DebugStruct<'a, 'b> {
-    let result = fmt.write_str(name);
-    DebugStruct { fmt, result, has_fields: false }
+    let visiting = is_visiting(fmt);
+    let result = if visiting {
+        visit(fmt, |v| v.begin(DebugNode::Struct(name)))
+    } else {
+        fmt.write_str(name)
+    };
+    DebugStruct { fmt, result, has_fields: false, visiting }
 }
 
 impl<'a, 'b: 'a> DebugStruct<'a, 'b> {
@@ -143,6 +170,11 @@ pub fn field_with<F>(&mut self, name: &str, value_fmt: F) -> &mut Self
         F: FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
     {
         self.result = self.result.and_then(|_| {
+            if self.visiting {
+                visit(self.fmt, |v| v.field(name))?;
+                return value_fmt(self.fmt);
+            }
+
             if self.is_pretty() {
                 if !self.has_fields {
                     self.fmt.write_str(" {\n")?;
@@ -196,6 +228,10 @@ pub fn field_with<F>(&mut self, name: &str, value_fmt: F) -> &mut Self
     #[stable(feature = "debug_non_exhaustive", since = "1.53.0")]
     pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
         self.result = self.result.and_then(|_| {
+            if self.visiting {
+                return visit(self.fmt, |v| v.end(true));
+            }
+
             if self.has_fields {
                 if self.is_pretty() {
                     let mut slot = None;
@@ -242,7 +278,9 @@ pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
     /// ```
     #[stable(feature = "debug_builders", since = "1.2.0")]
     pub fn finish(&mut self) -> fmt::Result {
-        if self.has_fields {
+        if self.visiting {
+            self.result = self.result.and_then(|_| visit(self.fmt, |v| v.end(false)));
+        } else if self.has_fields {
             self.result = self.result.and_then(|_| {
                 if self.is_pretty() { self.fmt.write_str("}") } else { self.fmt.write_str(" }") }
             });
@@ -291,14 +329,20 @@ pub struct DebugTuple<'a, 'b: 'a> {
     result: fmt::Result,
     fields: usize,
     empty_name: bool,
+    visiting: bool,
 }
 
 pub(super) fn debug_tuple_new<'a, 'b>(
     fmt: &'a mut fmt::Formatter<'b>,
     name: &str,
 ) -> DebugTuple<'a, 'b> {
-    let result = fmt.write_str(name);
-    DebugTuple { fmt, result, fields: 0, empty_name: name.is_empty() }
+    let visiting = is_visiting(fmt);
+    let result = if visiting {
+        visit(fmt, |v| v.begin(DebugNode::Tuple(name)))
+    } else {
+        fmt.write_str(name)
+    };
+    DebugTuple { fmt, result, fields: 0, empty_name: name.is_empty(), visiting }
 }
 
 impl<'a, 'b: 'a> DebugTuple<'a, 'b> {
@@ -340,6 +384,11 @@ pub fn field_with<F>(&mut self, value_fmt: F) -> &mut Self
         F: FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
     {
         self.result = self.result.and_then(|_| {
+            if self.visiting {
+                visit(self.fmt, |v| v.element())?;
+                return value_fmt(self.fmt);
+            }
+
             if self.is_pretty() {
                 if self.fields == 0 {
                     self.fmt.write_str("(\n")?;
@@ -386,6 +435,10 @@ pub fn field_with<F>(&mut self, value_fmt: F) -> &mut Self
     #[stable(feature = "debug_more_non_exhaustive", since = "1.83.0")]
     pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
         self.result = self.result.and_then(|_| {
+            if self.visiting {
+                return visit(self.fmt, |v| v.end(true));
+            }
+
             if self.fields > 0 {
                 if self.is_pretty() {
                     let mut slot = None;
@@ -429,7 +482,9 @@ pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
     /// ```
     #[stable(feature = "debug_builders", since = "1.2.0")]
     pub fn finish(&mut self) -> fmt::Result {
-        if self.fields > 0 {
+        if self.visiting {
+            self.result = self.result.and_then(|_| visit(self.fmt, |v| v.end(false)));
+        } else if self.fields > 0 {
             self.result = self.result.and_then(|_| {
                 if self.fields == 1 && self.empty_name && !self.is_pretty() {
                     self.fmt.write_str(",")?;
@@ -450,6 +505,7 @@ struct DebugInner<'a, 'b: 'a> {
     fmt: &'a mut fmt::Formatter<'b>,
     result: fmt::Result,
     has_fields: bool,
+    visiting: bool,
 }
 
 impl<'a, 'b: 'a> DebugInner<'a, 'b> {
@@ -458,6 +514,11 @@ fn entry_with<F>(&mut self, entry_fmt: F)
         F: FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
     {
         self.result = self.result.and_then(|_| {
+            if self.visiting {
+                visit(self.fmt, |v| v.element())?;
+                return entry_fmt(self.fmt);
+            }
+
             if self.is_pretty() {
                 if !self.has_fields {
                     self.fmt.write_str("\n")?;
@@ -478,6 +539,16 @@ fn entry_with<F>(&mut self, entry_fmt: F)
         self.has_fields = true;
     }
 
+    fn begin(fmt: &'a mut fmt::Formatter<'b>, node: DebugNode<'_>, open: &str) -> Self {
+        let visiting = is_visiting(fmt);
+        let result = if visiting { visit(fmt, |v| v.begin(node)) } else { fmt.write_str(open) };
+        DebugInner { fmt, result, has_fields: false, visiting }
+    }
+
+    fn end(&mut self, close: &str) -> fmt::Result {
+        if self.visiting { visit(self.fmt, |v| v.end(false)) } else { self.fmt.write_str(close) }
+    }
+
     fn is_pretty(&self) -> bool {
         self.fmt.alternate()
     }
@@ -516,8 +587,7 @@ pub struct DebugSet<'a, 'b: 'a> {
 }
 
 pub(super) fn debug_set_new<'a, 'b>(fmt: &'a mut fmt::Formatter<'b>) -> DebugSet<'a, 'b> {
-    let result = fmt.write_str("{");
-    DebugSet { inner: DebugInner { fmt, result, has_fields: false } }
+    DebugSet { inner: DebugInner::begin(fmt, DebugNode::Set, "{") }
 }
 
 impl<'a, 'b: 'a> DebugSet<'a, 'b> {
@@ -629,6 +699,10 @@ pub fn entries<D, I>(&mut self, entries: I) -> &mut Self
     #[stable(feature = "debug_more_non_exhaustive", since = "1.83.0")]
     pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
         self.inner.result = self.inner.result.and_then(|_| {
+            if self.inner.visiting {
+                return visit(self.inner.fmt, |v| v.end(true));
+            }
+
             if self.inner.has_fields {
                 if self.inner.is_pretty() {
                     let mut slot = None;
@@ -670,7 +744,7 @@ pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
     /// ```
     #[stable(feature = "debug_builders", since = "1.2.0")]
     pub fn finish(&mut self) -> fmt::Result {
-        self.inner.result = self.inner.result.and_then(|_| self.inner.fmt.write_str("}"));
+        self.inner.result = self.inner.result.and_then(|_| self.inner.end("}"));
         self.inner.result
     }
 }
@@ -708,8 +782,7 @@ pub struct DebugList<'a, 'b: 'a> {
 }
 
 pub(super) fn debug_list_new<'a, 'b>(fmt: &'a mut fmt::Formatter<'b>) -> DebugList<'a, 'b> {
-    let result = fmt.write_str("[");
-    DebugList { inner: DebugInner { fmt, result, has_fields: false } }
+    DebugList { inner: DebugInner::begin(fmt, DebugNode::List, "[") }
 }
 
 impl<'a, 'b: 'a> DebugList<'a, 'b> {
@@ -821,6 +894,10 @@ pub fn entries<D, I>(&mut self, entries: I) -> &mut Self
     #[stable(feature = "debug_more_non_exhaustive", since = "1.83.0")]
     pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
         self.inner.result.and_then(|_| {
+            if self.inner.visiting {
+                return visit(self.inner.fmt, |v| v.end(true));
+            }
+
             if self.inner.has_fields {
                 if self.inner.is_pretty() {
                     let mut slot = None;
@@ -861,7 +938,7 @@ pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
     /// ```
     #[stable(feature = "debug_builders", since = "1.2.0")]
     pub fn finish(&mut self) -> fmt::Result {
-        self.inner.result = self.inner.result.and_then(|_| self.inner.fmt.write_str("]"));
+        self.inner.result = self.inner.result.and_then(|_| self.inner.end("]"));
         self.inner.result
     }
 }
@@ -901,11 +978,14 @@ pub struct DebugMap<'a, 'b: 'a> {
     has_key: bool,
     // The state of newlines is tracked between keys and values
     state: PadAdapterState,
+    visiting: bool,
 }
 
 pub(super) fn debug_map_new<'a, 'b>(fmt: &'a mut fmt::Formatter<'b>) -> DebugMap<'a, 'b> {
-    let result = fmt.write_str("{");
-    DebugMap { fmt, result, has_fields: false, has_key: false, state: Default::default() }
+    let visiting = is_visiting(fmt);
+    let result =
+        if visiting { visit(fmt, |v| v.begin(DebugNode::Map)) } else { fmt.write_str("{") };
+    DebugMap { fmt, result, has_fields: false, has_key: false, state: Default::default(), visiting }
 }
 
 impl<'a, 'b: 'a> DebugMap<'a, 'b> {
@@ -988,7 +1068,10 @@ pub fn key_with<F>(&mut self, key_fmt: F) -> &mut Self
                                     without completing the previous one"
             );
 
-            if self.is_pretty() {
+            if self.visiting {
+                visit(self.fmt, |v| v.key())?;
+                key_fmt(self.fmt)?;
+            } else if self.is_pretty() {
                 if !self.has_fields {
                     self.fmt.write_str("\n")?;
                 }
@@ -1060,7 +1143,10 @@ pub fn value_with<F>(&mut self, value_fmt: F) -> &mut Self
         self.result = self.result.and_then(|_| {
             assert!(self.has_key, "attempted to format a map value before its key");
 
-            if self.is_pretty() {
+            if self.visiting {
+                visit(self.fmt, |v| v.value())?;
+                value_fmt(self.fmt)?;
+            } else if self.is_pretty() {
                 let mut slot = None;
                 let mut writer = PadAdapter::wrap(self.fmt, &mut slot, &mut self.state);
                 value_fmt(&mut writer)?;
@@ -1151,6 +1237,10 @@ pub fn finish_non_exhaustive(&mut self) -> fmt::Result {
         self.result = self.result.and_then(|_| {
             assert!(!self.has_key, "attempted to finish a map with a partial entry");
 
+            if self.visiting {
+                return visit(self.fmt, |v| v.end(true));
+            }
+
             if self.has_fields {
                 if self.is_pretty() {
                     let mut slot = None;
@@ -1200,7 +1290,7 @@ pub fn finish(&mut self) -> fmt::Result {
         self.result = self.result.and_then(|_| {
             assert!(!self.has_key, "attempted to finish a map with a partial entry");
 
-            self.fmt.write_str("}")
+            if self.visiting { visit(self.fmt, |v| v.end(false)) } else { self.fmt.write_str("}") }
         });
         self.result
     }
@@ -16,6 +16,7 @@
 mod nofloat;
 mod num;
 mod rt;
+mod visit;
 
 #[stable(feature = "fmt_flags_align", since = "1.28.0")]
 #[rustc_diagnostic_item = "Alignment"]
@@ -37,6 +38,8 @@ pub enum Alignment {
 pub use self::builders::{DebugList, DebugMap, DebugSet, DebugStruct, DebugTuple};
 #[unstable(feature = "debug_closure_helpers", issue = "117729")]
 pub use self::builders::{FromFn, from_fn};
+#[unstable(feature = "debug_visitor", issue = "none")]
+pub use self::visit::{DebugNode, DebugVisitor, visit_debug};
 
 /// The type returned by formatter methods.
 ///
@@ -304,7 +307,8 @@ pub struct FormattingOptions {
     flags: u32,
     /// Width if width flag (bit 27) above is set. Otherwise, always 0.
     width: u16,
-    /// Precision if precision flag (bit 28) above is set. Otherwise, always 0.
+    /// Precision if precision flag (bit 28) above is set. Otherwise 0, or
+    /// [`VISITING`](Self::VISITING) for a formatter driven by [`visit_debug`].
     precision: u16,
 }
 
@@ -534,7 +538,29 @@ pub const fn get_debug_as_hex(&self) -> Option<DebugAsHex> {
     /// You may alternatively use [`Formatter::new()`].
     #[unstable(feature = "formatting_options", issue = "118117")]
     pub fn create_formatter<'a>(self, write: &'a mut (dyn Write + 'a)) -> Formatter<'a> {
-        Formatter { options: self, buf: write }
+        Formatter { options: self.visiting(false), buf: write }
+    }
+
+    /// The precision that marks the options of a formatter whose writer is the
+    /// one of a [`visit_debug`] call, which can't be set without the precision
+    /// flag through the methods above.
+    const VISITING: u16 = u16::MAX;
+
+    /// Whether these are the options of a formatter whose writer is the one of
+    /// a [`visit_debug`] call.
+    #[inline]
+    const fn is_visiting(&self) -> bool {
+        self.flags & flags::PRECISION_FLAG == 0 && self.precision == Self::VISITING
+    }
+
+    /// Marks or unmarks these as options of a formatter whose writer is the one
+    /// of a [`visit_debug`] call. Options with a precision can't be marked.
+    #[inline]
+    const fn visiting(mut self, visiting: bool) -> Self {
+        if self.flags & flags::PRECISION_FLAG == 0 {
+            self.precision = if visiting { Self::VISITING } else { 0 };
+        }
+        self
     }
 }
 
@@ -575,13 +601,13 @@ impl<'a> Formatter<'a> {
     /// You may alternatively use [`FormattingOptions::create_formatter()`].
     #[unstable(feature = "formatting_options", issue = "118117")]
     pub fn new(write: &'a mut (dyn Write + 'a), options: FormattingOptions) -> Self {
-        Formatter { options, buf: write }
+        Formatter { options: options.visiting(false), buf: write }
     }
 
     /// Creates a new formatter based on this one with given [`FormattingOptions`].
     #[unstable(feature = "formatting_options", issue = "118117")]
     pub fn with_options<'b>(&'b mut self, options: FormattingOptions) -> Formatter<'b> {
-        Formatter { options, buf: self.buf }
+        Formatter { options: options.visiting(self.options.is_visiting()), buf: self.buf }
     }
 }
 
@@ -1461,7 +1487,18 @@ pub trait UpperExp {
 /// [`write!`]: crate::write!
 #[stable(feature = "rust1", since = "1.0.0")]
 pub fn write(output: &mut dyn Write, args: Arguments<'_>) -> Result {
-    let mut formatter = Formatter::new(output, FormattingOptions::new());
+    write_with(Formatter::new(output, FormattingOptions::new()), args)
+}
+
+/// Writes `args` to the writer of a formatter that is part of a [`visit_debug`]
+/// call, through a fresh `Formatter` that is part of it too.
+#[cold]
+fn write_visiting(output: &mut dyn Write, args: Arguments<'_>) -> Result {
+    write_with(Formatter { options: FormattingOptions::new().visiting(true), buf: output }, args)
+}
+
+#[inline]
+fn write_with(mut formatter: Formatter<'_>, args: Arguments<'_>) -> Result {
     let mut idx = 0;
 
     match args.fmt {
@@ -1536,8 +1573,8 @@ unsafe fn run(fmt: &mut Formatter<'_>, arg: &rt::Placeholder, args: &[rt::Argume
     // which guarantees its index is always within bounds.
     let value = unsafe { args.get_unchecked(arg.position) };
 
-    // Set all the formatting options.
-    fmt.options = options;
+    // Set all the formatting options, staying part of a `visit_debug` call.
+    fmt.options = options.visiting(fmt.options.is_visiting());
 
     // Then actually do some printing
     // SAFETY: this is a placeholder argument.
@@ -1603,8 +1640,9 @@ fn wrap_buf<'b, 'c, F>(&'b mut self, wrap: F) -> Formatter<'c>
             // We want to change this
             buf: wrap(self.buf),
 
-            // And preserve these
-            options: self.options,
+            // And preserve these, except that the new writer isn't the one of a
+            // `visit_debug` call
+            options: self.options.visiting(false),
         }
     }
 
@@ -1955,6 +1993,8 @@ pub fn write_str(&mut self, data: &str) -> Result {
     pub fn write_fmt(&mut self, fmt: Arguments<'_>) -> Result {
         if let Some(s) = fmt.as_statically_known_str() {
             self.buf.write_str(s)
+        } else if self.options.is_visiting() {
+            write_visiting(self.buf, fmt)
         } else {
             write(self.buf, fmt)
         }
@@ -2676,6 +2716,8 @@ fn write_char(&mut self, c: char) -> Result {
     fn write_fmt(&mut self, args: Arguments<'_>) -> Result {
         if let Some(s) = args.as_statically_known_str() {
             self.buf.write_str(s)
+        } else if self.options.is_visiting() {
+            write_visiting(self.buf, args)
         } else {
             write(self.buf, args)
         }
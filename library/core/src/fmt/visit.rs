@@ -0,0 +1,199 @@
+//! Structural traversal of [`Debug`] implementations.
+
+use crate::fmt::{Debug, Formatter, FormattingOptions, Result, Write};
+
+/// The kind of composite value being entered, as reported to
+/// [`DebugVisitor::begin`].
+///
+/// Each variant corresponds to one of the debug builders on [`Formatter`].
+#[unstable(feature = "debug_visitor", issue = "none")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DebugNode<'a> {
+    /// A value formatted with [`Formatter::debug_struct`], with its name.
+    Struct(&'a str),
+    /// A value formatted with [`Formatter::debug_tuple`], with its name.
+    ///
+    /// Plain tuples such as `(1, 2)` have an empty name.
+    Tuple(&'a str),
+    /// A value formatted with [`Formatter::debug_list`].
+    List,
+    /// A value formatted with [`Formatter::debug_set`].
+    Set,
+    /// A value formatted with [`Formatter::debug_map`].
+    Map,
+}
+
+/// A sink receiving the structure of a value's [`Debug`] output.
+///
+/// When a value is passed to [`visit_debug`], the `debug_struct`,
+/// `debug_tuple`, `debug_list`, `debug_set` and `debug_map` builders it uses
+/// no longer write text. Instead they report each composite value to the
+/// visitor as a [`begin`] call, followed by its children, followed by an
+/// [`end`] call. Every child is announced by exactly one of [`field`],
+/// [`element`], [`key`] or [`value`] before its own events.
+///
+/// Everything else a `Debug` implementation writes, such as the output of
+/// integers, strings or a hand-written `write!`, is passed to [`text`]. The
+/// text of one leaf value may arrive over several calls; it ends at the next
+/// call to any other method.
+///
+/// This lets tools walk any `#[derive(Debug)]` value as a tree, for example
+/// to diff two values or encode them for structured logging, without parsing
+/// the textual output.
+///
+/// [`begin`]: DebugVisitor::begin
+/// [`end`]: DebugVisitor::end
+/// [`field`]: DebugVisitor::field
+/// [`element`]: DebugVisitor::element
+/// [`key`]: DebugVisitor::key
+/// [`value`]: DebugVisitor::value
+/// [`text`]: DebugVisitor::text
+///
+/// # Examples
+///
+/// ```
+/// #![feature(debug_visitor)]
+/// use std::fmt::{self, DebugNode, DebugVisitor};
+///
+/// #[derive(Debug)]
+/// struct Line {
+///     start: Point,
+///     end: Point,
+/// }
+///
+/// #[derive(Debug)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// /// Collects a `path = value` line for every leaf.
+/// #[derive(Default)]
+/// struct Leaves {
+///     path: Vec<String>,
+///     leaf: String,
+///     out: Vec<String>,
+/// }
+///
+/// impl Leaves {
+///     fn flush(&mut self) {
+///         if !self.leaf.is_empty() {
+///             let leaf = std::mem::take(&mut self.leaf);
+///             self.out.push(format!("{} = {leaf}", self.path.join(".")));
+///         }
+///     }
+/// }
+///
+/// impl DebugVisitor for Leaves {
+///     fn begin(&mut self, _node: DebugNode<'_>) -> fmt::Result {
+///         self.path.push(String::new());
+///         Ok(())
+///     }
+///
+///     fn field(&mut self, name: &str) -> fmt::Result {
+///         self.flush();
+///         *self.path.last_mut().unwrap() = name.to_string();
+///         Ok(())
+///     }
+///
+///     fn text(&mut self, s: &str) -> fmt::Result {
+///         self.leaf.push_str(s);
+///         Ok(())
+///     }
+///
+///     fn end(&mut self, _non_exhaustive: bool) -> fmt::Result {
+///         self.flush();
+///         self.path.pop();
+///         Ok(())
+///     }
+/// }
+///
+/// let line = Line { start: Point { x: 1, y: 2 }, end: Point { x: 3, y: 4 } };
+/// let mut leaves = Leaves::default();
+/// fmt::visit_debug(&line, &mut leaves)?;
+/// assert_eq!(leaves.out, ["start.x = 1", "start.y = 2", "end.x = 3", "end.y = 4"]);
+/// # fmt::Result::Ok(())
+/// ```
+#[unstable(feature = "debug_visitor", issue = "none")]
+pub trait DebugVisitor {
+    /// Called when a composite value begins.
+    fn begin(&mut self, node: DebugNode<'_>) -> Result;
+
+    /// Called before each field of a [`DebugNode::Struct`].
+    fn field(&mut self, name: &str) -> Result {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Called before each field of a [`DebugNode::Tuple`] and each entry of a
+    /// [`DebugNode::List`] or [`DebugNode::Set`].
+    fn element(&mut self) -> Result {
+        Ok(())
+    }
+
+    /// Called before each key of a [`DebugNode::Map`].
+    fn key(&mut self) -> Result {
+        Ok(())
+    }
+
+    /// Called before each value of a [`DebugNode::Map`].
+    fn value(&mut self) -> Result {
+        Ok(())
+    }
+
+    /// Called with text written outside of the debug builders.
+    fn text(&mut self, s: &str) -> Result;
+
+    /// Called when the innermost composite value ends.
+    ///
+    /// `non_exhaustive` is `true` if the value was finished with one of the
+    /// `finish_non_exhaustive` methods.
+    fn end(&mut self, non_exhaustive: bool) -> Result;
+}
+
+/// Walks `value`'s [`Debug`] implementation, reporting its structure to
+/// `visitor` instead of producing text.
+///
+/// The value is formatted with default [`FormattingOptions`], as if by `{:?}`.
+/// See [`DebugVisitor`] for the events that are produced. Values that are
+/// written with a precision, as in `write!(f, "{:.2?}", value)`, are passed to
+/// the visitor as text.
+///
+/// # Errors
+///
+/// Returns the first error returned by `visitor`, or by the `Debug`
+/// implementation itself.
+#[unstable(feature = "debug_visitor", issue = "none")]
+pub fn visit_debug(value: &dyn Debug, visitor: &mut dyn DebugVisitor) -> Result {
+    let mut writer = VisitorWriter { visitor };
+    let mut fmt = Formatter { options: FormattingOptions::new().visiting(true), buf: &mut writer };
+    value.fmt(&mut fmt)
+}
+
+/// Returns the visitor of the [`visit_debug`] call that `fmt` is part of, if any.
+///
+/// The debug builders use this to report their structure instead of writing text.
+pub(super) fn debug_visitor<'f>(fmt: &'f mut Formatter<'_>) -> Option<&'f mut dyn DebugVisitor> {
+    if !fmt.options.is_visiting() {
+        return None;
+    }
+    let writer = &mut *fmt.buf as *mut dyn Write as *mut VisitorWriter<'f>;
+    // SAFETY: only `visit_debug` and `write_visiting` mark options as visiting,
+    // for formatters that write to a `VisitorWriter`, and every way of making a
+    // formatter with another writer unmarks them.
+    Some(unsafe { &mut *(*writer).visitor })
+}
+
+/// The `Write` end of a [`visit_debug`] call: plain text goes to
+/// [`DebugVisitor::text`], and the builders find the visitor itself through
+/// [`debug_visitor`].
+struct VisitorWriter<'a> {
+    visitor: &'a mut (dyn DebugVisitor + 'a),
+}
+
+impl Write for VisitorWriter<'_> {
+    fn write_str(&mut self, s: &str) -> Result {
+        self.visitor.text(s)
+    }
+}
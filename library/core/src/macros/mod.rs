@@ -1758,6 +1758,51 @@ macro_rules! trace_macros {
         /* compiler built-in */
     }
 
+    /// Compiles a function once for each of the listed sets of target features and once
+    /// without them, and calls the first version that the running CPU supports.
+    ///
+    /// On x86 ELF targets whose C library resolves ifuncs, the version is picked by an ifunc
+    /// resolver when the program is loaded. Elsewhere, the supported features are detected the
+    /// first time the function is called, and the choice is cached. On targets without runtime
+    /// feature detection in `std::arch`, the function is compiled only once, without the extra
+    /// features. `#![no_std]` crates can't use this attribute.
+    ///
+    /// ```ignore (requires the detection macros of std)
+    /// #[multiversion(targets = ["avx2", "sse4.2,popcnt"])]
+    /// fn count_ones(values: &[u64]) -> u32 {
+    ///     values.iter().map(|v| v.count_ones()).sum()
+    /// }
+    /// ```
+    #[unstable(feature = "multiversion", issue = "none")]
+    #[allow_internal_unstable(multiversion_internals)]
+    #[rustc_builtin_macro]
+    #[cfg(not(bootstrap))]
+    pub macro multiversion($item:item) {
+        /* compiler built-in */
+    }
+
+    /// Expands `#[multiversion]` once its targets have been parsed.
+    #[unstable(
+        feature = "multiversion_internals",
+        issue = "none",
+        reason = "`multiversion_expand` is only for internal use by `multiversion`"
+    )]
+    #[allow_internal_unsafe]
+    #[allow_internal_unstable(
+        is_loongarch_feature_detected,
+        is_riscv_feature_detected,
+        stdarch_arm_feature_detection,
+        stdarch_mips_feature_detection,
+        stdarch_powerpc_feature_detection,
+        stdarch_s390x_feature_detection
+    )]
+    #[rustc_builtin_macro]
+    #[doc(hidden)]
+    #[cfg(not(bootstrap))]
+    pub macro multiversion_expand($item:item) {
+        /* compiler built-in */
+    }
+
     /// Unstable placeholder for type ascription.
     #[allow_internal_unstable(builtin_syntax)]
     #[unstable(
@@ -119,3 +119,12 @@
 )]
 #[cfg(not(bootstrap))]
 pub use crate::macros::builtin::define_opaque;
+
+#[unstable(feature = "multiversion", issue = "none")]
+#[cfg(not(bootstrap))]
+pub use crate::macros::builtin::multiversion;
+
+#[unstable(feature = "multiversion_internals", issue = "none")]
+#[doc(hidden)]
+#[cfg(not(bootstrap))]
+pub use crate::macros::builtin::multiversion_expand;
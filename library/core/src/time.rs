@@ -1415,6 +1415,89 @@ fn fmt_decimal(
     }
 }
 
+impl Duration {
+    /// Returns an object that implements [`Display`] for printing the duration
+    /// in days, hours, minutes and seconds, the way a person would write it.
+    ///
+    /// Durations of less than a minute are printed like their [`Debug`]
+    /// representation. Longer durations are split into their non-zero
+    /// components, with sub-second precision only shown if a precision is
+    /// requested. Width and fill flags are ignored for those.
+    ///
+    /// [`Display`]: fmt::Display
+    /// [`Debug`]: fmt::Debug
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(duration_display_human)]
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(Duration::from_millis(1500).display_human().to_string(), "1.5s");
+    /// assert_eq!(Duration::from_secs(3725).display_human().to_string(), "1h 2m 5s");
+    /// assert_eq!(Duration::from_secs(90_000).display_human().to_string(), "1d 1h");
+    /// assert_eq!(format!("{:.2}", Duration::from_millis(61_250).display_human()), "1m 1.25s");
+    /// ```
+    #[unstable(feature = "duration_display_human", issue = "none")]
+    #[must_use = "this does not display the duration, \
+                  it returns an object that can be displayed"]
+    #[inline]
+    pub const fn display_human(&self) -> DisplayHuman {
+        DisplayHuman(*self)
+    }
+}
+
+/// Helper struct for printing a [`Duration`] in a human-friendly form with
+/// `format!` and `{}`.
+///
+/// This `struct` is created by the [`display_human`] method on [`Duration`].
+/// See its documentation for more.
+///
+/// [`display_human`]: Duration::display_human
+#[unstable(feature = "duration_display_human", issue = "none")]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayHuman(Duration);
+
+#[unstable(feature = "duration_display_human", issue = "none")]
+impl fmt::Display for DisplayHuman {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const SECS_PER_MINUTE: u64 = 60;
+        const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+        const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+
+        let Duration { secs, nanos } = self.0;
+        if secs < SECS_PER_MINUTE {
+            return fmt::Debug::fmt(&self.0, f);
+        }
+
+        let components = [
+            (secs / SECS_PER_DAY, "d"),
+            (secs % SECS_PER_DAY / SECS_PER_HOUR, "h"),
+            (secs % SECS_PER_HOUR / SECS_PER_MINUTE, "m"),
+        ];
+        let mut sep = "";
+        for (value, unit) in components {
+            if value > 0 {
+                write!(f, "{sep}{value}{unit}")?;
+                sep = " ";
+            }
+        }
+
+        let secs = secs % SECS_PER_MINUTE;
+        match f.precision() {
+            Some(precision) if precision > 0 => {
+                // Truncate rather than round, so that the printed value never exceeds
+                // the actual duration.
+                let precision = precision.min(9);
+                let frac = nanos.as_inner() / 10u32.pow(9 - precision as u32);
+                write!(f, "{sep}{secs}.{frac:0precision$}s")
+            }
+            _ if secs > 0 => write!(f, "{sep}{secs}s"),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// An error which can be returned when converting a floating-point value of seconds
 /// into a [`Duration`].
 ///
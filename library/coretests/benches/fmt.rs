@@ -162,3 +162,26 @@ fn write_u8_min(bh: &mut Bencher) {
         black_box(format!("{}", black_box(u8::MIN)));
     });
 }
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct DebugPoint {
+    x: i32,
+    y: i32,
+    label: (u8, &'static str),
+    tags: [u16; 3],
+}
+
+// Exercises every debug builder entry point, which all check for an active
+// `visit_debug` visitor before writing.
+#[bench]
+fn write_derived_debug(bh: &mut Bencher) {
+    let point = DebugPoint { x: 1, y: -2, label: (3, "p"), tags: [4, 5, 6] };
+    bh.iter(|| {
+        let mut mem = String::new();
+        let wr = &mut mem as &mut dyn fmt::Write;
+        for _ in 0..1000 {
+            write!(wr, "{:?}", black_box(&point)).unwrap();
+        }
+    });
+}
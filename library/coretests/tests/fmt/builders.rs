@@ -1042,3 +1042,149 @@ struct Foo {
         .trim()
     );
 }
+
+mod debug_visitor {
+    use std::collections::BTreeMap;
+    use std::fmt::{self, DebugNode, DebugVisitor};
+
+    /// Records every event as a token, merging consecutive text chunks.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl Recorder {
+        fn push(&mut self, token: String) -> fmt::Result {
+            self.0.push(token);
+            Ok(())
+        }
+    }
+
+    impl DebugVisitor for Recorder {
+        fn begin(&mut self, node: DebugNode<'_>) -> fmt::Result {
+            self.push(format!("{node:?}"))
+        }
+
+        fn field(&mut self, name: &str) -> fmt::Result {
+            self.push(format!("{name}:"))
+        }
+
+        fn element(&mut self) -> fmt::Result {
+            self.push("-".to_string())
+        }
+
+        fn key(&mut self) -> fmt::Result {
+            self.push("key".to_string())
+        }
+
+        fn value(&mut self) -> fmt::Result {
+            self.push("value".to_string())
+        }
+
+        fn text(&mut self, s: &str) -> fmt::Result {
+            match self.0.last_mut() {
+                Some(last) if last.starts_with('=') => last.push_str(s),
+                _ => self.0.push(format!("={s}")),
+            }
+            Ok(())
+        }
+
+        fn end(&mut self, non_exhaustive: bool) -> fmt::Result {
+            self.push(if non_exhaustive { "end..".to_string() } else { "end".to_string() })
+        }
+    }
+
+    fn record(value: &dyn fmt::Debug) -> String {
+        let mut recorder = Recorder::default();
+        fmt::visit_debug(value, &mut recorder).unwrap();
+        recorder.0.join(" ")
+    }
+
+    #[test]
+    fn test_derived() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Foo {
+            bar: i32,
+            baz: (bool, &'static str),
+        }
+
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        enum Qux {
+            Unit,
+            Tuple(u8),
+        }
+
+        assert_eq!(
+            record(&Foo { bar: -1, baz: (true, "a\"b") }),
+            r#"Struct("Foo") bar: =-1 baz: Tuple("") - =true - ="a\"b" end end"#,
+        );
+        assert_eq!(record(&Qux::Unit), "=Unit");
+        assert_eq!(record(&Qux::Tuple(7)), r#"Tuple("Tuple") - =7 end"#);
+    }
+
+    #[test]
+    fn test_collections() {
+        assert_eq!(record(&[1, 2]), "List - =1 - =2 end");
+        assert_eq!(record(&Vec::<u8>::new()), "List end");
+
+        let map = BTreeMap::from([("a", Some(1)), ("b", None)]);
+        assert_eq!(
+            record(&map),
+            r#"Map key ="a" value Tuple("Some") - =1 end key ="b" value =None end"#,
+        );
+    }
+
+    #[test]
+    fn test_non_exhaustive() {
+        struct Foo;
+
+        impl fmt::Debug for Foo {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.debug_struct("Foo").field("bar", &true).finish_non_exhaustive()
+            }
+        }
+
+        assert_eq!(record(&Foo), r#"Struct("Foo") bar: =true end.."#);
+    }
+
+    #[test]
+    fn test_nested_through_write() {
+        struct Wrapper(Vec<char>);
+
+        impl fmt::Debug for Wrapper {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // Structure is still reported when nested values are formatted
+                // through `format_args!`, even with different options.
+                write!(fmt, "wrapped {:#?}", self.0)
+            }
+        }
+
+        assert_eq!(record(&Wrapper(vec!['x'])), "=wrapped  List - ='x' end");
+    }
+
+    #[test]
+    fn test_options_on_other_writer() {
+        struct Buffered;
+
+        impl fmt::Debug for Buffered {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // A formatter over another writer isn't part of the visit, even
+                // with the options of one that is.
+                let mut text = String::new();
+                let mut inner = fmt::Formatter::new(&mut text, fmt.options());
+                inner.debug_list().entry(&1).finish()?;
+                fmt.write_str(&text)
+            }
+        }
+
+        assert_eq!(record(&Buffered), "=[1]");
+    }
+
+    #[test]
+    fn test_text_output_unchanged() {
+        // Builders outside of `visit_debug` must keep writing text.
+        let map = BTreeMap::from([(1, [2])]);
+        assert_eq!(format!("{map:?}"), "{1: [2]}");
+        assert_eq!(format!("{map:#?}"), "{\n    1: [\n        2,\n    ],\n}");
+    }
+}
@@ -455,6 +455,17 @@ fn debug_formatting_precision_high() {
     assert_eq!(format!("{:.20?}", Duration::new(4, 001_000_000)), "4.00100000000000000000s");
 }
 
+#[test]
+fn display_human() {
+    assert_eq!(Duration::ZERO.display_human().to_string(), "0ns");
+    assert_eq!(Duration::new(59, 500_000_000).display_human().to_string(), "59.5s");
+    assert_eq!(Duration::from_secs(60).display_human().to_string(), "1m");
+    assert_eq!(Duration::new(3_601, 999_999_999).display_human().to_string(), "1h 1s");
+    assert_eq!(Duration::from_secs(2 * 86_400 + 5 * 60).display_human().to_string(), "2d 5m");
+    assert_eq!(format!("{:.3}", Duration::new(61, 123_456_789).display_human()), "1m 1.123s");
+    assert_eq!(format!("{:.12}", Duration::new(120, 1).display_human()), "2m 0.000000001s");
+}
+
 #[test]
 fn duration_const() {
     // test that the methods of `Duration` are usable in a const context
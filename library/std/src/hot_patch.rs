@@ -0,0 +1,48 @@
+//! Loading the patches that `-Zhot-patch` builds into a running executable.
+//!
+//! An executable built with `-Zhot-patch` gets a `.hotpatch` directory next to
+//! it, whose `manifest.json` lists the patches built against it in the order
+//! they must be applied. Each patch is a directory of shared objects, one per
+//! codegen unit that changed, and [`load`] applies one of them to the running
+//! process.
+//!
+//! This module is tied to the unstable `-Zhot-patch` flag, and the layout of
+//! the patches that it reads may change with any release of the compiler.
+
+#![unstable(
+    feature = "hot_patch",
+    reason = "this module is an implementation detail of `-Zhot-patch`, \
+              and will not be stabilized",
+    issue = "none"
+)]
+#![doc(hidden)]
+
+use crate::io;
+use crate::path::Path;
+
+/// Loads the shared object at `path`, which is part of a patch built by
+/// `-Zhot-patch`, and redirects every function of the running executable that
+/// it defines to its new version. Returns the number of functions redirected.
+///
+/// Functions that the executable doesn't define were added since it was built,
+/// so nothing but the patch calls them, and they are left alone.
+///
+/// # Safety
+///
+/// The shared object must belong to a patch built against the running
+/// executable, and the patches listed before it in the manifest must have
+/// been loaded. The patched functions must not be executing their first
+/// instructions in another thread while they are overwritten, and the changes
+/// of the patch must be limited to code: the layout of types and the values of
+/// statics are shared with the executable as they are.
+///
+/// # Errors
+///
+/// Fails if the shared object can't be loaded or isn't a patch, or if the code
+/// of the executable can't be made writable, e.g. because of a security policy
+/// that forbids writable code.
+///
+/// Only x86_64 and AArch64 Linux are supported.
+pub unsafe fn load(path: &Path) -> io::Result<usize> {
+    unsafe { crate::sys::hot_patch::load(path) }
+}
@@ -480,6 +480,10 @@
 // The Rust prelude
 pub mod prelude;
 
+// The loader of the patches built by `-Zhot-patch`, which isn't meant to be
+// stable
+pub mod hot_patch;
+
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use core::any;
 #[stable(feature = "core_array", since = "1.35.0")]
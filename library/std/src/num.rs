@@ -26,3 +26,6 @@
 pub use core::num::{NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize};
 #[stable(feature = "nonzero", since = "1.28.0")]
 pub use core::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize};
+
+#[unstable(feature = "bigint", issue = "none")]
+pub use alloc_crate::num::{BigInt, BigUint, ParseBigIntError};
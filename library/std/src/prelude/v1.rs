@@ -112,6 +112,15 @@
 #[cfg(not(bootstrap))]
 pub use core::prelude::v1::define_opaque;
 
+#[unstable(feature = "multiversion", issue = "none")]
+#[cfg(not(bootstrap))]
+pub use core::prelude::v1::multiversion;
+
+#[unstable(feature = "multiversion_internals", issue = "none")]
+#[doc(hidden)]
+#[cfg(not(bootstrap))]
+pub use core::prelude::v1::multiversion_expand;
+
 // The file so far is equivalent to core/src/prelude/v1.rs. It is duplicated
 // rather than glob imported because we want docs to show these re-exports as
 // pointing to within `std`.
@@ -0,0 +1,123 @@
+use crate::ffi::{CStr, CString, c_char, c_void};
+use crate::os::unix::ffi::OsStrExt;
+use crate::path::Path;
+use crate::sys::cvt;
+use crate::{io, ptr, slice};
+
+/// The table of the functions that a shared object replaces, which the
+/// compiler defines in every shared object of a patch.
+const TABLE_SYMBOL: &CStr = c"__rustc_hotpatch_table";
+
+/// The version of the layout of the table that this loader understands.
+const TABLE_VERSION: usize = 1;
+
+#[repr(C)]
+struct TableEntry {
+    symbol: *const c_char,
+    function: *const c_void,
+}
+
+pub unsafe fn load(path: &Path) -> io::Result<usize> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::const_error!(io::ErrorKind::InvalidInput, "path contains a nul byte"))?;
+
+    // Keep the symbols of the patch out of the global scope: the executable,
+    // and every later patch, must keep resolving them to the executable.
+    let patch = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    if patch.is_null() {
+        return Err(dl_error());
+    }
+    let executable = unsafe { libc::dlopen(ptr::null(), libc::RTLD_NOW) };
+    if executable.is_null() {
+        return Err(dl_error());
+    }
+
+    let table = unsafe { libc::dlsym(patch, TABLE_SYMBOL.as_ptr()) }.cast::<usize>();
+    if table.is_null() {
+        return Err(io::const_error!(io::ErrorKind::InvalidData, "not a hot patch"));
+    }
+    let (version, len) = unsafe { (*table, *table.add(1)) };
+    if version != TABLE_VERSION {
+        return Err(io::const_error!(io::ErrorKind::InvalidData, "unsupported hot patch version"));
+    }
+    let entries = unsafe { slice::from_raw_parts(table.add(2).cast::<TableEntry>(), len) };
+
+    let mut redirected = 0;
+    for entry in entries {
+        let old = unsafe { libc::dlsym(executable, entry.symbol) };
+        if old.is_null() {
+            continue;
+        }
+        unsafe { write_jump(old.cast(), entry.function)? };
+        redirected += 1;
+    }
+    Ok(redirected)
+}
+
+fn dl_error() -> io::Error {
+    let message = unsafe { libc::dlerror() };
+    if message.is_null() {
+        return io::const_error!(io::ErrorKind::Other, "failed to load hot patch");
+    }
+    io::Error::other(unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned())
+}
+
+/// Overwrites the patchable entry of a function, which `-Zhot-patch` fills
+/// with enough nops for the jump, with a jump to `target`.
+unsafe fn write_jump(entry: *mut u8, target: *const c_void) -> io::Result<()> {
+    let code = jump(target)?;
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = entry.addr() & !(page_size - 1);
+    let len = entry.addr() + code.len() - start;
+    let pages = entry.with_addr(start).cast::<c_void>();
+
+    cvt(unsafe {
+        libc::mprotect(pages, len, libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC)
+    })?;
+    unsafe { ptr::copy_nonoverlapping(code.as_ptr(), entry, code.len()) };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        flush_instruction_cache(entry, code.len())
+    };
+    cvt(unsafe { libc::mprotect(pages, len, libc::PROT_READ | libc::PROT_EXEC) })?;
+    Ok(())
+}
+
+/// `movabs r11, target; jmp r11`. `r11` is a scratch register in every calling
+/// convention that Rust uses on x86_64, and never holds an argument.
+#[cfg(target_arch = "x86_64")]
+fn jump(target: *const c_void) -> io::Result<[u8; 13]> {
+    let mut code = [0x49, 0xbb, 0, 0, 0, 0, 0, 0, 0, 0, 0x41, 0xff, 0xe3];
+    code[2..10].copy_from_slice(&(target.addr() as u64).to_le_bytes());
+    Ok(code)
+}
+
+/// `ldr x16, 8; br x16`, followed by the address. `x16` is the scratch
+/// register that veneers use, and never holds an argument.
+#[cfg(target_arch = "aarch64")]
+fn jump(target: *const c_void) -> io::Result<[u8; 16]> {
+    let mut code = [0; 16];
+    // Instructions are little-endian even on big-endian targets.
+    code[0..4].copy_from_slice(&0x5800_0050u32.to_le_bytes());
+    code[4..8].copy_from_slice(&0xd61f_0200u32.to_le_bytes());
+    code[8..16].copy_from_slice(&(target.addr() as u64).to_ne_bytes());
+    Ok(code)
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn jump(_target: *const c_void) -> io::Result<[u8; 0]> {
+    Err(io::const_error!(
+        io::ErrorKind::Unsupported,
+        "hot patching is not supported on this target"
+    ))
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn flush_instruction_cache(start: *mut u8, len: usize) {
+    unsafe extern "C" {
+        fn __clear_cache(start: *mut c_char, end: *mut c_char);
+    }
+
+    unsafe { __clear_cache(start.cast(), start.add(len).cast()) };
+}
@@ -0,0 +1,11 @@
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "linux")] {
+        mod linux;
+        pub use linux::load;
+    } else {
+        mod unsupported;
+        pub use unsupported::load;
+    }
+}
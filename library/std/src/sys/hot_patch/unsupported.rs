@@ -0,0 +1,6 @@
+use crate::io;
+use crate::path::Path;
+
+pub unsafe fn load(_path: &Path) -> io::Result<usize> {
+    Err(io::Error::UNSUPPORTED_PLATFORM)
+}
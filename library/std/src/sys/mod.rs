@@ -13,6 +13,7 @@
 pub mod cmath;
 pub mod exit_guard;
 pub mod fs;
+pub mod hot_patch;
 pub mod io;
 pub mod net;
 pub mod os_str;
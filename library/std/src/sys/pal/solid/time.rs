@@ -55,3 +55,15 @@ pub fn checked_sub_duration(&self, other: &Duration) -> Option<SystemTime> {
         Some(SystemTime(self.0.checked_sub_unsigned(other.as_secs())?))
     }
 }
+
+pub fn thread_cpu_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
+
+pub fn process_cpu_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
+
+pub fn boot_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
@@ -213,3 +213,15 @@ fn timestamp_rdtsc() -> Option<Duration> {
         Some(Duration::from_nanos(ns))
     }
 }
+
+pub fn thread_cpu_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
+
+pub fn process_cpu_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
+
+pub fn boot_time() -> crate::io::Result<Duration> {
+    Err(crate::io::Error::UNSUPPORTED_PLATFORM)
+}
@@ -307,3 +307,78 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .finish()
     }
 }
+
+/// Reads `clock` without panicking, for clocks that may be unavailable at
+/// runtime (e.g. per-thread CPU clocks inside some sandboxes).
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_os = "fuchsia",
+    target_os = "haiku",
+))]
+fn clock_duration(clock: libc::clockid_t) -> io::Result<Duration> {
+    use crate::mem::MaybeUninit;
+    use crate::sys::cvt;
+
+    let mut t = MaybeUninit::<libc::timespec>::uninit();
+    cvt(unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) })?;
+    let t = unsafe { t.assume_init() };
+    Ok(Duration::new(t.tv_sec as u64, t.tv_nsec as u32))
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly",
+        target_os = "illumos",
+        target_os = "solaris",
+        target_os = "fuchsia",
+        target_os = "haiku",
+    ))] {
+        pub fn thread_cpu_time() -> io::Result<Duration> {
+            clock_duration(libc::CLOCK_THREAD_CPUTIME_ID)
+        }
+
+        pub fn process_cpu_time() -> io::Result<Duration> {
+            clock_duration(libc::CLOCK_PROCESS_CPUTIME_ID)
+        }
+    } else {
+        pub fn thread_cpu_time() -> io::Result<Duration> {
+            Err(io::Error::UNSUPPORTED_PLATFORM)
+        }
+
+        pub fn process_cpu_time() -> io::Result<Duration> {
+            Err(io::Error::UNSUPPORTED_PLATFORM)
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(target_os = "linux", target_os = "android"))] {
+        pub fn boot_time() -> io::Result<Duration> {
+            clock_duration(libc::CLOCK_BOOTTIME)
+        }
+    } else if #[cfg(target_vendor = "apple")] {
+        // Unlike `CLOCK_UPTIME_RAW` used for `Instant`, `CLOCK_MONOTONIC` keeps
+        // counting while the system is asleep.
+        pub fn boot_time() -> io::Result<Duration> {
+            clock_duration(libc::CLOCK_MONOTONIC)
+        }
+    } else {
+        pub fn boot_time() -> io::Result<Duration> {
+            Err(io::Error::UNSUPPORTED_PLATFORM)
+        }
+    }
+}
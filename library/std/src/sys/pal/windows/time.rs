@@ -3,10 +3,10 @@
 
 use crate::cmp::Ordering;
 use crate::ptr::null;
-use crate::sys::c;
+use crate::sys::{c, cvt};
 use crate::sys_common::IntoInner;
 use crate::time::Duration;
-use crate::{fmt, mem};
+use crate::{fmt, io, mem};
 
 const NANOS_PER_SEC: u64 = 1_000_000_000;
 const INTERVALS_PER_SEC: u64 = NANOS_PER_SEC / 100;
@@ -162,6 +162,31 @@ fn intervals2dur(intervals: u64) -> Duration {
     Duration::new(intervals / INTERVALS_PER_SEC, ((intervals % INTERVALS_PER_SEC) * 100) as u32)
 }
 
+fn filetime2intervals(t: &c::FILETIME) -> u64 {
+    (t.dwLowDateTime as u64) | ((t.dwHighDateTime as u64) << 32)
+}
+
+/// CPU time (kernel plus user) consumed by the calling thread.
+pub fn thread_cpu_time() -> io::Result<Duration> {
+    let mut times: [c::FILETIME; 4] = unsafe { mem::zeroed() };
+    let [creation, exit, kernel, user] = &mut times;
+    unsafe { cvt(c::GetThreadTimes(c::GetCurrentThread(), creation, exit, kernel, user))? };
+    Ok(intervals2dur(filetime2intervals(kernel) + filetime2intervals(user)))
+}
+
+/// CPU time (kernel plus user) consumed by all threads of the current process.
+pub fn process_cpu_time() -> io::Result<Duration> {
+    let mut times: [c::FILETIME; 4] = unsafe { mem::zeroed() };
+    let [creation, exit, kernel, user] = &mut times;
+    unsafe { cvt(c::GetProcessTimes(c::GetCurrentProcess(), creation, exit, kernel, user))? };
+    Ok(intervals2dur(filetime2intervals(kernel) + filetime2intervals(user)))
+}
+
+/// Time elapsed since the system was started, including time spent suspended.
+pub fn boot_time() -> io::Result<Duration> {
+    Ok(Duration::from_millis(unsafe { c::GetTickCount64() }))
+}
+
 mod perf_counter {
     use super::NANOS_PER_SEC;
     use crate::sync::atomic::{AtomicU64, Ordering};
@@ -31,17 +31,28 @@
 
 #![stable(feature = "time", since = "1.3.0")]
 
+#[unstable(feature = "duration_display_human", issue = "none")]
+pub use core::time::DisplayHuman;
 #[stable(feature = "time", since = "1.3.0")]
 pub use core::time::Duration;
 #[stable(feature = "duration_checked_float", since = "1.66.0")]
 pub use core::time::TryFromFloatSecsError;
 
+#[unstable(feature = "time_civil", issue = "none")]
+pub use self::civil::{ParseUtcDateTimeError, UtcDateTime};
+#[unstable(feature = "boot_instant", issue = "none")]
+pub use self::clocks::BootInstant;
+#[unstable(feature = "cpu_time", issue = "none")]
+pub use self::clocks::{ProcessCpuTime, ThreadCpuTime};
 use crate::error::Error;
 use crate::fmt;
 use crate::ops::{Add, AddAssign, Sub, SubAssign};
 use crate::sys::time;
 use crate::sys_common::{FromInner, IntoInner};
 
+mod civil;
+mod clocks;
+
 /// A measurement of a monotonically nondecreasing clock.
 /// Opaque and useful only with [`Duration`].
 ///
@@ -0,0 +1,429 @@
+//! Conversion between [`SystemTime`] and the proleptic Gregorian calendar in UTC.
+//!
+//! This is deliberately minimal: there is no support for time zones other than
+//! fixed offsets while parsing, and leap seconds are not represented.
+
+use super::{Duration, SystemTime, UNIX_EPOCH};
+use crate::error::Error;
+use crate::fmt;
+use crate::str::FromStr;
+
+const SECS_PER_DAY: i128 = 86_400;
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// A calendar date and time of day in UTC, with nanosecond precision.
+///
+/// Dates use the proleptic Gregorian calendar, so years before 1582 are
+/// computed as if the Gregorian rules had always applied, and year `0` is
+/// 1 BC. Leap seconds cannot be represented.
+///
+/// The [`Display`] implementation produces an [RFC 3339] timestamp and the
+/// [`FromStr`] implementation parses one, converting any UTC offset to UTC.
+///
+/// [`Display`]: fmt::Display
+/// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+///
+/// # Examples
+///
+/// ```
+/// #![feature(time_civil)]
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let t = UNIX_EPOCH + Duration::new(1_000_000_000, 5_000_000);
+/// let utc = t.to_utc();
+/// assert_eq!((utc.year(), utc.month(), utc.day()), (2001, 9, 9));
+/// assert_eq!(utc.to_string(), "2001-09-09T01:46:40.005Z");
+///
+/// let parsed: std::time::UtcDateTime = "2001-09-09T03:46:40.005+02:00".parse().unwrap();
+/// assert_eq!(parsed, utc);
+/// assert_eq!(parsed.to_system_time(), Some(t));
+/// ```
+#[unstable(feature = "time_civil", issue = "none")]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcDateTime {
+    // Field order matters for the derived `Ord`.
+    year: i64,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+}
+
+impl UtcDateTime {
+    /// Creates a date and time from its components.
+    ///
+    /// Returns `None` if any component is out of range: `month` must be in
+    /// `1..=12`, `day` must exist in that month, `hour` must be below 24,
+    /// `minute` and `second` below 60 and `nanosecond` below 1 000 000 000.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn new(
+        year: i64,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        nanosecond: u32,
+    ) -> Option<UtcDateTime> {
+        let valid = (1..=12).contains(&month)
+            && day >= 1
+            && day <= days_in_month(year, month)
+            && hour < 24
+            && minute < 60
+            && second < 60
+            && nanosecond < NANOS_PER_SEC;
+        valid.then_some(UtcDateTime { year, month, day, hour, minute, second, nanosecond })
+    }
+
+    /// Returns the year. Year `0` is 1 BC, year `-1` is 2 BC, and so on.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn year(&self) -> i64 {
+        self.year
+    }
+
+    /// Returns the month, from 1 (January) to 12 (December).
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, starting at 1.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the hour of the day, from 0 to 23.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Returns the minute of the hour, from 0 to 59.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns the second of the minute, from 0 to 59.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Returns the fractional part of the second, in nanoseconds.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Converts this date and time back to a [`SystemTime`].
+    ///
+    /// Returns `None` if the result cannot be represented by the platform's
+    /// `SystemTime`.
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let secs = self.unix_secs();
+        if secs >= 0 {
+            let secs = u64::try_from(secs).ok()?;
+            UNIX_EPOCH.checked_add(Duration::new(secs, self.nanosecond))
+        } else {
+            let secs = u64::try_from(-secs).ok()?;
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(secs))?
+                .checked_add(Duration::new(0, self.nanosecond))
+        }
+    }
+
+    /// Builds a date and time from (possibly negative) seconds since the Unix epoch.
+    fn from_unix(secs: i128, nanosecond: u32) -> UtcDateTime {
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        UtcDateTime {
+            year: year as i64,
+            month,
+            day,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day / 60 % 60) as u8,
+            second: (secs_of_day % 60) as u8,
+            nanosecond,
+        }
+    }
+
+    /// Returns the number of whole seconds since the Unix epoch.
+    fn unix_secs(&self) -> i128 {
+        days_from_civil(self.year, self.month, self.day) * SECS_PER_DAY
+            + i128::from(self.hour) * 3600
+            + i128::from(self.minute) * 60
+            + i128::from(self.second)
+    }
+}
+
+impl SystemTime {
+    /// Returns the calendar date and time in UTC that this system time represents.
+    ///
+    /// Leap seconds are not accounted for, matching the POSIX definition of
+    /// `time_t` that [`UNIX_EPOCH`] is based on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(time_civil)]
+    /// use std::time::SystemTime;
+    ///
+    /// let utc = SystemTime::UNIX_EPOCH.to_utc();
+    /// assert_eq!(utc.to_string(), "1970-01-01T00:00:00Z");
+    /// ```
+    #[unstable(feature = "time_civil", issue = "none")]
+    #[must_use]
+    pub fn to_utc(&self) -> UtcDateTime {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => UtcDateTime::from_unix(i128::from(d.as_secs()), d.subsec_nanos()),
+            Err(e) => {
+                let d = e.duration();
+                let secs = -i128::from(d.as_secs());
+                match d.subsec_nanos() {
+                    0 => UtcDateTime::from_unix(secs, 0),
+                    nanos => UtcDateTime::from_unix(secs - 1, NANOS_PER_SEC - nanos),
+                }
+            }
+        }
+    }
+}
+
+#[unstable(feature = "time_civil", issue = "none")]
+impl fmt::Debug for UtcDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Formats the date and time as an RFC 3339 timestamp such as
+/// `2024-02-29T13:05:09.250Z`.
+///
+/// Without a precision, the fractional seconds are omitted if they are zero and
+/// otherwise printed with 3, 6 or 9 digits. With a precision (`{:.3}`), exactly
+/// that many digits (at most 9) are printed, truncating the value. Years
+/// outside `0..=9999` cannot be expressed in RFC 3339 and are printed with a
+/// sign and at least four digits, as in ISO 8601's expanded representation.
+#[unstable(feature = "time_civil", issue = "none")]
+impl fmt::Display for UtcDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if (0..=9999).contains(&self.year) {
+            write!(f, "{:04}", self.year)?;
+        } else {
+            write!(f, "{:+05}", self.year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.month, self.day, self.hour, self.minute, self.second
+        )?;
+
+        let digits = match f.precision() {
+            Some(precision) => precision.min(9),
+            None if self.nanosecond == 0 => 0,
+            None if self.nanosecond % 1_000_000 == 0 => 3,
+            None if self.nanosecond % 1_000 == 0 => 6,
+            None => 9,
+        };
+        if digits > 0 {
+            let frac = self.nanosecond / 10u32.pow(9 - digits as u32);
+            write!(f, ".{frac:0digits$}")?;
+        }
+        f.write_str("Z")
+    }
+}
+
+/// An error which can be returned when parsing an RFC 3339 timestamp into a
+/// [`UtcDateTime`].
+#[unstable(feature = "time_civil", issue = "none")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUtcDateTimeError {
+    kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseErrorKind {
+    /// The input does not have the shape of an RFC 3339 timestamp.
+    InvalidFormat,
+    /// A field has the right shape but an impossible value, like month 13.
+    OutOfRange,
+}
+
+#[unstable(feature = "time_civil", issue = "none")]
+impl fmt::Display for ParseUtcDateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::InvalidFormat => f.write_str("invalid RFC 3339 timestamp syntax"),
+            ParseErrorKind::OutOfRange => f.write_str("timestamp field out of range"),
+        }
+    }
+}
+
+#[unstable(feature = "time_civil", issue = "none")]
+impl Error for ParseUtcDateTimeError {}
+
+/// Parses an RFC 3339 timestamp such as `1985-04-12T23:20:50.52Z` or
+/// `1996-12-19T16:39:57-08:00`.
+///
+/// The `T` separator may also be a lowercase `t` or a space, and `Z` may be
+/// lowercase. Fractional seconds beyond nanosecond precision are truncated.
+/// A non-zero offset is applied so that the result is in UTC. Leap seconds
+/// (second `60`) are rejected.
+#[unstable(feature = "time_civil", issue = "none")]
+impl FromStr for UtcDateTime {
+    type Err = ParseUtcDateTimeError;
+
+    fn from_str(s: &str) -> Result<UtcDateTime, ParseUtcDateTimeError> {
+        const INVALID: ParseUtcDateTimeError =
+            ParseUtcDateTimeError { kind: ParseErrorKind::InvalidFormat };
+        const OUT_OF_RANGE: ParseUtcDateTimeError =
+            ParseUtcDateTimeError { kind: ParseErrorKind::OutOfRange };
+
+        let mut p = Parser { rest: s.as_bytes() };
+        let year = p.digits(4).ok_or(INVALID)?;
+        p.expect(b"-").ok_or(INVALID)?;
+        let month = p.digits(2).ok_or(INVALID)?;
+        p.expect(b"-").ok_or(INVALID)?;
+        let day = p.digits(2).ok_or(INVALID)?;
+        p.expect(b"Tt ").ok_or(INVALID)?;
+        let hour = p.digits(2).ok_or(INVALID)?;
+        p.expect(b":").ok_or(INVALID)?;
+        let minute = p.digits(2).ok_or(INVALID)?;
+        p.expect(b":").ok_or(INVALID)?;
+        let second = p.digits(2).ok_or(INVALID)?;
+
+        let mut nanosecond = 0;
+        if p.expect(b".").is_some() {
+            let mut scale = NANOS_PER_SEC / 10;
+            let mut any = false;
+            while let Some(digit) = p.digits(1) {
+                nanosecond += digit * scale;
+                scale /= 10;
+                any = true;
+            }
+            if !any {
+                return Err(INVALID);
+            }
+        }
+
+        let offset_secs: i128 = match p.expect(b"Zz+-").ok_or(INVALID)? {
+            b'Z' | b'z' => 0,
+            sign => {
+                let hours = p.digits(2).ok_or(INVALID)?;
+                p.expect(b":").ok_or(INVALID)?;
+                let minutes = p.digits(2).ok_or(INVALID)?;
+                if hours > 23 || minutes > 59 {
+                    return Err(OUT_OF_RANGE);
+                }
+                let offset = i128::from(hours * 3600 + minutes * 60);
+                if sign == b'-' { -offset } else { offset }
+            }
+        };
+        if !p.rest.is_empty() {
+            return Err(INVALID);
+        }
+
+        let local = UtcDateTime::new(
+            i64::from(year),
+            month as u8,
+            day as u8,
+            hour as u8,
+            minute as u8,
+            second as u8,
+            nanosecond,
+        )
+        .ok_or(OUT_OF_RANGE)?;
+        if offset_secs == 0 {
+            return Ok(local);
+        }
+        Ok(UtcDateTime::from_unix(local.unix_secs() - offset_secs, nanosecond))
+    }
+}
+
+/// A cursor over the bytes of a timestamp being parsed.
+struct Parser<'a> {
+    rest: &'a [u8],
+}
+
+impl Parser<'_> {
+    /// Consumes exactly `n` ASCII digits and returns their value.
+    fn digits(&mut self, n: usize) -> Option<u32> {
+        let (digits, rest) = self.rest.split_at_checked(n)?;
+        let mut value = 0;
+        for &b in digits {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            value = value * 10 + u32::from(b - b'0');
+        }
+        self.rest = rest;
+        Some(value)
+    }
+
+    /// Consumes one byte if it is any of `options` and returns it.
+    fn expect(&mut self, options: &[u8]) -> Option<u8> {
+        let (&b, rest) = self.rest.split_first()?;
+        if !options.contains(&b) {
+            return None;
+        }
+        self.rest = rest;
+        Some(b)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The two conversions below are Howard Hinnant's `days_from_civil` and
+// `civil_from_days` algorithms, which work in 400-year eras starting on
+// March 1st so that the leap day is the last day of the (shifted) year.
+
+/// Returns the number of days since 1970-01-01 for the given date.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i128 {
+    let (month, day) = (i128::from(month), i128::from(day));
+    let year = i128::from(year) - i128::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the `(year, month, day)` that is `days` days after 1970-01-01.
+fn civil_from_days(days: i128) -> (i128, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = (if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 }) as u8;
+    let year = year_of_era + era * 400 + i128::from(month <= 2);
+    (year, month, day)
+}
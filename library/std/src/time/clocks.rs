@@ -0,0 +1,264 @@
+//! Clocks measuring something other than wall-clock or monotonic time.
+
+use crate::ops::{Add, AddAssign, Sub, SubAssign};
+use crate::sys::time;
+use crate::time::Duration;
+use crate::{fmt, io};
+
+/// A measurement of the CPU time consumed by the current thread.
+///
+/// CPU time only advances while the thread is actually running on a processor,
+/// so it excludes time spent blocked, sleeping or waiting to be scheduled. This
+/// makes it suitable for profiling work done by a particular thread, without
+/// the noise that other threads or processes introduce into [`Instant`].
+///
+/// A `ThreadCpuTime` is only meaningful relative to other measurements taken
+/// *on the same thread*; comparing values read on different threads is not
+/// an error, but the result is meaningless.
+///
+/// # Platform-specific behavior
+///
+/// This uses `clock_gettime(CLOCK_THREAD_CPUTIME_ID)` on Unix platforms that
+/// support it and `GetThreadTimes` on Windows. On other platforms [`now`]
+/// returns an [`Unsupported`] error.
+///
+/// [`Instant`]: crate::time::Instant
+/// [`now`]: ThreadCpuTime::now
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(cpu_time)]
+/// use std::time::ThreadCpuTime;
+///
+/// let start = ThreadCpuTime::now()?;
+/// let sum: u64 = (0..1_000_000u64).sum();
+/// println!("summed to {sum} using {:?} of CPU time", start.elapsed()?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[unstable(feature = "cpu_time", issue = "none")]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadCpuTime(Duration);
+
+/// A measurement of the CPU time consumed by the current process.
+///
+/// This is the sum of the CPU time used by every thread of the process,
+/// including threads that have already exited. On a multi-core machine it can
+/// therefore advance faster than wall-clock time.
+///
+/// # Platform-specific behavior
+///
+/// This uses `clock_gettime(CLOCK_PROCESS_CPUTIME_ID)` on Unix platforms that
+/// support it and `GetProcessTimes` on Windows. On other platforms [`now`]
+/// returns an [`Unsupported`] error.
+///
+/// [`now`]: ProcessCpuTime::now
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+#[unstable(feature = "cpu_time", issue = "none")]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcessCpuTime(Duration);
+
+/// A monotonic measurement that keeps advancing while the system is suspended.
+///
+/// [`Instant`] is not guaranteed to count time during which the system is
+/// asleep, and on several platforms it does not. `BootInstant` does, which
+/// makes it the right choice for timeouts and durations that must account for
+/// suspend, such as lease expiry or time since the last user interaction.
+///
+/// # Platform-specific behavior
+///
+/// This uses `CLOCK_BOOTTIME` on Linux and Android, `CLOCK_MONOTONIC` on Apple
+/// platforms and `GetTickCount64` on Windows (which has millisecond
+/// resolution). On other platforms [`now`] returns an [`Unsupported`] error.
+///
+/// [`Instant`]: crate::time::Instant
+/// [`now`]: BootInstant::now
+/// [`Unsupported`]: io::ErrorKind::Unsupported
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(boot_instant)]
+/// use std::time::BootInstant;
+///
+/// let start = BootInstant::now()?;
+/// // ... the machine may be put to sleep here ...
+/// println!("{:?} have passed, including any time spent suspended", start.elapsed()?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[unstable(feature = "boot_instant", issue = "none")]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BootInstant(Duration);
+
+macro_rules! cpu_time_impls {
+    ($name:ident, $now:path) => {
+        impl $name {
+            /// Returns the CPU time consumed so far.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the platform does not provide this clock,
+            /// or if reading it fails.
+            #[unstable(feature = "cpu_time", issue = "none")]
+            pub fn now() -> io::Result<$name> {
+                $now().map($name)
+            }
+
+            /// Returns the total CPU time this measurement represents.
+            ///
+            /// The zero point is platform-specific, but is typically the
+            /// creation of the thread or process being measured.
+            #[must_use]
+            #[unstable(feature = "cpu_time", issue = "none")]
+            pub fn as_duration(&self) -> Duration {
+                self.0
+            }
+
+            /// Returns the CPU time consumed between `earlier` and `self`, or
+            /// zero if `earlier` is later than `self`.
+            #[must_use]
+            #[unstable(feature = "cpu_time", issue = "none")]
+            pub fn duration_since(&self, earlier: $name) -> Duration {
+                self.0.saturating_sub(earlier.0)
+            }
+
+            /// Returns the CPU time consumed since this measurement was taken.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if reading the clock fails.
+            #[unstable(feature = "cpu_time", issue = "none")]
+            pub fn elapsed(&self) -> io::Result<Duration> {
+                Ok($name::now()?.duration_since(*self))
+            }
+        }
+
+        #[unstable(feature = "cpu_time", issue = "none")]
+        impl Sub<$name> for $name {
+            type Output = Duration;
+
+            fn sub(self, other: $name) -> Duration {
+                self.duration_since(other)
+            }
+        }
+
+        #[unstable(feature = "cpu_time", issue = "none")]
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.0).finish()
+            }
+        }
+    };
+}
+
+cpu_time_impls!(ThreadCpuTime, time::thread_cpu_time);
+cpu_time_impls!(ProcessCpuTime, time::process_cpu_time);
+
+impl BootInstant {
+    /// Returns the current time since boot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform does not provide a suspend-aware
+    /// monotonic clock, or if reading it fails.
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn now() -> io::Result<BootInstant> {
+        time::boot_time().map(BootInstant)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    #[must_use]
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn duration_since(&self, earlier: BootInstant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or `None` if that instant is later than this one.
+    #[must_use]
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn checked_duration_since(&self, earlier: BootInstant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    /// Returns the amount of time elapsed since this instant, including any
+    /// time the system spent suspended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the clock fails.
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn elapsed(&self) -> io::Result<Duration> {
+        Ok(BootInstant::now()?.duration_since(*self))
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be
+    /// represented, `None` otherwise.
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn checked_add(&self, duration: Duration) -> Option<BootInstant> {
+        self.0.checked_add(duration).map(BootInstant)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be
+    /// represented, `None` otherwise.
+    #[unstable(feature = "boot_instant", issue = "none")]
+    pub fn checked_sub(&self, duration: Duration) -> Option<BootInstant> {
+        self.0.checked_sub(duration).map(BootInstant)
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl Add<Duration> for BootInstant {
+    type Output = BootInstant;
+
+    /// # Panics
+    ///
+    /// This function may panic if the resulting point in time cannot be
+    /// represented. See [`BootInstant::checked_add`] for a version without panic.
+    fn add(self, other: Duration) -> BootInstant {
+        self.checked_add(other).expect("overflow when adding duration to instant")
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl AddAssign<Duration> for BootInstant {
+    fn add_assign(&mut self, other: Duration) {
+        *self = *self + other;
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl Sub<Duration> for BootInstant {
+    type Output = BootInstant;
+
+    fn sub(self, other: Duration) -> BootInstant {
+        self.checked_sub(other).expect("overflow when subtracting duration from instant")
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl SubAssign<Duration> for BootInstant {
+    fn sub_assign(&mut self, other: Duration) {
+        *self = *self - other;
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl Sub<BootInstant> for BootInstant {
+    type Output = Duration;
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    fn sub(self, other: BootInstant) -> Duration {
+        self.duration_since(other)
+    }
+}
+
+#[unstable(feature = "boot_instant", issue = "none")]
+impl fmt::Debug for BootInstant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BootInstant").field(&self.0).finish()
+    }
+}
@@ -1,7 +1,15 @@
+#![feature(boot_instant)]
+#![feature(cpu_time)]
 #![feature(duration_constants)]
+#![feature(time_civil)]
 
 use std::fmt::Debug;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::hint::black_box;
+use std::io;
+use std::time::{
+    BootInstant, Duration, Instant, ProcessCpuTime, SystemTime, ThreadCpuTime, UNIX_EPOCH,
+    UtcDateTime,
+};
 
 macro_rules! assert_almost_eq {
     ($a:expr, $b:expr) => {{
@@ -227,3 +235,85 @@ fn check<T: Eq + Copy + Debug>(start: Option<T>, op: impl Fn(&T, Duration) -> Op
     check(instant.checked_add(Duration::from_secs(100)), Instant::checked_sub);
     check(instant.checked_add(Duration::from_secs(i64::MAX as _)), Instant::checked_sub);
 }
+
+#[test]
+fn system_time_to_utc() {
+    let utc = (UNIX_EPOCH + Duration::new(951_782_400, 0)).to_utc();
+    assert_eq!((utc.year(), utc.month(), utc.day()), (2000, 2, 29));
+    assert_eq!((utc.hour(), utc.minute(), utc.second(), utc.nanosecond()), (0, 0, 0, 0));
+
+    let before_epoch = UNIX_EPOCH - Duration::new(1, 500_000_000);
+    let utc = before_epoch.to_utc();
+    assert_eq!(utc.to_string(), "1969-12-31T23:59:58.500Z");
+    assert_eq!(utc.to_system_time(), Some(before_epoch));
+
+    let now = SystemTime::now();
+    assert_eq!(now.to_utc().to_system_time(), Some(now));
+}
+
+#[test]
+fn utc_date_time_rfc3339() {
+    let t: UtcDateTime = "1985-04-12T23:20:50.52Z".parse().unwrap();
+    assert_eq!(t, UtcDateTime::new(1985, 4, 12, 23, 20, 50, 520_000_000).unwrap());
+    assert_eq!(t.to_string(), "1985-04-12T23:20:50.520Z");
+    assert_eq!(format!("{t:.1}"), "1985-04-12T23:20:50.5Z");
+    assert_eq!(format!("{t:.0}"), "1985-04-12T23:20:50Z");
+
+    // Offsets are converted to UTC, possibly crossing a year boundary.
+    let t: UtcDateTime = "1996-12-31 16:39:57-08:00".parse().unwrap();
+    assert_eq!(t.to_string(), "1997-01-01T00:39:57Z");
+    let t: UtcDateTime = "0000-01-01t00:30:00+01:00".parse().unwrap();
+    assert_eq!(t.to_string(), "-0001-12-31T23:30:00Z");
+
+    // Digits beyond nanosecond precision are truncated.
+    let t: UtcDateTime = "2001-01-01T00:00:00.1234567899z".parse().unwrap();
+    assert_eq!(t.nanosecond(), 123_456_789);
+
+    for invalid in [
+        "2023-02-29T00:00:00Z",
+        "2024-13-01T00:00:00Z",
+        "2024-01-01T24:00:00Z",
+        "2024-01-01T23:59:60Z",
+        "2024-01-01T00:00:00",
+        "2024-01-01T00:00:00.Z",
+        "2024-01-01T00:00:00+01",
+        "2024-01-01T00:00:00Zjunk",
+        "24-01-01T00:00:00Z",
+    ] {
+        assert!(invalid.parse::<UtcDateTime>().is_err(), "{invalid} should not parse");
+    }
+}
+
+#[test]
+fn cpu_time_advances() {
+    let (thread_start, process_start) = match (ThreadCpuTime::now(), ProcessCpuTime::now()) {
+        (Ok(t), Ok(p)) => (t, p),
+        (Err(e), _) | (_, Err(e)) if e.kind() == io::ErrorKind::Unsupported => return,
+        (Err(e), _) | (_, Err(e)) => panic!("failed to read CPU time: {e}"),
+    };
+    let wall = Instant::now();
+    let mut x = 0u64;
+    while wall.elapsed() < Duration::from_millis(50) {
+        x = black_box(x.wrapping_add(1));
+    }
+    let thread_end = ThreadCpuTime::now().unwrap();
+    let process_end = ProcessCpuTime::now().unwrap();
+    assert!(thread_end > thread_start);
+    assert!(process_end >= process_start);
+    assert!(process_end.as_duration() >= thread_end.as_duration() - thread_start.as_duration());
+    assert_eq!(thread_start - thread_end, Duration::ZERO);
+}
+
+#[test]
+fn boot_instant_monotonic() {
+    let a = match BootInstant::now() {
+        Ok(a) => a,
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => return,
+        Err(e) => panic!("failed to read boot clock: {e}"),
+    };
+    let b = BootInstant::now().unwrap();
+    assert!(b >= a);
+    assert_eq!(a - b, Duration::ZERO);
+    assert_eq!(a.checked_duration_since(a + Duration::from_secs(1)), None);
+    assert_eq!((a + Duration::from_secs(1)) - a, Duration::from_secs(1));
+}
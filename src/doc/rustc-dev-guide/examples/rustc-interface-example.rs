@@ -53,7 +53,9 @@ fn main() {
         // Registry of diagnostics codes.
         registry: registry::Registry::new(rustc_errors::codes::DIAGNOSTICS),
         make_codegen_backend: None,
+        cache_backend: None,
         expanded_args: Vec::new(),
+        early_sarif_log: None,
         ice_file: None,
         hash_untracked_state: None,
         using_internal_features: &rustc_driver::USING_INTERNAL_FEATURES,
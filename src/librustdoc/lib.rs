@@ -857,7 +857,8 @@ fn main_args(early_dcx: &mut EarlyDiagCtxt, at_args: &[String]) {
     let scrape_examples_options = options.scrape_examples_options.clone();
     let bin_crate = options.bin_crate;
 
-    let config = core::create_config(input, options, &render_options);
+    let mut config = core::create_config(input, options, &render_options);
+    config.early_sarif_log = early_dcx.take_sarif_log();
 
     let registered_lints = config.register_lints.is_some();
 
@@ -0,0 +1,25 @@
+//@ assembly-output: emit-asm
+//@ only-x86_64-unknown-linux-gnu
+//@ compile-flags: -Copt-level=3 -Ccodegen-units=1
+
+// Checks that on ELF targets `#[multiversion]` defines an ifunc whose resolver picks the
+// version, and that calls go to it instead of checking a cached choice.
+
+#![crate_type = "lib"]
+#![feature(multiversion)]
+
+// CHECK: .globl [[IFUNC:__rust_multiversion_[0-9a-f]+]]
+// CHECK-NEXT: .type [[IFUNC]],@gnu_indirect_function
+// CHECK-NEXT: {{.*}}[[IFUNC]]{{.*}}resolve
+#[multiversion(targets = ["avx2"])]
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+// CHECK-LABEL: call_double:
+// CHECK-NOT: SELECTED
+// CHECK: {{call|jmp}}{{.*}}[[IFUNC]]
+#[no_mangle]
+pub fn call_double(x: u32) -> u32 {
+    double(x)
+}
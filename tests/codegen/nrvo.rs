@@ -8,10 +8,41 @@
 pub fn nrvo(init: fn(&mut [u8; 4096])) -> [u8; 4096] {
     // CHECK-LABEL: nrvo
     // CHECK: @llvm.memset
-    // FIXME: turn on nrvo then check-not: @llvm.memcpy
+    // CHECK-NOT: @llvm.memcpy
     // CHECK: ret
     // CHECK-EMPTY
     let mut buf = [0; 4096];
     init(&mut buf);
     buf
 }
+
+pub struct Big {
+    header: u64,
+    data: [u8; 4096],
+}
+
+// The struct should be built in the return place.
+#[no_mangle]
+pub fn nrvo_struct(header: u64, fill: fn(&mut [u8; 4096])) -> Big {
+    // CHECK-LABEL: nrvo_struct
+    // CHECK-NOT: @llvm.memcpy
+    // CHECK: ret
+    // CHECK-EMPTY
+    let mut big = Big { header: 0, data: [0; 4096] };
+    fill(&mut big.data);
+    big.header = header;
+    big
+}
+
+// The `Ok` variant should be built in the return place, without copying the struct into it.
+#[no_mangle]
+pub fn nrvo_result(header: u64, fill: fn(&mut [u8; 4096])) -> Result<Big, u32> {
+    // CHECK-LABEL: nrvo_result
+    // CHECK-NOT: @llvm.memcpy
+    // CHECK: ret
+    // CHECK-EMPTY
+    let mut big = Big { header: 0, data: [0; 4096] };
+    fill(&mut big.data);
+    big.header = header;
+    Ok(big)
+}
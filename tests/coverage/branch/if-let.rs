@@ -54,5 +54,3 @@ fn main() {
     }
     if_let_chain(None, None);
 }
-
-// FIXME(#124118) Actually instrument if-let and let-chains for branch coverage.
@@ -31,5 +31,3 @@ fn main() {
     let_else(Some("x"));
     let_else(None);
 }
-
-// FIXME(#124118) Actually instrument let-else for branch coverage.
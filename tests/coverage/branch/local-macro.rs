@@ -0,0 +1,33 @@
+#![feature(coverage_attribute)]
+//@ edition: 2021
+//@ compile-flags: -Zcoverage-options=branch
+//@ llvm-cov-flags: --show-branches=count
+
+// Branches written in a `macro_rules!` macro of the current crate are
+// reported at the macro invocation.
+
+macro_rules! is_even {
+    ($x:expr) => {
+        $x % 2 == 0
+    };
+}
+
+fn local_macro(x: u32) {
+    if is_even!(x) {
+        say("even");
+    } else {
+        say("odd");
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    local_macro(2);
+    local_macro(4);
+    local_macro(5);
+}
@@ -0,0 +1,18 @@
+#![feature(coverage_attribute)]
+//@ edition: 2021
+//@ compile-flags: -Zcoverage-options=branch
+//@ llvm-cov-flags: --show-branches=count
+
+use std::num::ParseIntError;
+
+fn parse_and_increment(input: &str) -> Result<u32, ParseIntError> {
+    let n = input.parse::<u32>()?;
+    Ok(n + 1)
+}
+
+#[coverage(off)]
+fn main() {
+    let _ = parse_and_increment("1");
+    let _ = parse_and_increment("2");
+    let _ = parse_and_increment("x");
+}
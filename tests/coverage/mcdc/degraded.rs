@@ -0,0 +1,27 @@
+#![feature(coverage_attribute)]
+//@ edition: 2021
+//@ min-llvm-version: 19
+//@ compile-flags: -Zcoverage-options=mcdc,discard-last-condition-spans-in-codegen
+//@ llvm-cov-flags: --show-branches=count --show-mcdc
+
+// A decision whose conditions can't all be reported is degraded, so that its
+// remaining conditions are reported as plain branches.
+
+fn mcdc_degraded(a: bool, b: bool) {
+    if a && b {
+        say("a and b");
+    } else {
+        say("not both");
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    mcdc_degraded(true, false);
+    mcdc_degraded(false, true);
+}
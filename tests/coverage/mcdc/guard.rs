@@ -0,0 +1,29 @@
+#![feature(coverage_attribute)]
+//@ edition: 2021
+//@ min-llvm-version: 19
+//@ compile-flags: -Zcoverage-options=mcdc
+//@ llvm-cov-flags: --show-branches=count --show-mcdc
+
+fn mcdc_guard(x: Option<u32>, b: bool) {
+    match x {
+        Some(a) if a > 0 && b => {
+            say("positive and b");
+        }
+        _ => {
+            say("something else");
+        }
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    mcdc_guard(Some(0), true);
+    mcdc_guard(Some(1), false);
+    mcdc_guard(Some(2), true);
+    mcdc_guard(None, true);
+}
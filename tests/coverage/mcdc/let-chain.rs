@@ -0,0 +1,30 @@
+#![feature(coverage_attribute, let_chains)]
+//@ edition: 2021
+//@ min-llvm-version: 19
+//@ compile-flags: -Zcoverage-options=mcdc
+//@ llvm-cov-flags: --show-branches=count --show-mcdc
+
+fn mcdc_let_chain(flag: bool, input: Option<u32>) {
+    if flag && let Some(x) = input {
+        consume(x);
+    } else {
+        say("not both");
+    }
+}
+
+#[coverage(off)]
+fn consume(x: u32) {
+    core::hint::black_box(x);
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    mcdc_let_chain(false, Some(1));
+    mcdc_let_chain(true, None);
+    mcdc_let_chain(true, Some(2));
+}
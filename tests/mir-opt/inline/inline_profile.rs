@@ -0,0 +1,32 @@
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+// Checks that `-Zinline-mir-profile` inlines calls that the profile marks as hot,
+// even above the usual threshold, and leaves calls that it marks as cold alone.
+//
+//@ compile-flags: -Zinline-mir-profile={{src-base}}/inline/auxiliary/inline_profile.txt
+
+// EMIT_MIR inline_profile.main.Inline.after.mir
+fn main() {
+    // CHECK-LABEL: fn main(
+    // CHECK: (inlined hot)
+    hot();
+    // CHECK-NOT: (inlined cold)
+    // CHECK: = cold()
+    cold();
+}
+
+// Cost is approximately 5 * 25 + 5 = 130.
+pub fn hot() {
+    g();
+    g();
+    g();
+    g();
+    g();
+}
+
+// Cost is approximately 25 + 5 = 30.
+pub fn cold() {
+    g();
+}
+
+#[inline(never)]
+fn g() {}
@@ -1,5 +1,5 @@
-// skip-filecheck
-// This is a miscompilation, #111005 to track
+// Regression test for #111005: the local copied to the return place must not be renamed if it is
+// modified after the copy.
 
 //@ test-mir-pass: RenameReturnPlace
 
@@ -10,6 +10,9 @@
 // EMIT_MIR nrvo_miscompile_111005.wrong.RenameReturnPlace.diff
 #[custom_mir(dialect = "runtime", phase = "initial")]
 pub fn wrong(arg: char) -> char {
+    // CHECK-LABEL: fn wrong(
+    // CHECK: _0 = copy _2;
+    // CHECK: _2 = const 'b';
     mir! {
         {
             let temp = arg;
@@ -20,6 +23,155 @@ pub fn wrong(arg: char) -> char {
     }
 }
 
+// EMIT_MIR nrvo_miscompile_111005.wrong_array.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn wrong_array(arg: [u8; 64], other: [u8; 64]) -> [u8; 64] {
+    // CHECK-LABEL: fn wrong_array(
+    // CHECK: _0 = copy _3;
+    // CHECK: _3 = copy _2;
+    mir! {
+        {
+            let temp = arg;
+            RET = temp;
+            temp = other;
+            Return()
+        }
+    }
+}
+
+// EMIT_MIR nrvo_miscompile_111005.wrong_through_pointer.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn wrong_through_pointer(arg: [u8; 64], other: [u8; 64]) -> [u8; 64] {
+    // CHECK-LABEL: fn wrong_through_pointer(
+    // CHECK: _0 = copy _3;
+    // CHECK: (*_4) = copy _2;
+    mir! {
+        {
+            let temp = arg;
+            let ptr = &raw mut temp;
+            RET = temp;
+            *ptr = other;
+            Return()
+        }
+    }
+}
+
+// EMIT_MIR nrvo_miscompile_111005.wrong_set_discriminant_through_pointer.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn wrong_set_discriminant_through_pointer(arg: Option<[u8; 64]>) -> Option<[u8; 64]> {
+    // CHECK-LABEL: fn wrong_set_discriminant_through_pointer(
+    // CHECK: _0 = copy _2;
+    // CHECK: discriminant((*_3)) = 0;
+    mir! {
+        {
+            let temp = arg;
+            let ptr = &raw mut temp;
+            RET = temp;
+            SetDiscriminant(*ptr, 0);
+            Return()
+        }
+    }
+}
+
+// EMIT_MIR nrvo_miscompile_111005.wrong_deinit_through_pointer.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn wrong_deinit_through_pointer(arg: [u8; 64]) -> [u8; 64] {
+    // CHECK-LABEL: fn wrong_deinit_through_pointer(
+    // CHECK: _0 = copy _2;
+    // CHECK: Deinit((*_3));
+    mir! {
+        {
+            let temp = arg;
+            let ptr = &raw mut temp;
+            RET = temp;
+            Deinit(*ptr);
+            Return()
+        }
+    }
+}
+
+// A call that may write through a pointer to the local is fine before the copy.
+// EMIT_MIR nrvo_miscompile_111005.renamed_after_call.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn renamed_after_call(arg: [u8; 64], init: fn(&mut [u8; 64])) -> [u8; 64] {
+    // CHECK-LABEL: fn renamed_after_call(
+    // CHECK: _0 = copy _1;
+    // CHECK: _4 = &mut _0;
+    // CHECK: _5 = copy _2(move _4)
+    // CHECK-NOT: _0 =
+    // CHECK: return;
+    mir! {
+        let temp: [u8; 64];
+        let ptr: &mut [u8; 64];
+        let unit: ();
+        {
+            temp = arg;
+            ptr = &mut temp;
+            Call(unit = init(Move(ptr)), ReturnTo(bb1), UnwindContinue())
+        }
+        bb1 = {
+            RET = Move(temp);
+            Return()
+        }
+    }
+}
+
+// But not after it, if the local is borrowed.
+// EMIT_MIR nrvo_miscompile_111005.wrong_call_after_copy.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn wrong_call_after_copy(arg: [u8; 64], init: fn(&mut [u8; 64]), other: fn()) -> [u8; 64] {
+    // CHECK-LABEL: fn wrong_call_after_copy(
+    // CHECK: _0 = copy _4;
+    // CHECK: _7 = copy _3()
+    mir! {
+        let temp: [u8; 64];
+        let ptr: &mut [u8; 64];
+        let unit: ();
+        let unit2: ();
+        {
+            temp = arg;
+            ptr = &mut temp;
+            Call(unit = init(Move(ptr)), ReturnTo(bb1), UnwindContinue())
+        }
+        bb1 = {
+            RET = temp;
+            Call(unit2 = other(), ReturnTo(bb2), UnwindContinue())
+        }
+        bb2 = {
+            Return()
+        }
+    }
+}
+
+// The local is renamed to the field of `Ok`, which is then set as the variant of `_0`.
+// EMIT_MIR nrvo_miscompile_111005.renamed_into_ok.RenameReturnPlace.diff
+#[custom_mir(dialect = "runtime", phase = "initial")]
+pub fn renamed_into_ok(arg: [u8; 64], init: fn(&mut [u8; 64])) -> Result<[u8; 64], u32> {
+    // CHECK-LABEL: fn renamed_into_ok(
+    // CHECK: ((_0 as Ok).0: [u8; 64]) = copy _1;
+    // CHECK: _4 = &mut ((_0 as Ok).0: [u8; 64]);
+    // CHECK: discriminant(_0) = 0;
+    mir! {
+        let temp: [u8; 64];
+        let ptr: &mut [u8; 64];
+        let unit: ();
+        {
+            temp = arg;
+            ptr = &mut temp;
+            Call(unit = init(Move(ptr)), ReturnTo(bb1), UnwindContinue())
+        }
+        bb1 = {
+            RET = Ok(Move(temp));
+            Return()
+        }
+    }
+}
+
 fn main() {
     assert_eq!(wrong('a'), 'a');
+    assert_eq!(wrong_array([1; 64], [2; 64]), [1; 64]);
+    assert_eq!(wrong_through_pointer([1; 64], [2; 64]), [1; 64]);
+    assert_eq!(wrong_set_discriminant_through_pointer(Some([1; 64])), Some([1; 64]));
+    assert_eq!(renamed_after_call([1; 64], |buf| buf[0] = 2)[0], 2);
+    assert_eq!(renamed_into_ok([1; 64], |buf| buf[0] = 2).unwrap()[0], 2);
 }
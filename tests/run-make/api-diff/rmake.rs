@@ -0,0 +1,121 @@
+// Checks that `-Zapi-diff` reports the changes to the public API of a crate
+// since an earlier version of it, and the bump they require.
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+const OLD: &str = r#"
+pub fn parse(s: &str) -> u32 { s.len() as u32 }
+pub fn removed() {}
+pub struct Config { pub verbose: bool }
+pub struct Options { pub verbose: bool, level: u8 }
+#[non_exhaustive]
+pub enum Mode { Fast, Slow }
+pub struct Wrapper<T>(T);
+pub fn show<T: std::fmt::Debug + Clone>(_: T) {}
+pub trait Visitor { fn visit(&self) {} }
+fn private() {}
+"#;
+
+fn api_diff(source: &str) -> Value {
+    rfs::write("lib.rs", source);
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .crate_name("mycrate")
+        .emit("metadata")
+        .output("new.rmeta")
+        .arg("-Zapi-diff=old.rmeta")
+        .run()
+        .stdout_utf8();
+    serde_json::from_str(&output).unwrap()
+}
+
+fn signature_change<'a>(diff: &'a Value, path: &str) -> &'a str {
+    let change = change(diff, "signature changed", path).unwrap_or_else(|| panic!("{diff}"));
+    change["severity"].as_str().unwrap()
+}
+
+fn change<'a>(diff: &'a Value, change: &str, path: &str) -> Option<&'a Value> {
+    diff["changes"].as_array().unwrap().iter().find(|c| c["change"] == change && c["path"] == path)
+}
+
+fn main() {
+    rfs::write("lib.rs", OLD);
+    rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .crate_name("mycrate")
+        .emit("metadata")
+        .output("old.rmeta")
+        .arg("-Cmetadata=old")
+        .run();
+
+    let diff = api_diff(OLD);
+    assert_eq!(diff["$message_type"], "api-diff");
+    assert_eq!(diff["crate"], "mycrate");
+    assert_eq!(diff["bump"], "patch", "{diff}");
+    assert!(diff["changes"].as_array().unwrap().is_empty(), "{diff}");
+
+    // Private items aren't part of the API.
+    let diff = api_diff(&OLD.replace("fn private() {}", "fn private(_: u8) {}"));
+    assert_eq!(diff["bump"], "patch", "{diff}");
+
+    let diff = api_diff(&format!("{OLD}\npub fn added() {{}}"));
+    assert_eq!(diff["bump"], "minor", "{diff}");
+    assert_eq!(change(&diff, "added", "mycrate::added").unwrap()["severity"], "minor");
+
+    let diff = api_diff(&OLD.replace("pub fn removed() {}", ""));
+    assert_eq!(diff["bump"], "major", "{diff}");
+    assert!(change(&diff, "removed", "mycrate::removed").is_some(), "{diff}");
+
+    let diff = api_diff(&OLD.replace("-> u32 { s.len() as u32 }", "-> u64 { s.len() as u64 }"));
+    assert_eq!(diff["bump"], "major", "{diff}");
+    let parse = change(&diff, "signature changed", "mycrate::parse").unwrap();
+    assert!(parse["old"].as_str().unwrap().contains("u32"), "{parse}");
+    assert!(parse["new"].as_str().unwrap().contains("u64"), "{parse}");
+
+    let diff = api_diff(&OLD.replace("pub verbose: bool", "pub verbose: std::rc::Rc<bool>"));
+    assert!(change(&diff, "auto trait removed", "mycrate::Config").is_some(), "{diff}");
+
+    // Changes that keep every use of the old version compiling are minor.
+    let diff = api_diff(&OLD.replace("level: u8", "level: u8, pub debug: bool"));
+    assert_eq!(diff["bump"], "minor", "{diff}");
+    assert_eq!(signature_change(&diff, "mycrate::Options"), "minor");
+
+    let diff = api_diff(&OLD.replace("Fast, Slow", "Fast, Slow, Auto"));
+    assert_eq!(diff["bump"], "minor", "{diff}");
+    assert_eq!(signature_change(&diff, "mycrate::Mode"), "minor");
+
+    let diff = api_diff(
+        &OLD.replace("Wrapper<T>(T);", "Wrapper<T, A = ()>(T, std::marker::PhantomData<A>);"),
+    );
+    assert_eq!(diff["bump"], "minor", "{diff}");
+    assert_eq!(signature_change(&diff, "mycrate::Wrapper"), "minor");
+
+    let diff = api_diff(&OLD.replace("Debug + Clone>", "Debug>"));
+    assert_eq!(diff["bump"], "minor", "{diff}");
+    assert_eq!(signature_change(&diff, "mycrate::show"), "minor");
+
+    // Their counterparts aren't.
+    let diff =
+        api_diff(&OLD.replace("pub verbose: bool }", "pub verbose: bool, pub debug: bool }"));
+    assert_eq!(signature_change(&diff, "mycrate::Config"), "major");
+
+    let diff = api_diff(&OLD.replace("Debug + Clone>", "Debug + Clone + Default>"));
+    assert_eq!(signature_change(&diff, "mycrate::show"), "major");
+
+    let diff = api_diff(&OLD.replace("fn visit(&self) {}", "fn visit(&self);"));
+    assert_eq!(diff["bump"], "major", "{diff}");
+    assert!(change(&diff, "kind changed", "mycrate::Visitor::visit").is_some(), "{diff}");
+
+    // The baseline has to be a version of the same crate.
+    rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .crate_name("othercrate")
+        .emit("metadata")
+        .arg("-Zapi-diff=old.rmeta")
+        .run_fail()
+        .assert_stderr_contains("`old.rmeta` is not an earlier version of `othercrate`");
+}
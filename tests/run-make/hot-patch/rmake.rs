@@ -0,0 +1,84 @@
+// Checks that `-Zhot-patch` builds the codegen units that changed into shared
+// objects listed in the manifest, that a running instance of the previous
+// build can load them, and that it starts over when there is no previous
+// session to compare with.
+
+//@ ignore-cross-compile
+//@ only-linux
+//@ only-x86_64
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{dynamic_lib_extension, rfs, run_with_args, rustc};
+
+fn build(name: &str, edited: u32, extra_args: &[&str]) -> Value {
+    rfs::write(
+        format!("{name}.rs"),
+        format!(
+            "#![feature(hot_patch)]\n\
+             mod a {{ pub fn edited() -> u32 {{ {edited} }} }}\n\
+             mod b {{ pub fn untouched() -> u32 {{ 0 }} }}\n\
+             fn main() {{\n\
+                 println!(\"{{}}\", a::edited() + b::untouched());\n\
+                 for object in std::env::args().skip(1) {{\n\
+                     let redirected = unsafe {{ std::hot_patch::load(object.as_ref()) }};\n\
+                     assert!(redirected.unwrap() > 0);\n\
+                 }}\n\
+                 println!(\"{{}}\", a::edited() + b::untouched());\n\
+             }}\n"
+        ),
+    );
+    rustc()
+        .input(format!("{name}.rs"))
+        .incremental(format!("incr-{name}"))
+        .arg("-Zhot-patch")
+        .args(extra_args)
+        .run();
+    serde_json::from_slice(&rfs::read(format!("{name}.hotpatch/manifest.json"))).unwrap()
+}
+
+/// The paths of the shared objects of the `index`th patch.
+fn patch_objects(name: &str, manifest: &Value, index: usize) -> Vec<String> {
+    let patch = &manifest["patches"][index];
+    let objects = patch["objects"].as_array().unwrap();
+    assert!(!objects.is_empty(), "{manifest}");
+    objects
+        .iter()
+        .map(|object| {
+            let object = object.as_str().unwrap();
+            assert!(object.ends_with(dynamic_lib_extension()), "{object}");
+            format!("{name}.hotpatch/{}/{object}", patch["dir"].as_str().unwrap())
+        })
+        .collect()
+}
+
+fn main() {
+    let manifest = build("main", 1, &[]);
+    assert_eq!(manifest["base"], "main");
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 0);
+    // Keep the base executable, which the build of the patch replaces.
+    rfs::copy("main", "main-base");
+
+    let manifest = build("main", 2, &[]);
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 1, "{manifest}");
+    let objects = patch_objects("main", &manifest, 0);
+
+    // The running base executable picks up the new version of `a::edited`.
+    let objects: Vec<&str> = objects.iter().map(String::as_str).collect();
+    run_with_args("main-base", &objects).assert_stdout_equals("1\n2\n");
+
+    // Nothing changed, so there is nothing to patch.
+    let manifest = build("main", 2, &[]);
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 1, "{manifest}");
+
+    // Changing a tracked option recompiles everything.
+    let manifest = build("main", 2, &["-Cdebuginfo=1"]);
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 0, "{manifest}");
+
+    // With a single codegen unit, every change recompiles all of them, which
+    // still makes a patch.
+    let manifest = build("single", 1, &["-Ccodegen-units=1"]);
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 0, "{manifest}");
+    let manifest = build("single", 2, &["-Ccodegen-units=1"]);
+    assert_eq!(manifest["patches"].as_array().unwrap().len(), 1, "{manifest}");
+    assert_eq!(patch_objects("single", &manifest, 0).len(), 1, "{manifest}");
+}
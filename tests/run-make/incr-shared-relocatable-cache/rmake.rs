@@ -0,0 +1,38 @@
+// Checks that with `-Zincremental-relocatable`, the incremental cache of a
+// source tree can seed the cache of a copy of it at another location, given
+// with `-Zincremental-shared-cache`.
+
+//@ ignore-cross-compile
+
+use run_make_support::{cwd, rfs, rustc};
+
+fn compile(checkout: &str, relocatable: bool, shared_cache: &str, incr_state: &str) {
+    let mut rustc = rustc();
+    rustc
+        .current_dir(checkout)
+        .input("lib.rs")
+        .crate_type("rlib")
+        .incremental("incr")
+        .remap_path_prefix(cwd().join(checkout), "/checkout")
+        .arg(format!("-Zincremental-shared-cache={}", cwd().join(shared_cache).display()))
+        .arg(format!("-Zassert-incr-state={incr_state}"));
+    if relocatable {
+        rustc.arg("-Zincremental-relocatable");
+    }
+    rustc.run();
+}
+
+fn main() {
+    for checkout in ["first", "second", "third"] {
+        rfs::create_dir(checkout);
+        rfs::copy("lib.rs", format!("{checkout}/lib.rs"));
+    }
+
+    compile("first", true, "missing", "not-loaded");
+    compile("second", true, "first/incr", "loaded");
+    // The local cache is used once there is one.
+    compile("second", true, "missing", "loaded");
+
+    // Without the flag, the absolute prefix is part of the cache key.
+    compile("third", false, "first/incr", "not-loaded");
+}
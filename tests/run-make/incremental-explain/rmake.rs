@@ -0,0 +1,50 @@
+// Checks that `-Zincremental-explain` reports the item whose HIR changed, and
+// the command-line options that changed.
+
+//@ ignore-cross-compile
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+fn explain(edited: u32, extra_args: &[&str]) -> Value {
+    rfs::write(
+        "lib.rs",
+        format!("pub fn edited() -> u32 {{ {edited} }}\npub fn untouched() -> u32 {{ 0 }}\n"),
+    );
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .incremental("incr")
+        .arg("-Zincremental-explain=json")
+        .args(extra_args)
+        .run();
+    let stderr = output.stderr_utf8();
+    let line = stderr
+        .lines()
+        .find(|line| line.contains(r#""$message_type":"incremental-explain""#))
+        .unwrap_or_else(|| panic!("no explanation in stderr:\n{stderr}"));
+    serde_json::from_str(line).unwrap()
+}
+
+fn main() {
+    explain(1, &[]);
+
+    let report = explain(2, &[]);
+    let red_nodes = report["red_nodes"].as_array().unwrap();
+    let edited = red_nodes
+        .iter()
+        .position(|node| {
+            node["kind"] == "opt_hir_owner_nodes"
+                && node["node"].as_str().unwrap().ends_with("::edited)")
+        })
+        .unwrap_or_else(|| panic!("`edited` is not among the red nodes: {report}"));
+    assert!(!red_nodes.iter().any(|node| node["node"].as_str().unwrap().contains("untouched")));
+    let invalidated = report["invalidated_cgus"].as_array().unwrap();
+    assert!(
+        invalidated.iter().any(|cgu| cgu["causes"].as_array().unwrap().contains(&edited.into())),
+        "no codegen unit was recompiled because of `edited`: {report}",
+    );
+
+    let report = explain(2, &["-Cpanic=abort"]);
+    assert_eq!(report["changed_options"], serde_json::json!(["-C panic"]));
+}
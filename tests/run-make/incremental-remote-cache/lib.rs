@@ -0,0 +1,11 @@
+pub mod a {
+    pub fn answer() -> u32 {
+        42
+    }
+}
+
+pub mod b {
+    pub fn question() -> &'static str {
+        "six by nine"
+    }
+}
@@ -0,0 +1,67 @@
+// Checks that `-Zincremental-remote-cache` stores the cache of a session blob
+// by blob, with a record of each work product, and that a session without a
+// local cache fetches it, recompiling only the codegen units whose object files
+// can't be fetched.
+
+//@ ignore-cross-compile
+
+use std::path::Path;
+
+use run_make_support::{rfs, rustc};
+
+fn compile(incr_dir: &str, incr_state: &str) -> String {
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .incremental(incr_dir)
+        .arg("-Zincremental-remote-cache=cache")
+        .arg("-Zincremental-info")
+        .arg(format!("-Zassert-incr-state={incr_state}"))
+        .run()
+        .stderr_utf8()
+}
+
+/// The path of the blob stored under the key `hex` in the cache directory.
+fn blob_path(hex: &str) -> String {
+    format!("cache/{}/{hex}", &hex[..2])
+}
+
+fn main() {
+    let stderr = compile("incr-first", "not-loaded");
+    assert!(stderr.contains("remote cache: "), "{stderr}");
+    assert!(stderr.contains(" files stored"), "{stderr}");
+
+    let stderr = compile("incr-second", "loaded");
+    assert!(stderr.contains(" files fetched, 0 work product files missing"), "{stderr}");
+
+    // Find the records of the work products among the blobs, and drop the
+    // object files they list.
+    let mut records = Vec::new();
+    rfs::read_dir_entries("cache", |dir| {
+        rfs::read_dir_entries(dir, |blob| {
+            let data = rfs::read(blob);
+            if let Ok(list) = String::from_utf8(data)
+                && list.lines().count() > 0
+                && list.lines().all(|line| line.len() > 33 && line.ends_with(".o"))
+            {
+                records.push(list);
+            }
+        });
+    });
+    assert!(!records.is_empty(), "no work product records stored");
+    let mut dropped = 0;
+    for line in records.iter().flat_map(|record| record.lines()) {
+        let (hex, _file_name) = line.split_once(' ').unwrap();
+        if Path::new(&blob_path(hex)).exists() {
+            rfs::remove_file(blob_path(hex));
+            dropped += 1;
+        }
+    }
+    assert!(dropped > 0, "{records:?}");
+
+    // The cache is still used, but the codegen units are recompiled.
+    let stderr = compile("incr-third", "loaded");
+    assert!(!stderr.contains(" 0 work product files missing"), "{stderr}");
+    assert!(stderr.contains(" work product files missing"), "{stderr}");
+    assert!(Path::new("liblib.rlib").exists());
+}
@@ -0,0 +1,27 @@
+pub enum Packet<T> {
+    Empty,
+    Small(T),
+    Large([T; 64]),
+}
+
+#[inline(never)]
+fn make<T: Copy>(value: T, large: bool) -> Packet<T> {
+    if large { Packet::Large([value; 64]) } else { Packet::Small(value) }
+}
+
+pub fn bytes(value: u8) -> Packet<u8> {
+    make(value, false)
+}
+
+pub fn words(value: u32) -> Packet<u32> {
+    make(value, true)
+}
+
+pub enum Balanced {
+    A(u64),
+    B(u64),
+}
+
+pub fn balanced(value: u64, a: bool) -> Balanced {
+    if a { Balanced::A(value) } else { Balanced::B(value) }
+}
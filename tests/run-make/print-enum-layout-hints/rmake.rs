@@ -0,0 +1,29 @@
+//! Checks that `-Z print-enum-layout-hints` reports each instantiation of an enum
+//! with an oversized variant, and leaves enums with balanced variants alone.
+
+//@ only-64bit
+
+use run_make_support::rustc;
+
+fn main() {
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .arg("-Zprint-enum-layout-hints")
+        .run()
+        .stdout_utf8();
+
+    let hint = |ty: &str, size: u64| {
+        let line = format!(
+            "print-enum-layout-hint enum `{ty}`: {size} bytes, 16 bytes with the fields of `Large` boxed"
+        );
+        output.find(&line).unwrap_or_else(|| panic!("no hint for `{ty}` in:\n{output}"))
+    };
+    // The tag of `Packet<u32>` is as large as a `u32`, to align the fields after it.
+    let words = hint("Packet<u32>", 260);
+    let bytes = hint("Packet<u8>", 65);
+    // Boxing saves more for the larger instantiation, so it comes first.
+    assert!(words < bytes, "{output}");
+    assert!(output.contains("print-enum-layout-hint     used by "), "{output}");
+    assert!(!output.contains("Balanced"), "{output}");
+}
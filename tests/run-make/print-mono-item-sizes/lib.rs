@@ -0,0 +1,10 @@
+#[inline(never)]
+pub fn generic<T: Copy>(x: T) -> [T; 16] {
+    [x; 16]
+}
+
+pub fn use_generic() -> ([u8; 16], [u64; 16]) {
+    (generic(1u8), generic(2u64))
+}
+
+pub static TABLE: [u32; 64] = [7; 64];
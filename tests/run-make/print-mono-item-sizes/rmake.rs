@@ -0,0 +1,74 @@
+//! Checks that `--print=mono-item-sizes` attributes the code in the object
+//! files to the mono items that produced it.
+
+//@ ignore-cross-compile
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .opt_level("0")
+        .arg("-Zunstable-options")
+        .print("mono-item-sizes=sizes.json")
+        .run();
+
+    let report: Value = serde_json::from_str(&rfs::read_to_string("sizes.json")).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["crate"], "lib");
+    let items = report["items"].as_array().unwrap();
+    let item = |name: &str| {
+        items
+            .iter()
+            .find(|item| item["name"] == name)
+            .unwrap_or_else(|| panic!("no mono item `{name}` in {report:#}"))
+    };
+
+    // Both instantiations are attributed to the generic function. Paths in
+    // the local crate are printed without the crate name.
+    for name in ["generic::<u8>", "generic::<u64>"] {
+        let generic = item(name);
+        assert_eq!(generic["generic"], "generic");
+        assert_eq!(generic["instantiations_of_generic"], 2);
+        assert_eq!(generic["inline_attr"], "never");
+        assert!(generic["bytes"].as_u64().unwrap() > 0);
+    }
+
+    let table = item("TABLE");
+    assert_eq!(table["kind"], "static");
+    assert_eq!(table["bytes"], 256);
+
+    // Items are sorted largest first, and add up to the total.
+    let sizes: Vec<u64> = items.iter().filter_map(|item| item["bytes"].as_u64()).collect();
+    assert!(sizes.windows(2).all(|w| w[0] >= w[1]));
+    let total_bytes = report["total_bytes"].as_u64().unwrap();
+    assert_eq!(total_bytes, sizes.iter().sum::<u64>());
+
+    // The report is printed right after codegen, so it does not depend on a
+    // link step, nor on the object files outliving it.
+    for args in [&["--emit=obj"][..], &["-Zno-link"]] {
+        rustc()
+            .input("lib.rs")
+            .crate_type("rlib")
+            .opt_level("0")
+            .arg("-Zunstable-options")
+            .args(args)
+            .print("mono-item-sizes=sizes-no-link.json")
+            .run();
+        let report: Value =
+            serde_json::from_str(&rfs::read_to_string("sizes-no-link.json")).unwrap();
+        assert_eq!(report["total_bytes"], total_bytes, "with {args:?}");
+    }
+
+    // Without object files there is nothing to measure.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .arg("-Zunstable-options")
+        .emit("llvm-ir")
+        .print("mono-item-sizes")
+        .run_fail()
+        .assert_stderr_contains("`--print=mono-item-sizes` requires object files");
+}
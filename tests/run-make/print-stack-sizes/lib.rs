@@ -0,0 +1,38 @@
+use std::hint::black_box;
+
+#[inline(never)]
+pub fn leaf() -> u8 {
+    let buf = black_box([1u8; 4096]);
+    black_box(&buf);
+    7
+}
+
+#[inline(never)]
+pub fn middle() -> u8 {
+    leaf() + 1
+}
+
+#[inline(never)]
+pub fn top() -> u8 {
+    middle() + 1
+}
+
+#[inline(never)]
+pub fn recursive(n: u32) -> u32 {
+    if n == 0 { 0 } else { recursive(n - 1) + 1 }
+}
+
+#[inline(never)]
+pub fn indirect(f: fn() -> u8) -> u8 {
+    f()
+}
+
+#[inline(never)]
+pub fn external() -> u32 {
+    std::process::id()
+}
+
+#[inline(never)]
+pub fn calls_external() -> u32 {
+    external() + 1
+}
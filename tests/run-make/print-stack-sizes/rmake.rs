@@ -0,0 +1,97 @@
+//! Checks that `--print=stack-sizes` reads the frame sizes from the object
+//! files, and bounds the stack usage of each function along its calls.
+
+//@ ignore-cross-compile
+//@ only-elf
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    // Overflow checks would call the panic functions of `core`.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .opt_level("0")
+        .arg("-Coverflow-checks=off")
+        .arg("-Zunstable-options")
+        .print("stack-sizes=stack.json")
+        .run();
+
+    let report: Value = serde_json::from_str(&rfs::read_to_string("stack.json")).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["crate"], "lib");
+    let functions = report["functions"].as_array().unwrap();
+    let function = |name: &str| {
+        functions
+            .iter()
+            .find(|function| function["name"] == name)
+            .unwrap_or_else(|| panic!("no function `{name}` in {report:#}"))
+    };
+    let frame = |name: &str| function(name)["frame_bytes"].as_u64().unwrap();
+    let bound = |name: &str| function(name)["max_stack_bytes"].as_u64();
+    let local_bound =
+        |name: &str| function(name)["max_stack_bytes_without_external_callees"].as_u64();
+
+    // The buffer is on the stack of `leaf`, and counts towards its callers.
+    assert!(frame("leaf") >= 4096);
+    let leaf = bound("leaf").unwrap();
+    assert!(leaf >= frame("leaf"));
+    assert_eq!(bound("middle"), Some(frame("middle") + leaf));
+    assert_eq!(bound("top"), Some(frame("top") + frame("middle") + leaf));
+
+    // Recursion and indirect calls have no bound.
+    let recursive = function("recursive");
+    assert_eq!(recursive["recursive"], true);
+    assert_eq!(recursive["max_stack_bytes"], Value::Null);
+    let indirect = function("indirect");
+    assert_eq!(indirect["indirect_calls"], true);
+    assert_eq!(indirect["recursive"], false);
+    assert_eq!(indirect["max_stack_bytes"], Value::Null);
+
+    // The frame sizes of other crates are unknown, so calling them, even
+    // indirectly, leaves only the bound that ignores them.
+    let external = function("external");
+    assert_eq!(external["external_callees"], serde_json::json!(["std::process::id"]));
+    assert_eq!(bound("external"), None);
+    assert_eq!(local_bound("external"), Some(frame("external")));
+    assert_eq!(bound("calls_external"), None);
+    assert_eq!(local_bound("calls_external"), Some(frame("calls_external") + frame("external")));
+    assert_eq!(local_bound("top"), bound("top"));
+
+    // The report is printed right after codegen, so it does not depend on a
+    // link step.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .opt_level("0")
+        .arg("-Coverflow-checks=off")
+        .arg("-Zunstable-options")
+        .emit("obj")
+        .print("stack-sizes=stack-obj.json")
+        .run();
+    let report: Value = serde_json::from_str(&rfs::read_to_string("stack-obj.json")).unwrap();
+    assert!(report["functions"].as_array().unwrap().iter().any(|f| f["name"] == "top"));
+
+    // Cached objects from an incremental build without the report have no frame
+    // sizes, so they are not reused.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .opt_level("0")
+        .arg("-Coverflow-checks=off")
+        .incremental("incr")
+        .run();
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .opt_level("0")
+        .arg("-Coverflow-checks=off")
+        .incremental("incr")
+        .arg("-Zunstable-options")
+        .print("stack-sizes=stack-incr.json")
+        .run();
+    let report: Value = serde_json::from_str(&rfs::read_to_string("stack-incr.json")).unwrap();
+    let leaf = report["functions"].as_array().unwrap().iter().find(|f| f["name"] == "leaf");
+    assert!(leaf.unwrap()["frame_bytes"].as_u64().unwrap() >= 4096);
+}
@@ -0,0 +1,55 @@
+// With `--error-format=sarif`, diagnostics reported before the session exists
+// (here, an early warning about `-Zremark-dir`) must end up in the same SARIF
+// log as the session's own diagnostics: stderr has to parse as one document.
+// If the command line is rejected before a session is created, the early
+// diagnostics are still written, again as a single log.
+
+use run_make_support::rustc;
+use run_make_support::serde_json::{self, Value};
+
+fn parse_log(stderr: &str) -> Value {
+    // `from_str` rejects trailing characters, so a second log fails here.
+    serde_json::from_str(stderr).unwrap_or_else(|e| panic!("not a single SARIF log: {e}"))
+}
+
+fn main() {
+    let output = rustc()
+        .input("main.rs")
+        .error_format("sarif")
+        .arg("-Zunstable-options")
+        .arg("-Zremark-dir=remarks")
+        .run();
+    let log = parse_log(&output.stderr_utf8());
+    let run = &log["runs"][0];
+
+    let notifications = run["invocations"][0]["toolExecutionNotifications"].as_array().unwrap();
+    assert!(
+        notifications.iter().any(|n| n["message"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("using -Z remark-dir without enabling remarks")),
+        "missing early warning: {notifications:?}"
+    );
+    let results = run["results"].as_array().unwrap();
+    assert!(
+        results.iter().any(|r| r["ruleId"] == "unused_variables"),
+        "missing lint result: {results:?}"
+    );
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+
+    let output = rustc()
+        .input("main.rs")
+        .error_format("sarif")
+        .arg("-Zunstable-options")
+        .crate_type("not-a-crate-type")
+        .run_fail();
+    let log = parse_log(&output.stderr_utf8());
+    let invocation = &log["runs"][0]["invocations"][0];
+    assert_eq!(invocation["executionSuccessful"], false);
+    assert!(
+        invocation["toolExecutionNotifications"][0]["message"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("unknown crate type"),
+    );
+}
@@ -0,0 +1,10 @@
+#![crate_type = "staticlib"]
+
+pub fn describe(values: &[u32]) -> String {
+    format!("{} values", values.len())
+}
+
+#[no_mangle]
+pub extern "C" fn first_len() -> usize {
+    describe(&[1, 2, 3]).len()
+}
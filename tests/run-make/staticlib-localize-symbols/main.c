@@ -0,0 +1,12 @@
+#include <stddef.h>
+
+size_t first_len(void);
+size_t second_len(void);
+
+int main() {
+    // "3 values" and "[1, 2, 3]".
+    if (first_len() != 8 || second_len() != 9) {
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,36 @@
+// Two staticlibs that both bundle the standard library define the same
+// symbols, so they can't be linked into one binary. With
+// `-Zstaticlib-localize-symbols`, each staticlib is a single object in which
+// only the `#[no_mangle]` functions are global, and they can.
+
+//@ ignore-cross-compile
+//@ only-elf
+
+use run_make_support::object::{self, Object, ObjectSymbol};
+use run_make_support::{cc, extra_c_flags, rfs, run, rustc, static_lib_name};
+
+fn main() {
+    for name in ["first", "second"] {
+        rustc().input(format!("{name}.rs")).arg("-Zstaticlib-localize-symbols").run();
+
+        let data = rfs::read(static_lib_name(name));
+        let archive = object::read::archive::ArchiveFile::parse(&*data).unwrap();
+        let members: Vec<_> = archive.members().map(Result::unwrap).collect();
+        assert_eq!(members.len(), 1);
+        let file = object::File::parse(members[0].data(&*data).unwrap()).unwrap();
+        let globals: Vec<&str> = file
+            .symbols()
+            .filter(|symbol| symbol.is_global() && !symbol.is_undefined())
+            .map(|symbol| symbol.name().unwrap())
+            .collect();
+        assert_eq!(globals, [format!("{name}_len")]);
+    }
+
+    cc().input("main.c")
+        .input(static_lib_name("first"))
+        .input(static_lib_name("second"))
+        .out_exe("main")
+        .args(extra_c_flags())
+        .run();
+    run("main");
+}
@@ -0,0 +1,13 @@
+struct Printer;
+
+impl upstream::Visitor for Printer {
+    fn visit(&self) {}
+}
+
+pub fn run() {
+    upstream::used_downstream();
+    upstream::api();
+    upstream::uses_private();
+    upstream::UsedType.method();
+    upstream::Visitor::visit(&Printer);
+}
@@ -0,0 +1,44 @@
+// Checks that `-Zunused-pub-items` reports the public items that neither the
+// crate nor the crates depending on it use, as recorded by `-Zrecord-uses`.
+
+use run_make_support::rustc;
+
+fn main() {
+    rustc().input("upstream.rs").crate_type("rlib").run();
+    rustc()
+        .input("downstream.rs")
+        .crate_type("rlib")
+        .extern_("upstream", "libupstream.rlib")
+        .arg("-Zrecord-uses")
+        .run();
+
+    let output = rustc()
+        .input("upstream.rs")
+        .crate_type("rlib")
+        .arg("-Zunused-pub-items=libdownstream.rlib")
+        .run();
+    output
+        .assert_stderr_contains("public function `unused` is never used")
+        .assert_stderr_contains("public method `unused_method` is never used")
+        .assert_stderr_contains("public struct `UnusedType` is never used")
+        .assert_stderr_contains("public trait `UnusedTrait` is never used")
+        .assert_stderr_contains("public associated function `new` is never used")
+        .assert_stderr_contains("neither this crate nor the crate given to `-Z unused-pub-items`")
+        .assert_stderr_not_contains("`used_downstream`")
+        .assert_stderr_not_contains("`used_locally`")
+        .assert_stderr_not_contains("`api`")
+        .assert_stderr_not_contains("`allowed`")
+        .assert_stderr_not_contains("`method`")
+        .assert_stderr_not_contains("`UsedType`")
+        .assert_stderr_not_contains("`Visitor`")
+        .assert_stderr_not_contains("`not_exported`");
+
+    // Without `-Zrecord-uses`, there is nothing to compare with.
+    rustc().input("downstream.rs").crate_type("rlib").extern_("upstream", "libupstream.rlib").run();
+    rustc()
+        .input("upstream.rs")
+        .crate_type("rlib")
+        .arg("-Zunused-pub-items=libdownstream.rlib")
+        .run_fail()
+        .assert_stderr_contains("it was built without `-Z record-uses`");
+}
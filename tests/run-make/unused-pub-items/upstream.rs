@@ -0,0 +1,45 @@
+pub fn used_downstream() {}
+
+pub fn used_locally() {}
+
+pub fn api() {
+    used_locally();
+}
+
+pub fn unused() {}
+
+#[allow(dead_code)]
+pub fn allowed() {}
+
+pub struct UsedType;
+
+impl UsedType {
+    pub fn method(&self) {}
+
+    pub fn unused_method(&self) {}
+}
+
+pub struct UnusedType;
+
+pub trait Visitor {
+    fn visit(&self);
+}
+
+pub trait UnusedTrait {}
+
+mod private {
+    pub fn not_exported() {}
+}
+
+pub fn uses_private() {
+    private::not_exported();
+}
+
+#[derive(Debug, Clone)]
+pub struct OnlyDerived;
+
+impl OnlyDerived {
+    pub fn new() -> Self {
+        OnlyDerived
+    }
+}
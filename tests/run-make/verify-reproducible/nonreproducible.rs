@@ -0,0 +1,5 @@
+#![crate_type = "rlib"]
+
+pub fn working_directory() -> Option<&'static str> {
+    option_env!("PWD")
+}
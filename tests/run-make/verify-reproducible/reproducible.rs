@@ -0,0 +1,9 @@
+#![crate_type = "rlib"]
+
+pub fn greeting() -> &'static str {
+    "hello"
+}
+
+pub fn sum(values: &[u32]) -> u32 {
+    values.iter().sum()
+}
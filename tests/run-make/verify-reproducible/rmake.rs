@@ -0,0 +1,39 @@
+// `-Zverify-reproducible` builds the crate a second time in a different
+// directory and environment, and reports the parts of the outputs that differ.
+// Here, one crate is reproducible, and the other embeds the `PWD` environment
+// variable, which the second build changes.
+
+//@ ignore-cross-compile
+
+use run_make_support::{cwd, rfs, rustc};
+
+fn main() {
+    rustc().input("reproducible.rs").arg("-Zverify-reproducible").run();
+
+    // A source file above the working directory, and outputs at paths given by `--emit`.
+    rfs::create_dir("build");
+    rustc()
+        .current_dir("build")
+        .input("../reproducible.rs")
+        .emit("metadata=reproducible.rmeta,link=libreproducible.rlib")
+        .arg("-Zverify-reproducible")
+        .run();
+
+    // With debuginfo, the working directory is embedded unless it is remapped, and the
+    // remapping has to follow the second build to its working directory.
+    rustc()
+        .input("reproducible.rs")
+        .arg("-g")
+        .arg(format!("--remap-path-prefix={}=/src", cwd().display()))
+        .arg("-Zverify-reproducible")
+        .run();
+
+    rustc()
+        .input("nonreproducible.rs")
+        .arg("-Zverify-reproducible")
+        .run_fail()
+        .assert_stderr_contains("differs when the crate is built again in a different environment")
+        .assert_stderr_contains(
+            "in the metadata, the `optimized_mir` of `nonreproducible::working_directory` differs",
+        );
+}
@@ -71,9 +71,11 @@ fn compile(code: String, output: PathBuf, sysroot: PathBuf, linker: Option<&Path
         register_lints: None,
         override_queries: None,
         make_codegen_backend: None,
+        cache_backend: None,
         registry: rustc_driver::diagnostics_registry(),
         using_internal_features: &rustc_driver::USING_INTERNAL_FEATURES,
         expanded_args: Default::default(),
+        early_sarif_log: None,
     };
 
     interface::run_compiler(config, |compiler| {
@@ -1,4 +1,5 @@
 //@ run-pass
+//@ normalize-stderr: "from \d+ to \d+ bytes" -> "from $$SIZE to $$BOXED_SIZE bytes"
 
 #![warn(variant_size_differences)]
 #![allow(dead_code)]
@@ -1,11 +1,13 @@
 //@ check-pass
 //@ compile-flags: -Zno-profiler-runtime
-//@ revisions: default y yes on true_ all
+//@ revisions: default y yes on true_ all branch mcdc
 //@ [default] compile-flags: -Cinstrument-coverage
 //@ [y] compile-flags: -Cinstrument-coverage=y
 //@ [yes] compile-flags: -Cinstrument-coverage=yes
 //@ [on] compile-flags: -Cinstrument-coverage=on
 //@ [true_] compile-flags: -Cinstrument-coverage=true
 //@ [all] compile-flags: -Cinstrument-coverage=all
+//@ [branch] compile-flags: -Cinstrument-coverage=branch
+//@ [mcdc] compile-flags: -Cinstrument-coverage=mcdc
 
 fn main() {}
@@ -0,0 +1,35 @@
+// Checks that `-Zinline-mir-profile` reads its profile, and reports malformed lines.
+
+//@ revisions: valid malformed
+//@ compile-flags: -Zinline-mir -Copt-level=1
+//@[valid] build-pass
+//@[valid] compile-flags: -Zinline-mir-profile={{src-base}}/mir/auxiliary/inline-mir-profile.txt
+//@[malformed] build-fail
+//@[malformed] compile-flags: -Zinline-mir-profile={{src-base}}/mir/auxiliary/inline-mir-profile-malformed.txt
+
+struct Counter(u64);
+
+impl Counter {
+    fn bump(&mut self) {
+        self.0 += 1;
+    }
+}
+
+fn hot(counter: &mut Counter) {
+    counter.bump();
+}
+
+fn cold(counter: &mut Counter) {
+    counter.bump();
+    counter.bump();
+}
+
+fn main() {
+    let mut counter = Counter(0);
+    for _ in 0..1000 {
+        hot(&mut counter);
+    }
+    if counter.0 == 0 {
+        cold(&mut counter);
+    }
+}
@@ -0,0 +1,29 @@
+// Checks that the diagnostics of passes that run in parallel don't depend on
+// the number of threads: the well-formedness and coherence errors come first,
+// and then the errors of the bodies, in source order, however the bodies were
+// scheduled.
+//@ revisions: single parallel
+//@[single] compile-flags: -Z threads=1
+//@[parallel] compile-flags: -Z threads=16
+
+fn a() {
+    let _: u32 = true; //~ ERROR mismatched types
+}
+
+struct NeedsCopy<T: Copy>(T);
+
+fn b(_: NeedsCopy<String>) {} //~ ERROR the trait bound `String: Copy` is not satisfied
+
+fn c() {
+    let _: u32 = true; //~ ERROR mismatched types
+}
+
+trait Trait {}
+impl Trait for u32 {}
+impl Trait for u32 {} //~ ERROR conflicting implementations of trait `Trait` for type `u32`
+
+fn d() {
+    let _: u32 = true; //~ ERROR mismatched types
+}
+
+fn main() {}
@@ -22,6 +22,14 @@
 //@[check_cfg] compile-flags: --print=check-cfg
 //@[check_cfg] error-pattern: the `-Z unstable-options` flag must also be passed
 
+//@ revisions: mono_item_sizes
+//@[mono_item_sizes] compile-flags: --print=mono-item-sizes
+//@[mono_item_sizes] error-pattern: the `-Z unstable-options` flag must also be passed
+
+//@ revisions: stack_sizes
+//@[stack_sizes] compile-flags: --print=stack-sizes
+//@[stack_sizes] error-pattern: the `-Z unstable-options` flag must also be passed
+
 //@ revisions: supported_crate_types
 //@[supported_crate_types] compile-flags: --print=supported-crate-types
 //@[supported_crate_types] error-pattern: the `-Z unstable-options` flag must also be passed
@@ -30,6 +38,10 @@
 //@[target_spec_json] compile-flags: --print=target-spec-json
 //@[target_spec_json] error-pattern: the `-Z unstable-options` flag must also be passed
 
+//@ revisions: type_layouts
+//@[type_layouts] compile-flags: --print=type-layouts
+//@[type_layouts] error-pattern: the `-Z unstable-options` flag must also be passed
+
 // =======================
 // Stable print requests
 // =======================
@@ -0,0 +1,13 @@
+//@ compile-flags: -Z unstable-options --print=type-layouts --crate-type=lib
+//@ compile-flags: -Z print-type-layouts-filter=T
+//@ check-pass
+//@ ignore-pass
+
+// `--print=type-layouts` also works in builds without codegen, such as
+// `--emit=metadata`. `type-layouts-json.rs` checks the rendering itself.
+
+#![allow(dead_code)]
+
+struct T {
+    x: u32,
+}
@@ -0,0 +1,20 @@
+//@ compile-flags: -Z unstable-options --print=type-layouts --crate-type=lib
+//@ compile-flags: -Z print-type-layouts-filter=S,E
+//@ build-pass
+//@ ignore-pass
+
+// This file checks the JSON rendering of `--print=type-layouts`, with the
+// types from `padding.rs`. Only `S` and `E` pass the filter.
+
+#![allow(dead_code)]
+
+struct S {
+    a: u8,
+    b: u8,
+    g: i32,
+}
+
+enum E {
+    A(i32, i8),
+    B(S),
+}
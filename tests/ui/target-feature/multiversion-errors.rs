@@ -0,0 +1,59 @@
+//@ only-x86_64
+//@ edition: 2021
+
+#![feature(multiversion)]
+#![allow(dead_code)]
+
+#[multiversion]
+//~^ ERROR malformed `multiversion` attribute input
+fn no_targets() {}
+
+#[multiversion(targets("avx2"))]
+//~^ ERROR malformed `multiversion` attribute input
+fn not_an_array() {}
+
+#[multiversion(targets = "avx2")]
+//~^ ERROR malformed `multiversion` attribute input
+fn not_a_list() {}
+
+#[multiversion(targets = [])]
+//~^ ERROR malformed `multiversion` attribute input
+fn empty() {}
+
+#[multiversion(targets = [avx2])]
+//~^ ERROR malformed `multiversion` attribute input
+fn not_a_string() {}
+
+#[multiversion(targets = ["avx2,"])]
+//~^ ERROR malformed `multiversion` attribute input
+fn empty_feature() {}
+
+#[multiversion(targets = ["avx2", "avx2"])]
+//~^ ERROR the same target features are listed twice in `#[multiversion]`
+fn duplicate() {}
+
+#[multiversion(targets = ["avx2"])]
+struct NotAFunction;
+//~^ ERROR `#[multiversion]` can only be applied to free functions
+
+struct S;
+
+impl S {
+    #[multiversion(targets = ["avx2"])]
+    fn method(&self) {}
+    //~^ ERROR `#[multiversion]` can only be applied to free functions
+}
+
+#[multiversion(targets = ["avx2"])]
+async fn asynchronous() {}
+//~^ ERROR `#[multiversion]` cannot be applied to `async` functions
+
+#[multiversion(targets = ["avx2"])]
+const fn constant() {}
+//~^ ERROR `#[multiversion]` cannot be applied to `const` functions
+
+#[multiversion(targets = ["avx2"])]
+fn opaque() -> impl Sized {}
+//~^ ERROR `#[multiversion]` cannot be applied to functions that return `impl Trait`
+
+fn main() {}
@@ -0,0 +1,12 @@
+// Checks that `#[multiversion]` is rejected in `#![no_std]` crates, which can't use the
+// feature detection macros of `std::arch`.
+
+#![feature(multiversion)]
+#![no_std]
+#![crate_type = "lib"]
+
+#[multiversion(targets = ["avx2"])]
+//~^ ERROR `#[multiversion]` cannot be used in `#![no_std]` crates
+pub fn sum(values: &[u32]) -> u32 {
+    values.iter().sum()
+}
@@ -0,0 +1,62 @@
+//@ run-pass
+//@ only-x86_64
+// Checks that `#[multiversion]` functions behave like the function they are
+// applied to, whichever version the CPU selects. On x86_64 Linux, non-generic
+// functions dispatch through an ifunc and generic ones through a cached choice.
+
+#![feature(multiversion)]
+
+#[multiversion(targets = ["avx2", "sse4.2,popcnt"])]
+fn count_ones(values: &[u64]) -> u32 {
+    values.iter().map(|v| v.count_ones()).sum()
+}
+
+#[multiversion(targets = ["avx2"])]
+fn sum<T: Copy + std::iter::Sum<T>, const N: usize>(values: [T; N]) -> T {
+    values.into_iter().sum()
+}
+
+#[multiversion(targets = ["avx2"])]
+fn default_of<T: Default>() -> T {
+    T::default()
+}
+
+#[multiversion(targets = ["avx2"])]
+fn swap((a, b): (u8, u8), mut scale: u8) -> (u8, u8) {
+    scale += 1;
+    (b * scale, a * scale)
+}
+
+#[multiversion(targets = ["avx2"])]
+fn len(values: impl IntoIterator<Item = u8>) -> usize {
+    values.into_iter().count()
+}
+
+#[multiversion(targets = ["avx2"])]
+unsafe fn read(ptr: *const u32) -> u32 {
+    unsafe { *ptr }
+}
+
+#[multiversion(targets = ["avx2", "sse2"])]
+fn first<'a>(values: &'a [&'a str]) -> Option<&'a str> {
+    values.first().copied()
+}
+
+fn main() {
+    for _ in 0..2 {
+        assert_eq!(count_ones(&[0b1011, u64::MAX]), 67);
+        assert_eq!(sum([1, 2, 3]), 6);
+        assert_eq!(sum([0.5, 0.25]), 0.75);
+        assert_eq!(default_of::<String>(), "");
+        assert_eq!(swap((2, 3), 1), (6, 4));
+        assert_eq!(len(vec![1, 2, 3]), 3);
+        assert_eq!(unsafe { read(&7) }, 7);
+        assert_eq!(first(&["a", "b"]), Some("a"));
+    }
+
+    #[multiversion(targets = ["avx2"])]
+    fn local(x: i32) -> i32 {
+        x * 2
+    }
+    assert_eq!(local(21), 42);
+}
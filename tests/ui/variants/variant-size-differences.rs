@@ -1,3 +1,5 @@
+//@ normalize-stderr: "from \d+ to \d+ bytes" -> "from $$SIZE to $$BOXED_SIZE bytes"
+
 #![deny(variant_size_differences)]
 
 enum _En {